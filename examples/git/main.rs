@@ -0,0 +1,161 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env::args_os;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use module::Merge;
+use module::types::Last;
+use module_util::file::{File, Toml};
+use serde::Deserialize;
+
+// Single-valued keys keep the value from the most specific file.
+type Single<T> = Option<Last<T>>;
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct User {
+    name: Single<String>,
+    email: Single<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct Core {
+    editor: Single<String>,
+    autocrlf: Single<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct Credential {
+    // Multi-valued keys accumulate across all files.
+    helper: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct Include {
+    path: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct Config {
+    user: User,
+    core: Core,
+    credential: Credential,
+    alias: HashMap<String, Last<String>>,
+
+    include: Include,
+    #[serde(rename = "includeIf")]
+    include_if: HashMap<String, Include>,
+}
+
+impl Config {
+    /// Get all includes that apply to the repository at `gitdir`.
+    fn includes(&self, gitdir: &Path) -> Vec<PathBuf> {
+        let conditional = self
+            .include_if
+            .iter()
+            .filter(|(condition, _)| matches(condition, gitdir))
+            .flat_map(|(_, x)| x.path.iter());
+
+        self.include
+            .path
+            .iter()
+            .chain(conditional)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Evaluate an `includeIf` condition.
+///
+/// Only `gitdir:` conditions are supported. Like git, a pattern ending in `/`
+/// matches every repository below that directory.
+fn matches(condition: &str, gitdir: &Path) -> bool {
+    let Some(pattern) = condition.strip_prefix("gitdir:") else {
+        return false;
+    };
+
+    match pattern.strip_suffix('/') {
+        Some(dir) => gitdir.starts_with(dir),
+        None => gitdir == Path::new(pattern),
+    }
+}
+
+/// Read the system, global and local configuration of the repository at
+/// `gitdir`, in that order.
+///
+/// Like git, the includes of each file are processed right after the file, so
+/// later layers still take precedence over them.
+fn read(dir: &Path, gitdir: &Path) -> Result<Config, module::Error> {
+    let mut file = File::<Config, Toml>::toml();
+    let mut included = HashSet::new();
+
+    for layer in ["system.toml", "global.toml", "local.toml"] {
+        file.read_optional(dir.join(layer))?;
+
+        loop {
+            let pending: Vec<PathBuf> = file
+                .value()
+                .map(|x| x.includes(gitdir))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|x| included.insert(x.clone()))
+                .collect();
+
+            if pending.is_empty() {
+                break;
+            }
+
+            for path in pending {
+                file.read(dir.join(path))?;
+            }
+        }
+    }
+
+    Ok(file.finish().unwrap_or_default())
+}
+
+fn main() {
+    let mut args = args_os().skip(1);
+    let gitdir: PathBuf = args.next().unwrap_or_else(|| "/work/app".into()).into();
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("git");
+
+    match read(&dir, &gitdir) {
+        Ok(config) => print!("{config}"),
+        Err(e) => eprintln!("error: {e}"),
+    }
+}
+
+// The rest is just for pretty printing to the console, like `git config --list`.
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let single = [
+            ("user.name", &self.user.name),
+            ("user.email", &self.user.email),
+            ("core.editor", &self.core.editor),
+            ("core.autocrlf", &self.core.autocrlf),
+        ];
+
+        for (key, value) in single {
+            if let Some(value) = value.as_deref() {
+                writeln!(f, "{key}={value}")?;
+            }
+        }
+
+        for helper in &self.credential.helper {
+            writeln!(f, "credential.helper={helper}")?;
+        }
+
+        let aliases: BTreeMap<_, _> = self.alias.iter().collect();
+        for (name, value) in aliases {
+            writeln!(f, "alias.{name}={}", **value)?;
+        }
+
+        Ok(())
+    }
+}
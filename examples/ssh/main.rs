@@ -22,6 +22,7 @@ struct Section {
 
     // Any options not defined above end up here.
     #[serde(flatten)]
+    #[merge(flatten)]
     extra: HashMap<String, NoMerge<toml::Value>>,
 }
 
@@ -31,6 +32,7 @@ struct Config {
     hosts: HashMap<String, Section>,
 
     #[serde(default, flatten)]
+    #[merge(flatten)]
     global: Section,
 }
 
@@ -67,7 +69,7 @@ impl fmt::Display for Section {
         }
 
         for (k, v) in self.extra.iter() {
-            writeln!(f, "{k} {}", &**v)?;
+            writeln!(f, "{k} {}", **v)?;
         }
 
         Ok(())
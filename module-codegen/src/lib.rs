@@ -0,0 +1,286 @@
+//! Standalone code generator for the [`module`](https://github.com/threadexio/module-rs) crate.
+//!
+//! This crate is an alternative to the `Merge` derive macro for crates that
+//! cannot afford to depend on proc-macros. Instead of expanding the derive at
+//! compile time of the schema crate, the `Merge` implementations are generated
+//! ahead of time, either from a build script or by the `module-codegen` CLI,
+//! and the output is [`include!`]d into the schema crate.
+//!
+//! The generator and the derive macro share their expansion code through the
+//! `module-derive-internals` crate, so the generated implementations are
+//! exactly what `#[derive(Merge)]` would have produced.
+//!
+//! # Marking types
+//!
+//! Only items that are marked with `derive(Merge)` are processed. Because the
+//! schema crate does not depend on the derive macro, the marker and any
+//! `#[merge(...)]` attributes must be hidden from the compiler. The generator
+//! understands attributes behind `cfg_attr(module_codegen, ...)`, which the
+//! compiler strips as `module_codegen` is never set:
+//!
+//! ```rust,ignore
+//! #[cfg_attr(module_codegen, derive(Merge))]
+//! pub struct Config {
+//!     #[cfg_attr(module_codegen, merge(rename = "Port"))]
+//!     pub port: Option<u16>,
+//!     pub items: Vec<String>,
+//! }
+//!
+//! include!("schema_merge.rs");
+//! ```
+//!
+//! Plain `#[derive(Merge)]` and `#[merge(...)]` attributes are accepted too,
+//! for input files that are not compiled directly.
+//!
+//! Only top-level structs and enums of the input are considered.
+//!
+//! # Usage
+//!
+//! From the command line:
+//!
+//! ```text
+//! module-codegen gen src/schema.rs -o src/schema_merge.rs
+//! ```
+//!
+//! From a build script:
+//!
+//! ```rust,no_run
+//! use std::path::Path;
+//! use std::{env, fs};
+//!
+//! let input = fs::read_to_string("src/schema.rs").unwrap();
+//! let output = module_codegen::generate_merge_impls(&input).unwrap();
+//!
+//! let out_dir = env::var("OUT_DIR").unwrap();
+//! fs::write(Path::new(&out_dir).join("schema_merge.rs"), output).unwrap();
+//!
+//! println!("cargo::rerun-if-changed=src/schema.rs");
+//! ```
+#![forbid(unsafe_code)]
+
+use std::fmt;
+
+use syn::punctuated::Punctuated;
+use syn::{Token, parse_quote};
+
+/// The header placed at the top of every generated file.
+pub const HEADER: &str = "// @generated by module-codegen. Do not edit by hand.\n";
+
+/// The `cfg` predicate under which attributes are read by the generator.
+pub const CFG: &str = "module_codegen";
+
+/// Error returned by [`generate_merge_impls`].
+#[derive(Debug)]
+pub struct CodegenError(syn::Error);
+
+impl CodegenError {
+    /// Get the underlying [`syn::Error`].
+    pub fn into_inner(self) -> syn::Error {
+        self.0
+    }
+}
+
+impl From<syn::Error> for CodegenError {
+    fn from(e: syn::Error) -> Self {
+        Self(e)
+    }
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for CodegenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Generate `Merge` implementations for the items of the Rust source `input`.
+///
+/// Returns the source code of the generated implementations, formatted and
+/// prefixed with [`HEADER`]. See the [crate-level docs](crate) for how items
+/// are selected.
+pub fn generate_merge_impls(input: &str) -> Result<String, CodegenError> {
+    let file: syn::File = syn::parse_str(input)?;
+
+    let mut items = Vec::new();
+    for input in derive_inputs(file) {
+        let tokens = module_derive_internals::expand_merge(input);
+        let expanded: syn::File = syn::parse2(tokens)?;
+        items.extend(expanded.items);
+    }
+
+    let file = syn::File {
+        shebang: None,
+        attrs: Vec::new(),
+        items,
+    };
+
+    Ok(format!("{HEADER}\n{}", prettyplease::unparse(&file)))
+}
+
+fn derive_inputs(file: syn::File) -> Vec<syn::DeriveInput> {
+    file.items
+        .into_iter()
+        .filter_map(|item| match item {
+            syn::Item::Struct(x) => Some(syn::DeriveInput::from(x)),
+            syn::Item::Enum(x) => Some(syn::DeriveInput::from(x)),
+            _ => None,
+        })
+        .map(|mut input| {
+            unwrap_cfg_attrs(&mut input.attrs);
+            match input.data {
+                syn::Data::Struct(ref mut x) => x
+                    .fields
+                    .iter_mut()
+                    .for_each(|field| unwrap_cfg_attrs(&mut field.attrs)),
+                syn::Data::Enum(ref mut x) => {
+                    for variant in &mut x.variants {
+                        unwrap_cfg_attrs(&mut variant.attrs);
+                        variant
+                            .fields
+                            .iter_mut()
+                            .for_each(|field| unwrap_cfg_attrs(&mut field.attrs));
+                    }
+                }
+                syn::Data::Union(_) => {}
+            }
+            input
+        })
+        .filter(|input| is_marked(&input.attrs))
+        .collect()
+}
+
+/// Replace `#[cfg_attr(module_codegen, a, b)]` with `#[a]` and `#[b]`.
+fn unwrap_cfg_attrs(attrs: &mut Vec<syn::Attribute>) {
+    let mut out = Vec::with_capacity(attrs.len());
+
+    for attr in attrs.drain(..) {
+        match cfg_attr_metas(&attr) {
+            Some(metas) => out.extend(metas.into_iter().map(|x| parse_quote!(#[#x]))),
+            None => out.push(attr),
+        }
+    }
+
+    *attrs = out;
+}
+
+fn cfg_attr_metas(attr: &syn::Attribute) -> Option<Vec<syn::Meta>> {
+    let syn::Meta::List(ref list) = attr.meta else {
+        return None;
+    };
+
+    if !list.path.is_ident("cfg_attr") {
+        return None;
+    }
+
+    let metas = list
+        .parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)
+        .ok()?;
+
+    let mut metas = metas.into_iter();
+    match metas.next()? {
+        syn::Meta::Path(x) if x.is_ident(CFG) => Some(metas.collect()),
+        _ => None,
+    }
+}
+
+fn is_marked(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .any(|path| path.segments.last().is_some_and(|x| x.ident == "Merge"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cfg_attr_same_as_plain() {
+        let plain = r#"
+            #[derive(Debug, Merge)]
+            struct Config {
+                #[merge(rename = "Port")]
+                port: Option<u16>,
+                #[merge(skip)]
+                cache: Vec<u8>,
+            }
+        "#;
+
+        let cfg_attr = r#"
+            #[derive(Debug)]
+            #[cfg_attr(module_codegen, derive(Merge))]
+            struct Config {
+                #[cfg_attr(module_codegen, merge(rename = "Port"))]
+                port: Option<u16>,
+                #[cfg_attr(module_codegen, merge(skip))]
+                cache: Vec<u8>,
+            }
+        "#;
+
+        assert_eq!(
+            generate_merge_impls(plain).unwrap(),
+            generate_merge_impls(cfg_attr).unwrap(),
+            "cfg_attr and plain attributes should generate the same code"
+        );
+    }
+
+    #[test]
+    fn test_enum_cfg_attr_same_as_plain() {
+        let plain = r#"
+            #[derive(Merge)]
+            enum Source {
+                Inline(#[merge(rename = "text")] Vec<String>),
+                File {
+                    #[merge(skip)]
+                    handle: u32,
+                    lines: Vec<String>,
+                },
+            }
+        "#;
+
+        let cfg_attr = r#"
+            #[cfg_attr(module_codegen, derive(Merge))]
+            enum Source {
+                Inline(#[cfg_attr(module_codegen, merge(rename = "text"))] Vec<String>),
+                File {
+                    #[cfg_attr(module_codegen, merge(skip))]
+                    handle: u32,
+                    lines: Vec<String>,
+                },
+            }
+        "#;
+
+        assert_eq!(
+            generate_merge_impls(plain).unwrap(),
+            generate_merge_impls(cfg_attr).unwrap(),
+            "cfg_attr and plain attributes should generate the same code"
+        );
+    }
+
+    #[test]
+    fn test_other_cfg_ignored() {
+        let input = r#"
+            #[cfg_attr(feature = "derive", derive(Merge))]
+            struct Config {
+                port: Option<u16>,
+            }
+        "#;
+
+        assert_eq!(generate_merge_impls(input).unwrap().trim(), HEADER.trim());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(generate_merge_impls("struct {").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Command line interface for `module-codegen`.
+//!
+//! ```text
+//! module-codegen gen <input> [-o <output>]
+//! ```
+#![forbid(unsafe_code)]
+
+use std::env::args_os;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: module-codegen gen <input> [-o <output>]";
+
+struct Args {
+    input: PathBuf,
+    output: Option<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = OsString>) -> Result<Args, String> {
+    match args.next() {
+        Some(x) if x == "gen" => {}
+        Some(x) => return Err(format!("unknown command `{}`", x.to_string_lossy())),
+        None => return Err("missing command".into()),
+    }
+
+    let mut input = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        if arg == "-o" || arg == "--output" {
+            let path = args.next().ok_or("missing value for `-o`")?;
+            output = Some(PathBuf::from(path));
+        } else if input.is_none() {
+            input = Some(PathBuf::from(arg));
+        } else {
+            return Err(format!("unexpected argument `{}`", arg.to_string_lossy()));
+        }
+    }
+
+    let input = input.ok_or("missing input file")?;
+    Ok(Args { input, output })
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let input = fs::read_to_string(&args.input)
+        .map_err(|e| format!("failed to read `{}`: {e}", args.input.display()))?;
+
+    let output = module_codegen::generate_merge_impls(&input)
+        .map_err(|e| format!("{}: {e}", args.input.display()))?;
+
+    match args.output {
+        Some(path) => fs::write(&path, output)
+            .map_err(|e| format!("failed to write `{}`: {e}", path.display())),
+        None => io::stdout()
+            .write_all(output.as_bytes())
+            .map_err(|e| format!("failed to write output: {e}")),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(args_os().skip(1)) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("error: {e}");
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
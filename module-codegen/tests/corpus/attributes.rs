@@ -0,0 +1,38 @@
+#[derive(Merge)]
+pub struct Renamed {
+    #[merge(rename = "Port")]
+    pub port: Option<u16>,
+    #[merge(rename = "Host")]
+    pub host: Option<String>,
+}
+
+#[derive(Merge)]
+pub struct Skipped {
+    #[merge(skip)]
+    pub cache: Vec<u8>,
+    #[merge(skip(deny_other))]
+    pub generation: u64,
+    pub value: Vec<u8>,
+}
+
+#[derive(module::Merge)]
+pub struct With {
+    #[merge(with = custom)]
+    pub a: i32,
+    #[merge(with = custom::nested, rename = "bee")]
+    pub b: i32,
+}
+
+#[derive(Merge)]
+pub struct TupleAttrs(#[merge(rename = "first")] Vec<i32>, #[merge(skip)] i32);
+
+#[derive(Merge)]
+#[merge(trim_neutral)]
+pub struct TrimNeutral {
+    pub port: Option<u16>,
+    pub hosts: Vec<String>,
+    #[merge(skip)]
+    pub cache: Vec<u8>,
+    #[merge(rename = "Tuned")]
+    pub tuned: Option<bool>,
+}
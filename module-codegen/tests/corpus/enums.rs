@@ -0,0 +1,26 @@
+#[derive(Merge)]
+pub enum Unit {
+    A,
+    B,
+}
+
+#[derive(Merge)]
+pub enum Source {
+    Inline(#[merge(rename = "text")] Vec<String>),
+    File {
+        #[merge(skip)]
+        handle: u32,
+        lines: Vec<String>,
+    },
+    Empty,
+}
+
+#[derive(Merge)]
+pub enum Generic<T> {
+    Value(T),
+    Nothing,
+}
+
+pub enum NotMarked {
+    A(i32),
+}
@@ -0,0 +1,29 @@
+#[derive(Merge)]
+pub struct Unit;
+
+#[derive(Merge)]
+pub struct Tuple(Vec<i32>, Option<String>);
+
+#[derive(Debug, Default, Merge)]
+pub struct Named {
+    pub key: Option<String>,
+    pub items: Vec<i32>,
+}
+
+#[derive(Merge)]
+pub struct Generic<T, U>
+where
+    U: Clone,
+{
+    pub a: T,
+    pub b: Option<U>,
+}
+
+pub struct NotMarked {
+    pub a: i32,
+}
+
+#[derive(Clone)]
+pub struct OtherDerive {
+    pub a: i32,
+}
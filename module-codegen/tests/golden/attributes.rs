@@ -0,0 +1,70 @@
+// @generated by module-codegen. Do not edit by hand.
+
+impl ::module::Merge for Renamed {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        ::module::Merge::merge_ref(&mut self.port, _other.port).value("Port")?;
+        ::module::Merge::merge_ref(&mut self.host, _other.host).value("Host")?;
+        Ok(())
+    }
+}
+impl ::module::Merge for Skipped {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        if !::module::__private::is_unset::<u64>(&_other.generation) {
+            return ::core::result::Result::Err(
+                    ::module::Error::custom(
+                        "field `generation` cannot be set here (it is managed internally)",
+                    ),
+                )
+                .value("generation");
+        }
+        ::module::Merge::merge_ref(&mut self.value, _other.value).value("value")?;
+        Ok(())
+    }
+}
+impl ::module::Merge for With {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        custom::merge_ref(&mut self.a, _other.a).value("a")?;
+        custom::nested::merge_ref(&mut self.b, _other.b).value("bee")?;
+        Ok(())
+    }
+}
+impl ::module::Merge for TupleAttrs {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        ::module::Merge::merge_ref(&mut self.0, _other.0).value("first")?;
+        Ok(())
+    }
+}
+impl ::module::Merge for TrimNeutral {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        if !::module::merge::Neutral::is_neutral(&_other.port) {
+            ::module::Merge::merge_ref(&mut self.port, _other.port).value("port")?;
+        }
+        if !::module::merge::Neutral::is_neutral(&_other.hosts) {
+            ::module::Merge::merge_ref(&mut self.hosts, _other.hosts).value("hosts")?;
+        }
+        if !::module::merge::Neutral::is_neutral(&_other.tuned) {
+            ::module::Merge::merge_ref(&mut self.tuned, _other.tuned).value("Tuned")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+// @generated by module-codegen. Do not edit by hand.
+
+impl ::module::Merge for Unit {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        match (self, _other) {
+            (Self::A, Self::A) => Ok(()),
+            (Self::B, Self::B) => Ok(()),
+            #[allow(unreachable_patterns)]
+            _ => ::core::result::Result::Err(::module::Error::collision()),
+        }
+    }
+}
+impl ::module::Merge for Source {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        match (self, _other) {
+            (Self::Inline { 0: __self_0 }, Self::Inline { 0: __other_0 }) => {
+                ::module::Merge::merge_ref(__self_0, __other_0).value("text")?;
+                Ok(())
+            }
+            (
+                Self::File { handle: __self_0, lines: __self_1 },
+                Self::File { handle: __other_0, lines: __other_1 },
+            ) => {
+                ::module::Merge::merge_ref(__self_1, __other_1).value("lines")?;
+                Ok(())
+            }
+            (Self::Empty, Self::Empty) => Ok(()),
+            #[allow(unreachable_patterns)]
+            _ => ::core::result::Result::Err(::module::Error::collision()),
+        }
+    }
+}
+impl<T> ::module::Merge for Generic<T>
+where
+    T: ::module::Merge,
+{
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        match (self, _other) {
+            (Self::Value { 0: __self_0 }, Self::Value { 0: __other_0 }) => {
+                ::module::Merge::merge_ref(__self_0, __other_0).value("0")?;
+                Ok(())
+            }
+            (Self::Nothing, Self::Nothing) => Ok(()),
+            #[allow(unreachable_patterns)]
+            _ => ::core::result::Result::Err(::module::Error::collision()),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+// @generated by module-codegen. Do not edit by hand.
+
+impl ::module::Merge for Unit {
+    fn merge_ref(&mut self, _: Self) -> ::core::result::Result<(), ::module::Error> {
+        Ok(())
+    }
+}
+impl ::module::Merge for Tuple {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        ::module::Merge::merge_ref(&mut self.0, _other.0).value("0")?;
+        ::module::Merge::merge_ref(&mut self.1, _other.1).value("1")?;
+        Ok(())
+    }
+}
+impl ::module::Merge for Named {
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        ::module::Merge::merge_ref(&mut self.key, _other.key).value("key")?;
+        ::module::Merge::merge_ref(&mut self.items, _other.items).value("items")?;
+        Ok(())
+    }
+}
+impl<T, U> ::module::Merge for Generic<T, U>
+where
+    U: Clone,
+    T: ::module::Merge,
+    U: ::module::Merge,
+{
+    fn merge_ref(
+        &mut self,
+        _other: Self,
+    ) -> ::core::result::Result<(), ::module::Error> {
+        use ::module::Context as _;
+        ::module::Merge::merge_ref(&mut self.a, _other.a).value("a")?;
+        ::module::Merge::merge_ref(&mut self.b, _other.b).value("b")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+#![allow(missing_docs)]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn path(p: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(p)
+}
+
+fn corpus() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(path("corpus"))
+        .unwrap()
+        .map(|x| x.unwrap().path())
+        .collect();
+
+    files.sort();
+    files
+}
+
+/// Compare the generator output against the files in `tests/golden`.
+///
+/// Set `MODULE_CODEGEN_BLESS=1` to update the golden files.
+#[test]
+fn test_golden() {
+    let bless = env::var_os("MODULE_CODEGEN_BLESS").is_some();
+
+    for input in corpus() {
+        let golden = path("golden").join(input.file_name().unwrap());
+
+        let source = fs::read_to_string(&input).unwrap();
+        let generated = module_codegen::generate_merge_impls(&source).unwrap();
+
+        if bless {
+            fs::write(&golden, &generated).unwrap();
+            continue;
+        }
+
+        let expected = fs::read_to_string(&golden).unwrap();
+        assert_eq!(
+            generated,
+            expected,
+            "generated output for `{}` differs from golden file",
+            input.display()
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! Expansion code shared by `module-derive` and `module-codegen`.
+//!
+//! This crate is an implementation detail of the [`module`] crate. It has no
+//! stable API.
+//!
+//! [`module`]: https://github.com/threadexio/module-rs
+#![forbid(unsafe_code)]
+
+mod merge;
+
+/// Expand `#[derive(Merge)]` on `input`.
+///
+/// Errors are returned as a `compile_error!` invocation.
+pub fn expand_merge(input: syn::DeriveInput) -> proc_macro2::TokenStream {
+    merge::expand(input)
+}
@@ -0,0 +1,1286 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+use syn::Token;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+
+pub fn expand(input: syn::DeriveInput) -> TokenStream {
+    match Merge::new(input) {
+        Ok(merge) => merge.to_token_stream(),
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+struct Merge {
+    name: syn::Ident,
+    generics: syn::Generics,
+    data: Data,
+    attributes: ContainerAttributes,
+}
+
+enum Data {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+struct Variant {
+    name: syn::Ident,
+    fields: Fields,
+}
+
+impl Merge {
+    pub fn new(input: syn::DeriveInput) -> syn::Result<Self> {
+        // `rename_all` on a struct renames its fields, but on an enum it
+        // renames the variants. The fields of a variant are renamed by the
+        // `rename_all` of the variant.
+        let data = match input.data {
+            syn::Data::Struct(x) => {
+                let rename_all = serde_attr::rename(&input.attrs, "rename_all");
+                Data::Struct(Fields::new(x.fields, rename_all.as_deref())?)
+            }
+            syn::Data::Enum(x) => Data::Enum(
+                x.variants
+                    .into_iter()
+                    .map(|x| {
+                        let rename_all = serde_attr::rename(&x.attrs, "rename_all");
+
+                        Ok(Variant {
+                            name: x.ident,
+                            fields: Fields::new(x.fields, rename_all.as_deref())?,
+                        })
+                    })
+                    .collect::<syn::Result<_>>()?,
+            ),
+            syn::Data::Union(x) => {
+                return Err(syn::Error::new(
+                    x.union_token.span,
+                    "Merge cannot be derived on unions",
+                ));
+            }
+        };
+
+        let name = input.ident;
+        let generics = input.generics;
+        let attributes = ContainerAttributes::new(input.attrs)?;
+
+        let this = Self {
+            name,
+            generics,
+            data,
+            attributes,
+        };
+
+        this.validate()?;
+        Ok(this)
+    }
+
+    /// Check the attributes of fields that depend on the container.
+    fn validate(&self) -> syn::Result<()> {
+        for field in self.all_fields() {
+            let span = field.name.span();
+
+            if field.attributes.resolve && !self.attributes.resolve {
+                return Err(syn::Error::new(
+                    span,
+                    "`resolve` on fields requires `#[merge(resolve)]` on the container",
+                ));
+            }
+
+            if !field.attributes.overridable {
+                continue;
+            }
+
+            match self.data {
+                Data::Enum(_) => {
+                    return Err(syn::Error::new(
+                        span,
+                        "`overridable` fields are not supported on enums",
+                    ));
+                }
+                Data::Struct(ref fields) => {
+                    let fields = fields.as_fields().unwrap_or_default();
+
+                    if !fields.iter().any(|x| x.name.is_named("__priorities")) {
+                        return Err(syn::Error::new(
+                            span,
+                            "`overridable` fields require `#[module::overridable_struct]`",
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the fields of the struct, or of all variants of the enum.
+    fn all_fields(&self) -> impl Iterator<Item = &Field> + Clone {
+        let (structs, variants) = match self.data {
+            Data::Struct(ref x) => (x.as_fields().unwrap_or_default(), &[][..]),
+            Data::Enum(ref x) => (&[][..], &x[..]),
+        };
+
+        structs.iter().chain(
+            variants
+                .iter()
+                .flat_map(|x| x.fields.as_fields().unwrap_or_default()),
+        )
+    }
+
+    fn make_impl_header(&self) -> TokenStream {
+        let Self { generics, name, .. } = self;
+        let krate = &self.attributes.krate;
+
+        let merged_fields: Vec<&Field> = self
+            .all_fields()
+            .filter(|x| x.is_merged_with_trait())
+            .collect();
+
+        let extra_predicates: Punctuated<syn::WherePredicate, Token![,]> = generics
+            .params
+            .iter()
+            .filter_map(|x| match x {
+                syn::GenericParam::Type(x) => Some(x),
+                _ => None,
+            })
+            .filter(|x| merged_fields.iter().any(|f| uses_ident(&f.ty, &x.ident)))
+            .map(|x| {
+                syn::WherePredicate::Type(syn::PredicateType {
+                    lifetimes: None,
+                    bounded_ty: syn::Type::Path(syn::TypePath {
+                        qself: None,
+                        path: syn::Path {
+                            leading_colon: None,
+                            segments: [syn::PathSegment::from(x.ident.clone())]
+                                .into_iter()
+                                .collect(),
+                        },
+                    }),
+                    colon_token: Token![:]([Span::call_site()]),
+                    bounds: {
+                        [syn::TypeParamBound::Trait(syn::TraitBound {
+                            paren_token: None,
+                            modifier: syn::TraitBoundModifier::None,
+                            lifetimes: None,
+                            path: syn::parse_quote!(#krate::Merge),
+                        })]
+                        .into_iter()
+                        .collect()
+                    },
+                })
+            })
+            .collect();
+
+        let extra_predicates = match self.attributes.bound {
+            Some(ref x) => x.clone(),
+            None => extra_predicates,
+        };
+
+        let where_clause = match generics.where_clause.clone() {
+            Some(mut x) => {
+                x.predicates.extend(extra_predicates);
+                x
+            }
+            None => syn::WhereClause {
+                where_token: Token![where](Span::call_site()),
+                predicates: extra_predicates,
+            },
+        };
+
+        quote! {
+            impl #generics #krate::Merge for #name #generics
+            #where_clause
+        }
+    }
+
+    fn make_impl_body(&self) -> TokenStream {
+        let krate = &self.attributes.krate;
+        let fields = match self.data {
+            Data::Struct(ref x) => x,
+            Data::Enum(ref variants) => return self.make_enum_body(variants),
+        };
+
+        let Some(fields) = fields.as_fields() else {
+            return quote! {
+                fn merge_ref(&mut self, _: Self) -> ::core::result::Result<(), #krate::Error> {
+                    Ok(())
+                }
+            };
+        };
+
+        let mut checks = TokenStream::new();
+        let mut merge_ref_fields = TokenStream::new();
+        let mut compact_names = Vec::new();
+
+        for field in fields {
+            let name = &field.name;
+
+            checks.extend(field.deny_other_check(krate, &quote! { _other.#name }));
+
+            if field.attributes.skip.is_some() {
+                continue;
+            }
+
+            let with_value = self.value_context(field, &mut compact_names);
+
+            if field.attributes.overridable {
+                let take = with_value(quote! {
+                    #krate::__private::take_overridable(
+                        self.__priorities.#name,
+                        _other.__priorities.#name,
+                    )
+                });
+
+                merge_ref_fields.extend(field.skip_if(
+                    &quote! { &self.#name },
+                    quote! {
+                        if #take {
+                            self.#name = _other.#name;
+                            self.__priorities.#name = _other.__priorities.#name;
+                        }
+                    },
+                ));
+
+                continue;
+            }
+
+            let merge_base_path = field.merge_path(krate);
+            let merge_ref = with_value(quote! {
+                #merge_base_path::merge_ref(&mut self.#name, _other.#name)
+            });
+
+            let merge_ref = if self.attributes.trim_neutral {
+                quote! {
+                    if !#krate::merge::Neutral::is_neutral(&_other.#name) {
+                        #merge_ref;
+                    }
+                }
+            } else {
+                quote! { #merge_ref; }
+            };
+
+            merge_ref_fields.extend(field.skip_if(&quote! { &self.#name }, merge_ref));
+        }
+
+        if !compact_names.is_empty() {
+            let fields = quote! {
+                static __FIELDS: &[&str] = &[#(#compact_names),*];
+            };
+
+            checks = quote! { #fields #checks };
+        }
+
+        // Only `merge_ref` is generated. The default `merge` of the trait
+        // calls it.
+        quote! {
+            fn merge_ref(&mut self, _other: Self) -> ::core::result::Result<(), #krate::Error> {
+                use #krate::Context as _;
+                #checks
+                #merge_ref_fields
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Merge {
+    /// Get a function that adds the name of `field` to the context of the
+    /// error of a merge expression and propagates it.
+    ///
+    /// With `compact_names`, string literal names are pushed to `names` and
+    /// the error is handled by one shared, non-generic helper instead of the
+    /// generic `Context::value`. Flattened fields add nothing.
+    fn value_context(
+        &self,
+        field: &Field,
+        names: &mut Vec<syn::LitStr>,
+    ) -> impl Fn(TokenStream) -> TokenStream {
+        let value = field.value_name();
+        let flatten = field.attributes.flatten;
+
+        let idx = match value {
+            _ if flatten => None,
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(ref x),
+                ..
+            }) if self.attributes.compact_names => u16::try_from(names.len()).ok().inspect(|_| {
+                names.push(x.clone());
+            }),
+            _ => None,
+        };
+
+        let krate = self.attributes.krate.clone();
+        move |expr| match idx {
+            _ if flatten => quote! { #expr? },
+            Some(idx) => quote! {
+                match #expr {
+                    ::core::result::Result::Ok(x) => x,
+                    ::core::result::Result::Err(e) => {
+                        return ::core::result::Result::Err(
+                            #krate::__private::push_value_idx(e, __FIELDS, #idx),
+                        );
+                    }
+                }
+            },
+            None => quote! { #expr.value(#value)? },
+        }
+    }
+
+    /// Merge 2 values of the same variant field by field. Values of different
+    /// variants collide.
+    fn make_enum_body(&self, variants: &[Variant]) -> TokenStream {
+        let krate = &self.attributes.krate;
+        let mut arms = TokenStream::new();
+        let mut has_fields = false;
+        let mut compact_names = Vec::new();
+
+        for variant in variants {
+            let name = &variant.name;
+
+            let Some(fields) = variant.fields.as_fields() else {
+                arms.extend(quote! {
+                    (Self::#name, Self::#name) => Ok(()),
+                });
+
+                continue;
+            };
+
+            let mut checks = TokenStream::new();
+            let mut merge_ref_fields = TokenStream::new();
+
+            for (i, field) in fields.iter().enumerate() {
+                has_fields = true;
+
+                let this = format_ident!("__self_{i}");
+                let other = format_ident!("__other_{i}");
+
+                checks.extend(field.deny_other_check(krate, &quote! { #other }));
+
+                if field.attributes.skip.is_some() {
+                    continue;
+                }
+
+                let with_value = self.value_context(field, &mut compact_names);
+
+                let merge_base_path = field.merge_path(krate);
+                let merge_ref = with_value(quote! {
+                    #merge_base_path::merge_ref(#this, #other)
+                });
+
+                let merge_ref = if self.attributes.trim_neutral {
+                    quote! {
+                        if !#krate::merge::Neutral::is_neutral(&#other) {
+                            #merge_ref;
+                        }
+                    }
+                } else {
+                    quote! { #merge_ref; }
+                };
+
+                merge_ref_fields.extend(field.skip_if(&quote! { &*#this }, merge_ref));
+            }
+
+            let this = variant.pattern("__self");
+            let other = variant.pattern("__other");
+
+            arms.extend(quote! {
+                (#this, #other) => {
+                    #checks
+                    #merge_ref_fields
+                    Ok(())
+                }
+            });
+        }
+
+        let prelude = has_fields.then(|| {
+            let fields = (!compact_names.is_empty()).then(|| {
+                quote! {
+                    static __FIELDS: &[&str] = &[#(#compact_names),*];
+                }
+            });
+
+            quote! {
+                use #krate::Context as _;
+                #fields
+            }
+        });
+
+        quote! {
+            fn merge_ref(&mut self, _other: Self) -> ::core::result::Result<(), #krate::Error> {
+                #prelude
+
+                match (self, _other) {
+                    #arms
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::result::Result::Err(#krate::Error::collision()),
+                }
+            }
+        }
+    }
+
+    fn make_resolve_impl(&self) -> TokenStream {
+        let (structs, variants) = match self.data {
+            Data::Struct(ref x) => (x.as_fields().unwrap_or_default(), &[][..]),
+            Data::Enum(ref x) => (&[][..], &x[..]),
+        };
+
+        if !self.attributes.resolve {
+            return TokenStream::new();
+        }
+
+        let Self { name, generics, .. } = self;
+        let where_clause = &generics.where_clause;
+        let krate = &self.attributes.krate;
+
+        let mut resolve_fields = TokenStream::new();
+        for field in structs.iter().filter(|x| x.attributes.resolve) {
+            let name = &field.name;
+            let context = field.resolve_context();
+
+            resolve_fields.extend(quote! {
+                #krate::merge::Resolve::resolve_in_place(&mut self.#name)#context?;
+            });
+        }
+
+        if let Data::Enum(_) = self.data {
+            let arms = variants.iter().map(|variant| {
+                let fields = variant.fields.as_fields().unwrap_or_default();
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, x)| x.attributes.resolve);
+
+                let (names, resolve): (Vec<_>, Vec<_>) = fields
+                    .map(|(i, field)| {
+                        let this = format_ident!("__self_{i}");
+                        let context = field.resolve_context();
+
+                        (
+                            (&field.name, this.clone()),
+                            quote! {
+                                #krate::merge::Resolve::resolve_in_place(#this)#context?;
+                            },
+                        )
+                    })
+                    .unzip();
+
+                let (names, bindings): (Vec<_>, Vec<_>) = names.into_iter().unzip();
+                let name = &variant.name;
+
+                quote! {
+                    Self::#name { #(#names: #bindings,)* .. } => { #(#resolve)* }
+                }
+            });
+
+            resolve_fields = quote! {
+                match self {
+                    #(#arms)*
+                    #[allow(unreachable_patterns)]
+                    _ => {}
+                }
+            };
+        }
+
+        quote! {
+            impl #generics #krate::merge::Resolve for #name #generics
+            #where_clause
+            {
+                fn resolve_in_place(&mut self) -> ::core::result::Result<(), #krate::Error> {
+                    use #krate::Context as _;
+                    #resolve_fields
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+impl ToTokens for Merge {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let header = self.make_impl_header();
+        let body = self.make_impl_body();
+        let resolve = self.make_resolve_impl();
+
+        let x = quote! {
+            #header { #body }
+            #resolve
+        };
+
+        // panic!("{x}")
+        x.to_tokens(tokens)
+    }
+}
+
+enum Fields {
+    Unit,
+    Fields(Vec<Field>),
+}
+
+impl Fields {
+    pub fn new(fields: syn::Fields, rename_all: Option<&str>) -> syn::Result<Self> {
+        let from_iter = |iter: syn::punctuated::Iter<'_, syn::Field>| {
+            let x = iter
+                .into_iter()
+                .cloned()
+                .enumerate()
+                .map(|(i, field)| (syn::Index::from(i), field))
+                .map(|(i, field)| Field::new(i, field, rename_all))
+                .collect::<syn::Result<_>>()?;
+
+            Ok(Self::Fields(x))
+        };
+
+        match fields {
+            syn::Fields::Unit => Ok(Self::Unit),
+            syn::Fields::Unnamed(x) => from_iter(x.unnamed.iter()),
+            syn::Fields::Named(x) => from_iter(x.named.iter()),
+        }
+    }
+
+    pub fn as_fields(&self) -> Option<&[Field]> {
+        match self {
+            Self::Unit => None,
+            Self::Fields(x) => Some(x),
+        }
+    }
+}
+
+struct Field {
+    attributes: Attributes,
+    name: FieldName,
+    /// The name of the field as serde sees it, if it differs.
+    serde_name: Option<String>,
+    ty: syn::Type,
+}
+
+impl Field {
+    pub fn new(i: syn::Index, field: syn::Field, rename_all: Option<&str>) -> syn::Result<Self> {
+        let serde_name = match field.ident {
+            Some(ref x) => serde_attr::rename(&field.attrs, "rename")
+                .or_else(|| rename_all.and_then(|rule| serde_attr::apply_rule(rule, x))),
+            None => None,
+        };
+
+        let attributes = Attributes::new(field.attrs)?;
+
+        let name = match field.ident {
+            Some(x) => FieldName::Named(x),
+            None => FieldName::Unnamed(i),
+        };
+
+        Ok(Self {
+            attributes,
+            name,
+            serde_name,
+            ty: field.ty,
+        })
+    }
+
+    /// The name of the field in the error context.
+    ///
+    /// This is the `#[merge(rename)]` of the field, or else its name in serde.
+    pub fn value_name(&self) -> syn::Expr {
+        if let Some(ref x) = self.attributes.rename {
+            return x.clone();
+        }
+
+        let name = match self.serde_name {
+            Some(ref x) => x.clone(),
+            None => self.name.to_token_stream().to_string(),
+        };
+
+        syn::Expr::Lit(syn::ExprLit {
+            attrs: Vec::new(),
+            lit: syn::Lit::Str(syn::LitStr::new(&name, self.name.span())),
+        })
+    }
+}
+
+impl Field {
+    /// The path of the `merge_ref` function for the field.
+    pub fn merge_path(&self, krate: &syn::Path) -> syn::Path {
+        if let Some(ref x) = self.attributes.with {
+            return x.clone();
+        }
+
+        match self.attributes.strategy {
+            Some(ref x) => syn::parse_quote!(#krate::__private::strategy::#x),
+            None => syn::parse_quote!(#krate::Merge),
+        }
+    }
+
+    /// The check of `#[merge(skip(deny_other))]` for the field of `other`.
+    pub fn deny_other_check(&self, krate: &syn::Path, other: &TokenStream) -> Option<TokenStream> {
+        let Some(SkipMode::DenyOther) = self.attributes.skip else {
+            return None;
+        };
+
+        let ty = &self.ty;
+        let value = self.value_name();
+        let msg = format!(
+            "field `{}` cannot be set here (it is managed internally)",
+            self.name.to_token_stream()
+        );
+
+        Some(quote! {
+            if !#krate::__private::is_unset::<#ty>(&#other) {
+                return ::core::result::Result::Err(#krate::Error::custom(#msg))
+                    .value(#value);
+            }
+        })
+    }
+}
+
+impl Field {
+    /// Guard `merge` with the `#[merge(skip_if)]` predicate of the field,
+    /// which is given `this`.
+    pub fn skip_if(&self, this: &TokenStream, merge: TokenStream) -> TokenStream {
+        match self.attributes.skip_if {
+            Some(ref predicate) => quote! {
+                if !#predicate(#this) {
+                    #merge
+                }
+            },
+            None => merge,
+        }
+    }
+
+    /// The `.value(...)` call that adds the name of the field to the error of
+    /// resolving it, unless it is flattened.
+    pub fn resolve_context(&self) -> Option<TokenStream> {
+        if self.attributes.flatten {
+            return None;
+        }
+
+        let value = self.value_name();
+        Some(quote! { .value(#value) })
+    }
+
+    /// Check whether the field is merged with its own `Merge` implementation.
+    pub fn is_merged_with_trait(&self) -> bool {
+        let Attributes {
+            skip,
+            with,
+            strategy,
+            overridable,
+            ..
+        } = &self.attributes;
+
+        skip.is_none() && with.is_none() && strategy.is_none() && !overridable
+    }
+}
+
+/// Check whether `ident` appears anywhere in `ty`.
+fn uses_ident(ty: &syn::Type, ident: &syn::Ident) -> bool {
+    fn walk(tokens: TokenStream, ident: &syn::Ident) -> bool {
+        tokens.into_iter().any(|x| match x {
+            proc_macro2::TokenTree::Ident(x) => x == *ident,
+            proc_macro2::TokenTree::Group(x) => walk(x.stream(), ident),
+            _ => false,
+        })
+    }
+
+    walk(ty.to_token_stream(), ident)
+}
+
+impl Variant {
+    /// A pattern for the variant that binds every field to `{prefix}_{i}`.
+    pub fn pattern(&self, prefix: &str) -> TokenStream {
+        let name = &self.name;
+        let fields = self.fields.as_fields().unwrap_or_default();
+
+        let names = fields.iter().map(|x| &x.name);
+        let bindings = (0..fields.len()).map(|i| format_ident!("{prefix}_{i}"));
+
+        quote! {
+            Self::#name { #(#names: #bindings),* }
+        }
+    }
+}
+
+enum SkipMode {
+    /// Keep the value of `self` and discard `other`.
+    Keep,
+    /// Like `Keep`, but error if `other` is not the default value.
+    DenyOther,
+}
+
+struct Attributes {
+    rename: Option<syn::Expr>,
+    skip: Option<SkipMode>,
+    skip_if: Option<syn::Path>,
+    with: Option<syn::Path>,
+    /// One of the built-in strategies in `module::__private::strategy`.
+    strategy: Option<syn::Ident>,
+    resolve: bool,
+    overridable: bool,
+    flatten: bool,
+}
+
+impl Attributes {
+    pub fn new(attrs: Vec<syn::Attribute>) -> syn::Result<Self> {
+        let mut rename = None;
+        let mut skip = None;
+        let mut skip_if = None;
+        let mut with = None;
+        let mut strategy = None;
+        let mut resolve = false;
+        let mut overridable = false;
+        let mut flatten = false;
+
+        for parsed_attr in parse_merge_attributes(attrs)? {
+            match parsed_attr {
+                parse::Attribute::Rename(x) => rename = Some(x.name),
+                parse::Attribute::Skip(x) => {
+                    skip = Some(match x.mode {
+                        None => SkipMode::Keep,
+                        Some(x) if x.ident == "deny_other" => SkipMode::DenyOther,
+                        Some(x) => {
+                            return Err(syn::Error::new(
+                                x.ident.span(),
+                                format!("unknown skip mode `{}`", x.ident),
+                            ));
+                        }
+                    })
+                }
+                parse::Attribute::SkipIf(x) => skip_if = Some(x.path),
+                parse::Attribute::With(x) => with = Some(x.path),
+                parse::Attribute::Strategy(x) => strategy = Some(x.ident),
+                parse::Attribute::Resolve(_) => resolve = true,
+                parse::Attribute::Overridable(_) => overridable = true,
+                parse::Attribute::Flatten(_) => flatten = true,
+                x @ (parse::Attribute::TrimNeutral(_)
+                | parse::Attribute::CompactNames(_)
+                | parse::Attribute::Crate(_)
+                | parse::Attribute::Bound(_)) => {
+                    return Err(syn::Error::new(
+                        x.span(),
+                        "this attribute can only be used on the container",
+                    ));
+                }
+            }
+        }
+
+        if let Some(ref strategy) = strategy {
+            if with.is_some() {
+                return Err(syn::Error::new(
+                    strategy.span(),
+                    "`strategy` and `with` cannot be used together",
+                ));
+            }
+
+            if !["last", "first", "lines"].iter().any(|x| strategy == x) {
+                return Err(syn::Error::new(
+                    strategy.span(),
+                    format!("unknown merge strategy `{strategy}`"),
+                ));
+            }
+        }
+
+        Ok(Self {
+            rename,
+            skip,
+            skip_if,
+            with,
+            strategy,
+            resolve,
+            overridable,
+            flatten,
+        })
+    }
+}
+
+struct ContainerAttributes {
+    trim_neutral: bool,
+    resolve: bool,
+    compact_names: bool,
+    /// The path of the `module` crate.
+    krate: syn::Path,
+    /// Replaces the `Merge` bounds of the generic parameters.
+    bound: Option<Punctuated<syn::WherePredicate, Token![,]>>,
+}
+
+impl ContainerAttributes {
+    pub fn new(attrs: Vec<syn::Attribute>) -> syn::Result<Self> {
+        let mut trim_neutral = false;
+        let mut resolve = false;
+        let mut compact_names = false;
+        let mut krate = syn::parse_quote!(::module);
+        let mut bound = None;
+
+        for parsed_attr in parse_merge_attributes(attrs)? {
+            match parsed_attr {
+                parse::Attribute::Crate(x) => krate = x.path,
+                parse::Attribute::Bound(x) => {
+                    bound = Some(x.predicates.parse_with(Punctuated::parse_terminated)?);
+                }
+                parse::Attribute::TrimNeutral(_) => trim_neutral = true,
+                parse::Attribute::Resolve(_) => resolve = true,
+                parse::Attribute::CompactNames(_) => compact_names = true,
+                x @ (parse::Attribute::Rename(_)
+                | parse::Attribute::Skip(_)
+                | parse::Attribute::SkipIf(_)
+                | parse::Attribute::With(_)
+                | parse::Attribute::Strategy(_)
+                | parse::Attribute::Overridable(_)
+                | parse::Attribute::Flatten(_)) => {
+                    return Err(syn::Error::new(
+                        x.span(),
+                        "this attribute can only be used on fields",
+                    ));
+                }
+            }
+        }
+
+        Ok(Self {
+            trim_neutral,
+            resolve,
+            compact_names,
+            krate,
+            bound,
+        })
+    }
+}
+
+fn parse_merge_attributes(attrs: Vec<syn::Attribute>) -> syn::Result<Vec<parse::Attribute>> {
+    let mut parsed = Vec::new();
+
+    for attr in attrs {
+        let syn::Meta::List(meta) = attr.meta else {
+            continue;
+        };
+
+        if meta.path.get_ident().is_none_or(|x| x != "merge") {
+            continue;
+        }
+
+        parsed.extend(Parser::parse2(
+            parse::Attributes::parse_terminated,
+            meta.tokens,
+        )?);
+    }
+
+    Ok(parsed)
+}
+
+enum FieldName {
+    Named(syn::Ident),
+    Unnamed(syn::Index),
+}
+
+impl FieldName {
+    pub fn is_named(&self, name: &str) -> bool {
+        matches!(self, Self::Named(x) if x == name)
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Named(x) => x.span(),
+            Self::Unnamed(x) => x.span,
+        }
+    }
+}
+
+impl ToTokens for FieldName {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Named(x) => x.to_tokens(tokens),
+            Self::Unnamed(x) => x.to_tokens(tokens),
+        }
+    }
+}
+
+/// Names of fields according to `#[serde(...)]` attributes.
+mod serde_attr {
+    /// Get the deserialization name of `key` (`rename` or `rename_all`) in
+    /// `#[serde(...)]` attributes.
+    pub fn rename(attrs: &[syn::Attribute], key: &str) -> Option<String> {
+        let mut out = None;
+
+        for attr in attrs.iter().filter(|x| x.path().is_ident("serde")) {
+            let _ = attr.parse_nested_meta(|x| {
+                if !x.path.is_ident(key) {
+                    // Skip over the arguments of other attributes.
+                    if x.input.peek(syn::Token![=]) {
+                        let _: syn::Expr = x.value()?.parse()?;
+                    } else if x.input.peek(syn::token::Paren) {
+                        x.parse_nested_meta(|x| {
+                            let _: syn::Expr = x.value()?.parse()?;
+                            Ok(())
+                        })?;
+                    }
+
+                    return Ok(());
+                }
+
+                if x.input.peek(syn::Token![=]) {
+                    let name: syn::LitStr = x.value()?.parse()?;
+                    out = Some(name.value());
+                } else {
+                    // `rename(serialize = "..", deserialize = "..")`
+                    x.parse_nested_meta(|x| {
+                        let name: syn::LitStr = x.value()?.parse()?;
+                        if x.path.is_ident("deserialize") {
+                            out = Some(name.value());
+                        }
+                        Ok(())
+                    })?;
+                }
+
+                Ok(())
+            });
+        }
+
+        out
+    }
+
+    /// Apply the `rename_all` rule `rule` to the field `ident`, like serde
+    /// does.
+    pub fn apply_rule(rule: &str, ident: &syn::Ident) -> Option<String> {
+        let name = ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name);
+
+        let pascal = || {
+            name.split('_')
+                .map(|x| {
+                    let mut chars = x.chars();
+                    match chars.next() {
+                        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                        None => String::new(),
+                    }
+                })
+                .collect::<String>()
+        };
+
+        let x = match rule {
+            "lowercase" => name.to_ascii_lowercase(),
+            "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+            "PascalCase" => pascal(),
+            "camelCase" => {
+                let pascal = pascal();
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            "snake_case" => name.to_owned(),
+            "kebab-case" => name.replace('_', "-"),
+            "SCREAMING-KEBAB-CASE" => name.to_ascii_uppercase().replace('_', "-"),
+            _ => return None,
+        };
+
+        Some(x)
+    }
+}
+
+#[allow(dead_code)]
+mod parse {
+    use super::*;
+
+    pub struct Rename {
+        pub rename: kw::rename,
+        pub equals: Token![=],
+        pub name: syn::Expr,
+    }
+
+    impl Parse for Rename {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let rename = input.parse()?;
+            let equals = input.parse()?;
+            let name = input.parse()?;
+
+            Ok(Self {
+                rename,
+                equals,
+                name,
+            })
+        }
+    }
+
+    pub struct Skip {
+        pub skip: kw::skip,
+        pub mode: Option<SkipArgs>,
+    }
+
+    impl Parse for Skip {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let skip = input.parse()?;
+
+            let mode = if input.peek(syn::token::Paren) {
+                Some(input.parse()?)
+            } else {
+                None
+            };
+
+            Ok(Self { skip, mode })
+        }
+    }
+
+    pub struct SkipArgs {
+        pub paren: syn::token::Paren,
+        pub ident: syn::Ident,
+    }
+
+    impl Parse for SkipArgs {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let content;
+            let paren = syn::parenthesized!(content in input);
+            let ident = content.parse()?;
+
+            Ok(Self { paren, ident })
+        }
+    }
+
+    pub struct SkipIf {
+        pub skip_if: kw::skip_if,
+        pub equals: Token![=],
+        pub path: syn::Path,
+    }
+
+    impl Parse for SkipIf {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let skip_if = input.parse()?;
+            let equals = input.parse()?;
+            let path = input.parse()?;
+
+            Ok(Self {
+                skip_if,
+                equals,
+                path,
+            })
+        }
+    }
+
+    pub struct With {
+        pub with: kw::with,
+        pub equals: Token![=],
+        pub path: syn::Path,
+    }
+
+    impl Parse for With {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let with = input.parse()?;
+            let equals = input.parse()?;
+            let path = input.parse()?;
+
+            Ok(Self { with, equals, path })
+        }
+    }
+
+    pub struct Strategy {
+        pub strategy: kw::strategy,
+        pub equals: Token![=],
+        pub ident: syn::Ident,
+    }
+
+    impl Parse for Strategy {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let strategy = input.parse()?;
+            let equals = input.parse()?;
+            let ident = input.parse()?;
+
+            Ok(Self {
+                strategy,
+                equals,
+                ident,
+            })
+        }
+    }
+
+    pub struct Crate {
+        pub krate: Token![crate],
+        pub equals: Token![=],
+        pub path: syn::Path,
+    }
+
+    impl Parse for Crate {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let krate = input.parse()?;
+            let equals = input.parse()?;
+            let path = input.parse()?;
+
+            Ok(Self {
+                krate,
+                equals,
+                path,
+            })
+        }
+    }
+
+    pub struct Bound {
+        pub bound: kw::bound,
+        pub equals: Token![=],
+        pub predicates: syn::LitStr,
+    }
+
+    impl Parse for Bound {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let bound = input.parse()?;
+            let equals = input.parse()?;
+            let predicates = input.parse()?;
+
+            Ok(Self {
+                bound,
+                equals,
+                predicates,
+            })
+        }
+    }
+
+    pub struct TrimNeutral {
+        pub trim_neutral: kw::trim_neutral,
+    }
+
+    impl Parse for TrimNeutral {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let trim_neutral = input.parse()?;
+
+            Ok(Self { trim_neutral })
+        }
+    }
+
+    pub struct Resolve {
+        pub resolve: kw::resolve,
+    }
+
+    impl Parse for Resolve {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let resolve = input.parse()?;
+
+            Ok(Self { resolve })
+        }
+    }
+
+    pub struct Overridable {
+        pub overridable: kw::overridable,
+    }
+
+    impl Parse for Overridable {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let overridable = input.parse()?;
+
+            Ok(Self { overridable })
+        }
+    }
+
+    pub struct Flatten {
+        pub flatten: kw::flatten,
+    }
+
+    impl Parse for Flatten {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let flatten = input.parse()?;
+
+            Ok(Self { flatten })
+        }
+    }
+
+    pub struct CompactNames {
+        pub compact_names: kw::compact_names,
+    }
+
+    impl Parse for CompactNames {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let compact_names = input.parse()?;
+
+            Ok(Self { compact_names })
+        }
+    }
+
+    pub enum Attribute {
+        Rename(Rename),
+        Skip(Skip),
+        SkipIf(SkipIf),
+        With(With),
+        Strategy(Strategy),
+        TrimNeutral(TrimNeutral),
+        Resolve(Resolve),
+        Overridable(Overridable),
+        Flatten(Flatten),
+        CompactNames(CompactNames),
+        Crate(Crate),
+        Bound(Bound),
+    }
+
+    impl Attribute {
+        pub fn span(&self) -> Span {
+            match self {
+                Self::Rename(x) => x.rename.span,
+                Self::Skip(x) => x.skip.span,
+                Self::SkipIf(x) => x.skip_if.span,
+                Self::With(x) => x.with.span,
+                Self::Strategy(x) => x.strategy.span,
+                Self::TrimNeutral(x) => x.trim_neutral.span,
+                Self::Resolve(x) => x.resolve.span,
+                Self::Overridable(x) => x.overridable.span,
+                Self::Flatten(x) => x.flatten.span,
+                Self::CompactNames(x) => x.compact_names.span,
+                Self::Crate(x) => x.krate.span,
+                Self::Bound(x) => x.bound.span,
+            }
+        }
+    }
+
+    impl Parse for Attribute {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let lookahead = input.lookahead1();
+
+            if lookahead.peek(kw::rename) {
+                let x = Rename::parse(input)?;
+                Ok(Self::Rename(x))
+            } else if lookahead.peek(kw::skip_if) {
+                let x = SkipIf::parse(input)?;
+                Ok(Self::SkipIf(x))
+            } else if lookahead.peek(kw::skip) {
+                let x = Skip::parse(input)?;
+                Ok(Self::Skip(x))
+            } else if lookahead.peek(kw::with) {
+                let x = With::parse(input)?;
+                Ok(Self::With(x))
+            } else if lookahead.peek(kw::strategy) {
+                let x = Strategy::parse(input)?;
+                Ok(Self::Strategy(x))
+            } else if lookahead.peek(kw::trim_neutral) {
+                let x = TrimNeutral::parse(input)?;
+                Ok(Self::TrimNeutral(x))
+            } else if lookahead.peek(kw::resolve) {
+                let x = Resolve::parse(input)?;
+                Ok(Self::Resolve(x))
+            } else if lookahead.peek(kw::overridable) {
+                let x = Overridable::parse(input)?;
+                Ok(Self::Overridable(x))
+            } else if lookahead.peek(kw::flatten) {
+                let x = Flatten::parse(input)?;
+                Ok(Self::Flatten(x))
+            } else if lookahead.peek(kw::compact_names) {
+                let x = CompactNames::parse(input)?;
+                Ok(Self::CompactNames(x))
+            } else if lookahead.peek(Token![crate]) {
+                let x = Crate::parse(input)?;
+                Ok(Self::Crate(x))
+            } else if lookahead.peek(kw::bound) {
+                let x = Bound::parse(input)?;
+                Ok(Self::Bound(x))
+            } else {
+                Err(lookahead.error())
+            }
+        }
+    }
+
+    pub type Attributes = Punctuated<Attribute, Token![,]>;
+
+    mod kw {
+        syn::custom_keyword!(rename);
+        syn::custom_keyword!(skip);
+        syn::custom_keyword!(skip_if);
+        syn::custom_keyword!(with);
+        syn::custom_keyword!(strategy);
+        syn::custom_keyword!(trim_neutral);
+        syn::custom_keyword!(resolve);
+        syn::custom_keyword!(overridable);
+        syn::custom_keyword!(flatten);
+        syn::custom_keyword!(compact_names);
+        syn::custom_keyword!(bound);
+    }
+}
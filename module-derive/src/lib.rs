@@ -1,14 +1,71 @@
 //! Derive macros for the [`module`](https://github.com/threadexio/module-rs) crate.
 #![forbid(unsafe_code)]
 
-mod merge;
+mod overridable_struct;
+#[cfg(feature = "test-macros")]
+mod test_matrix;
 
 /// Derive the `Merge` trait.
 ///
-/// This macro can be used only on `struct` items.
+/// This macro can be used on `struct` and `enum` items.
 ///
-/// Generate a `Merge` implementation for the annotated type. The generated code
-/// calls `.merge` and `.merge_ref` on each field.
+/// Generate a `Merge` implementation for the annotated type. The generated
+/// `merge_ref` calls `.merge_ref` on each field, and `merge` is the default one
+/// of the trait.
+///
+/// For enums, 2 values of the same variant are merged field by field, exactly
+/// like structs, and 2 unit variants merge to themselves. Values of different
+/// variants always collide.
+///
+/// # Container attributes
+///
+/// ## `trim_neutral`
+///
+/// * **Syntax:** `#[merge(trim_neutral)]`
+///
+/// Do not merge fields of `other` that are neutral, like `None` or empty
+/// collections. Instead, the field of `self` is kept as is. This avoids the
+/// cost of merging large structs where most fields are usually unset.
+///
+/// The type of every field that is not skipped must implement `Neutral`.
+///
+/// ## `resolve`
+///
+/// * **Syntax:** `#[merge(resolve)]`
+///
+/// Also implement `Resolve` for the type. The generated implementation
+/// resolves every field marked with `#[merge(resolve)]`, in order, and leaves
+/// all other fields as they are.
+///
+/// ## `compact_names`
+///
+/// * **Syntax:** `#[merge(compact_names)]`
+///
+/// Generate smaller code for adding field names to errors. All field names
+/// are put in a single static array and errors are handled by one shared,
+/// non-generic function, instead of a generic `Context::value` call for each
+/// field. Errors render exactly the same.
+///
+/// This is meant for large schemas where binary size matters. How much it
+/// saves depends on the schema and the compiler, so measure it. The
+/// `module-size` crate in the repository compares both modes. Fields renamed
+/// to anything other than a string literal are handled as usual.
+///
+/// ## `crate`
+///
+/// * **Syntax:** `#[merge(crate = path::to::module)]`
+///
+/// Use `path::to::module` instead of `::module` in the generated code. This is
+/// needed when the `module` crate is only available through a re-export.
+///
+/// ## `bound`
+///
+/// * **Syntax:** `#[merge(bound = "T: Merge")]`
+///
+/// Use these where predicates instead of the generated ones. By default, every
+/// generic type parameter that is used by a field merged with its own `Merge`
+/// implementation must be `Merge`. Parameters only used by fields with `skip`,
+/// `with`, `strategy` or `overridable` are not bounded.
 ///
 /// # Field attributes
 ///
@@ -18,6 +75,10 @@ mod merge;
 ///
 /// Rename a field so it appears under a different name in the error context.
 ///
+/// Without it, the name of the field is its name in serde. That is the
+/// `#[serde(rename)]` of the field, or the field name transformed by the
+/// `#[serde(rename_all)]` of the struct or enum variant.
+///
 /// ## `skip`
 ///
 /// * **Syntax:** `#[merge(skip)]`
@@ -25,16 +86,172 @@ mod merge;
 /// Completely skip merging this field. This instructs the macro to not emit
 /// code for merging the field. Skipped fields retain the value of `self`.
 ///
+/// * **Syntax:** `#[merge(skip(deny_other))]`
+///
+/// Like `skip`, but instead of silently discarding the field of `other`,
+/// return an error if it is not equal to its `Default` value. Use this for
+/// fields that are managed internally and must not be set by modules. The type
+/// of the field must implement `PartialEq` and `Default`.
+///
+/// ## `skip_if`
+///
+/// * **Syntax:** `#[merge(skip_if = path::to::predicate)]`
+///
+/// Skip merging this field if `predicate(&self.field)` returns `true`. The
+/// field keeps the value of `self`, just like with `skip`. Otherwise, it is
+/// merged as usual.
+///
 /// ## `with`
 ///
 /// * **Syntax:** `#[merge(with = path::to::custom::merge)]`
 ///
-/// Use `$module::merge` and `$module::merge_ref` to merge this field instead of
-/// its own `Merge` implementation.
+/// Use `$module::merge_ref` to merge this field instead of its own `Merge`
+/// implementation.
 ///
 /// This can be used to make external types `Merge` without having to use
 /// newtypes.
+///
+/// ## `flatten`
+///
+/// * **Syntax:** `#[merge(flatten)]`
+///
+/// Do not add the name of this field to the error context. Errors of the
+/// field look like errors of the fields of the parent, like with
+/// `#[serde(flatten)]`.
+///
+/// ## `strategy`
+///
+/// * **Syntax:** `#[merge(strategy = last)]`
+///
+/// Merge this field like one of the wrappers of `module::types`, without
+/// changing its type. The strategy is one of:
+///
+/// * `last`: like `Last`, keep the value of `other`
+/// * `first`: like `First`, keep the value of `self`
+/// * `lines`: like `Lines`, concatenate 2 `String`s as lines
+///
+/// This cannot be used together with `with`.
+///
+/// ## `resolve`
+///
+/// * **Syntax:** `#[merge(resolve)]`
+///
+/// Resolve this field in the `Resolve` implementation of the type. The type of
+/// the field must implement `Resolve`. Requires `#[merge(resolve)]` on the
+/// container.
+///
+/// ## `overridable`
+///
+/// * **Syntax:** `#[merge(overridable)]`
+///
+/// Merge this field like an `Overridable`, using the priority stored by
+/// `#[overridable_struct]`. Requires `#[overridable_struct]` on the struct.
 #[proc_macro_derive(Merge, attributes(merge))]
 pub fn merge(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    self::merge::merge(item)
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    module_derive_internals::expand_merge(input).into()
+}
+
+/// Give plain fields the priorities of `Overridable`.
+///
+/// Fields marked with `#[merge(overridable)]` keep their type, but they merge
+/// like an `Overridable` of it: the value with the least priority wins and
+/// equal priorities collide. Fields that a module does not set have no
+/// priority and never win over fields that it does.
+///
+/// The priority of each field is stored in a hidden `__priorities` field that
+/// this attribute adds to the struct. Code that constructs the struct
+/// directly must initialize it with `Default::default()`.
+///
+/// If the struct derives `Deserialize`, `#[merge(overridable)]` fields
+/// deserialize just like `Overridable`, either as the plain value or as
+/// `{ value, priority }`. A field that is missing is not set and takes its
+/// `Default` value.
+///
+/// This attribute must be placed above `#[derive(...)]` and the struct must
+/// also derive `Merge`.
+///
+/// ```rust,ignore
+/// #[module::overridable_struct]
+/// #[derive(Deserialize, Merge)]
+/// struct Config {
+///     #[merge(overridable)]
+///     port: u16,
+///     hosts: Vec<String>,
+/// }
+///
+/// let config: Config = ...;
+/// let port: u16 = config.port;
+/// ```
+#[proc_macro_attribute]
+pub fn overridable_struct(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    self::overridable_struct::expand(attr.into(), input).into()
+}
+
+/// Generate tests for how the fields of a struct merge.
+///
+/// This attribute must be applied to an inline module that contains a
+/// `matrix!` declaration. Each entry of the declaration names a struct and the
+/// expected merge behavior of each one of its fields:
+///
+/// ```rust,ignore
+/// #[merge_test_matrix]
+/// mod tests {
+///     use super::*;
+///
+///     matrix! {
+///         Config {
+///             port: optional,
+///             rustflags: append,
+///             jobs: override_lowest,
+///             mode: (kept, filled, collided, kept),
+///         }
+///     }
+/// }
+/// ```
+///
+/// For every field, 4 `#[test]` functions are generated. Each one merges 2
+/// values of the type of the field and checks the outcome against the
+/// declaration. The values are taken from the `TestValues` trait:
+///
+/// | Case                     | Left         | Right        |
+/// | ------------------------ | ------------ | ------------ |
+/// | `(set, unset)`           | `sample_a()` | `unset()`    |
+/// | `(unset, set)`           | `unset()`    | `sample_a()` |
+/// | `(set, set-equal)`       | `sample_a()` | `sample_a()` |
+/// | `(set, set-different)`   | `sample_a()` | `sample_b()` |
+///
+/// The outcome of each case is one of:
+///
+/// * `kept`: The result is the left value.
+/// * `filled`: The result is the right value.
+/// * `merged`: The result is neither of the values.
+/// * `collided`: Merging failed with a collision.
+///
+/// The expected outcomes of a field are either given explicitly as a tuple, in
+/// the order of the cases above, or by one of the following strategies:
+///
+/// | Strategy          | Outcomes                                   | Typical type       |
+/// | ----------------- | ------------------------------------------ | ------------------ |
+/// | `collide`         | `(collided, collided, collided, collided)` | `u16`              |
+/// | `optional`        | `(kept, filled, collided, collided)`       | `Option<u16>`      |
+/// | `append`          | `(kept, filled, merged, merged)`           | `Vec<String>`      |
+/// | `first`           | `(kept, kept, kept, kept)`                 | `First<u16>`       |
+/// | `last`            | `(filled, filled, kept, filled)`           | `Last<u16>`        |
+/// | `override_lowest` | `(kept, filled, collided, kept)`           | `Overridable<u16>` |
+///
+/// The declaration must list every field of the struct and nothing else,
+/// otherwise compilation fails.
+#[cfg(feature = "test-macros")]
+#[proc_macro_attribute]
+pub fn merge_test_matrix(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let item = syn::parse_macro_input!(item as syn::ItemMod);
+    self::test_matrix::expand(attr.into(), item).into()
 }
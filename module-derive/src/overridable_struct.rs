@@ -0,0 +1,237 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+
+pub fn expand(attr: TokenStream, mut input: syn::DeriveInput) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(attr.span(), "overridable_struct does not take arguments")
+            .to_compile_error();
+    }
+
+    if !input.generics.params.is_empty() {
+        return syn::Error::new(
+            input.generics.span(),
+            "overridable_struct does not support generic structs",
+        )
+        .to_compile_error();
+    }
+
+    let syn::Data::Struct(syn::DataStruct {
+        fields: syn::Fields::Named(ref mut fields),
+        ..
+    }) = input.data
+    else {
+        return syn::Error::new(
+            input.ident.span(),
+            "overridable_struct can only be used on structs with named fields",
+        )
+        .to_compile_error();
+    };
+
+    let krate = match krate(&input.attrs) {
+        Ok(x) => x,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let priorities = format_ident!("__{name}Priorities");
+    let repr = format_ident!("__{name}Repr");
+
+    let overridable: Vec<&syn::Field> = fields
+        .named
+        .iter()
+        .filter(|x| is_overridable(&x.attrs))
+        .collect();
+
+    if overridable.is_empty() {
+        return syn::Error::new(
+            input.ident.span(),
+            "expected at least one field with `#[merge(overridable)]`",
+        )
+        .to_compile_error();
+    }
+
+    let priority_fields = overridable.iter().map(|x| {
+        let name = &x.ident;
+        quote! {
+            #name: ::core::option::Option<#krate::types::overridable::Priority>,
+        }
+    });
+
+    let priorities_struct = quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #vis struct #priorities {
+            #(#priority_fields)*
+        }
+    };
+
+    // Only generate the serde glue if the struct is `Deserialize`. Otherwise,
+    // `#[serde]` attributes would not compile.
+    let derives = derives(&input.attrs);
+    let deserialize = derives.iter().any(|x| x == "Deserialize");
+    let serde = deserialize || derives.iter().any(|x| x == "Serialize");
+
+    let repr_struct = deserialize.then(|| {
+        let container_attrs: Vec<syn::Attribute> = input
+            .attrs
+            .iter()
+            .filter(|x| x.path().is_ident("serde"))
+            .cloned()
+            .collect();
+
+        let repr_fields = fields.named.iter().map(|x| {
+            let name = &x.ident;
+            let ty = &x.ty;
+            let attrs = x.attrs.iter().filter(|x| x.path().is_ident("serde"));
+
+            if is_overridable(&x.attrs) {
+                quote! {
+                    #(#attrs)*
+                    #name: ::core::option::Option<#krate::types::Overridable<#ty>>,
+                }
+            } else {
+                quote! {
+                    #(#attrs)*
+                    #name: #ty,
+                }
+            }
+        });
+
+        let (take, assign): (Vec<_>, Vec<_>) = fields
+            .named
+            .iter()
+            .map(|x| {
+                let name = &x.ident;
+
+                if is_overridable(&x.attrs) {
+                    (
+                        quote! {
+                            let (#name, __priority) = match __repr.#name {
+                                ::core::option::Option::Some(x) => {
+                                    let priority = x.priority();
+                                    (x.into_value(), ::core::option::Option::Some(priority))
+                                }
+                                ::core::option::Option::None => {
+                                    (::core::default::Default::default(), ::core::option::Option::None)
+                                }
+                            };
+                            __priorities.#name = __priority;
+                        },
+                        quote! { #name, },
+                    )
+                } else {
+                    (quote! { let #name = __repr.#name; }, quote! { #name, })
+                }
+            })
+            .unzip();
+
+        let repr_lit = syn::LitStr::new(&repr.to_string(), Span::call_site());
+
+        input.attrs.push(syn::parse_quote!(#[serde(from = #repr_lit)]));
+
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #[derive(::serde::Deserialize)]
+            #(#container_attrs)*
+            #vis struct #repr {
+                #(#repr_fields)*
+            }
+
+            impl ::core::convert::From<#repr> for #name {
+                fn from(__repr: #repr) -> Self {
+                    let mut __priorities = #priorities::default();
+                    #(#take)*
+
+                    Self {
+                        #(#assign)*
+                        __priorities,
+                    }
+                }
+            }
+        }
+    });
+
+    let serde_skip = serde.then(|| quote!(#[serde(skip)]));
+    fields.named.push(syn::parse_quote! {
+        #[doc(hidden)]
+        #[merge(skip)]
+        #serde_skip
+        __priorities: #priorities
+    });
+
+    quote! {
+        #input
+        #priorities_struct
+        #repr_struct
+    }
+}
+
+/// Get the path of the `module` crate from `#[merge(crate = ...)]`, just like
+/// `#[derive(Merge)]` does.
+fn krate(attrs: &[syn::Attribute]) -> syn::Result<syn::Path> {
+    let mut krate = syn::parse_quote!(::module);
+
+    for attr in attrs.iter().filter(|x| x.path().is_ident("merge")) {
+        attr.parse_nested_meta(|x| {
+            if x.path.is_ident("crate") {
+                krate = x.value()?.parse()?;
+            } else if x.input.peek(syn::Token![=]) {
+                let _: syn::Expr = x.value()?.parse()?;
+            } else if x.input.peek(syn::token::Paren) {
+                x.parse_nested_meta(|_| Ok(()))?;
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(krate)
+}
+
+fn is_overridable(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let syn::Meta::List(ref meta) = attr.meta else {
+            return false;
+        };
+
+        if !meta.path.is_ident("merge") {
+            return false;
+        }
+
+        let mut found = false;
+        let _ = meta.parse_nested_meta(|x| {
+            found |= x.path.is_ident("overridable");
+
+            // Skip over the arguments of other attributes.
+            if x.input.peek(syn::Token![=]) {
+                let _: syn::Expr = x.value()?.parse()?;
+            } else if x.input.peek(syn::token::Paren) {
+                x.parse_nested_meta(|_| Ok(()))?;
+            }
+
+            Ok(())
+        });
+
+        found
+    })
+}
+
+/// Get the last segment of every path in `#[derive(...)]` attributes.
+fn derives(attrs: &[syn::Attribute]) -> Vec<String> {
+    let mut out = Vec::new();
+
+    for attr in attrs.iter().filter(|x| x.path().is_ident("derive")) {
+        let _ = attr.parse_nested_meta(|x| {
+            if let Some(last) = x.path.segments.last() {
+                out.push(last.ident.to_string());
+            }
+            Ok(())
+        });
+    }
+
+    out
+}
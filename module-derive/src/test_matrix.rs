@@ -0,0 +1,289 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Token, braced, parenthesized};
+
+pub fn expand(attr: TokenStream, mut item: syn::ItemMod) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(attr.span(), "merge_test_matrix does not take arguments")
+            .to_compile_error();
+    }
+
+    let Some((_, ref mut items)) = item.content else {
+        return syn::Error::new(
+            item.ident.span(),
+            "merge_test_matrix can only be used on inline modules",
+        )
+        .to_compile_error();
+    };
+
+    let mut found = false;
+    let mut expanded = Vec::with_capacity(items.len());
+
+    for x in items.drain(..) {
+        match x {
+            syn::Item::Macro(ref m) if m.mac.path.is_ident("matrix") => {
+                found = true;
+
+                let tokens = match syn::parse2::<Matrix>(m.mac.tokens.clone()) {
+                    Ok(matrix) => matrix.to_tokens(),
+                    Err(e) => e.to_compile_error(),
+                };
+
+                expanded.push(syn::Item::Verbatim(tokens));
+            }
+            x => expanded.push(x),
+        }
+    }
+
+    if !found {
+        return syn::Error::new(
+            item.ident.span(),
+            "expected a `matrix! { ... }` declaration in the module",
+        )
+        .to_compile_error();
+    }
+
+    *items = expanded;
+    quote! { #item }
+}
+
+/// `matrix! { Config { port: collide, ... } ... }`
+struct Matrix {
+    tables: Vec<Table>,
+}
+
+impl Parse for Matrix {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut tables = Vec::new();
+        while !input.is_empty() {
+            tables.push(input.parse()?);
+            let _: Option<Token![,]> = input.parse()?;
+        }
+
+        Ok(Self { tables })
+    }
+}
+
+impl Matrix {
+    fn to_tokens(&self) -> TokenStream {
+        self.tables.iter().map(Table::to_tokens).collect()
+    }
+}
+
+/// `Config { port: collide, ... }`
+struct Table {
+    path: syn::Path,
+    brace: syn::token::Brace,
+    rows: Punctuated<Row, Token![,]>,
+}
+
+impl Parse for Table {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+
+        let content;
+        let brace = braced!(content in input);
+        let rows = content.parse_terminated(Row::parse, Token![,])?;
+
+        Ok(Self { path, brace, rows })
+    }
+}
+
+impl Table {
+    fn to_tokens(&self) -> TokenStream {
+        let Self { path, brace, rows } = self;
+
+        let name = path
+            .segments
+            .last()
+            .map(|x| snake_case(&x.ident.to_string()))
+            .unwrap_or_default();
+
+        // Destructuring the struct without `..` fails to compile if the table
+        // does not list every field, or lists fields the struct does not have.
+        let mut pattern = path.to_token_stream();
+        brace.surround(&mut pattern, |tokens| {
+            for field in rows.iter().map(|x| &x.field) {
+                tokens.extend(quote_spanned!(field.span() => #field: _,));
+            }
+        });
+        let exhaustive = quote_spanned! { path.span() =>
+            const _: fn(#path) = |#pattern| {};
+        };
+
+        let mut tests = Vec::with_capacity(rows.len() * CASES.len());
+        for row in rows {
+            let field = &row.field;
+            let field_name = field.to_string();
+            let field_name = field_name.trim_start_matches("r#");
+            let display = format!(
+                "{}.{field_name}",
+                quote!(#path).to_string().replace(' ', "")
+            );
+
+            for ((case, suffix), outcome) in CASES.iter().zip(row.expected.outcomes()) {
+                let test = format_ident!("{name}_{field_name}_{suffix}");
+                let case = syn::Ident::new(case, Span::call_site());
+                let outcome = syn::Ident::new(outcome, row.expected.span());
+
+                tests.push(quote! {
+                    #[test]
+                    fn #test() {
+                        ::module::test_util::matrix::check(
+                            |x: &#path| &x.#field,
+                            #display,
+                            ::module::test_util::matrix::Case::#case,
+                            ::module::test_util::matrix::Outcome::#outcome,
+                        );
+                    }
+                });
+            }
+        }
+
+        quote! {
+            #exhaustive
+            #(#tests)*
+        }
+    }
+}
+
+/// The `Case` variants, in the order of the outcomes in a table, along with
+/// the suffix of their test names.
+const CASES: [(&str, &str); 4] = [
+    ("SetUnset", "set_unset"),
+    ("UnsetSet", "unset_set"),
+    ("SetSetEqual", "set_set_equal"),
+    ("SetSetDifferent", "set_set_different"),
+];
+
+/// `port: collide` or `port: (kept, filled, collided, collided)`
+struct Row {
+    field: syn::Ident,
+    expected: Expected,
+}
+
+impl Parse for Row {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let field = input.parse()?;
+        let _: Token![:] = input.parse()?;
+        let expected = input.parse()?;
+
+        Ok(Self { field, expected })
+    }
+}
+
+enum Expected {
+    Strategy(syn::Ident, [&'static str; 4]),
+    Explicit(Span, [&'static str; 4]),
+}
+
+const STRATEGIES: &[(&str, [&str; 4])] = &[
+    ("collide", ["Collided", "Collided", "Collided", "Collided"]),
+    ("optional", ["Kept", "Filled", "Collided", "Collided"]),
+    ("append", ["Kept", "Filled", "Merged", "Merged"]),
+    ("first", ["Kept", "Kept", "Kept", "Kept"]),
+    ("last", ["Filled", "Filled", "Kept", "Filled"]),
+    ("override_lowest", ["Kept", "Filled", "Collided", "Kept"]),
+];
+
+const OUTCOMES: [(&str, &str); 4] = [
+    ("kept", "Kept"),
+    ("filled", "Filled"),
+    ("merged", "Merged"),
+    ("collided", "Collided"),
+];
+
+impl Parse for Expected {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::token::Paren) {
+            let content;
+            let paren = parenthesized!(content in input);
+            let list = content.parse_terminated(syn::Ident::parse, Token![,])?;
+
+            if list.len() != CASES.len() {
+                return Err(syn::Error::new(
+                    paren.span.join(),
+                    format!(
+                        "expected {} outcomes: (set, unset), (unset, set), (set, set-equal), (set, set-different)",
+                        CASES.len()
+                    ),
+                ));
+            }
+
+            let mut outcomes = [""; 4];
+            for (i, x) in list.iter().enumerate() {
+                outcomes[i] = OUTCOMES
+                    .iter()
+                    .find(|(name, _)| x == name)
+                    .map(|(_, variant)| *variant)
+                    .ok_or_else(|| {
+                        syn::Error::new(
+                            x.span(),
+                            format!(
+                                "unknown outcome `{x}`, expected one of: {}",
+                                OUTCOMES.map(|(name, _)| name).join(", ")
+                            ),
+                        )
+                    })?;
+            }
+
+            return Ok(Self::Explicit(paren.span.join(), outcomes));
+        }
+
+        let strategy: syn::Ident = input.parse()?;
+        let outcomes = STRATEGIES
+            .iter()
+            .find(|(name, _)| strategy == name)
+            .map(|(_, x)| *x)
+            .ok_or_else(|| {
+                syn::Error::new(
+                    strategy.span(),
+                    format!(
+                        "unknown strategy `{strategy}`, expected one of: {}",
+                        STRATEGIES
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                )
+            })?;
+
+        Ok(Self::Strategy(strategy, outcomes))
+    }
+}
+
+impl Expected {
+    fn span(&self) -> Span {
+        match self {
+            Self::Strategy(x, _) => x.span(),
+            Self::Explicit(x, _) => *x,
+        }
+    }
+
+    fn outcomes(&self) -> [&'static str; 4] {
+        match self {
+            Self::Strategy(_, x) | Self::Explicit(_, x) => *x,
+        }
+    }
+}
+
+fn snake_case(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 4);
+
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
@@ -0,0 +1,68 @@
+//! Generate the schema measured by the binaries of this crate.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const STRUCTS: usize = 100;
+
+/// Names that appear in many structs, like in real schemas.
+const COMMON: &[&str] = &[
+    "enabled", "name", "path", "timeout", "retries", "hosts", "port", "user", "labels", "level",
+];
+
+const TYPES: &[&str] = &[
+    "Option<u32>",
+    "Option<String>",
+    "Vec<String>",
+    "Option<bool>",
+];
+
+fn schema(attr: &str) -> String {
+    let mut out = String::new();
+
+    for i in 0..STRUCTS {
+        let _ = writeln!(
+            out,
+            "#[derive(Debug, Default, Merge)]\n{attr}\nstruct S{i} {{"
+        );
+
+        for (j, name) in COMMON.iter().enumerate() {
+            let ty = TYPES[(i + j) % TYPES.len()];
+            let _ = writeln!(out, "    {name}: {ty},");
+        }
+
+        for j in 0..4 {
+            let ty = TYPES[j % TYPES.len()];
+            let _ = writeln!(out, "    s{i}_field{j}: {ty},");
+        }
+
+        let _ = writeln!(out, "}}\n");
+    }
+
+    let _ = writeln!(
+        out,
+        "#[derive(Debug, Default, Merge)]\n{attr}\nstruct Schema {{"
+    );
+    for i in 0..STRUCTS {
+        let _ = writeln!(out, "    s{i}: S{i},");
+    }
+    let _ = writeln!(out, "}}");
+
+    out
+}
+
+fn main() {
+    let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo");
+    let out_dir = Path::new(&out_dir);
+
+    fs::write(out_dir.join("normal.rs"), schema("")).expect("failed to write schema");
+    fs::write(
+        out_dir.join("compact.rs"),
+        schema("#[merge(compact_names)]"),
+    )
+    .expect("failed to write schema");
+
+    println!("cargo::rerun-if-changed=build.rs");
+}
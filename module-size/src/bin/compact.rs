@@ -0,0 +1,18 @@
+//! The generated schema with `#[merge(compact_names)]`.
+//!
+//! See: `module-size/README.md`.
+
+use std::hint::black_box;
+
+use module::Merge;
+
+include!(concat!(env!("OUT_DIR"), "/compact.rs"));
+
+fn main() {
+    let a = black_box(Schema::default());
+    let b = black_box(Schema::default());
+
+    if let Err(e) = a.merge(b) {
+        eprintln!("{e}");
+    }
+}
@@ -0,0 +1,18 @@
+//! The generated schema with the default codegen.
+//!
+//! See: `module-size/README.md`.
+
+use std::hint::black_box;
+
+use module::Merge;
+
+include!(concat!(env!("OUT_DIR"), "/normal.rs"));
+
+fn main() {
+    let a = black_box(Schema::default());
+    let b = black_box(Schema::default());
+
+    if let Err(e) = a.merge(b) {
+        eprintln!("{e}");
+    }
+}
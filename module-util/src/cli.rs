@@ -0,0 +1,370 @@
+//! Reporting errors from command line apps.
+//!
+//! Most command line apps do the same thing when evaluation fails: print the
+//! error to the terminal and exit with some code that says what went wrong.
+//! [`exit_on_error`] does exactly that. [`render_for_terminal`] does only the
+//! printing part, for apps that want to do something else before exiting.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # use module::Merge;
+//! # use serde::Deserialize;
+//! use module_util::cli::exit_on_error;
+//! use module_util::file::toml;
+//!
+//! #[derive(Deserialize, Merge)]
+//! struct Config {
+//!     // ...
+//! }
+//!
+//! let config: Config = exit_on_error(toml("config.toml"));
+//! ```
+
+use std::env;
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::io::{self, IsTerminal};
+use std::process;
+
+use module::merge::{Error, ErrorKind};
+
+/// Whether to use colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorChoice {
+    /// Use colors only if the standard error is a terminal and the
+    /// [`NO_COLOR`] environment variable is not set.
+    ///
+    /// [`NO_COLOR`]: https://no-color.org
+    #[default]
+    Auto,
+    /// Always use colors.
+    Always,
+    /// Never use colors.
+    Never,
+}
+
+impl ColorChoice {
+    /// Decide whether to use colors for the standard error.
+    pub fn use_color(self) -> bool {
+        resolve(self, env::var_os("NO_COLOR"), io::stderr().is_terminal())
+    }
+}
+
+fn resolve(choice: ColorChoice, no_color: Option<OsString>, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => is_terminal && no_color.is_none_or(|x| x.is_empty()),
+    }
+}
+
+/// Get the exit code for `error`.
+///
+/// The exit codes follow `sysexits.h` and will not change.
+///
+/// | Error                                   | Exit code           |
+/// | --------------------------------------- | ------------------- |
+/// | `Collision`                             | 65 (`EX_DATAERR`)   |
+/// | `Cycle`                                 | 65 (`EX_DATAERR`)   |
+/// | IO error with kind `NotFound`           | 66 (`EX_NOINPUT`)   |
+/// | IO error with kind `PermissionDenied`   | 77 (`EX_NOPERM`)    |
+/// | any other IO error                      | 74 (`EX_IOERR`)     |
+/// | any other `Custom`                      | 78 (`EX_CONFIG`)    |
+/// | `Multiple`                              | that of the first   |
+///
+/// IO errors are detected with [`Error::io_kind`].
+pub fn exit_code(error: &Error) -> u8 {
+    match (&error.kind, error.io_kind()) {
+        (ErrorKind::Multiple(errors), _) => errors.first().map_or(78, exit_code),
+        (ErrorKind::Collision | ErrorKind::Cycle, _) => 65,
+        (_, Some(io::ErrorKind::NotFound)) => 66,
+        (_, Some(io::ErrorKind::PermissionDenied)) => 77,
+        (_, Some(_)) => 74,
+        _ => 78,
+    }
+}
+
+/// Render `error` for printing to a terminal.
+///
+/// This is like the [`Display`] implementation of [`Error`], except that:
+///
+/// * The message starts with `error: `.
+/// * It ends with a hint on how to fix the error, if there is one.
+/// * Each error of an [`ErrorKind::Multiple`] is rendered the same way.
+/// * If `color` says so, the message is highlighted with ANSI escape codes.
+///   The kind of the error is in bold, the value path is underlined and the
+///   module backtrace is dimmed.
+///
+/// [`Display`]: std::fmt::Display
+pub fn render_for_terminal(error: &Error, color: ColorChoice) -> String {
+    let style = Style {
+        color: color.use_color(),
+    };
+
+    let mut out = String::new();
+    let _ = render(&mut out, error, style);
+    out
+}
+
+/// Unwrap `result` or print the error and exit.
+///
+/// The error is printed to the standard error with [`render_for_terminal`]
+/// and [`ColorChoice::Auto`]. The process exits with the [`exit_code`] of the
+/// error.
+pub fn exit_on_error<T>(result: Result<T, Error>) -> T {
+    match result {
+        Ok(x) => x,
+        Err(e) => {
+            eprint!("{}", render_for_terminal(&e, ColorChoice::Auto));
+            process::exit(exit_code(&e).into())
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(self, out: &mut String, code: &str, text: &str) {
+        if self.color {
+            let _ = write!(out, "\x1b[{code}m{text}\x1b[0m");
+        } else {
+            out.push_str(text);
+        }
+    }
+}
+
+const RED_BOLD: &str = "1;31";
+const CYAN_BOLD: &str = "1;36";
+const BOLD: &str = "1";
+const DIM: &str = "2";
+const UNDERLINE: &str = "4";
+
+fn render(out: &mut String, error: &Error, style: Style) -> std::fmt::Result {
+    style.paint(out, RED_BOLD, "error");
+    out.push_str(": ");
+    style.paint(out, BOLD, &error.kind.to_string());
+
+    if let Some(note) = error.note() {
+        write!(out, " ({note})")?;
+    }
+
+    if !error.value.is_empty() {
+        out.push_str(" while evaluating ");
+        style.paint(out, UNDERLINE, &error.value.to_string());
+    }
+
+    writeln!(out)?;
+
+    let mut modules = error.modules.iter().rev();
+    if let Some(first) = modules.next() {
+        writeln!(out)?;
+        style.paint(out, DIM, &format!("    in {first}"));
+        writeln!(out)?;
+
+        for x in modules {
+            style.paint(out, DIM, &format!("  from {x}"));
+            writeln!(out)?;
+        }
+    }
+
+    if let Some(hint) = hint(error) {
+        writeln!(out)?;
+        style.paint(out, CYAN_BOLD, "hint");
+        writeln!(out, ": {hint}")?;
+    }
+
+    if let ErrorKind::Multiple(ref errors) = error.kind {
+        for x in errors {
+            writeln!(out)?;
+            render(out, x, style)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn hint(error: &Error) -> Option<String> {
+    match error.kind {
+        ErrorKind::Collision => Some(
+            "both values have the same priority, wrap them in `Overridable` and give one of them a higher priority"
+                .to_owned(),
+        ),
+        ErrorKind::Cycle if !error.modules.is_empty() => {
+            let modules: Vec<String> = error.modules.iter().map(|x| x.to_string()).collect();
+            Some(format!(
+                "the modules import each other: {}",
+                modules.join(" -> ")
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use module::merge::{Context, Merge};
+
+    fn collision() -> Error {
+        1i32.merge(2)
+            .value("port")
+            .value("server")
+            .module("user.toml")
+            .module("config.toml")
+            .unwrap_err()
+    }
+
+    fn cycle() -> Error {
+        Err::<(), _>(Error::cycle())
+            .module("a.toml")
+            .module("b.toml")
+            .module("a.toml")
+            .unwrap_err()
+    }
+
+    fn custom() -> Error {
+        Err::<(), _>(Error::custom("expected a string"))
+            .value("name")
+            .module("config.toml")
+            .unwrap_err()
+    }
+
+    #[test]
+    fn test_render_plain() {
+        assert_eq!(
+            render_for_terminal(&collision(), ColorChoice::Never),
+            "error: value collision while evaluating 'server.port'
+
+    in user.toml
+  from config.toml
+
+hint: both values have the same priority, wrap them in `Overridable` and give one of them a higher priority
+"
+        );
+
+        assert_eq!(
+            render_for_terminal(&cycle(), ColorChoice::Never),
+            "error: cyclic imports
+
+    in a.toml
+  from b.toml
+  from a.toml
+
+hint: the modules import each other: a.toml -> b.toml -> a.toml
+"
+        );
+
+        assert_eq!(
+            render_for_terminal(&custom(), ColorChoice::Never),
+            "error: expected a string while evaluating 'name'
+
+    in config.toml
+"
+        );
+
+        assert_eq!(
+            render_for_terminal(&custom().and(custom()), ColorChoice::Never),
+            "error: 2 errors
+
+error: expected a string while evaluating 'name'
+
+    in config.toml
+
+error: expected a string while evaluating 'name'
+
+    in config.toml
+"
+        );
+    }
+
+    #[test]
+    fn test_render_note() {
+        let error = custom().with_note("at line 3, column 7");
+
+        assert_eq!(
+            render_for_terminal(&error, ColorChoice::Never),
+            "error: expected a string (at line 3, column 7) while evaluating 'name'
+
+    in config.toml
+"
+        );
+
+        assert_eq!(
+            render_for_terminal(&error, ColorChoice::Always),
+            "\x1b[1;31merror\x1b[0m: \x1b[1mexpected a string\x1b[0m (at line 3, column 7) while evaluating \x1b[4m'name'\x1b[0m
+
+\x1b[2m    in config.toml\x1b[0m
+"
+        );
+    }
+
+    #[test]
+    fn test_render_color() {
+        assert_eq!(
+            render_for_terminal(&collision(), ColorChoice::Always),
+            "\x1b[1;31merror\x1b[0m: \x1b[1mvalue collision\x1b[0m while evaluating \x1b[4m'server.port'\x1b[0m
+
+\x1b[2m    in user.toml\x1b[0m
+\x1b[2m  from config.toml\x1b[0m
+
+\x1b[1;36mhint\x1b[0m: both values have the same priority, wrap them in `Overridable` and give one of them a higher priority
+"
+        );
+
+        assert_eq!(
+            render_for_terminal(&cycle(), ColorChoice::Always),
+            "\x1b[1;31merror\x1b[0m: \x1b[1mcyclic imports\x1b[0m
+
+\x1b[2m    in a.toml\x1b[0m
+\x1b[2m  from b.toml\x1b[0m
+\x1b[2m  from a.toml\x1b[0m
+
+\x1b[1;36mhint\x1b[0m: the modules import each other: a.toml -> b.toml -> a.toml
+"
+        );
+
+        assert_eq!(
+            render_for_terminal(&custom(), ColorChoice::Always),
+            "\x1b[1;31merror\x1b[0m: \x1b[1mexpected a string\x1b[0m while evaluating \x1b[4m'name'\x1b[0m
+
+\x1b[2m    in config.toml\x1b[0m
+"
+        );
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert!(resolve(ColorChoice::Auto, None, true));
+        assert!(!resolve(ColorChoice::Auto, None, false));
+        assert!(!resolve(ColorChoice::Auto, Some("1".into()), true));
+        assert!(resolve(ColorChoice::Auto, Some("".into()), true));
+
+        assert!(resolve(ColorChoice::Always, Some("1".into()), false));
+        assert!(!resolve(ColorChoice::Never, None, true));
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code(&collision()), 65);
+        assert_eq!(exit_code(&cycle()), 65);
+        assert_eq!(exit_code(&custom()), 78);
+        assert_eq!(
+            exit_code(&io::Error::from(io::ErrorKind::NotFound).into()),
+            66
+        );
+        assert_eq!(
+            exit_code(&io::Error::from(io::ErrorKind::PermissionDenied).into()),
+            77
+        );
+        assert_eq!(
+            exit_code(&io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            74
+        );
+        assert_eq!(exit_code(&custom().and(collision())), 78);
+    }
+}
@@ -0,0 +1,321 @@
+use std::path::Path;
+
+use module::Error;
+use serde::de::DeserializeOwned;
+
+use super::{File, Format, Module, ModuleSeed, read};
+
+#[cfg(feature = "ini")]
+use super::Ini;
+#[cfg(feature = "json")]
+use super::Json;
+#[cfg(feature = "json5")]
+use super::Json5;
+#[cfg(feature = "ron")]
+use super::Ron;
+#[cfg(feature = "toml")]
+use super::Toml;
+#[cfg(feature = "yaml")]
+use super::Yaml;
+
+/// A [`Format`] that picks the format of each module from its extension.
+///
+/// This reads modules of all formats that are enabled with features:
+///
+/// | Extension        | Format    | Feature |
+/// | ---------------- | --------- | ------- |
+/// | `.json`          | [`Json`]  | `json`  |
+/// | `.toml`          | [`Toml`]  | `toml`  |
+/// | `.yaml`, `.yml`  | [`Yaml`]  | `yaml`  |
+/// | `.ron`           | [`Ron`]   | `ron`   |
+/// | `.json5`         | [`Json5`] | `json5` |
+/// | `.ini`           | [`Ini`]   | `ini`   |
+///
+/// Extensions are compared case-insensitively. Modules with any other
+/// extension fail to read. Because the format is picked for every module, a
+/// `.toml` module can import a `.json` module and so on.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::File;
+/// let mut file = File::<i32, _>::auto();
+/// file.read("config.toml").unwrap();
+/// ```
+///
+/// [`Json`]: super::Json
+/// [`Toml`]: super::Toml
+/// [`Yaml`]: super::Yaml
+/// [`Ron`]: super::Ron
+/// [`Json5`]: super::Json5
+/// [`Ini`]: super::Ini
+#[derive(Debug, Clone)]
+pub struct AnyFormat {
+    #[cfg(feature = "json")]
+    json: Json,
+    #[cfg(feature = "toml")]
+    toml: Toml,
+    #[cfg(feature = "yaml")]
+    yaml: Yaml,
+    #[cfg(feature = "ron")]
+    ron: Ron,
+    #[cfg(feature = "json5")]
+    json5: Json5,
+    #[cfg(feature = "ini")]
+    ini: Ini,
+    extensions: Vec<&'static str>,
+}
+
+impl AnyFormat {
+    /// Create a new [`AnyFormatBuilder`].
+    ///
+    /// The builder starts out with the default options of every format.
+    pub fn builder() -> AnyFormatBuilder {
+        AnyFormatBuilder::default()
+    }
+}
+
+impl Default for AnyFormat {
+    fn default() -> Self {
+        let extensions = [
+            (cfg!(feature = "json"), &["json"][..]),
+            (cfg!(feature = "toml"), &["toml"]),
+            (cfg!(feature = "yaml"), &["yaml", "yml"]),
+            (cfg!(feature = "ron"), &["ron"]),
+            (cfg!(feature = "json5"), &["json5"]),
+            (cfg!(feature = "ini"), &["ini"]),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .flat_map(|(_, x)| x.iter().copied())
+        .collect();
+
+        Self {
+            #[cfg(feature = "json")]
+            json: Json::default(),
+            #[cfg(feature = "toml")]
+            toml: Toml::default(),
+            #[cfg(feature = "yaml")]
+            yaml: Yaml::default(),
+            #[cfg(feature = "ron")]
+            ron: Ron::default(),
+            #[cfg(feature = "json5")]
+            json5: Json5::default(),
+            #[cfg(feature = "ini")]
+            ini: Ini::default(),
+            extensions,
+        }
+    }
+}
+
+/// A builder for [`AnyFormat`].
+///
+/// See: [`AnyFormat::builder`].
+#[derive(Debug, Default, Clone)]
+pub struct AnyFormatBuilder {
+    inner: AnyFormat,
+}
+
+impl AnyFormatBuilder {
+    /// Read `.json` modules with `format`.
+    #[cfg(feature = "json")]
+    pub fn json(mut self, format: Json) -> Self {
+        self.inner.json = format;
+        self
+    }
+
+    /// Read `.toml` modules with `format`.
+    #[cfg(feature = "toml")]
+    pub fn toml(mut self, format: Toml) -> Self {
+        self.inner.toml = format;
+        self
+    }
+
+    /// Read `.yaml` and `.yml` modules with `format`.
+    #[cfg(feature = "yaml")]
+    pub fn yaml(mut self, format: Yaml) -> Self {
+        self.inner.yaml = format;
+        self
+    }
+
+    /// Read `.ron` modules with `format`.
+    #[cfg(feature = "ron")]
+    pub fn ron(mut self, format: Ron) -> Self {
+        self.inner.ron = format;
+        self
+    }
+
+    /// Read `.json5` modules with `format`.
+    #[cfg(feature = "json5")]
+    pub fn json5(mut self, format: Json5) -> Self {
+        self.inner.json5 = format;
+        self
+    }
+
+    /// Read `.ini` modules with `format`.
+    #[cfg(feature = "ini")]
+    pub fn ini(mut self, format: Ini) -> Self {
+        self.inner.ini = format;
+        self
+    }
+
+    /// Build the [`AnyFormat`].
+    pub fn build(self) -> AnyFormat {
+        self.inner
+    }
+}
+
+/// Call `$f` with the format of `$path`, or return an error if there is none.
+macro_rules! dispatch {
+    ($self:ident, $path:expr, |$format:ident| $f:expr) => {{
+        let path: &Path = $path;
+        let ext = path
+            .extension()
+            .and_then(|x| x.to_str())
+            .unwrap_or_default();
+
+        #[cfg(feature = "json")]
+        if reads(&$self.json, ext) {
+            let $format = &mut $self.json;
+            return $f;
+        }
+        #[cfg(feature = "toml")]
+        if reads(&$self.toml, ext) {
+            let $format = &mut $self.toml;
+            return $f;
+        }
+        #[cfg(feature = "yaml")]
+        if reads(&$self.yaml, ext) {
+            let $format = &mut $self.yaml;
+            return $f;
+        }
+        #[cfg(feature = "ron")]
+        if reads(&$self.ron, ext) {
+            let $format = &mut $self.ron;
+            return $f;
+        }
+        #[cfg(feature = "json5")]
+        if reads(&$self.json5, ext) {
+            let $format = &mut $self.json5;
+            return $f;
+        }
+        #[cfg(feature = "ini")]
+        if reads(&$self.ini, ext) {
+            let $format = &mut $self.ini;
+            return $f;
+        }
+
+        let _ = ext;
+        Err(Error::custom(format!(
+            "cannot read '{}': unknown extension, expected one of: {}",
+            path.display(),
+            $self.extensions.join(", ")
+        )))
+    }};
+}
+
+/// Check whether `format` reads files with the extension `ext`.
+// Unused without any formats.
+#[allow(dead_code)]
+fn reads<F>(format: &F, ext: &str) -> bool
+where
+    F: Format,
+{
+    format
+        .extensions()
+        .iter()
+        .any(|x| x.eq_ignore_ascii_case(ext))
+}
+
+impl Format for AnyFormat {
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        dispatch!(self, path, |format| format.read(path, seed))
+    }
+
+    // Without any formats, `contents` and `seed` are never read.
+    #[cfg_attr(
+        not(any(
+            feature = "json",
+            feature = "toml",
+            feature = "yaml",
+            feature = "ron",
+            feature = "json5",
+            feature = "ini"
+        )),
+        allow(unused_variables)
+    )]
+    fn read_str<T>(
+        &mut self,
+        name: &Path,
+        contents: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        dispatch!(self, name, |format| format.read_str(name, contents, seed))
+    }
+
+    // Without any formats, `contents` and `seed` are never read.
+    #[cfg_attr(
+        not(any(
+            feature = "json",
+            feature = "toml",
+            feature = "yaml",
+            feature = "ron",
+            feature = "json5",
+            feature = "ini"
+        )),
+        allow(unused_variables)
+    )]
+    fn read_bytes<T>(
+        &mut self,
+        name: &Path,
+        contents: &[u8],
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        dispatch!(self, name, |format| format.read_bytes(name, contents, seed))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+impl<T> File<T, AnyFormat> {
+    /// Create a new [`File`] that reads files of any format, based on their
+    /// extension.
+    ///
+    /// See: [`AnyFormat`].
+    ///
+    /// Equivalent to: `File::new(AnyFormat::default())`
+    pub fn auto() -> Self {
+        Self::new(AnyFormat::default())
+    }
+
+    /// Create a new [`File`] that reads files of any format with the options
+    /// of `format`.
+    ///
+    /// See: [`AnyFormat::builder`].
+    ///
+    /// Equivalent to: `File::new(format)`
+    pub fn auto_with(format: AnyFormat) -> Self {
+        Self::new(format)
+    }
+}
+
+/// Read the module at `path` with [`AnyFormat`].
+///
+/// See: [`AnyFormat`].
+pub fn read_auto<T>(path: impl AsRef<Path>) -> Result<T, Error>
+where
+    T: module::Merge + DeserializeOwned,
+{
+    read(path, AnyFormat::default())
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use module::eval::prelude::*;
+
+use super::DirImport;
+use super::file::DisplayPath;
+
+/// List the modules of `import`, in the order they must be merged.
+///
+/// `dir` is the directory of `import`, already resolved. Returns [`None`] if
+/// `dir` does not exist and `import` is optional.
+pub(crate) fn list(
+    dir: &Path,
+    import: &DirImport,
+    extensions: &[&str],
+) -> Result<Option<Vec<PathBuf>>, Error> {
+    ModuleGuard::new(DisplayPath(dir.to_path_buf())).run(|| {
+        let dir = match fs::canonicalize(dir) {
+            Ok(x) => x,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && import.optional => return Ok(None),
+            Err(e) => return Err(Error::from(e)),
+        };
+
+        let mut paths = Vec::new();
+        walk(&dir, import.recursive, extensions, &mut paths).map_err(Error::from)?;
+
+        // Sort by the path relative to `dir`, byte by byte, so the order does
+        // not depend on the locale or the file system.
+        paths.sort_by(|a, b| {
+            let key = |x: &PathBuf| x.strip_prefix(&dir).unwrap_or(x).as_os_str().to_owned();
+            key(a).as_encoded_bytes().cmp(key(b).as_encoded_bytes())
+        });
+
+        Ok(Some(paths))
+    })
+}
+
+fn walk(
+    dir: &Path,
+    recursive: bool,
+    extensions: &[&str],
+    out: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        // Symbolic links are followed for files, but not for directories, so
+        // that walking can never loop.
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if recursive {
+                walk(&path, recursive, extensions, out)?;
+            }
+            continue;
+        }
+
+        if file_type.is_symlink() && !fs::metadata(&path)?.is_file() {
+            continue;
+        }
+
+        let matches = extensions.is_empty()
+            || path
+                .extension()
+                .is_some_and(|x| extensions.iter().any(|e| x == *e));
+
+        if matches {
+            out.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the default priority offset of the `i`-th module of `import`.
+///
+/// See: [`DirImport::layered`].
+pub(crate) fn offset(import: &DirImport, i: usize) -> isize {
+    match import.layered {
+        true => isize::try_from(i).map_or(isize::MIN, |x| -x),
+        false => 0,
+    }
+}
+
+/// A module read by a [`DirImport`].
+#[derive(Clone)]
+pub(crate) struct ViaDir {
+    pub(crate) path: PathBuf,
+    pub(crate) importer: PathBuf,
+}
+
+impl fmt::Display for ViaDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (via dir import in {})",
+            self.path.display(),
+            self.importer.display()
+        )
+    }
+}
@@ -1,12 +1,22 @@
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
 use std::fmt;
 use std::fs;
+use std::io;
+use std::mem;
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
 
-use module::{Context, Error, Merge};
+use module::eval::prelude::*;
+use module::merge::{MultiError, Resolve};
 use serde::de::DeserializeOwned;
 
-use super::{Format, Module};
+use super::dir;
+use super::format::DEFAULT_IMPORT_KEY;
+use super::manifest::{self, ContentHash, Manifest};
+use super::resolve::{Imported, Importer, Resolver};
+use super::{DirImport, Format, Imports, Module, ModuleSeed, PathIdentity};
 
 /// An evaluator for files.
 ///
@@ -14,10 +24,25 @@ use super::{Format, Module};
 /// [`Module`] as the top-level format of the module and [`serde`] to parse the
 /// contents of the file.
 ///
-/// * [`File`] is capable of detecting import-cycles between modules.
+/// * [`File`] is capable of detecting import-cycles between modules. A module
+///   that is imported more than once without a cycle, for example by 2
+///   different modules, is only evaluated the first time.
 ///
 /// * Import paths are resolved relative to the path of the importer module.
 ///
+/// * Whole directories can be imported with [`DirImport`].
+///
+/// * Whether 2 paths are the same module is decided by its [`PathIdentity`].
+///
+/// * Modules are merged before their imports, unless the [`ImportOrder`] says
+///   otherwise.
+///
+/// * When the value of a module fails to merge, the module trace of the error
+///   starts at that module and leads back through its importers. For a
+///   collision, this is the module that was merged last. The note of the
+///   error names the other modules that defined the same top-level key, like
+///   `also defined in a.json`.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -54,21 +79,588 @@ use super::{Format, Module};
 /// ```
 #[derive(Debug)]
 pub struct File<T, F> {
-    evaluated: HashSet<PathBuf>,
+    evaluated: VisitedSet<OsString>,
+    evaluated_paths: Vec<PathBuf>,
+    origins: Origins,
     value: Option<T>,
     format: F,
+    resolver: Resolver,
+    identity: PathIdentity,
+    import_order: ImportOrder,
+    import_key: &'static str,
+    max_depth: Option<usize>,
+    depth: usize,
+    recorded: Option<Manifest>,
+    enforced: Option<Manifest>,
+    on_merge: Option<OnMerge<T>>,
+    on_layer: Option<OnLayer<T>>,
+    saturated: bool,
+    skipped: usize,
+    collecting: Option<Collecting<T>>,
+    lost: Vec<Error>,
+    cache: Option<Cache<T>>,
+}
+
+/// The order in which a module and its imports are merged.
+///
+/// See: [`File::set_import_order`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportOrder {
+    /// Merge the value of a module before the values of its imports.
+    ///
+    /// This is the default.
+    #[default]
+    ImporterFirst,
+
+    /// Merge the values of the imports of a module before its own value.
+    ///
+    /// The imports act like defaults that the importer builds upon.
+    ImportsFirst,
+}
+
+impl ImportOrder {
+    /// Merge the value of a module with `merge` and read its imports with
+    /// `imports`, in this order.
+    ///
+    /// If the imports are read first and fail, the value is not merged. The
+    /// outer error is the error of `merge`, after which the module must be
+    /// forgotten. Otherwise the result of `imports` is returned.
+    pub(crate) fn walk<S, M, I>(
+        self,
+        state: &mut S,
+        merge: M,
+        imports: I,
+    ) -> Result<Result<(), Error>, Error>
+    where
+        M: FnOnce(&mut S) -> Result<(), Error>,
+        I: FnOnce(&mut S) -> Result<(), Error>,
+    {
+        match self {
+            Self::ImporterFirst => {
+                merge(state)?;
+                Ok(imports(state))
+            }
+            Self::ImportsFirst => {
+                let r = imports(state);
+                if r.is_ok() {
+                    merge(state)?;
+                }
+                Ok(r)
+            }
+        }
+    }
+}
+
+/// The state of [`File::read_collecting`].
+struct Collecting<T> {
+    errors: Vec<Error>,
+    merge: fn(&mut Option<T>, T) -> Result<(), Error>,
+    /// Whether modules that fail to be read are collected too, see
+    /// [`File::read_lossy`].
+    lossy: bool,
+}
+
+impl<T> Clone for Collecting<T> {
+    fn clone(&self) -> Self {
+        Self {
+            errors: self.errors.clone(),
+            merge: self.merge,
+            lossy: self.lossy,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Collecting<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collecting")
+            .field("errors", &self.errors)
+            .field("lossy", &self.lossy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// `merge` is always the same function for the same `T`, so it is not
+/// compared.
+impl<T> PartialEq for Collecting<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.errors == other.errors && self.lossy == other.lossy
+    }
+}
+
+impl<T> Eq for Collecting<T> {}
+
+/// The hook set with [`File::on_layer`].
+struct OnLayer<T>(Box<LayerHook<T>>);
+
+type LayerHook<T> = dyn FnMut(&mut T, isize);
+
+impl<T> fmt::Debug for OnLayer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnLayer").finish_non_exhaustive()
+    }
+}
+
+/// The modules kept by [`File::cache_modules`], with their top-level keys.
+struct Cache<T> {
+    modules: HashMap<OsString, (Module<T>, Vec<String>)>,
+    clone: fn(&Module<T>) -> Module<T>,
+}
+
+impl<T> fmt::Debug for Cache<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache")
+            .field("modules", &self.modules.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The modules that defined each top-level key of the accumulated value, in
+/// the order they were merged.
+type Origins = HashMap<String, Vec<PathBuf>>;
+
+/// The hook set with [`File::on_merge`].
+struct OnMerge<T>(Box<Hook<T>>);
+
+type Hook<T> = dyn FnMut(&T) -> ControlFlow<()>;
+
+impl<T> fmt::Debug for OnMerge<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnMerge").finish_non_exhaustive()
+    }
 }
 
 impl<T, F> File<T, F> {
     /// Create a new [`File`] that reads files according to `format`.
+    ///
+    /// The [base directory] is set to the current working directory, if it
+    /// can be determined.
+    ///
+    /// [base directory]: File::base_dir
     pub fn new(format: F) -> Self {
         Self {
-            evaluated: HashSet::new(),
+            evaluated: VisitedSet::new(),
+            evaluated_paths: Vec::new(),
+            origins: Origins::new(),
             value: None,
             format,
+            resolver: Resolver::default(),
+            identity: PathIdentity::default(),
+            import_order: ImportOrder::default(),
+            import_key: DEFAULT_IMPORT_KEY,
+            max_depth: None,
+            depth: 0,
+            recorded: None,
+            enforced: None,
+            on_merge: None,
+            on_layer: None,
+            saturated: false,
+            skipped: 0,
+            collecting: None,
+            lost: Vec::new(),
+            cache: None,
         }
     }
 
+    /// Get the base directory.
+    ///
+    /// Relative imports are normally resolved against the directory of the
+    /// importer module. If the importer has no parent directory, they are
+    /// resolved against this directory instead. If there is no base directory
+    /// either, such imports fail with an error.
+    pub fn base_dir(&self) -> Option<&Path> {
+        self.resolver.base_dir.as_deref()
+    }
+
+    /// Set the base directory.
+    ///
+    /// See: [`File::base_dir`].
+    pub fn set_base_dir<P>(&mut self, dir: Option<P>)
+    where
+        P: Into<PathBuf>,
+    {
+        self.resolver.base_dir = dir.map(Into::into);
+    }
+
+    /// Get the [`PathIdentity`] used to tell modules apart.
+    pub fn path_identity(&self) -> PathIdentity {
+        self.identity
+    }
+
+    /// Set the [`PathIdentity`] used to tell modules apart.
+    ///
+    /// This should be set before reading any modules. Modules that were
+    /// already read keep the keys computed with the previous identity.
+    pub fn set_path_identity(&mut self, identity: PathIdentity) {
+        self.identity = identity;
+    }
+
+    /// Get the [`ImportOrder`].
+    pub fn import_order(&self) -> ImportOrder {
+        self.import_order
+    }
+
+    /// Set the [`ImportOrder`].
+    ///
+    /// This decides whether the value of a module is merged before or after
+    /// the values of its imports. Modules are still read in the same order,
+    /// only the merges are reordered.
+    ///
+    /// The order only matters for values whose merge depends on it. For
+    /// example, a [`Vec`] is appended to, so [`ImportOrder::ImportsFirst`]
+    /// puts the items of the imports first. Merging [`Overridable`] values is
+    /// decided by their priorities, no matter the order. So an importer does
+    /// not override its imports just because it is merged last, it still
+    /// needs a higher priority to do that. Values with the same priority
+    /// collide in either order.
+    ///
+    /// With [`ImportOrder::ImportsFirst`], if the imports of a module
+    /// [saturate] the evaluation, the value of the module is skipped too.
+    ///
+    /// Defaults to [`ImportOrder::ImporterFirst`].
+    ///
+    /// [`Overridable`]: module::types::Overridable
+    /// [saturate]: File::is_saturated
+    pub fn set_import_order(&mut self, order: ImportOrder) {
+        self.import_order = order;
+    }
+
+    /// Get the key of the imports of modules.
+    ///
+    /// See: [`File::set_import_key`].
+    pub fn import_key(&self) -> &'static str {
+        self.import_key
+    }
+
+    /// Set the key of the imports of modules.
+    ///
+    /// Modules list their imports under `key` instead of `imports`, for
+    /// example `include` or `extends`. The `imports` key is then not special
+    /// anymore and is part of the value of the module, like any other key.
+    ///
+    /// Defaults to `imports`.
+    pub fn set_import_key(&mut self, key: &'static str) {
+        self.import_key = key;
+    }
+
+    /// Set the key of the imports of modules.
+    ///
+    /// See: [`File::set_import_key`].
+    pub fn with_import_key(mut self, key: &'static str) -> Self {
+        self.set_import_key(key);
+        self
+    }
+
+    /// Check whether environment variables in import paths are expanded.
+    ///
+    /// See: [`File::set_expand_env`].
+    pub fn expands_env(&self) -> bool {
+        self.resolver.expand_env
+    }
+
+    /// Set whether environment variables in import paths are expanded.
+    ///
+    /// If enabled, `${VAR}` and `$VAR` in the paths of imports are replaced
+    /// with the value of the environment variable `VAR` before the paths are
+    /// resolved, like `${XDG_CONFIG_HOME}/app/base.toml`. A variable that is
+    /// not defined is an error. A `$` that does not start a variable name is
+    /// kept as is. Paths that are not valid UTF-8 are never expanded.
+    ///
+    /// Defaults to `false`.
+    pub fn set_expand_env(&mut self, expand: bool) {
+        self.resolver.expand_env = expand;
+    }
+
+    /// Set whether environment variables in import paths are expanded.
+    ///
+    /// See: [`File::set_expand_env`].
+    pub fn with_expand_env(mut self, expand: bool) -> Self {
+        self.set_expand_env(expand);
+        self
+    }
+
+    /// Check whether import paths are expanded as glob patterns.
+    ///
+    /// See: [`File::set_glob`].
+    #[cfg(feature = "glob")]
+    pub fn globs(&self) -> bool {
+        self.resolver.glob
+    }
+
+    /// Set whether import paths are expanded as glob patterns.
+    ///
+    /// If enabled, an import path that contains any of `*`, `?` or `[` is a
+    /// pattern, like `conf.d/*.toml`. The pattern is resolved just like any
+    /// other import path and every file that matches it is read, in the order
+    /// of their paths compared byte by byte. Directories that match are
+    /// skipped. A pattern that matches nothing is not an error.
+    ///
+    /// Paths without any of these characters are read as usual.
+    ///
+    /// Defaults to `false`.
+    #[cfg(feature = "glob")]
+    pub fn set_glob(&mut self, glob: bool) {
+        self.resolver.glob = glob;
+    }
+
+    /// Set whether import paths are expanded as glob patterns.
+    ///
+    /// See: [`File::set_glob`].
+    #[cfg(feature = "glob")]
+    pub fn with_glob(mut self, glob: bool) -> Self {
+        self.set_glob(glob);
+        self
+    }
+
+    /// Get the maximum import depth.
+    ///
+    /// See: [`File::set_max_depth`].
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Set the maximum import depth.
+    ///
+    /// Modules passed to [`read()`] are at depth `0`, their imports are at
+    /// depth `1` and so on. Reading a module deeper than `max` fails with an
+    /// error instead. This guards against imports that nest so deep that they
+    /// would overflow the stack. Defaults to `None`, which means no limit.
+    ///
+    /// [`read()`]: File::read
+    pub fn set_max_depth(&mut self, max: Option<usize>) {
+        self.max_depth = max;
+    }
+
+    /// Set the maximum import depth.
+    ///
+    /// See: [`File::set_max_depth`].
+    pub fn with_max_depth(mut self, max: Option<usize>) -> Self {
+        self.set_max_depth(max);
+        self
+    }
+
+    /// Move keys of every module read from now on according to `remap`.
+    ///
+    /// See: [`Remapped`].
+    ///
+    /// [`Remapped`]: super::Remapped
+    #[cfg(feature = "json")]
+    pub fn with_remap(self, remap: super::Remap) -> File<T, super::Remapped<F>> {
+        File {
+            evaluated: self.evaluated,
+            evaluated_paths: self.evaluated_paths,
+            origins: self.origins,
+            value: self.value,
+            format: super::Remapped::new(self.format, remap),
+            resolver: self.resolver,
+            identity: self.identity,
+            import_order: self.import_order,
+            import_key: self.import_key,
+            max_depth: self.max_depth,
+            depth: self.depth,
+            recorded: self.recorded,
+            enforced: self.enforced,
+            on_merge: self.on_merge,
+            on_layer: self.on_layer,
+            saturated: self.saturated,
+            skipped: self.skipped,
+            collecting: self.collecting,
+            lost: self.lost,
+            cache: self.cache,
+        }
+    }
+
+    /// Check whether a [`Manifest`] of the modules read is recorded.
+    ///
+    /// See: [`File::set_record_manifest`].
+    pub fn records_manifest(&self) -> bool {
+        self.recorded.is_some()
+    }
+
+    /// Set whether to record a [`Manifest`] of the modules read.
+    ///
+    /// Recording hashes the contents of every module, so it is disabled by
+    /// default. Disabling it discards everything recorded so far.
+    ///
+    /// See: [`File::record_manifest`].
+    pub fn set_record_manifest(&mut self, record: bool) {
+        match record {
+            true => _ = self.recorded.get_or_insert_with(Manifest::new),
+            false => self.recorded = None,
+        }
+    }
+
+    /// Set whether to record a [`Manifest`] of the modules read.
+    ///
+    /// See: [`File::set_record_manifest`].
+    pub fn with_record_manifest(mut self, record: bool) -> Self {
+        self.set_record_manifest(record);
+        self
+    }
+
+    /// Get a [`Manifest`] of all modules read so far.
+    ///
+    /// The manifest lists every module [`File`] has read since recording was
+    /// enabled with [`set_record_manifest()`], including modules read during
+    /// [`speculate()`] or before a [`restore()`]. The hash of each module is
+    /// computed from its contents at the time it was read. The manifest is
+    /// empty if recording is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use module_util::file::File;
+    /// # type Config = i32;
+    /// let mut file = File::<Config, _>::json().with_record_manifest(true);
+    /// file.read("config.json").unwrap();
+    ///
+    /// let lockfile = file.record_manifest().to_lockfile().unwrap();
+    /// std::fs::write("config.lock", lockfile).unwrap();
+    /// ```
+    ///
+    /// [`set_record_manifest()`]: File::set_record_manifest
+    /// [`speculate()`]: File::speculate
+    /// [`restore()`]: File::restore
+    pub fn record_manifest(&self) -> Manifest {
+        self.recorded.clone().unwrap_or_default()
+    }
+
+    /// Only allow reading the modules listed in `manifest`.
+    ///
+    /// From now on, reading a module fails if it is not listed in `manifest`,
+    /// or if its contents changed since `manifest` was recorded. Both checks
+    /// happen before the contents of the module are parsed. The module is
+    /// then parsed from the same contents that were checked.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use module_util::file::{File, Manifest};
+    /// # type Config = i32;
+    /// let lockfile = std::fs::read_to_string("config.lock").unwrap();
+    /// let manifest = Manifest::from_lockfile(&lockfile).unwrap();
+    ///
+    /// let mut file = File::<Config, _>::json().with_manifest(manifest);
+    /// file.read("config.json").unwrap();
+    /// ```
+    pub fn with_manifest(mut self, manifest: Manifest) -> Self {
+        self.enforced = Some(manifest);
+        self
+    }
+
+    /// Call `f` with the accumulated value after every module is merged.
+    ///
+    /// If `f` returns [`ControlFlow::Break`], the evaluation is [saturated]:
+    /// no more modules are read, including the remaining imports of modules
+    /// being read and modules passed to [`read()`] later. Reading them
+    /// succeeds without doing anything and they are counted in
+    /// [`skipped_modules()`].
+    ///
+    /// This is useful when some value makes all later modules irrelevant, for
+    /// example a top-level `locked` flag.
+    ///
+    /// See also: [`MergeCell::on_merge`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use module::Merge;
+    /// # use serde::Deserialize;
+    /// # use module_util::file::File;
+    /// use std::ops::ControlFlow;
+    ///
+    /// #[derive(Deserialize, Merge)]
+    /// struct Config {
+    ///     locked: Option<bool>,
+    /// }
+    ///
+    /// let mut file = File::json().on_merge(|x: &Config| match x.locked {
+    ///     Some(true) => ControlFlow::Break(()),
+    ///     _ => ControlFlow::Continue(()),
+    /// });
+    ///
+    /// file.read("config.json").unwrap();
+    /// ```
+    ///
+    /// [saturated]: File::is_saturated
+    /// [`read()`]: File::read
+    /// [`skipped_modules()`]: File::skipped_modules
+    pub fn on_merge<H>(mut self, f: H) -> Self
+    where
+        H: FnMut(&T) -> ControlFlow<()> + 'static,
+    {
+        self.on_merge = Some(OnMerge(Box::new(f)));
+        self
+    }
+
+    /// Call `f` with the value of every module of a [layered] [`DirImport`]
+    /// and the offset of the module, before the value is merged.
+    ///
+    /// The `i`-th module of such an import has the offset `-i`. `f` should
+    /// shift the default priorities of the value by the offset, usually with
+    /// [`Overridable::shift_default`], so that later modules override
+    /// earlier ones. Without this hook, layered imports are read like any
+    /// other dir import.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use module::Merge;
+    /// # use serde::Deserialize;
+    /// # use module_util::file::File;
+    /// use module::types::Overridable;
+    ///
+    /// #[derive(Deserialize, Merge)]
+    /// struct Config {
+    ///     port: Option<Overridable<u16>>,
+    /// }
+    ///
+    /// let mut file = File::json().on_layer(|x: &mut Config, offset| {
+    ///     if let Some(port) = &mut x.port {
+    ///         port.shift_default(offset);
+    ///     }
+    /// });
+    ///
+    /// file.read("config.json").unwrap();
+    /// ```
+    ///
+    /// [layered]: DirImport::layered
+    /// [`Overridable::shift_default`]: module::types::Overridable::shift_default
+    pub fn on_layer<H>(mut self, f: H) -> Self
+    where
+        H: FnMut(&mut T, isize) + 'static,
+    {
+        self.on_layer = Some(OnLayer(Box::new(f)));
+        self
+    }
+
+    /// Shift the default priorities of `value`, the value of a module with
+    /// `offset`, with the [`on_layer()`] hook.
+    ///
+    /// [`on_layer()`]: File::on_layer
+    pub(crate) fn layer(&mut self, value: &mut T, offset: isize) {
+        if let (Some(OnLayer(f)), true) = (&mut self.on_layer, offset != 0) {
+            f(value, offset);
+        }
+    }
+
+    /// Check whether the [`on_merge()`] hook has stopped the evaluation.
+    ///
+    /// [`on_merge()`]: File::on_merge
+    pub fn is_saturated(&self) -> bool {
+        self.saturated
+    }
+
+    /// Get the number of modules that were not read because the evaluation
+    /// was [saturated].
+    ///
+    /// Only modules that were about to be read are counted. The imports of
+    /// skipped modules are never discovered, so they are not counted.
+    ///
+    /// [saturated]: File::is_saturated
+    pub fn skipped_modules(&self) -> usize {
+        self.skipped
+    }
+
     /// Get a reference to the [`Format`] used.
     pub fn format(&self) -> &F {
         &self.format
@@ -79,6 +671,42 @@ impl<T, F> File<T, F> {
         &mut self.format
     }
 
+    /// Get the value accumulated so far.
+    ///
+    /// Returns [`None`] if no file has been [`read()`] successfully.
+    ///
+    /// [`read()`]: File::read
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Get the canonical paths of all modules evaluated so far, in the order
+    /// they were read.
+    ///
+    /// Every module is listed once, even if it was imported many times. This
+    /// includes modules that failed to merge in [`read_collecting()`], but not
+    /// modules that failed to be read. Unlike [`record_manifest()`], modules
+    /// forgotten by [`restore()`] are not listed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # type File = module_util::file::File<i32, module_util::file::Json>;
+    /// let mut file = File::json();
+    /// file.read("config.json").unwrap();
+    ///
+    /// for path in file.evaluated() {
+    ///     println!("watching {}", path.display());
+    /// }
+    /// ```
+    ///
+    /// [`read_collecting()`]: File::read_collecting
+    /// [`record_manifest()`]: File::record_manifest
+    /// [`restore()`]: File::restore
+    pub fn evaluated(&self) -> impl ExactSizeIterator<Item = &Path> {
+        self.evaluated_paths.iter().map(PathBuf::as_path)
+    }
+
     /// Finish the evaluation and return the final value.
     ///
     /// Returns [`None`] if no file has been [`read()`] successfully. Otherwise,
@@ -105,6 +733,168 @@ impl<T, F> File<T, F> {
     pub fn finish(self) -> Option<T> {
         self.value
     }
+
+    /// Finish the evaluation and return the final value, along with all
+    /// errors of [`read_lossy()`].
+    ///
+    /// The value is partial if there are any errors. It holds the values of
+    /// every module that merged successfully and nothing of the modules that
+    /// did not. A module that failed to be read is missing along with its
+    /// imports, but the modules after it are still read. The value is
+    /// [`None`] if no module merged at all.
+    ///
+    /// The errors are in the order they occurred.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # type File = module_util::file::File<Vec<i32>, module_util::file::Json>;
+    /// let mut file = File::json();
+    /// file.read_lossy("/etc/app.json");
+    /// file.read_lossy("app.json");
+    ///
+    /// let (value, errors) = file.finish_lossy();
+    /// for e in &errors {
+    ///     eprintln!("{e}");
+    /// }
+    ///
+    /// println!("{:?}", value.unwrap_or_default());
+    /// ```
+    ///
+    /// [`read_lossy()`]: File::read_lossy
+    pub fn finish_lossy(self) -> (Option<T>, Vec<Error>) {
+        (self.value, self.lost)
+    }
+}
+
+impl<T, F> File<T, F>
+where
+    T: Clone,
+{
+    /// Capture the current state of the evaluation.
+    ///
+    /// The snapshot contains a clone of the value accumulated so far, the
+    /// set of modules already evaluated and the errors kept for
+    /// [`finish_lossy()`]. Taking a snapshot is as expensive as cloning `T`.
+    ///
+    /// The [`Format`] and the [base directory] are not part of the snapshot.
+    ///
+    /// See: [`File::restore`].
+    ///
+    /// [base directory]: File::base_dir
+    /// [`finish_lossy()`]: File::finish_lossy
+    pub fn snapshot(&self) -> FileSnapshot<T> {
+        FileSnapshot {
+            evaluated: self.evaluated.clone(),
+            evaluated_paths: self.evaluated_paths.clone(),
+            origins: self.origins.clone(),
+            value: self.value.clone(),
+            saturated: self.saturated,
+            skipped: self.skipped,
+            collecting: self.collecting.clone(),
+            lost: self.lost.clone(),
+        }
+    }
+
+    /// Restore the state of the evaluation from `snapshot`.
+    ///
+    /// Any modules read after `snapshot` was taken are forgotten, as if they
+    /// had never been read.
+    pub fn restore(&mut self, snapshot: FileSnapshot<T>) {
+        let FileSnapshot {
+            evaluated,
+            evaluated_paths,
+            origins,
+            value,
+            saturated,
+            skipped,
+            collecting,
+            lost,
+        } = snapshot;
+        self.evaluated = evaluated;
+        self.evaluated_paths = evaluated_paths;
+        self.origins = origins;
+        self.value = value;
+        self.saturated = saturated;
+        self.skipped = skipped;
+        self.collecting = collecting;
+        self.lost = lost;
+
+        // A read may have been interrupted by a panic.
+        self.depth = 0;
+    }
+
+    /// Start over with the evaluation of `state` and get the state of the
+    /// current evaluation.
+    ///
+    /// This lets one [`File`] evaluate several module trees in turn.
+    pub(crate) fn swap_state(&mut self, state: FileSnapshot<T>) -> FileSnapshot<T> {
+        let current = FileSnapshot {
+            evaluated: mem::replace(&mut self.evaluated, VisitedSet::new()),
+            evaluated_paths: mem::take(&mut self.evaluated_paths),
+            origins: mem::take(&mut self.origins),
+            value: self.value.take(),
+            saturated: self.saturated,
+            skipped: self.skipped,
+            collecting: self.collecting.take(),
+            lost: mem::take(&mut self.lost),
+        };
+
+        self.restore(state);
+        current
+    }
+
+    /// Keep every module read from now on, so that each module is parsed only
+    /// once, even by later evaluations.
+    ///
+    /// See: [`File::swap_state`].
+    pub(crate) fn cache_modules(&mut self) {
+        self.cache = Some(Cache {
+            modules: HashMap::new(),
+            clone: Module::clone,
+        });
+    }
+
+    /// Run `f` speculatively.
+    ///
+    /// `f` may freely read more modules or otherwise change the evaluation.
+    /// After it returns, the evaluation is restored to the state it had before
+    /// `f` was called. This also happens if `f` panics.
+    ///
+    /// Changes to the [`Format`] and the [base directory] made by `f` are kept.
+    ///
+    /// This is equivalent to taking a [`snapshot()`] before calling `f` and
+    /// [`restore()`]-ing it afterwards, so it costs one clone of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # type File = module_util::file::File<Vec<i32>, module_util::file::Json>;
+    /// let mut file = File::json();
+    /// file.read("config.json").unwrap();
+    ///
+    /// let preview = file.speculate(|file| {
+    ///     file.read("extra.json")?;
+    ///     Ok::<_, module::Error>(file.value().cloned())
+    /// });
+    ///
+    /// // `extra.json` was not actually read.
+    /// let config = file.finish();
+    /// ```
+    ///
+    /// [base directory]: File::base_dir
+    /// [`snapshot()`]: File::snapshot
+    /// [`restore()`]: File::restore
+    pub fn speculate<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let snapshot = self.snapshot();
+
+        let guard = RestoreGuard {
+            file: self,
+            snapshot: Some(snapshot),
+        };
+
+        f(&mut *guard.file)
+    }
 }
 
 impl<T, F> File<T, F>
@@ -119,42 +909,664 @@ where
     where
         P: AsRef<Path>,
     {
+        if self.skip() {
+            return Ok(());
+        }
+
         let path = path.as_ref();
-        let path = fs::canonicalize(path).map_err(Error::custom)?;
-        self._read(&path).with_module(|| DisplayPath(path))
+        let path = fs::canonicalize(path).map_err(Error::from)?;
+        self.guarded(DisplayPath(path.clone()), |this| this._read(&path, None, 0))
+    }
+
+    /// Read all modules in the directory `dir`.
+    ///
+    /// This is like reading `dir` with a [`DirImport`], except that the
+    /// modules are read as if they were passed to [`read()`] one by one. So
+    /// the modules are the files in `dir` whose extension is one of the
+    /// [`Format::extensions`], they are read in the order of their paths
+    /// compared byte by byte and errors of each module show only the module
+    /// itself in the module trace.
+    ///
+    /// Subdirectories are not read, see [`File::read_dir_with`] for that and
+    /// other options.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use module_util::file::File;
+    /// let mut file = File::<i32, _>::json();
+    /// file.read("/etc/app/app.json").unwrap();
+    /// file.read_dir("/etc/app/app.d").unwrap();
+    /// ```
+    ///
+    /// [`read()`]: File::read
+    pub fn read_dir<P>(&mut self, dir: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        self.read_dir_with(&DirImport::new(dir.as_ref()))
+    }
+
+    /// Read all modules in the directory of `import`, with its options.
+    ///
+    /// A relative [`DirImport::dir`] is relative to the current working
+    /// directory, just like the path passed to [`read()`].
+    ///
+    /// See: [`File::read_dir`].
+    ///
+    /// [`read()`]: File::read
+    pub fn read_dir_with(&mut self, import: &DirImport) -> Result<(), Error> {
+        let Some(paths) = dir::list(&import.dir, import, self.format.extensions())? else {
+            return Ok(());
+        };
+
+        for (i, path) in paths.into_iter().enumerate() {
+            if self.skip() {
+                return Ok(());
+            }
+
+            let path = fs::canonicalize(path).map_err(Error::from)?;
+            let offset = dir::offset(import, i);
+            self.guarded(DisplayPath(path.clone()), |this| {
+                this._read(&path, None, offset)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a module from `contents` instead of a file.
+    ///
+    /// `contents` is parsed with [`Format::read_str`] and merged like any
+    /// other module. `name` takes the place of the path of the module: it is
+    /// shown in the module trace of errors and it identifies the module, so
+    /// a second module with the same name is skipped, just like reading the
+    /// same file twice.
+    ///
+    /// Imports of the module are read from files. Relative imports are
+    /// resolved as if the module were a file at `name`, so against the
+    /// [base directory] if `name` has no parent directory.
+    ///
+    /// Modules read from strings are not listed by [`evaluated()`] and not
+    /// recorded in the [`Manifest`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::Merge;
+    /// # use serde::Deserialize;
+    /// # use module_util::file::File;
+    /// #[derive(Deserialize, Merge)]
+    /// struct Config {
+    ///     items: Vec<i32>,
+    /// }
+    ///
+    /// let mut file = File::<Config, _>::json();
+    /// file.read_str("defaults", r#"{ "items": [1] }"#).unwrap();
+    /// file.read_str("overrides", r#"{ "items": [2] }"#).unwrap();
+    ///
+    /// assert_eq!(file.finish().unwrap().items, [1, 2]);
+    /// ```
+    ///
+    /// [base directory]: File::base_dir
+    /// [`evaluated()`]: File::evaluated
+    pub fn read_str<D>(&mut self, name: D, contents: &str) -> Result<(), Error>
+    where
+        D: fmt::Display,
+    {
+        if self.skip() {
+            return Ok(());
+        }
+
+        let name = name.to_string();
+        self.guarded(name.clone(), |this| {
+            this.read_source(Source::Str {
+                name: Path::new(&name),
+                contents,
+            })
+        })
+    }
+
+    /// Read the module at `path`, collecting all merge errors.
+    ///
+    /// This is like [`read()`], but a module that fails to merge does not
+    /// stop the evaluation. Its error is collected and the evaluation
+    /// continues with the imports of the module, as if it had merged.
+    ///
+    /// A module that fails to merge is dropped as a whole. The accumulated
+    /// value is left exactly as it was before that module, so every module
+    /// either contributes all of its values or none of them. For this, the
+    /// accumulated value is cloned before each module is merged.
+    ///
+    /// Merging a module stops at its first error, so at most one error is
+    /// collected for each module. Other errors, like missing files, parse
+    /// errors or cycles, still stop the evaluation. Such an error is returned
+    /// after all merge errors collected before it.
+    ///
+    /// # Errors
+    ///
+    /// If any module failed to merge, or if reading failed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # type File = module_util::file::File<Vec<i32>, module_util::file::Json>;
+    /// let mut file = File::json();
+    ///
+    /// if let Err(errors) = file.read_collecting("config.json") {
+    ///     for e in &errors {
+    ///         eprintln!("{e}");
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`read()`]: File::read
+    pub fn read_collecting<P>(&mut self, path: P) -> Result<(), MultiError>
+    where
+        P: AsRef<Path>,
+        T: Clone,
+    {
+        self.collect(path.as_ref(), false)
+    }
+
+    fn collect(&mut self, path: &Path, lossy: bool) -> Result<(), MultiError>
+    where
+        T: Clone,
+    {
+        self.collecting = Some(Collecting {
+            errors: Vec::new(),
+            merge: merge_or_keep::<T>,
+            lossy,
+        });
+
+        // Stop collecting even if reading panics.
+        let guard = CollectingGuard { file: self };
+        let r = guard.file.read(path);
+
+        let mut errors = guard
+            .file
+            .collecting
+            .take()
+            .map(|x| x.errors)
+            .unwrap_or_default();
+        errors.extend(r.err());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.into())
+        }
+    }
+
+    /// Read the module at `path`, keeping all errors for later.
+    ///
+    /// This is like [`read_collecting()`], but the errors are kept inside the
+    /// [`File`] instead of being returned. They can be retrieved, along with
+    /// the partial value, with [`finish_lossy()`]. This makes it possible to
+    /// read many modules and report everything that is wrong with them at
+    /// the end.
+    ///
+    /// Unlike [`read_collecting()`], errors other than merge errors do not
+    /// stop the evaluation either. An import that cannot be read, for
+    /// example because it is missing or does not parse, is kept as an error
+    /// and the evaluation continues with the next import.
+    ///
+    /// See: [`finish_lossy()`].
+    ///
+    /// [`read_collecting()`]: File::read_collecting
+    /// [`finish_lossy()`]: File::finish_lossy
+    pub fn read_lossy<P>(&mut self, path: P)
+    where
+        P: AsRef<Path>,
+        T: Clone,
+    {
+        if let Err(errors) = self.collect(path.as_ref(), true) {
+            self.lost.extend(errors);
+        }
+    }
+
+    /// Read the module at `path` if it exists.
+    ///
+    /// Returns `Ok(false)` without reading anything if `path` does not exist
+    /// or the evaluation is [saturated].
+    /// Otherwise, this is the same as [`read()`] and returns `Ok(true)` on
+    /// success.
+    ///
+    /// This is useful for layered configurations, where any layer may be
+    /// missing. Only `path` itself is optional. Missing imports of the module
+    /// are still an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # type File = module_util::file::File<i32, module_util::file::Json>;
+    /// let mut file = File::json();
+    ///
+    /// for layer in ["/etc/app.json", "/home/user/.app.json", "app.json"] {
+    ///     file.read_optional(layer).unwrap();
+    /// }
+    /// ```
+    ///
+    /// [`read()`]: File::read
+    /// [saturated]: File::is_saturated
+    pub fn read_optional<P>(&mut self, path: P) -> Result<bool, Error>
+    where
+        P: AsRef<Path>,
+    {
+        if self.skip() {
+            return Ok(false);
+        }
+
+        let path = match fs::canonicalize(path) {
+            Ok(x) => x,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(Error::from(e)),
+        };
+
+        self.guarded(DisplayPath(path.clone()), |this| this._read(&path, None, 0))?;
+        Ok(true)
+    }
+
+    /// Read the module at `path`, finish the evaluation and resolve the
+    /// final value.
+    ///
+    /// This is a shortcut for [`read()`], [`finish()`] and then
+    /// [`Resolve::resolve_in_place`]. It is meant for values that contain
+    /// [`Candidates`].
+    ///
+    /// Errors about ties name the modules of the tied values only if the
+    /// `current-module` feature of [`module`] is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use module::Merge;
+    /// # use serde::Deserialize;
+    /// # use module_util::file::File;
+    /// use module::types::Candidates;
+    ///
+    /// #[derive(Deserialize, Merge)]
+    /// #[merge(resolve)]
+    /// struct Config {
+    ///     #[merge(resolve)]
+    ///     jobs: Candidates<u32>,
+    /// }
+    ///
+    /// let config: Config = File::json().read_resolved("config.json").unwrap();
+    /// let jobs = config.jobs.resolved();
+    /// ```
+    ///
+    /// [`read()`]: File::read
+    /// [`finish()`]: File::finish
+    /// [`Candidates`]: module::types::Candidates
+    pub fn read_resolved<P>(mut self, path: P) -> Result<T, Error>
+    where
+        P: AsRef<Path>,
+        T: Resolve,
+    {
+        self.read(path)?;
+
+        let mut value = self
+            .finish()
+            .ok_or_else(|| Error::custom("no module was read"))?;
+
+        value.resolve_in_place()?;
+        Ok(value)
     }
 
-    fn _read(&mut self, path: &Path) -> Result<(), Error> {
-        if self.evaluated.contains(path) {
-            return Err(Error::cycle());
+    /// Run `f` inside a [`ModuleGuard`] for `module`.
+    ///
+    /// Errors collected by [`read_collecting()`] while `f` runs get `module`
+    /// added to their backtrace, just like the error `f` returns.
+    ///
+    /// [`read_collecting()`]: File::read_collecting
+    fn guarded<D>(
+        &mut self,
+        module: D,
+        f: impl FnOnce(&mut Self) -> Result<(), Error>,
+    ) -> Result<(), Error>
+    where
+        D: fmt::Display + Clone + Send + Sync + 'static,
+    {
+        let start = self.collecting.as_ref().map_or(0, |x| x.errors.len());
+        let r = ModuleGuard::new(module.clone()).run(|| f(self));
+
+        if let Some(ref mut x) = self.collecting {
+            for e in &mut x.errors[start..] {
+                e.modules.push(module.clone());
+            }
+        }
+
+        r
+    }
+
+    /// Check whether the next module must be skipped because the evaluation
+    /// is saturated, and count it if so.
+    fn skip(&mut self) -> bool {
+        if self.saturated {
+            self.skipped += 1;
+        }
+        self.saturated
+    }
+
+    /// Read the module at `path`, shifting its default priorities by `offset`.
+    fn _read(&mut self, path: &Path, importer: Option<&Path>, offset: isize) -> Result<(), Error> {
+        self.read_source(Source::File {
+            path,
+            importer,
+            offset,
+        })
+    }
+
+    fn read_source(&mut self, source: Source<'_>) -> Result<(), Error> {
+        check_depth(self.max_depth, self.depth)?;
+
+        self.depth += 1;
+        let r = self.eval(source);
+        self.depth -= 1;
+        r
+    }
+
+    /// Merge the module of `source` and read its imports.
+    fn eval(&mut self, source: Source<'_>) -> Result<(), Error> {
+        let (key, path) = match source {
+            Source::File { path, .. } => (self.identity.key(path), path),
+            Source::Str { name, .. } => {
+                // Paths cannot contain NUL, so this never clashes with a file.
+                let mut key = OsString::from("\0");
+                key.push(self.identity.key(name));
+                (key, name)
+            }
+        };
+
+        // Modules are evaluated at most once. A module that has already been
+        // completed was imported through another path, so it is skipped. Only
+        // a module that is still in progress is a cycle.
+        if self.evaluated.enter(key.clone())? == Visit::Completed {
+            return Ok(());
+        }
+
+        let keys = RefCell::new(Vec::new());
+        let seed = ModuleSeed::new(self.import_key).with_keys(Some(&keys));
+        let (module, offset) = match source {
+            Source::File {
+                path,
+                importer,
+                offset,
+            } => (self.load_cached(&key, path, importer, seed), offset),
+            Source::Str { name, contents } => (self.format.read_str(name, contents, seed), 0),
+        };
+
+        let Module { imports, mut value } = match module {
+            Ok(x) => x,
+            Err(e) => {
+                self.evaluated.abandon(&key);
+                return Err(e);
+            }
+        };
+
+        self.layer(&mut value, offset);
+
+        let is_file = matches!(source, Source::File { .. });
+        let keys = keys.into_inner();
+
+        // The module is listed before its imports, because it was read
+        // before them.
+        let index = self.evaluated_paths.len();
+        if is_file {
+            self.evaluated_paths.push(path.to_path_buf());
         }
 
-        let Module { imports, value } = self.format.read(path)?;
+        let r = self.import_order.walk(
+            self,
+            // The imports may have saturated the evaluation.
+            |this| match this.skip() {
+                true => Ok(()),
+                false => this.merge_value(value, path, keys),
+            },
+            |this| this.read_imports(path, imports),
+        );
 
-        match self.value {
-            Some(ref mut x) => x.merge_ref(value)?,
-            None => self.value = Some(value),
+        let r = match r {
+            Ok(r) => r,
+            Err(e) => {
+                if is_file {
+                    self.evaluated_paths.remove(index);
+                }
+                self.evaluated.abandon(&key);
+                return Err(e);
+            }
+        };
+
+        self.evaluated.complete(&key);
+        r
+    }
+
+    fn read_imports(&mut self, path: &Path, imports: Imports) -> Result<(), Error> {
+        imports.0.into_iter().try_for_each(|x| {
+            let r = self.read_import(path, x);
+            self.keep_lossy(r)
+        })
+    }
+
+    /// Keep the error of reading an import for [`File::read_lossy`], instead
+    /// of returning it.
+    fn keep_lossy(&mut self, r: Result<(), Error>) -> Result<(), Error> {
+        match (r, &mut self.collecting) {
+            (Err(e), Some(x)) if x.lossy => {
+                x.errors.push(e);
+                Ok(())
+            }
+            (r, _) => r,
         }
+    }
 
-        let basename = path
-            .parent()
-            .expect("file path should always have an ancestor")
-            .to_path_buf();
+    /// Load the module at `path`, or take it from the [`Cache`] if there is
+    /// one.
+    fn load_cached(
+        &mut self,
+        key: &OsStr,
+        path: &Path,
+        importer: Option<&Path>,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error> {
+        let Some(ref cache) = self.cache else {
+            return self.load_module(path, importer, seed);
+        };
 
-        self.evaluated.insert(path.to_path_buf());
+        if let Some((module, keys)) = cache.modules.get(key) {
+            // The contents were checked against the manifest when the module
+            // was first read, but it may not have been a root back then.
+            if let Some(ref manifest) = self.enforced {
+                manifest.expect(path, importer)?;
+            }
+            if let (Some(recorded), None) = (&mut self.recorded, importer) {
+                recorded.push_root(path);
+            }
+
+            if let Some(x) = seed.keys() {
+                x.borrow_mut().extend(keys.iter().cloned());
+            }
+            return Ok((cache.clone)(module));
+        }
 
-        imports
-            .0
+        let module = self.load_module(path, importer, seed)?;
+
+        if let Some(ref mut cache) = self.cache {
+            let keys = seed.keys().map(|x| x.borrow().clone()).unwrap_or_default();
+            let cached = (cache.clone)(&module);
+            cache.modules.insert(key.to_os_string(), (cached, keys));
+        }
+
+        Ok(module)
+    }
+
+    fn load_module(
+        &mut self,
+        path: &Path,
+        importer: Option<&Path>,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error> {
+        let expected = match self.enforced {
+            Some(ref manifest) => Some(manifest.expect(path, importer)?),
+            None => None,
+        };
+
+        // Only read the contents up front if they must be hashed.
+        if expected.is_none() && self.recorded.is_none() {
+            return self.format.read(path, seed);
+        }
+
+        let contents = fs::read(path).map_err(Error::from)?;
+        let hash = ContentHash::of(&contents);
+
+        if let Some(expected) = expected {
+            manifest::verify(path, expected, hash)?;
+        }
+
+        if let Some(ref mut recorded) = self.recorded {
+            if importer.is_none() {
+                recorded.push_root(path);
+            }
+            recorded.push(path, hash);
+        }
+
+        self.format.read_bytes(path, &contents, seed)
+    }
+
+    /// Merge `value`, the value of the module at `path` with the top-level
+    /// `keys`, into the accumulated value.
+    ///
+    /// This runs inside the [`ModuleGuard`] of the module, which attaches the
+    /// module to any error.
+    fn merge_value(&mut self, value: T, path: &Path, keys: Vec<String>) -> Result<(), Error> {
+        let r = match self.collecting {
+            None => merge_opt(&mut self.value, value),
+            Some(ref mut x) => (x.merge)(&mut self.value, value),
+        };
+
+        if let Err(e) = r {
+            let e = self.blame(e, path);
+            match self.collecting {
+                None => return Err(e),
+                Some(ref mut x) => {
+                    x.errors.push(e);
+                    return Ok(());
+                }
+            }
+        }
+
+        for key in keys {
+            self.origins
+                .entry(key)
+                .or_default()
+                .push(path.to_path_buf());
+        }
+
+        if let (Some(OnMerge(f)), Some(value)) = (&mut self.on_merge, &self.value) {
+            self.saturated = f(value).is_break();
+        }
+
+        Ok(())
+    }
+
+    /// Note the other modules that defined the top-level key of the value
+    /// that collided in `error`, which was raised by the module at `path`.
+    fn blame(&self, error: Error, path: &Path) -> Error {
+        if !error.kind.is_collision() {
+            return error;
+        }
+
+        let Some(key) = error.value.components().next().map(|x| x.to_string()) else {
+            return error;
+        };
+
+        // Keys of maps are quoted in the path of the value, but the fields of
+        // structs are not.
+        let key = key
+            .strip_prefix('"')
+            .and_then(|x| x.strip_suffix('"'))
+            .unwrap_or(&key);
+
+        let others: Vec<String> = self
+            .origins
+            .get(key)
             .into_iter()
-            .map(|x| basename.join(x))
-            .try_for_each(|p| self.read(p))
+            .flatten()
+            .filter(|x| *x != path)
+            .map(|x| x.display().to_string())
+            .collect();
+
+        if others.is_empty() {
+            return error;
+        }
+
+        let note = match error.note() {
+            Some(note) => format!("{note}; also defined in {}", others.join(", ")),
+            None => format!("also defined in {}", others.join(", ")),
+        };
+
+        error.with_note(note)
+    }
+}
+
+/// Where a module is read from.
+#[derive(Clone, Copy)]
+enum Source<'a> {
+    File {
+        path: &'a Path,
+        importer: Option<&'a Path>,
+        offset: isize,
+    },
+    Str {
+        name: &'a Path,
+        contents: &'a str,
+    },
+}
+
+/// Fail if a module at `depth` is deeper than `max`.
+///
+/// See: [`File::set_max_depth`].
+pub(crate) fn check_depth(max: Option<usize>, depth: usize) -> Result<(), Error> {
+    match max {
+        Some(max) if depth > max => {
+            Err(Error::custom("import depth exceeded").with_note(MaxDepth(max)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The note of the error returned when [`File::max_depth`] is exceeded.
+struct MaxDepth(usize);
+
+impl fmt::Display for MaxDepth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the limit is {}", self.0)
+    }
+}
+
+/// Merge `value` into a clone of `acc`, leaving `acc` untouched on error.
+///
+/// This is how [`File::read_collecting`] merges modules.
+fn merge_or_keep<T>(acc: &mut Option<T>, value: T) -> Result<(), Error>
+where
+    T: Merge + Clone,
+{
+    match acc {
+        Some(x) => {
+            *x = x.clone().merge(value)?;
+            Ok(())
+        }
+        None => {
+            *acc = Some(value);
+            Ok(())
+        }
     }
 }
 
 /// Read the module at `path` with `format`.
 ///
 /// See: [`File`]
-#[expect(clippy::missing_panics_doc)]
 pub fn read<T, F>(path: impl AsRef<Path>, format: F) -> Result<T, Error>
 where
     T: Merge + DeserializeOwned,
@@ -163,13 +1575,40 @@ where
     let mut file = File::new(format);
     file.read(path)?;
 
-    // SAFETY: `file` must have read at least one module. If it hadn't, the
-    //         above statement should have returned with an error.
-    let value = file
-        .finish()
-        .expect("File should have read at least one module");
+    // `file` must have read at least one module. If it hadn't, the above
+    // statement should have returned with an error.
+    file.finish()
+        .ok_or_else(|| Error::custom("no module was read"))
+}
+
+impl<T, F> Importer for File<T, F>
+where
+    T: Merge + DeserializeOwned,
+    F: Format,
+{
+    fn resolver(&self) -> &Resolver {
+        &self.resolver
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.format.extensions()
+    }
 
-    Ok(value)
+    fn import(&mut self, import: Imported) -> Result<(), Error> {
+        if self.skip() {
+            return Ok(());
+        }
+
+        let path = match fs::canonicalize(&import.path) {
+            Ok(x) => x,
+            Err(e) => return self.keep_lossy(Err(Error::from(e))),
+        };
+
+        let r = self.guarded(import.module(&path), |this| {
+            this._read(&path, Some(&import.importer), import.offset)
+        });
+        self.keep_lossy(r)
+    }
 }
 
 impl<T, F> Default for File<T, F>
@@ -181,7 +1620,76 @@ where
     }
 }
 
-struct DisplayPath(PathBuf);
+/// A snapshot of the state of a [`File`].
+///
+/// See: [`File::snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSnapshot<T> {
+    evaluated: VisitedSet<OsString>,
+    evaluated_paths: Vec<PathBuf>,
+    origins: Origins,
+    value: Option<T>,
+    saturated: bool,
+    skipped: usize,
+    collecting: Option<Collecting<T>>,
+    lost: Vec<Error>,
+}
+
+impl<T> FileSnapshot<T> {
+    /// Create the state of an evaluation that has not read any modules.
+    pub(crate) fn empty() -> Self {
+        Self {
+            evaluated: VisitedSet::new(),
+            evaluated_paths: Vec::new(),
+            origins: Origins::new(),
+            value: None,
+            saturated: false,
+            skipped: 0,
+            collecting: None,
+            lost: Vec::new(),
+        }
+    }
+
+    /// Destruct this [`FileSnapshot`] and get the accumulated value.
+    pub(crate) fn into_value(self) -> Option<T> {
+        self.value
+    }
+}
+
+/// Restores a [`File`] when dropped, even while unwinding.
+struct RestoreGuard<'a, T, F>
+where
+    T: Clone,
+{
+    file: &'a mut File<T, F>,
+    snapshot: Option<FileSnapshot<T>>,
+}
+
+impl<T, F> Drop for RestoreGuard<'_, T, F>
+where
+    T: Clone,
+{
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            self.file.restore(snapshot);
+        }
+    }
+}
+
+/// Stops [`File::read_collecting`] from collecting errors when dropped, even
+/// while unwinding.
+struct CollectingGuard<'a, T, F> {
+    file: &'a mut File<T, F>,
+}
+
+impl<T, F> Drop for CollectingGuard<'_, T, F> {
+    fn drop(&mut self) {
+        self.file.collecting = None;
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct DisplayPath(pub(crate) PathBuf);
 
 impl fmt::Display for DisplayPath {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
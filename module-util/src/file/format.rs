@@ -1,15 +1,25 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fmt;
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 
 use module::Error;
 use serde::Deserialize;
-use serde::de::DeserializeOwned;
+use serde::de::value::{CowStrDeserializer, I64Deserializer, U64Deserializer};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, IgnoredAny, IntoDeserializer, MapAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
 
 /// Imports of a [`Module`].
 ///
 /// See: [`Module::imports`]
 #[derive(Default, Clone, Deserialize)]
-pub struct Imports(pub(crate) Vec<PathBuf>);
+pub struct Imports(pub(crate) Vec<Import>);
 
 impl fmt::Debug for Imports {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -19,6 +29,12 @@ impl fmt::Debug for Imports {
 
 impl From<Vec<PathBuf>> for Imports {
     fn from(value: Vec<PathBuf>) -> Self {
+        Self(value.into_iter().map(Import::Path).collect())
+    }
+}
+
+impl From<Vec<Import>> for Imports {
+    fn from(value: Vec<Import>) -> Self {
         Self(value)
     }
 }
@@ -28,14 +44,160 @@ where
     A: Into<PathBuf>,
 {
     fn from_iter<T: IntoIterator<Item = A>>(iter: T) -> Self {
-        Self(iter.into_iter().map(Into::into).collect())
+        Self(iter.into_iter().map(|x| Import::Path(x.into())).collect())
+    }
+}
+
+/// A single import of a [`Module`].
+///
+/// Imports are written either as a path, or as a table with a `path` key for
+/// [`FileImport`]s or a `dir` key for [`DirImport`]s.
+///
+/// ```toml
+/// imports = [
+///     "base.toml",
+///     { path = "local.toml", optional = true },
+///     { dir = "conf.d", layered = true },
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Import {
+    /// Import the module at a path.
+    Path(PathBuf),
+    /// Import the module at a path, with options.
+    File(FileImport),
+    /// Import all modules in a directory.
+    Dir(DirImport),
+}
+
+impl From<PathBuf> for Import {
+    fn from(value: PathBuf) -> Self {
+        Self::Path(value)
+    }
+}
+
+impl From<FileImport> for Import {
+    fn from(value: FileImport) -> Self {
+        Self::File(value)
+    }
+}
+
+impl From<DirImport> for Import {
+    fn from(value: DirImport) -> Self {
+        Self::Dir(value)
+    }
+}
+
+/// Import the module at a path, with options.
+///
+/// Without any options, this is the same as a plain path import.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileImport {
+    /// The path of the module, resolved just like a path import.
+    pub path: PathBuf,
+
+    /// Do nothing if `path` does not exist.
+    ///
+    /// This is meant for modules that may or may not be present, like
+    /// per-machine overrides. Other errors, like missing permissions, are
+    /// still reported.
+    ///
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl FileImport {
+    /// Create a new [`FileImport`] of `path` with the default options.
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            optional: false,
+        }
+    }
+
+    /// Check whether the import must be skipped because it is optional and
+    /// `resolved`, its resolved path, does not exist.
+    pub(crate) fn is_missing(&self, resolved: &Path) -> bool {
+        self.optional
+            && matches!(fs::metadata(resolved), Err(e) if e.kind() == io::ErrorKind::NotFound)
+    }
+}
+
+/// Import all modules in a directory, like a `conf.d` directory.
+///
+/// The modules are all files in `dir` whose extension is one of the
+/// [`Format::extensions`]. They are merged in the order of their path
+/// relative to `dir`, compared byte by byte. The order does not depend on the
+/// locale, so `10-base.toml` comes before `50-site.toml`, which comes before
+/// `99-local.toml`.
+///
+/// Errors of these modules mention the module that has the import, like
+/// `conf.d/50-site.toml (via dir import in root.toml)`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DirImport {
+    /// The directory, resolved just like a path import.
+    pub dir: PathBuf,
+
+    /// Also import modules from the subdirectories of `dir`.
+    ///
+    /// Symbolic links to directories are not followed.
+    ///
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub recursive: bool,
+
+    /// Make later modules override earlier ones.
+    ///
+    /// The value of the `i`-th module is passed to the [`File::on_layer`]
+    /// hook with the offset `-i`, which decreases the default priority of its
+    /// [`Overridable`] values by `i`. So values of later modules win over
+    /// values of earlier modules, unless a priority is given explicitly.
+    /// Imports of these modules are not affected.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`Overridable`]: module::types::Overridable
+    #[serde(default)]
+    pub layered: bool,
+
+    /// Do nothing if `dir` does not exist.
+    ///
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl DirImport {
+    /// Create a new [`DirImport`] of `dir` with the default options.
+    pub fn new<P>(dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            dir: dir.into(),
+            recursive: false,
+            layered: false,
+            optional: false,
+        }
     }
 }
 
 /// The top-level structure of a [`File`] module.
 ///
+/// The imports are read from the `imports` key of the module. [`File`] can
+/// read them from another key instead, see: [`File::set_import_key`] and
+/// [`ModuleSeed`].
+///
 /// [`File`]: super::File
-#[derive(Debug, Default, Clone, Deserialize)]
+/// [`File::set_import_key`]: super::File::set_import_key
+#[derive(Debug, Default, Clone)]
 pub struct Module<T> {
     /// Imports of the module.
     ///
@@ -44,25 +206,556 @@ pub struct Module<T> {
     ///
     /// [`File`]: super::File
     /// [`read()`]: super::File::read
-    #[serde(default)]
     pub imports: Imports,
 
     /// Value of the module.
-    #[serde(flatten)]
     pub value: T,
 }
 
+impl<'de, T> Deserialize<'de> for Module<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ModuleSeed::default().deserialize(deserializer)
+    }
+}
+
+/// Deserializes a [`Module`] whose imports are read from a given key.
+///
+/// [`Format`]s get a [`ModuleSeed`] for every module they read and must
+/// deserialize the module with it.
+///
+/// The imports are taken out of the module as it is deserialized. All other
+/// keys are given to `T`, straight from the deserializer of the format, so
+/// `T` sees them exactly as if it were deserialized on its own.
+///
+/// # Example
+///
+/// ```rust
+/// # use module_util::file::ModuleSeed;
+/// use serde::de::DeserializeSeed;
+///
+/// let mut de = serde_json::Deserializer::from_str(r#"{ "extends": ["base.json"], "port": 8080 }"#);
+/// let module = ModuleSeed::<serde_json::Value>::new("extends")
+///     .deserialize(&mut de)
+///     .unwrap();
+///
+/// assert_eq!(module.value["port"], 8080);
+/// ```
+pub struct ModuleSeed<'a, T> {
+    import_key: &'a str,
+    keys: Option<&'a RefCell<Vec<String>>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> ModuleSeed<'a, T> {
+    /// Create a new [`ModuleSeed`] that reads the imports from `import_key`.
+    pub fn new(import_key: &'a str) -> Self {
+        Self {
+            import_key,
+            keys: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the key of the imports.
+    pub fn import_key(&self) -> &'a str {
+        self.import_key
+    }
+
+    /// Get a [`ModuleSeed`] for modules whose value is a `U`, with the same
+    /// import key.
+    ///
+    /// This is useful for formats that deserialize modules in several steps.
+    pub fn cast<U>(self) -> ModuleSeed<'a, U> {
+        ModuleSeed {
+            import_key: self.import_key,
+            keys: self.keys,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Record the top-level keys of the module in `keys`.
+    pub(crate) fn with_keys(mut self, keys: Option<&'a RefCell<Vec<String>>>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Get where the top-level keys of the module are recorded.
+    pub(crate) fn keys(&self) -> Option<&'a RefCell<Vec<String>>> {
+        self.keys
+    }
+}
+
+impl<T> Default for ModuleSeed<'_, T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_IMPORT_KEY)
+    }
+}
+
+impl<T> Clone for ModuleSeed<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ModuleSeed<'_, T> {}
+
+impl<T> fmt::Debug for ModuleSeed<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModuleSeed")
+            .field("import_key", &self.import_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de, T> DeserializeSeed<'de> for ModuleSeed<'_, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Module<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T> Visitor<'de> for ModuleSeed<'_, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Module<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a module")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Entries {
+            map: &mut map,
+            import_key: self.import_key,
+            keys: self.keys,
+            imports: None,
+        };
+
+        let value = T::deserialize(&mut entries)?;
+
+        // `T` may not have looked at every key, but the imports must be found
+        // wherever they are.
+        while entries.next_key::<IgnoredAny>()?.is_some() {
+            entries.next_value::<IgnoredAny>()?;
+        }
+
+        Ok(Module {
+            imports: entries.imports.unwrap_or_default(),
+            value,
+        })
+    }
+}
+
+/// The entries of a module, without its imports.
+///
+/// The imports are deserialized as soon as their key comes up.
+struct Entries<'a, A> {
+    map: &'a mut A,
+    import_key: &'a str,
+    keys: Option<&'a RefCell<Vec<String>>>,
+    imports: Option<Imports>,
+}
+
+impl<'de, A> MapAccess<'de> for Entries<'_, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        while let Some(key) = self.map.next_key::<Key<'de>>()? {
+            if !key.is(self.import_key) {
+                if let Some(keys) = self.keys {
+                    keys.borrow_mut().push(key.to_string());
+                }
+                return seed.deserialize(key.into_deserializer()).map(Some);
+            }
+
+            if self.imports.is_some() {
+                return Err(de::Error::custom(format_args!(
+                    "duplicate field `{}`",
+                    self.import_key
+                )));
+            }
+            self.imports = Some(self.map.next_value()?);
+        }
+
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.map.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+/// Deserializes the value of a module from its entries.
+///
+/// This accepts the same types as `#[serde(flatten)]` does, so unit structs
+/// and options can still be read from a module.
+impl<'de, A> Deserializer<'de> for &mut Entries<'_, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+/// A key of a module.
+///
+/// Keys are deserialized as identifiers, like the fields of a struct.
+enum Key<'de> {
+    Str(Cow<'de, str>),
+    U64(u64),
+    I64(i64),
+}
+
+impl Key<'_> {
+    fn is(&self, key: &str) -> bool {
+        matches!(self, Self::Str(x) if x == key)
+    }
+}
+
+impl fmt::Display for Key<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(x) => x.fmt(f),
+            Self::U64(x) => x.fmt(f),
+            Self::I64(x) => x.fmt(f),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Key<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a key")
+            }
+
+            fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E> {
+                Ok(Key::Str(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Key::Str(Cow::Owned(v.to_owned())))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+                Ok(Key::Str(Cow::Owned(v)))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Key::U64(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Key::I64(v))
+            }
+        }
+
+        deserializer.deserialize_identifier(KeyVisitor)
+    }
+}
+
+impl<'de, E> IntoDeserializer<'de, E> for Key<'de>
+where
+    E: de::Error,
+{
+    type Deserializer = KeyDeserializer<'de, E>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        match self {
+            Self::Str(x) => KeyDeserializer::Str(x.into_deserializer()),
+            Self::U64(x) => KeyDeserializer::U64(x.into_deserializer()),
+            Self::I64(x) => KeyDeserializer::I64(x.into_deserializer()),
+        }
+    }
+}
+
+/// The [`Deserializer`] of a [`Key`].
+enum KeyDeserializer<'de, E> {
+    Str(CowStrDeserializer<'de, E>),
+    U64(U64Deserializer<E>),
+    I64(I64Deserializer<E>),
+}
+
+impl<'de, E> Deserializer<'de> for KeyDeserializer<'de, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Str(x) => x.deserialize_any(visitor),
+            Self::U64(x) => x.deserialize_any(visitor),
+            Self::I64(x) => x.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Create an error with `message` at `line` and `column` of a module.
+///
+/// The location is attached as the note of the error, so it displays like
+/// `expected value (at line 12, column 5)`. Both are 1-based.
+// Unused without any formats.
+#[allow(dead_code)]
+pub(crate) fn syntax_error<D>(message: D, line: usize, column: usize) -> Error
+where
+    D: fmt::Display + Send + Sync + 'static,
+{
+    Error::custom(message).with_note(Location { line, column })
+}
+
+/// Read the module at `path` to a string, unless it is larger than
+/// `max_size` bytes.
+///
+/// At most one byte past the limit is ever read, no matter how large the
+/// module actually is.
+#[cfg(any(feature = "yaml", feature = "json5"))]
+pub(crate) fn read_limited(path: &Path, max_size: Option<u64>) -> Result<String, Error> {
+    use std::io::Read;
+
+    let file = fs::File::open(path).map_err(Error::from)?;
+
+    let mut data = String::new();
+    match max_size {
+        Some(max) => file.take(max.saturating_add(1)).read_to_string(&mut data),
+        None => io::BufReader::new(file).read_to_string(&mut data),
+    }
+    .map_err(Error::from)?;
+
+    check_size(&data, max_size)?;
+    Ok(data)
+}
+
+/// Check that `data` is no larger than `max_size` bytes.
+#[cfg(any(feature = "yaml", feature = "json5"))]
+pub(crate) fn check_size(data: &str, max_size: Option<u64>) -> Result<(), Error> {
+    match max_size {
+        Some(max) if data.len() as u64 > max => Err(Error::custom(format!(
+            "module exceeds the limit of {max} bytes"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+struct Location {
+    line: usize,
+    column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at line {}, column {}", self.line, self.column)
+    }
+}
+
+/// The key of the imports of a [`Module`], unless [`File`] says otherwise.
+///
+/// [`File`]: super::File
+pub(crate) const DEFAULT_IMPORT_KEY: &str = "imports";
+
 /// The format of a file.
 ///
 /// The job of a [`Format`] is to read a file, parse it and convert it to a
-/// [`Module`] so it can be merged.
+/// [`Module`] so it can be merged. The module is deserialized with the
+/// [`ModuleSeed`] given by the caller, which knows where its imports are.
+///
+/// The built-in formats report where in the module an error is, if they know,
+/// as the [note] of the error. So errors display like `expected value (at
+/// line 12, column 5)`, followed by the module. TOML errors are the exception,
+/// they show the location on their own.
+///
+/// # Example
 ///
+/// ```rust
+/// # use std::path::Path;
+/// # use module::Error;
+/// # use module_util::file::{Format, Module, ModuleSeed};
+/// use serde::de::{DeserializeOwned, DeserializeSeed};
+///
+/// struct Json;
+///
+/// impl Format for Json {
+///     fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+///     where
+///         T: DeserializeOwned,
+///     {
+///         let data = std::fs::read_to_string(path).map_err(Error::from)?;
+///         self.read_str(path, &data, seed)
+///     }
+///
+///     fn read_str<T>(
+///         &mut self,
+///         _name: &Path,
+///         contents: &str,
+///         seed: ModuleSeed<T>,
+///     ) -> Result<Module<T>, Error>
+///     where
+///         T: DeserializeOwned,
+///     {
+///         let mut de = serde_json::Deserializer::from_str(contents);
+///         seed.deserialize(&mut de).map_err(Error::custom)
+///     }
+/// }
+/// ```
+///
+/// [note]: Error::note
 /// [`File`]: super::File
 pub trait Format {
     /// Read the module at `path`.
     ///
     /// See [trait-level docs](Format) for more information.
-    fn read<T>(&mut self, path: &Path) -> Result<Module<T>, Error>
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
     where
         T: DeserializeOwned;
+
+    /// Read the module in `contents`.
+    ///
+    /// `name` is the name the module was given, see: [`File::read_str`]. It
+    /// takes the place of the path of the module.
+    ///
+    /// Defaults to returning an error, for formats that can only read files.
+    ///
+    /// [`File::read_str`]: super::File::read_str
+    fn read_str<T>(
+        &mut self,
+        name: &Path,
+        contents: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let _ = (name, contents, seed);
+        Err(Error::custom(
+            "this format cannot read modules from strings",
+        ))
+    }
+
+    /// Read the module in `contents`, the raw contents of a file.
+    ///
+    /// `name` is the path of the file. This is used by [`File`] when it has
+    /// already read the file, for example to check it against a
+    /// [`Manifest`], so that the module is parsed from exactly those
+    /// contents.
+    ///
+    /// Defaults to [`Format::read_str`], if `contents` is valid UTF-8.
+    ///
+    /// [`File`]: super::File
+    /// [`Manifest`]: super::Manifest
+    fn read_bytes<T>(
+        &mut self,
+        name: &Path,
+        contents: &[u8],
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let contents = str::from_utf8(contents).map_err(Error::custom)?;
+        self.read_str(name, contents, seed)
+    }
+
+    /// Get the file extensions of this format, without the leading `.`.
+    ///
+    /// [`DirImport`]s only read files with one of these extensions. If there
+    /// are none, they read all files.
+    ///
+    /// Defaults to no extensions.
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
 }
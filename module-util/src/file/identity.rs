@@ -0,0 +1,145 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+/// Rules for deciding whether 2 paths refer to the same module.
+///
+/// [`File`] uses this to remember which modules it has already evaluated. Two
+/// paths are considered the same module if their [keys] are equal. Keys are
+/// only used for comparison. Paths are always displayed with their original
+/// spelling.
+///
+/// Keys are computed by:
+///
+/// * treating `/` as a separator, and also `\` on Windows,
+/// * removing `.` components and trailing separators,
+/// * and, if enabled, folding the case of the path.
+///
+/// By default, case folding follows the platform. It is enabled on Windows and
+/// macOS, whose default filesystems are case-insensitive, and disabled
+/// everywhere else.
+///
+/// [`File`]: super::File
+/// [keys]: PathIdentity::key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathIdentity {
+    case_fold: bool,
+}
+
+/// The separators of path components.
+///
+/// `\` is a legal character of file names everywhere except Windows.
+const SEPARATORS: &[char] = if cfg!(windows) { &['/', '\\'] } else { &['/'] };
+
+impl Default for PathIdentity {
+    fn default() -> Self {
+        Self::platform()
+    }
+}
+
+impl PathIdentity {
+    /// Create a new [`PathIdentity`] that follows the platform's rules.
+    pub const fn platform() -> Self {
+        Self {
+            case_fold: cfg!(any(windows, target_os = "macos")),
+        }
+    }
+
+    /// Create a new [`PathIdentity`] that treats paths as case-sensitive.
+    pub const fn case_sensitive() -> Self {
+        Self { case_fold: false }
+    }
+
+    /// Create a new [`PathIdentity`] that treats paths as case-insensitive.
+    pub const fn case_insensitive() -> Self {
+        Self { case_fold: true }
+    }
+
+    /// Check whether this [`PathIdentity`] folds the case of paths.
+    pub const fn case_fold(&self) -> bool {
+        self.case_fold
+    }
+
+    /// Compute the key of `path`.
+    ///
+    /// Paths that are not valid UTF-8 are only normalized as far as the
+    /// platform's own [`Path::components`] allows.
+    pub fn key(&self, path: &Path) -> OsString {
+        let Some(s) = path.to_str() else {
+            return path.components().collect::<std::path::PathBuf>().into();
+        };
+
+        let mut key = String::with_capacity(s.len());
+
+        if s.starts_with(SEPARATORS) {
+            key.push('/');
+        }
+
+        let components = s.split(SEPARATORS).filter(|x| !x.is_empty() && *x != ".");
+
+        for (i, component) in components.enumerate() {
+            if i != 0 {
+                key.push('/');
+            }
+
+            if self.case_fold {
+                key.extend(component.chars().flat_map(char::to_lowercase));
+            } else {
+                key.push_str(component);
+            }
+        }
+
+        key.into()
+    }
+
+    /// Check whether `a` and `b` refer to the same module.
+    pub fn same(&self, a: &Path, b: &Path) -> bool {
+        self.key(a) == self.key(b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_normalizes() {
+        let id = PathIdentity::case_sensitive();
+
+        let key = |x: &str| id.key(Path::new(x));
+
+        assert_eq!(key("/etc/app/config.toml"), "/etc/app/config.toml");
+        assert_eq!(key("/etc/./app//config.toml"), "/etc/app/config.toml");
+        assert_eq!(key("/etc/app/"), "/etc/app");
+        assert_eq!(key("./config.toml"), "config.toml");
+    }
+
+    #[test]
+    fn test_key_backslash() {
+        let id = PathIdentity::case_sensitive();
+
+        let key = |x: &str| id.key(Path::new(x));
+
+        if cfg!(windows) {
+            assert_eq!(key("\\etc\\app\\config.toml"), "/etc/app/config.toml");
+            assert_eq!(key("C:\\app/config.toml"), "C:/app/config.toml");
+        } else {
+            assert_eq!(key("/etc/app\\config.toml"), "/etc/app\\config.toml");
+            assert!(!id.same(Path::new("/etc/a\\b"), Path::new("/etc/a/b")));
+        }
+    }
+
+    #[test]
+    fn test_case_fold() {
+        let a = Path::new("/etc/App/Base.toml");
+        let b = Path::new("/etc/app/base.toml");
+
+        assert!(PathIdentity::case_insensitive().same(a, b));
+        assert!(!PathIdentity::case_sensitive().same(a, b));
+    }
+
+    #[test]
+    fn test_platform_default() {
+        let id = PathIdentity::default();
+        assert_eq!(id.case_fold(), cfg!(any(windows, target_os = "macos")));
+    }
+}
@@ -0,0 +1,477 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use module::Error;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Unexpected, Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use super::format::syntax_error;
+use super::{Format, Module, ModuleSeed};
+
+/// A [`Format`] for [INI] modules.
+///
+/// INI has no standard, so this is the dialect that is read:
+///
+/// ```ini
+/// ; Comments start with `;` or `#` and take up the whole line.
+/// imports = base.ini, local.ini
+///
+/// name = "my app"
+/// debug = true
+///
+/// [server]
+/// host = localhost
+/// port: 8080
+///
+/// [server.tls]
+/// ciphers[] = TLS_AES_128_GCM_SHA256
+/// ciphers[] = TLS_AES_256_GCM_SHA384
+/// ```
+///
+/// * Keys and values are separated by `=` or `:`. Whitespace around both is
+///   ignored. Values surrounded by `"` are taken as they are, without the
+///   quotes.
+///
+/// * Keys before the first section are top-level keys. Sections nest with
+///   `.`, so `[server.tls]` is the `tls` table inside the `server` table. A
+///   section may appear more than once, in which case its keys are added to
+///   the same table.
+///
+/// * Every value is a string, which is converted to whatever type the module
+///   asks for. A value read as a sequence is split at `,`, unless it is
+///   quoted. Keys ending with `[]` build a sequence one line at a time
+///   instead, for values that contain `,`.
+///
+/// * Imports are read from the top-level `imports` key, a sequence of paths
+///   like any other. A `[imports]` section is a single [`FileImport`] or
+///   [`DirImport`].
+///
+/// * Any key may only appear once in each section.
+///
+/// Values that are read without knowing their type, for example by fields
+/// with `#[serde(flatten)]`, are `true` and `false` as booleans, numbers as
+/// numbers and everything else as strings. Quoted values are always
+/// strings. This can be turned off with [`Ini::builder`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{File, Ini};
+/// let mut file = File::<i32, _>::ini();
+/// file.read("config.ini").unwrap();
+/// ```
+///
+/// [INI]: https://en.wikipedia.org/wiki/INI_file
+/// [`FileImport`]: super::FileImport
+/// [`DirImport`]: super::DirImport
+#[derive(Debug, Clone, Copy)]
+pub struct Ini {
+    infer_types: bool,
+}
+
+impl Default for Ini {
+    fn default() -> Self {
+        Self { infer_types: true }
+    }
+}
+
+impl Ini {
+    /// Create a new [`IniBuilder`].
+    ///
+    /// The builder starts out with the default options.
+    pub fn builder() -> IniBuilder {
+        IniBuilder::default()
+    }
+
+    /// Check whether the types of values read without knowing their type are
+    /// inferred.
+    pub fn infer_types(&self) -> bool {
+        self.infer_types
+    }
+}
+
+/// A builder for [`Ini`].
+///
+/// See: [`Ini::builder`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IniBuilder {
+    inner: Ini,
+}
+
+impl IniBuilder {
+    /// Infer the types of values that are read without knowing their type.
+    ///
+    /// When this is disabled, such values are always strings.
+    ///
+    /// Defaults to `true`.
+    pub fn infer_types(mut self, enable: bool) -> Self {
+        self.inner.infer_types = enable;
+        self
+    }
+
+    /// Build the [`Ini`] format.
+    pub fn build(self) -> Ini {
+        self.inner
+    }
+}
+
+impl Format for Ini {
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let data = fs::read_to_string(path).map_err(Error::from)?;
+        self.read_str(path, &data, seed)
+    }
+
+    fn read_str<T>(
+        &mut self,
+        _name: &Path,
+        data: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let table = parse(data)?;
+        seed.deserialize(self.de(Node::Table(table)))
+            .map_err(Error::custom)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ini"]
+    }
+}
+
+impl Ini {
+    fn de(&self, node: Node) -> NodeDeserializer {
+        NodeDeserializer::new(node, self.infer_types)
+    }
+}
+
+type Table = Vec<(String, Node)>;
+
+/// A parsed value.
+#[derive(Debug)]
+enum Node {
+    Scalar(Scalar),
+    Seq(Vec<Scalar>),
+    Table(Table),
+}
+
+#[derive(Debug)]
+struct Scalar {
+    text: String,
+    quoted: bool,
+}
+
+impl Scalar {
+    fn new(text: &str) -> Self {
+        match text.strip_prefix('"').and_then(|x| x.strip_suffix('"')) {
+            Some(x) => Self {
+                text: x.to_owned(),
+                quoted: true,
+            },
+            None => Self {
+                text: text.to_owned(),
+                quoted: false,
+            },
+        }
+    }
+}
+
+fn parse(data: &str) -> Result<Table, Error> {
+    let mut root = Table::new();
+    let mut section = Vec::new();
+
+    for (i, line) in data.lines().enumerate() {
+        let line_no = i + 1;
+        let trimmed = line.trim();
+        let column = line.len() - line.trim_start().len() + 1;
+
+        if trimmed.is_empty() || trimmed.starts_with([';', '#']) {
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix('[') {
+            let Some(name) = name.strip_suffix(']') else {
+                return Err(syntax_error("expected `]`", line_no, column + trimmed.len()));
+            };
+
+            section = name.split('.').map(|x| x.trim().to_owned()).collect();
+            if section.iter().any(String::is_empty) {
+                return Err(syntax_error("expected a section name", line_no, column));
+            }
+
+            table(&mut root, &section).map_err(|e| syntax_error(e, line_no, column))?;
+            continue;
+        }
+
+        let Some(sep) = trimmed.find(['=', ':']) else {
+            return Err(syntax_error(
+                "expected `=` or `:`",
+                line_no,
+                column + trimmed.len(),
+            ));
+        };
+
+        let key = trimmed[..sep].trim();
+        let value = trimmed[sep + 1..].trim();
+
+        let (key, push) = match key.strip_suffix("[]") {
+            Some(x) => (x.trim_end(), true),
+            None => (key, false),
+        };
+
+        if key.is_empty() {
+            return Err(syntax_error("expected a key", line_no, column));
+        }
+
+        let table = table(&mut root, &section).map_err(|e| syntax_error(e, line_no, column))?;
+        let existing = table.iter_mut().find(|(k, _)| k == key).map(|(_, x)| x);
+
+        match (existing, push) {
+            (None, false) => table.push((key.to_owned(), Node::Scalar(Scalar::new(value)))),
+            (None, true) => table.push((key.to_owned(), Node::Seq(vec![Scalar::new(value)]))),
+            (Some(Node::Seq(x)), true) => x.push(Scalar::new(value)),
+            (Some(_), _) => {
+                return Err(syntax_error(
+                    format!("duplicate key `{key}`"),
+                    line_no,
+                    column,
+                ));
+            }
+        }
+    }
+
+    Ok(root)
+}
+
+/// Get the table at `path` inside `root`, creating it if needed.
+fn table<'a>(root: &'a mut Table, path: &[String]) -> Result<&'a mut Table, String> {
+    let mut table = root;
+
+    for name in path {
+        let i = match table.iter().position(|(k, _)| k == name) {
+            Some(i) => i,
+            None => {
+                table.push((name.clone(), Node::Table(Table::new())));
+                table.len() - 1
+            }
+        };
+
+        table = match table[i].1 {
+            Node::Table(ref mut x) => x,
+            _ => return Err(format!("`{name}` is not a section")),
+        };
+    }
+
+    Ok(table)
+}
+
+/// A [`Deserializer`] for [`Node`].
+///
+/// [`Deserializer`]: de::Deserializer
+struct NodeDeserializer {
+    node: Node,
+    infer_types: bool,
+}
+
+impl NodeDeserializer {
+    fn new(node: Node, infer_types: bool) -> Self {
+        Self { node, infer_types }
+    }
+
+    fn scalar<'de, V>(self, visitor: &V) -> Result<Scalar, de::value::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.node {
+            Node::Scalar(x) => Ok(x),
+            Node::Seq(_) => Err(de::Error::invalid_type(Unexpected::Seq, visitor)),
+            Node::Table(_) => Err(de::Error::invalid_type(Unexpected::Map, visitor)),
+        }
+    }
+
+    fn parse<'de, T, V>(self, visitor: &V) -> Result<T, de::value::Error>
+    where
+        T: FromStr,
+        V: Visitor<'de>,
+    {
+        let x = self.scalar(visitor)?;
+        x.text
+            .parse()
+            .map_err(|_| de::Error::invalid_value(Unexpected::Str(&x.text), visitor))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, de::value::Error> for NodeDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let x = self.parse(&visitor)?;
+            visitor.$visit(x)
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for NodeDeserializer {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let infer_types = self.infer_types;
+
+        match self.node {
+            Node::Scalar(x) if x.quoted || !infer_types => visitor.visit_string(x.text),
+            Node::Scalar(x) => match x.text.as_str() {
+                "true" => visitor.visit_bool(true),
+                "false" => visitor.visit_bool(false),
+                text => match (text.parse::<i64>(), text.parse::<f64>()) {
+                    (Ok(n), _) => visitor.visit_i64(n),
+                    (_, Ok(n)) if text.starts_with(|c: char| c.is_ascii_digit() || c == '-') => {
+                        visitor.visit_f64(n)
+                    }
+                    _ => visitor.visit_string(x.text),
+                },
+            },
+            Node::Seq(x) => {
+                let mut seq = SeqDeserializer::new(
+                    x.into_iter().map(|x| Self::new(Node::Scalar(x), infer_types)),
+                );
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Node::Table(x) => {
+                let mut map = MapDeserializer::new(
+                    x.into_iter().map(|(k, v)| (k, Self::new(v, infer_types))),
+                );
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let items = match self.node {
+            Node::Scalar(ref x) if !x.quoted && x.text.is_empty() => Vec::new(),
+            Node::Scalar(ref x) if !x.quoted => x.text.split(',').map(|x| Scalar::new(x.trim())).collect(),
+            Node::Scalar(x) => vec![x],
+            Node::Seq(x) => x,
+            Node::Table(_) => return self.deserialize_any(visitor),
+        };
+
+        Self::new(Node::Seq(items), self.infer_types).deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.node {
+            Node::Scalar(x) => visitor.visit_string(x.text),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.node {
+            Node::Scalar(x) => visitor.visit_enum(x.text.into_deserializer()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct map struct identifier ignored_any
+    }
+}
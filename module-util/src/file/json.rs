@@ -3,30 +3,317 @@ use std::io;
 use std::path::Path;
 
 use module::Error;
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, DeserializeSeed};
+use serde_json::de::{IoRead, Read, SliceRead, StrRead};
 
-use super::{Format, Module};
+use super::format::syntax_error;
+use super::{Format, Module, ModuleSeed};
 
 /// A [`Format`] for [JSON] modules.
 ///
 /// Uses [`serde_json`] under the hood.
 ///
+/// By default, modules must be strict JSON. Comments and trailing commas, as
+/// found in JSONC files, can be allowed with [`Json::builder`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{File, Json};
+/// let json = Json::builder()
+///     .allow_comments(true)
+///     .allow_trailing_commas(true)
+///     .build();
+///
+/// let mut file = File::<i32, _>::json_with(json);
+/// file.read("config.jsonc").unwrap();
+/// ```
+///
 /// [JSON]: https://www.json.org/json-en.html
 #[derive(Debug, Default, Clone, Copy)]
-pub struct Json;
+pub struct Json {
+    allow_comments: bool,
+    allow_trailing_commas: bool,
+}
+
+impl Json {
+    /// Create a new [`JsonBuilder`].
+    ///
+    /// The builder starts out with the default options.
+    pub fn builder() -> JsonBuilder {
+        JsonBuilder::default()
+    }
+
+    /// Check whether `//` and `/* */` comments are allowed.
+    pub fn allows_comments(&self) -> bool {
+        self.allow_comments
+    }
+
+    /// Check whether trailing commas in arrays and objects are allowed.
+    pub fn allows_trailing_commas(&self) -> bool {
+        self.allow_trailing_commas
+    }
+}
+
+/// A builder for [`Json`].
+///
+/// See: [`Json::builder`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonBuilder {
+    inner: Json,
+}
+
+impl JsonBuilder {
+    /// Allow `//` and `/* */` comments.
+    ///
+    /// Defaults to `false`.
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.inner.allow_comments = allow;
+        self
+    }
+
+    /// Allow trailing commas in arrays and objects.
+    ///
+    /// Defaults to `false`.
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.inner.allow_trailing_commas = allow;
+        self
+    }
+
+    /// Build the [`Json`] format.
+    pub fn build(self) -> Json {
+        self.inner
+    }
+}
 
 impl Format for Json {
-    fn read<T>(&mut self, path: &Path) -> Result<Module<T>, Error>
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.allow_comments && !self.allow_trailing_commas {
+            let reader = fs::File::options()
+                .read(true)
+                .open(path)
+                .map(io::BufReader::new)
+                .map_err(Error::from)?;
+
+            return deserialize(IoRead::new(reader), seed);
+        }
+
+        let data = fs::read_to_string(path).map_err(Error::from)?;
+        self.read_str(path, &data, seed)
+    }
+
+    fn read_str<T>(
+        &mut self,
+        _name: &Path,
+        data: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
     where
         T: DeserializeOwned,
     {
-        let reader = fs::File::options()
-            .read(true)
-            .open(path)
-            .map(io::BufReader::new)
-            .map_err(Error::custom)?;
-
-        let module = serde_json::from_reader(reader).map_err(Error::custom)?;
-        Ok(module)
+        if !self.allow_comments && !self.allow_trailing_commas {
+            deserialize(StrRead::new(data), seed)
+        } else {
+            deserialize(StrRead::new(&preprocess(data, self)?), seed)
+        }
+    }
+
+    fn read_bytes<T>(
+        &mut self,
+        name: &Path,
+        data: &[u8],
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.allow_comments && !self.allow_trailing_commas {
+            return deserialize(SliceRead::new(data), seed);
+        }
+
+        let data = str::from_utf8(data).map_err(Error::custom)?;
+        self.read_str(name, data, seed)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+}
+
+/// Deserialize the module in `read` with `seed`.
+fn deserialize<'de, R, T>(read: R, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+where
+    R: Read<'de>,
+    T: DeserializeOwned,
+{
+    let mut de = serde_json::Deserializer::new(read);
+    let module = seed.deserialize(&mut de).map_err(error)?;
+    de.end().map_err(error)?;
+    Ok(module)
+}
+
+/// Convert `e` to an [`Error`], moving its location to the note.
+fn error(e: serde_json::Error) -> Error {
+    if e.line() == 0 {
+        return Error::custom(e);
+    }
+
+    let suffix = format!(" at line {} column {}", e.line(), e.column());
+    let msg = e.to_string();
+    let msg = msg.strip_suffix(&suffix).unwrap_or(&msg).to_owned();
+
+    syntax_error(msg, e.line(), e.column())
+}
+
+/// Turn the comments and trailing commas allowed by `options` into whitespace.
+///
+/// Only whitespace is ever substituted and newlines are kept, so the positions
+/// reported by [`serde_json`] still match the original file.
+fn preprocess(data: &str, options: &Json) -> Result<String, Error> {
+    let mut out = data.as_bytes().to_vec();
+
+    let mut i = 0;
+    let mut in_string = false;
+    let mut pending_comma: Option<usize> = None;
+
+    while i < out.len() {
+        let c = out[i];
+
+        if in_string {
+            match c {
+                b'\\' => i += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+
+            i += 1;
+            continue;
+        }
+
+        match (c, out.get(i + 1)) {
+            (b'/', Some(b'/')) if options.allow_comments => {
+                while i < out.len() && out[i] != b'\n' {
+                    out[i] = b' ';
+                    i += 1;
+                }
+                continue;
+            }
+            (b'/', Some(b'*')) if options.allow_comments => {
+                let start = i;
+                out[i] = b' ';
+                out[i + 1] = b' ';
+                i += 2;
+
+                loop {
+                    match (out.get(i), out.get(i + 1)) {
+                        (Some(b'*'), Some(b'/')) => {
+                            out[i] = b' ';
+                            out[i + 1] = b' ';
+                            i += 2;
+                            break;
+                        }
+                        (Some(b'\n'), _) => i += 1,
+                        (Some(_), _) => {
+                            out[i] = b' ';
+                            i += 1;
+                        }
+                        (None, _) => {
+                            let line = data[..start].matches('\n').count() + 1;
+                            return Err(Error::custom(format!(
+                                "unterminated block comment starting at line {line}"
+                            )));
+                        }
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if let Some(comma) = pending_comma.take()
+            && matches!(c, b']' | b'}')
+            && options.allow_trailing_commas
+        {
+            out[comma] = b' ';
+        }
+
+        match c {
+            b'"' => in_string = true,
+            b',' => pending_comma = Some(i),
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    // Only ASCII characters were replaced by spaces, so this cannot fail.
+    String::from_utf8(out).map_err(Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(data: &str, allow_comments: bool, allow_trailing_commas: bool) -> String {
+        let options = Json::builder()
+            .allow_comments(allow_comments)
+            .allow_trailing_commas(allow_trailing_commas)
+            .build();
+
+        preprocess(data, &options).unwrap()
+    }
+
+    #[test]
+    fn test_comments() {
+        let data = "{ // a\n\"a\": 1, /* b\nc */ \"b\": \"// not a comment\" }";
+
+        assert_eq!(
+            strip(data, true, false),
+            "{     \n\"a\": 1,     \n     \"b\": \"// not a comment\" }"
+        );
+        assert_eq!(strip(data, false, false), data);
+    }
+
+    #[test]
+    fn test_trailing_commas() {
+        let data = "{ \"a\": [1, 2, ], \"b\": \",]\", }";
+
+        assert_eq!(
+            strip(data, false, true),
+            "{ \"a\": [1, 2  ], \"b\": \",]\"  }"
+        );
+        assert_eq!(strip(data, false, false), data);
+    }
+
+    #[test]
+    fn test_trailing_comma_before_comment() {
+        let data = "[1, // last\n]";
+        assert_eq!(strip(data, true, true), "[1         \n]");
+        assert_eq!(strip(data, true, false), "[1,        \n]");
+    }
+
+    #[test]
+    fn test_escaped_quote() {
+        let data = r#"["\"//", 1,]"#;
+        assert_eq!(strip(data, true, true), r#"["\"//", 1 ]"#);
+    }
+
+    #[test]
+    fn test_unterminated_comment() {
+        let options = Json::builder().allow_comments(true).build();
+
+        let err = preprocess("{\n/* a", &options).unwrap_err();
+        assert_eq!(
+            err.kind.to_string(),
+            "unterminated block comment starting at line 2"
+        );
     }
 }
@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use module::Error;
+use serde::de::{DeserializeOwned, DeserializeSeed};
+
+use super::format::{check_size, read_limited, syntax_error};
+use super::{Format, Module, ModuleSeed};
+
+/// A [`Format`] for [JSON5] modules.
+///
+/// Uses [`json5`] under the hood.
+///
+/// JSON5 is a superset of JSON meant for files edited by hand. It allows
+/// comments, trailing commas, unquoted keys and single-quoted strings, among
+/// others.
+///
+/// By default, modules are read without any limits other than those of
+/// [`json5`] itself. A limit on the size of modules can be set with
+/// [`Json5::builder`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{File, Json5};
+/// let json5 = Json5::builder().max_size(Some(64 * 1024)).build();
+///
+/// let mut file = File::<i32, _>::json5_with(json5);
+/// file.read("config.json5").unwrap();
+/// ```
+///
+/// [JSON5]: https://json5.org/
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Json5 {
+    max_size: Option<u64>,
+}
+
+impl Json5 {
+    /// Create a new [`Json5Builder`].
+    ///
+    /// The builder starts out with the default options.
+    pub fn builder() -> Json5Builder {
+        Json5Builder::default()
+    }
+
+    /// Get the maximum size of a module in bytes.
+    pub fn max_size(&self) -> Option<u64> {
+        self.max_size
+    }
+}
+
+/// A builder for [`Json5`].
+///
+/// See: [`Json5::builder`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Json5Builder {
+    inner: Json5,
+}
+
+impl Json5Builder {
+    /// Set the maximum size of a module in bytes.
+    ///
+    /// Larger modules are rejected before being parsed. Defaults to `None`,
+    /// which means no limit.
+    pub fn max_size(mut self, max: Option<u64>) -> Self {
+        self.inner.max_size = max;
+        self
+    }
+
+    /// Build the [`Json5`] format.
+    pub fn build(self) -> Json5 {
+        self.inner
+    }
+}
+
+impl Format for Json5 {
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let data = read_limited(path, self.max_size)?;
+        self.read_str(path, &data, seed)
+    }
+
+    fn read_str<T>(
+        &mut self,
+        _name: &Path,
+        data: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        check_size(data, self.max_size)?;
+
+        let mut de = json5::Deserializer::from_str(data).map_err(error)?;
+        let module = seed.deserialize(&mut de).map_err(error)?;
+        Ok(module)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json5"]
+    }
+}
+
+/// Convert `e` to an [`Error`], moving its location to the note.
+fn error(e: json5::Error) -> Error {
+    let json5::Error::Message { msg, location } = e;
+
+    let Some(location) = location else {
+        return Error::custom(msg);
+    };
+
+    // Syntax errors come with a rendered snippet of the module. Its last line
+    // holds the actual message, after a `= `.
+    let msg = match msg.rsplit_once("\n  = ") {
+        Some((_, x)) if msg.starts_with(" -->") => x.to_owned(),
+        _ => msg,
+    };
+
+    syntax_error(msg, location.line, location.column)
+}
@@ -0,0 +1,387 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use module::Error;
+
+/// The hash of the contents of a module.
+///
+/// This is a 64-bit [FNV-1a] hash. It is meant to detect accidental changes
+/// to modules, it offers no protection against deliberate tampering.
+///
+/// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/index.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContentHash(u64);
+
+impl ContentHash {
+    const PREFIX: &str = "fnv1a64:";
+
+    /// Compute the hash of `data`.
+    pub fn of(data: &[u8]) -> Self {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let hash = data
+            .iter()
+            .fold(OFFSET, |h, &b| (h ^ u64::from(b)).wrapping_mul(PRIME));
+
+        Self(hash)
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let hex = s.strip_prefix(Self::PREFIX)?;
+        if hex.len() != 16 {
+            return None;
+        }
+
+        u64::from_str_radix(hex, 16).ok().map(Self)
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{:016x}", Self::PREFIX, self.0)
+    }
+}
+
+/// A module recorded in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    path: PathBuf,
+    hash: ContentHash,
+}
+
+impl ManifestEntry {
+    /// Get the canonical path of the module.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the hash of the contents of the module.
+    pub fn hash(&self) -> ContentHash {
+        self.hash
+    }
+}
+
+/// A record of all modules read during an evaluation.
+///
+/// A [`Manifest`] lists the canonical path and the [`ContentHash`] of every
+/// module, in the order they were read, along with the roots of the
+/// evaluation, the modules that were not imported by another module.
+///
+/// Manifests are produced by [`File::record_manifest`] and can be enforced on
+/// a later evaluation with [`File::with_manifest`]. This makes it possible to
+/// declare every file an evaluation will touch before running it, as required
+/// by hermetic build systems.
+///
+/// # Lockfile
+///
+/// A [`Manifest`] can be stored as a line-based lockfile:
+///
+/// ```text
+/// # module manifest v1
+/// root /etc/app/config.json
+/// fnv1a64:a3c4e2f1b05d7c98 /etc/app/config.json
+/// fnv1a64:5e0d1c9b8a7f6e42 /etc/app/base.json
+/// ```
+///
+/// See: [`Manifest::to_lockfile`], [`Manifest::from_lockfile`].
+///
+/// [`File::record_manifest`]: super::File::record_manifest
+/// [`File::with_manifest`]: super::File::with_manifest
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    roots: Vec<PathBuf>,
+    entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    const HEADER: &str = "# module manifest v1";
+
+    /// Create a new empty [`Manifest`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the roots of the evaluation, in the order they were read.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Get all modules, in the order they were read.
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// Get the entry for the module at `path`.
+    pub fn get(&self, path: &Path) -> Option<&ManifestEntry> {
+        self.entries.iter().find(|x| x.path == path)
+    }
+
+    pub(crate) fn push_root(&mut self, path: &Path) {
+        if !self.roots.iter().any(|x| x == path) {
+            self.roots.push(path.to_path_buf());
+        }
+    }
+
+    pub(crate) fn push(&mut self, path: &Path, hash: ContentHash) {
+        match self.entries.iter_mut().find(|x| x.path == path) {
+            Some(x) => x.hash = hash,
+            None => self.entries.push(ManifestEntry {
+                path: path.to_path_buf(),
+                hash,
+            }),
+        }
+    }
+
+    /// Render the manifest as a lockfile.
+    ///
+    /// See: [type-level docs](Manifest#lockfile).
+    ///
+    /// # Errors
+    ///
+    /// If a path is not valid UTF-8 or contains a newline.
+    pub fn to_lockfile(&self) -> Result<String, Error> {
+        let path = |x: &Path| match x.to_str() {
+            Some(x) if !x.contains('\n') => Ok(x.to_owned()),
+            _ => Err(Error::custom(format!(
+                "cannot store '{}' in a lockfile",
+                x.display()
+            ))),
+        };
+
+        let mut out = format!("{}\n", Self::HEADER);
+
+        for root in &self.roots {
+            out.push_str(&format!("root {}\n", path(root)?));
+        }
+
+        for entry in &self.entries {
+            out.push_str(&format!("{} {}\n", entry.hash, path(&entry.path)?));
+        }
+
+        Ok(out)
+    }
+
+    /// Parse a lockfile produced by [`Manifest::to_lockfile`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not a valid lockfile.
+    pub fn from_lockfile(s: &str) -> Result<Self, Error> {
+        let mut lines = s.lines().enumerate();
+
+        match lines.next() {
+            Some((_, x)) if x.trim_end() == Self::HEADER => {}
+            _ => {
+                return Err(Error::custom(format!(
+                    "invalid lockfile: expected '{}' on the first line",
+                    Self::HEADER
+                )));
+            }
+        }
+
+        let mut manifest = Self::new();
+        for (i, line) in lines {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || Error::custom(format!("invalid lockfile: line {}", i + 1));
+
+            let (first, rest) = line.split_once(' ').ok_or_else(invalid)?;
+            if rest.is_empty() {
+                return Err(invalid());
+            }
+
+            match first {
+                "root" => manifest.push_root(Path::new(rest)),
+                hash => {
+                    let hash = ContentHash::parse(hash).ok_or_else(invalid)?;
+                    manifest.push(Path::new(rest), hash);
+                }
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Compare `self` against `other`.
+    ///
+    /// `self` is treated as the old manifest and `other` as the new one.
+    pub fn diff(&self, other: &Self) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+
+        for entry in &other.entries {
+            match self.get(&entry.path) {
+                None => diff.added.push(entry.path.clone()),
+                Some(old) if old.hash != entry.hash => {
+                    diff.changed
+                        .push((entry.path.clone(), old.hash, entry.hash));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for entry in &self.entries {
+            if other.get(&entry.path).is_none() {
+                diff.removed.push(entry.path.clone());
+            }
+        }
+
+        diff.roots_changed = self.roots != other.roots;
+        diff
+    }
+
+    /// Get the expected hash of the module at `path`.
+    ///
+    /// Fails if `path` is not in the manifest.
+    pub(crate) fn expect(
+        &self,
+        path: &Path,
+        importer: Option<&Path>,
+    ) -> Result<ContentHash, Error> {
+        if let Some(entry) = self.get(path) {
+            return Ok(entry.hash);
+        }
+
+        let msg = match importer {
+            Some(importer) => format!(
+                "'{}' (imported by '{}') is not in the manifest",
+                path.display(),
+                importer.display()
+            ),
+            None => format!("'{}' is not in the manifest", path.display()),
+        };
+
+        Err(Error::custom(msg))
+    }
+}
+
+/// Check that the module at `path` still has the `expected` hash.
+pub(crate) fn verify(path: &Path, expected: ContentHash, found: ContentHash) -> Result<(), Error> {
+    if expected == found {
+        return Ok(());
+    }
+
+    Err(Error::custom(format!(
+        "'{}' has changed since the manifest was recorded (expected {expected}, found {found})",
+        path.display()
+    )))
+}
+
+/// The differences between 2 [`Manifest`]s.
+///
+/// See: [`Manifest::diff`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ManifestDiff {
+    added: Vec<PathBuf>,
+    removed: Vec<PathBuf>,
+    changed: Vec<(PathBuf, ContentHash, ContentHash)>,
+    roots_changed: bool,
+}
+
+impl ManifestDiff {
+    /// Check whether the manifests are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && !self.roots_changed
+    }
+
+    /// Get the modules present only in the new manifest.
+    pub fn added(&self) -> &[PathBuf] {
+        &self.added
+    }
+
+    /// Get the modules present only in the old manifest.
+    pub fn removed(&self) -> &[PathBuf] {
+        &self.removed
+    }
+
+    /// Get the modules whose hash changed, along with the old and new hash.
+    pub fn changed(&self) -> &[(PathBuf, ContentHash, ContentHash)] {
+        &self.changed
+    }
+
+    /// Check whether the roots of the manifests differ.
+    pub fn roots_changed(&self) -> bool {
+        self.roots_changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(roots: &[&str], entries: &[(&str, &[u8])]) -> Manifest {
+        let mut m = Manifest::new();
+        roots.iter().for_each(|x| m.push_root(Path::new(x)));
+        entries
+            .iter()
+            .for_each(|(p, data)| m.push(Path::new(p), ContentHash::of(data)));
+        m
+    }
+
+    #[test]
+    fn test_hash() {
+        // Reference values of FNV-1a 64.
+        assert_eq!(ContentHash::of(b"").0, 0xcbf29ce484222325);
+        assert_eq!(ContentHash::of(b"a").0, 0xaf63dc4c8601ec8c);
+        assert_eq!(
+            ContentHash::of(b"a").to_string(),
+            "fnv1a64:af63dc4c8601ec8c"
+        );
+    }
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let m = manifest(&["/a.json"], &[("/a.json", b"a"), ("/b c.json", b"b")]);
+
+        let lockfile = m.to_lockfile().unwrap();
+        assert_eq!(
+            lockfile,
+            "# module manifest v1
+root /a.json
+fnv1a64:af63dc4c8601ec8c /a.json
+fnv1a64:af63df4c8601f1a5 /b c.json
+"
+        );
+        assert_eq!(Manifest::from_lockfile(&lockfile).unwrap(), m);
+    }
+
+    #[test]
+    fn test_lockfile_invalid() {
+        for x in [
+            "",
+            "root /a.json",
+            "# module manifest v1\nfnv1a64:af63dc4c8601ec8c",
+            "# module manifest v1\nfnv1a64:xyz /a.json",
+            "# module manifest v1\nsha256:af63dc4c8601ec8c /a.json",
+        ] {
+            assert!(Manifest::from_lockfile(x).is_err(), "{x:?}");
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = manifest(&["/a"], &[("/a", b"a"), ("/b", b"b"), ("/c", b"c")]);
+        let new = manifest(&["/a"], &[("/a", b"a"), ("/b", b"B"), ("/d", b"d")]);
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added(), &[PathBuf::from("/d")]);
+        assert_eq!(diff.removed(), &[PathBuf::from("/c")]);
+        assert_eq!(
+            diff.changed(),
+            &[(
+                PathBuf::from("/b"),
+                ContentHash::of(b"b"),
+                ContentHash::of(b"B")
+            )]
+        );
+        assert!(!diff.roots_changed());
+        assert!(!diff.is_empty());
+
+        assert!(old.diff(&old).is_empty());
+    }
+}
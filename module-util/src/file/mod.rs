@@ -1,20 +1,38 @@
 //! The [`File`] evaluator for working with modules from files.
 
+mod auto;
+mod dir;
 #[allow(clippy::module_inception)]
 mod file;
 mod format;
+mod identity;
+mod manifest;
+mod namespaced;
+#[cfg(feature = "json")]
+mod remap;
+mod resolve;
 
-pub use self::file::{File, read};
-pub use self::format::{Format, Imports, Module};
+pub use self::auto::{AnyFormat, AnyFormatBuilder, read_auto};
+#[cfg(feature = "json")]
+pub(crate) use self::file::{DisplayPath, check_depth};
+pub use self::file::{File, FileSnapshot, ImportOrder, read};
+pub use self::format::{DirImport, FileImport, Format, Import, Imports, Module, ModuleSeed};
+pub use self::identity::PathIdentity;
+pub use self::manifest::{ContentHash, Manifest, ManifestDiff, ManifestEntry};
+pub use self::namespaced::{NamespacedError, read_namespaced, read_namespaced_with};
+#[cfg(feature = "json")]
+pub use self::remap::{Applied, Remap, Remapped};
+#[cfg(feature = "json")]
+pub(crate) use self::resolve::{Imported, Importer, Resolver};
 
 macro_rules! formats {
     ($(
-        $mod:ident::$name:ident $(if $cfg:meta)?,
+        $mod:ident::$name:ident, $with:ident, $builder:ident $(if $cfg:meta)?,
     )*) => { $(
         $(#[cfg($cfg)])?
         mod $mod;
         $(#[cfg($cfg)])?
-        pub use self::$mod::$name;
+        pub use self::$mod::{$name, $builder};
 
         $(#[cfg($cfg)])?
         impl<T> File<T, $name> {
@@ -26,6 +44,15 @@ macro_rules! formats {
             pub fn $mod() -> Self {
                 Self::new($name::default())
             }
+
+            #[doc = concat!("Create a new [`File`] that reads [`", stringify!($name), "`] files with the options of `format`.")]
+            #[doc = ""]
+            #[doc = concat!("See: [`", stringify!($name), "::builder`].")]
+            #[doc = ""]
+            #[doc = concat!("Equivalent to: `File::new(format)`")]
+            pub fn $with(format: $name) -> Self {
+                Self::new(format)
+            }
         }
 
         $(#[cfg($cfg)])?
@@ -42,7 +69,10 @@ macro_rules! formats {
 }
 
 formats! {
-    json::Json if feature = "json",
-    toml::Toml if feature = "toml",
-    yaml::Yaml if feature = "yaml",
+    json::Json, json_with, JsonBuilder if feature = "json",
+    toml::Toml, toml_with, TomlBuilder if feature = "toml",
+    yaml::Yaml, yaml_with, YamlBuilder if feature = "yaml",
+    ron::Ron, ron_with, RonBuilder if feature = "ron",
+    json5::Json5, json5_with, Json5Builder if feature = "json5",
+    ini::Ini, ini_with, IniBuilder if feature = "ini",
 }
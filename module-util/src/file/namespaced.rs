@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+use std::path::Path;
+
+use module::eval::prelude::*;
+use serde::de::DeserializeOwned;
+
+use super::{File, FileSnapshot, Format};
+
+/// Read a separate module tree for each namespace.
+///
+/// Each item of `roots` is the name of a namespace and the path of a root
+/// module of that namespace. Every namespace gets its own value, which is
+/// evaluated just like [`File`] would, but all namespaces share `format`.
+/// Modules imported by more than one namespace are read and parsed only once.
+/// A namespace may appear more than once in `roots`, in which case all of its
+/// roots are merged into the same value.
+///
+/// Errors of a namespace have the namespace as the outermost module of their
+/// backtrace, like `namespace 'acme'`.
+///
+/// Equivalent to: `read_namespaced_with(roots, File::new(format))`
+///
+/// # Errors
+///
+/// If any of the namespaces fails to evaluate. The other namespaces are still
+/// evaluated and are available through the returned [`NamespacedError`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{Json, read_namespaced};
+/// # type TenantConfig = Vec<i32>;
+/// let tenants = read_namespaced::<TenantConfig, _, _, _, _>(
+///     [
+///         ("acme", "tenants/acme.json"),
+///         ("globex", "tenants/globex.json"),
+///     ],
+///     Json::default(),
+/// )
+/// .unwrap();
+///
+/// let acme = &tenants["acme"];
+/// ```
+pub fn read_namespaced<T, F, I, N, P>(
+    roots: I,
+    format: F,
+) -> Result<HashMap<String, T>, NamespacedError<T>>
+where
+    T: Merge + DeserializeOwned + Clone,
+    F: Format,
+    I: IntoIterator<Item = (N, P)>,
+    N: Into<String>,
+    P: AsRef<Path>,
+{
+    read_namespaced_with(roots, File::new(format))
+}
+
+/// Read a separate module tree for each namespace, with the options of
+/// `file`.
+///
+/// Every namespace is evaluated by `file` in turn, as if it were the only
+/// one, so all of the options of `file` apply to every namespace. For
+/// example, the [`on_merge()`] hook is called for every namespace and it
+/// only stops the evaluation of the namespace it was called for. Modules that
+/// `file` has already read are ignored.
+///
+/// See: [`read_namespaced()`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{File, read_namespaced_with};
+/// # type TenantConfig = Vec<i32>;
+/// let file = File::json().with_expand_env(true);
+///
+/// let tenants = read_namespaced_with::<TenantConfig, _, _, _, _>(
+///     [("acme", "tenants/acme.json")],
+///     file,
+/// )
+/// .unwrap();
+/// ```
+///
+/// [`on_merge()`]: File::on_merge
+pub fn read_namespaced_with<T, F, I, N, P>(
+    roots: I,
+    mut file: File<T, F>,
+) -> Result<HashMap<String, T>, NamespacedError<T>>
+where
+    T: Merge + DeserializeOwned + Clone,
+    F: Format,
+    I: IntoIterator<Item = (N, P)>,
+    N: Into<String>,
+    P: AsRef<Path>,
+{
+    file.swap_state(FileSnapshot::empty());
+    file.cache_modules();
+
+    // Namespaces, in the order they first appear in `roots`.
+    let mut namespaces: Vec<(String, Namespace<T>)> = Vec::new();
+
+    for (name, root) in roots {
+        let name = name.into();
+
+        let i = match namespaces.iter().position(|(x, _)| *x == name) {
+            Some(i) => i,
+            None => {
+                namespaces.push((name, Namespace::default()));
+                namespaces.len() - 1
+            }
+        };
+
+        let (name, namespace) = &mut namespaces[i];
+        if namespace.result.is_err() {
+            continue;
+        }
+
+        let state = mem::replace(&mut namespace.state, FileSnapshot::empty());
+        file.swap_state(state);
+        let r = file.read(root);
+        namespace.state = file.swap_state(FileSnapshot::empty());
+
+        namespace.result = r.with_module(|| NamespaceName(name.clone()));
+    }
+
+    let results: Vec<(String, Result<T, Error>)> = namespaces
+        .into_iter()
+        .map(|(name, x)| {
+            // A namespace always has at least one root, so it has a value
+            // unless reading failed.
+            let r = x.result.and_then(|()| {
+                x.state
+                    .into_value()
+                    .ok_or_else(|| Error::custom("no modules were read"))
+            });
+            (name, r)
+        })
+        .collect();
+
+    if results.iter().any(|(_, x)| x.is_err()) {
+        return Err(NamespacedError { results });
+    }
+
+    Ok(results
+        .into_iter()
+        .filter_map(|(name, x)| Some((name, x.ok()?)))
+        .collect())
+}
+
+/// The evaluation of one namespace.
+struct Namespace<T> {
+    state: FileSnapshot<T>,
+    result: Result<(), Error>,
+}
+
+impl<T> Default for Namespace<T> {
+    fn default() -> Self {
+        Self {
+            state: FileSnapshot::empty(),
+            result: Ok(()),
+        }
+    }
+}
+
+struct NamespaceName(String);
+
+impl fmt::Display for NamespaceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "namespace '{}'", self.0)
+    }
+}
+
+/// The error returned by [`read_namespaced`].
+///
+/// Holds the result of every namespace, in the order they first appeared in
+/// the roots, so that one broken namespace does not hide the others.
+#[derive(Debug)]
+pub struct NamespacedError<T> {
+    results: Vec<(String, Result<T, Error>)>,
+}
+
+impl<T> NamespacedError<T> {
+    /// Get the result of every namespace.
+    pub fn results(&self) -> &[(String, Result<T, Error>)] {
+        &self.results
+    }
+
+    /// Get the result of the namespace `name`.
+    pub fn get(&self, name: &str) -> Option<&Result<T, Error>> {
+        self.results.iter().find(|(x, _)| x == name).map(|(_, x)| x)
+    }
+
+    /// Get an iterator over the errors of all namespaces that failed.
+    pub fn errors(&self) -> impl Iterator<Item = (&str, &Error)> {
+        self.results.iter().filter_map(|(name, x)| match x {
+            Ok(_) => None,
+            Err(e) => Some((name.as_str(), e)),
+        })
+    }
+
+    /// Destruct this [`NamespacedError`] and get the result of every
+    /// namespace.
+    pub fn into_results(self) -> Vec<(String, Result<T, Error>)> {
+        self.results
+    }
+
+    /// Discard all namespaces but the first one that failed and get its
+    /// error.
+    ///
+    /// This is for callers that need either all namespaces or none.
+    pub fn into_strict(self) -> Error {
+        self.results
+            .into_iter()
+            .find_map(|(_, x)| x.err())
+            .unwrap_or_else(|| Error::custom("no namespace failed to evaluate"))
+    }
+}
+
+impl<T> fmt::Display for NamespacedError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed: Vec<&str> = self.errors().map(|(name, _)| name).collect();
+
+        write!(
+            f,
+            "{} of {} namespaces failed to evaluate: {}",
+            failed.len(),
+            self.results.len(),
+            failed.join(", ")
+        )
+    }
+}
+
+impl<T> std::error::Error for NamespacedError<T> where T: fmt::Debug {}
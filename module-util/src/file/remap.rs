@@ -0,0 +1,495 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use module::Error;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use super::{Format, Module, ModuleSeed};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    from: Vec<String>,
+    to: Vec<String>,
+    wildcard: bool,
+}
+
+impl Rule {
+    fn new(from: &str, to: &str) -> Result<Self, Error> {
+        let parse = |x: &str| {
+            let (x, wildcard) = match x.strip_suffix(".*") {
+                Some(x) => (x, true),
+                None => (x, false),
+            };
+
+            let components: Vec<String> = x.split('.').map(String::from).collect();
+            if components.iter().any(|x| x.is_empty() || x.contains('*')) {
+                return Err(Error::custom(format!("invalid remap path '{x}'")));
+            }
+
+            Ok((components, wildcard))
+        };
+
+        let (from_components, from_wildcard) = parse(from)?;
+        let (to_components, to_wildcard) = parse(to)?;
+
+        if from_wildcard != to_wildcard {
+            return Err(Error::custom(format!(
+                "cannot remap '{from}' to '{to}': either both or none of the paths must end in '.*'"
+            )));
+        }
+
+        Ok(Self {
+            from: from_components,
+            to: to_components,
+            wildcard: from_wildcard,
+        })
+    }
+
+    /// Apply the rule to `value`.
+    ///
+    /// Returns whether anything was moved.
+    fn apply(&self, value: &mut Value) -> Result<bool, Error> {
+        let Some(source) = take(value, &self.from) else {
+            return Ok(false);
+        };
+
+        if !self.wildcard {
+            insert(value, &self.to, source, &self.from)?;
+            return Ok(true);
+        }
+
+        let Value::Object(children) = source else {
+            return Err(Error::custom(format!(
+                "cannot remap '{}': expected an object",
+                self.from.join(".")
+            )));
+        };
+
+        let mut from = self.from.clone();
+        let mut to = self.to.clone();
+        for (k, v) in children {
+            from.push(k.clone());
+            to.push(k);
+
+            insert(value, &to, v, &from)?;
+
+            from.pop();
+            to.pop();
+        }
+
+        Ok(true)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suffix = if self.wildcard { ".*" } else { "" };
+        write!(
+            f,
+            "'{}{suffix}' has moved to '{}{suffix}'",
+            self.from.join("."),
+            self.to.join(".")
+        )
+    }
+}
+
+/// Remove the value at `path`, along with any objects left empty.
+fn take(value: &mut Value, path: &[String]) -> Option<Value> {
+    let (first, rest) = path.split_first()?;
+    let map = value.as_object_mut()?;
+
+    if rest.is_empty() {
+        return map.remove(first);
+    }
+
+    let child = map.get_mut(first)?;
+    let x = take(child, rest)?;
+
+    if child.as_object().is_some_and(Map::is_empty) {
+        map.remove(first);
+    }
+
+    Some(x)
+}
+
+/// Insert `x` at `path`, creating objects along the way.
+fn insert(value: &mut Value, path: &[String], x: Value, from: &[String]) -> Result<(), Error> {
+    let conflict = || {
+        Error::custom(format!(
+            "cannot move '{}' to '{}': destination already exists",
+            from.join("."),
+            path.join(".")
+        ))
+    };
+
+    let Some((last, parents)) = path.split_last() else {
+        return Err(conflict());
+    };
+
+    let mut current = value;
+    for component in parents {
+        let Value::Object(map) = current else {
+            return Err(conflict());
+        };
+
+        current = map
+            .entry(component.as_str())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    let Value::Object(map) = current else {
+        return Err(conflict());
+    };
+
+    if map.contains_key(last) {
+        return Err(conflict());
+    }
+
+    map.insert(last.clone(), x);
+    Ok(())
+}
+
+/// A table of keys that have moved.
+///
+/// Each rule moves the value at one path to another. Paths are `.`-separated
+/// lists of keys. A path ending in `.*` refers to every key below it, so
+/// `net.http.*` to `http.*` moves all keys of `net.http` into `http`. Rules
+/// are applied in the order they were added.
+///
+/// See: [`Remapped`].
+///
+/// # Example
+///
+/// ```rust
+/// # use module_util::file::Remap;
+/// let mut remap = Remap::new();
+/// remap.push("net.http.*", "http.*").unwrap();
+/// remap.push("old.flag", "features.new_flag").unwrap();
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Remap {
+    rules: Vec<Rule>,
+}
+
+impl Remap {
+    /// Create a new empty [`Remap`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule that moves `from` to `to`.
+    ///
+    /// # Errors
+    ///
+    /// If either path is malformed, or if only one of them ends in `.*`.
+    pub fn push(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        self.rules.push(Rule::new(from, to)?);
+        Ok(())
+    }
+
+    /// Load rules from a TOML document.
+    ///
+    /// The document is a list of `remap` tables, each with a `from` and `to`
+    /// key:
+    ///
+    /// ```toml
+    /// [[remap]]
+    /// from = "net.http.*"
+    /// to = "http.*"
+    ///
+    /// [[remap]]
+    /// from = "old.flag"
+    /// to = "features.new_flag"
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, Error> {
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Entry {
+            from: String,
+            to: String,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Document {
+            #[serde(default)]
+            remap: Vec<Entry>,
+        }
+
+        let document: Document = toml::from_str(s).map_err(Error::custom)?;
+
+        let mut remap = Self::new();
+        for Entry { from, to } in document.remap {
+            remap.push(&from, &to)?;
+        }
+
+        Ok(remap)
+    }
+}
+
+/// A rule of a [`Remap`] that was applied to a module.
+///
+/// See: [`Remapped::applied`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Applied {
+    module: PathBuf,
+    rule: Rule,
+}
+
+impl Applied {
+    /// Get the path of the module.
+    pub fn module(&self) -> &Path {
+        &self.module
+    }
+
+    /// Get the old path of the rule.
+    pub fn from(&self) -> String {
+        self.rule.from.join(".")
+    }
+
+    /// Get the new path of the rule.
+    pub fn to(&self) -> String {
+        self.rule.to.join(".")
+    }
+}
+
+impl fmt::Display for Applied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.module.display(), self.rule)
+    }
+}
+
+/// A [`Format`] that moves keys according to a [`Remap`].
+///
+/// This format reads modules with another format, applies the [`Remap`] to
+/// them and only then deserializes them. This allows modules written for an
+/// older layout of `T` to keep working.
+///
+/// If a module contains both the old and the new path of a rule, reading it
+/// fails with an error naming both paths. Each rule that moved something is
+/// recorded and can be reported to the user as a deprecation warning, see:
+/// [`Remapped::applied`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{File, Json, Remap, Remapped};
+/// # use module::Merge;
+/// # use serde::Deserialize;
+/// #[derive(Deserialize, Merge)]
+/// struct Config {
+///     http: Option<Http>,
+/// }
+///
+/// #[derive(Deserialize, Merge)]
+/// struct Http {
+///     port: Option<u16>,
+/// }
+///
+/// let mut remap = Remap::new();
+/// remap.push("net.http.*", "http.*").unwrap();
+///
+/// let mut file = File::<Config, _>::json().with_remap(remap);
+/// file.read("config.json").unwrap();
+///
+/// for warning in file.format().applied() {
+///     eprintln!("warning: {warning}");
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Remapped<F> {
+    format: F,
+    remap: Remap,
+    applied: Vec<Applied>,
+}
+
+impl<F> Remapped<F> {
+    /// Create a new [`Remapped`] that reads modules with `format`.
+    pub fn new(format: F, remap: Remap) -> Self {
+        Self {
+            format,
+            remap,
+            applied: Vec::new(),
+        }
+    }
+
+    /// Get the [`Remap`].
+    pub fn remap(&self) -> &Remap {
+        &self.remap
+    }
+
+    /// Get all rules applied so far, in the order they were applied.
+    pub fn applied(&self) -> &[Applied] {
+        &self.applied
+    }
+
+    /// Get a reference to the inner [`Format`].
+    pub fn inner(&self) -> &F {
+        &self.format
+    }
+
+    /// Get a mutable reference to the inner [`Format`].
+    pub fn inner_mut(&mut self) -> &mut F {
+        &mut self.format
+    }
+}
+
+impl<F> Remapped<F> {
+    /// Apply the [`Remap`] to the `module` at `path`.
+    ///
+    /// The keys of the module are only recorded afterwards, because the rules
+    /// may rename them.
+    fn apply<T>(
+        &mut self,
+        path: &Path,
+        module: Module<Value>,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let Module { imports, mut value } = module;
+
+        for rule in &self.remap.rules {
+            if rule.apply(&mut value)? {
+                self.applied.push(Applied {
+                    module: path.to_path_buf(),
+                    rule: rule.clone(),
+                });
+            }
+        }
+
+        if let (Some(keys), Value::Object(x)) = (seed.keys(), &value) {
+            keys.borrow_mut().extend(x.keys().cloned());
+        }
+
+        let value = serde_json::from_value(value).map_err(Error::custom)?;
+        Ok(Module { imports, value })
+    }
+}
+
+impl<F> Format for Remapped<F>
+where
+    F: Format,
+{
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let module = self.format.read(path, seed.cast().with_keys(None))?;
+        self.apply(path, module, seed)
+    }
+
+    fn read_str<T>(
+        &mut self,
+        name: &Path,
+        contents: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let module = self
+            .format
+            .read_str(name, contents, seed.cast().with_keys(None))?;
+        self.apply(name, module, seed)
+    }
+
+    fn read_bytes<T>(
+        &mut self,
+        name: &Path,
+        contents: &[u8],
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let module = self
+            .format
+            .read_bytes(name, contents, seed.cast().with_keys(None))?;
+        self.apply(name, module, seed)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.format.extensions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn apply(remap: &[(&str, &str)], mut value: Value) -> Result<Value, Error> {
+        for (from, to) in remap {
+            Rule::new(from, to)?.apply(&mut value)?;
+        }
+
+        Ok(value)
+    }
+
+    #[test]
+    fn test_wildcard() {
+        let value = json!({ "net": { "http": { "port": 80, "tls": { "on": true } } }, "x": 1 });
+
+        let value = apply(&[("net.http.*", "http.*")], value).unwrap();
+        assert_eq!(
+            value,
+            json!({ "http": { "port": 80, "tls": { "on": true } }, "x": 1 })
+        );
+    }
+
+    #[test]
+    fn test_wildcard_merges_into_existing() {
+        let value = json!({ "net": { "http": { "port": 80 } }, "http": { "host": "a" } });
+
+        let value = apply(&[("net.http.*", "http.*")], value).unwrap();
+        assert_eq!(value, json!({ "http": { "host": "a", "port": 80 } }));
+    }
+
+    #[test]
+    fn test_exact() {
+        let value = json!({ "old": { "flag": true, "other": 1 } });
+
+        let value = apply(&[("old.flag", "features.new_flag")], value).unwrap();
+        assert_eq!(
+            value,
+            json!({ "old": { "other": 1 }, "features": { "new_flag": true } })
+        );
+    }
+
+    #[test]
+    fn test_conflict() {
+        let value = json!({ "net": { "http": { "port": 80 } }, "http": { "port": 8080 } });
+
+        let err = apply(&[("net.http.*", "http.*")], value).unwrap_err();
+        assert_eq!(
+            err.kind.to_string(),
+            "cannot move 'net.http.port' to 'http.port': destination already exists"
+        );
+    }
+
+    #[test]
+    fn test_untouched() {
+        let value = json!({ "http": { "port": 80 } });
+
+        let x = apply(
+            &[("net.http.*", "http.*"), ("old.flag", "new")],
+            value.clone(),
+        )
+        .unwrap();
+        assert_eq!(x, value);
+    }
+
+    #[test]
+    fn test_invalid_rule() {
+        assert!(Rule::new("net.http.*", "http").is_err());
+        assert!(Rule::new("net..http", "http").is_err());
+        assert!(Rule::new("net.*.http", "http").is_err());
+    }
+}
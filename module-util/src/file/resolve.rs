@@ -0,0 +1,371 @@
+use std::borrow::Cow;
+use std::env;
+use std::fmt;
+#[cfg(feature = "glob")]
+use std::io;
+use std::path::{Path, PathBuf};
+
+use module::eval::prelude::*;
+
+use super::dir::{self, ViaDir};
+use super::file::DisplayPath;
+use super::{DirImport, Import};
+
+/// How the imports of modules are turned into the paths of the modules they
+/// refer to.
+///
+/// This is shared by everything that follows imports, so that they all agree
+/// with [`File`] on which modules are imported.
+///
+/// [`File`]: super::File
+#[derive(Debug, Clone)]
+pub(crate) struct Resolver {
+    pub(crate) base_dir: Option<PathBuf>,
+    pub(crate) expand_env: bool,
+    #[cfg(feature = "glob")]
+    pub(crate) glob: bool,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self {
+            base_dir: env::current_dir().ok(),
+            expand_env: false,
+            #[cfg(feature = "glob")]
+            glob: false,
+        }
+    }
+}
+
+impl Resolver {
+    /// Resolve `import` relative to the directory of `importer`.
+    fn resolve(&self, importer: &Path, import: &Path) -> Result<PathBuf, Error> {
+        resolve_import(importer, &self.expand(import)?, self.base_dir.as_deref())
+    }
+
+    /// Expand the environment variables in `import`, if enabled.
+    fn expand<'a>(&self, import: &'a Path) -> Result<Cow<'a, Path>, Error> {
+        match self.expand_env {
+            true => expand_env(import, |x| env::var(x)).map(Cow::Owned),
+            false => Ok(Cow::Borrowed(import)),
+        }
+    }
+
+    /// Get the paths that `import` matches, if it is a glob pattern.
+    #[cfg(feature = "glob")]
+    fn resolve_glob(&self, importer: &Path, import: &Path) -> Result<Option<Vec<PathBuf>>, Error> {
+        if !self.glob {
+            return Ok(None);
+        }
+
+        let import = self.expand(import)?;
+        let Some(pattern) = import.to_str().filter(|x| x.contains(['*', '?', '['])) else {
+            return Ok(None);
+        };
+
+        // Only the pattern itself may contain metacharacters, not the
+        // directory it is resolved against.
+        let escape = |x: &Path| escape_glob(pattern, x);
+
+        let base_dir = self.base_dir.as_deref().map(escape).transpose()?;
+        let pattern = resolve_import(&escape(importer)?, Path::new(pattern), base_dir.as_deref())?;
+
+        // Everything that went into `pattern` is valid UTF-8.
+        let pattern = pattern.to_string_lossy();
+
+        let mut paths = Vec::new();
+        for x in glob::glob(&pattern).map_err(Error::custom)? {
+            let x = x.map_err(|e| Error::from(io::Error::from(e)))?;
+            if !x.is_dir() {
+                paths.push(x);
+            }
+        }
+
+        paths.sort_by(|a, b| {
+            a.as_os_str()
+                .as_encoded_bytes()
+                .cmp(b.as_os_str().as_encoded_bytes())
+        });
+
+        Ok(Some(paths))
+    }
+}
+
+/// Something that reads the modules imported by other modules.
+pub(crate) trait Importer {
+    /// Get the [`Resolver`] of the imports.
+    fn resolver(&self) -> &Resolver;
+
+    /// Get the extensions of the modules that [`DirImport`]s read.
+    fn extensions(&self) -> &[&str];
+
+    /// Read the imported module.
+    fn import(&mut self, import: Imported) -> Result<(), Error>;
+
+    /// Read the modules that `import` of the module at `importer` refers to,
+    /// in order.
+    ///
+    /// Stops at the first error.
+    fn read_import(&mut self, importer: &Path, import: Import) -> Result<(), Error> {
+        let (path, file) = match import {
+            Import::Path(x) => (x, None),
+            Import::File(x) => (x.path.clone(), Some(x)),
+            Import::Dir(x) => return self.read_dir_import(importer, &x),
+        };
+
+        #[cfg(feature = "glob")]
+        if let Some(paths) = self.resolver().resolve_glob(importer, &path)? {
+            return paths
+                .into_iter()
+                .try_for_each(|x| self.import(Imported::new(x, importer, 0, false)));
+        }
+
+        let path = self.resolver().resolve(importer, &path)?;
+        if file.is_some_and(|x| x.is_missing(&path)) {
+            return Ok(());
+        }
+
+        self.import(Imported::new(path, importer, 0, false))
+    }
+
+    /// Read the modules of `import`, in the order they must be merged.
+    fn read_dir_import(&mut self, importer: &Path, import: &DirImport) -> Result<(), Error> {
+        let dir = self.resolver().resolve(importer, &import.dir)?;
+        let Some(paths) = dir::list(&dir, import, self.extensions())? else {
+            return Ok(());
+        };
+
+        paths.into_iter().enumerate().try_for_each(|(i, x)| {
+            let offset = dir::offset(import, i);
+            self.import(Imported::new(x, importer, offset, true))
+        })
+    }
+}
+
+/// A module imported by another module.
+#[derive(Debug)]
+pub(crate) struct Imported {
+    /// The path of the module. It is not canonical.
+    pub(crate) path: PathBuf,
+    /// The path of the module that imported it.
+    pub(crate) importer: PathBuf,
+    /// The default priority offset of the module.
+    pub(crate) offset: isize,
+    via_dir: bool,
+}
+
+impl Imported {
+    fn new(path: PathBuf, importer: &Path, offset: isize, via_dir: bool) -> Self {
+        Self {
+            path,
+            importer: importer.to_path_buf(),
+            offset,
+            via_dir,
+        }
+    }
+
+    /// Get the module to add to the backtrace of errors, if the module was
+    /// read from `path`.
+    pub(crate) fn module(&self, path: &Path) -> ImportedModule {
+        match self.via_dir {
+            true => ImportedModule::Dir(ViaDir {
+                path: path.to_path_buf(),
+                importer: self.importer.clone(),
+            }),
+            false => ImportedModule::Path(DisplayPath(path.to_path_buf())),
+        }
+    }
+}
+
+/// An [`Imported`] module in the backtrace of errors.
+#[derive(Clone)]
+pub(crate) enum ImportedModule {
+    Path(DisplayPath),
+    Dir(ViaDir),
+}
+
+impl fmt::Display for ImportedModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(x) => x.fmt(f),
+            Self::Dir(x) => x.fmt(f),
+        }
+    }
+}
+
+/// Resolve `import` relative to the directory of `importer`.
+///
+/// Falls back to `base_dir` if `importer` has no parent directory.
+fn resolve_import(
+    importer: &Path,
+    import: &Path,
+    base_dir: Option<&Path>,
+) -> Result<PathBuf, Error> {
+    if import.is_absolute() {
+        return Ok(import.to_path_buf());
+    }
+
+    importer
+        .parent()
+        .filter(|x| !x.as_os_str().is_empty())
+        .or(base_dir)
+        .map(|base| base.join(import))
+        .ok_or_else(|| {
+            Error::custom(format!(
+                "cannot resolve import '{}' of '{}': module has no parent directory",
+                import.display(),
+                importer.display(),
+            ))
+        })
+}
+
+/// Escape the glob metacharacters in `path`, which `pattern` is resolved
+/// against.
+#[cfg(feature = "glob")]
+fn escape_glob(pattern: &str, path: &Path) -> Result<PathBuf, Error> {
+    match path.to_str() {
+        Some(x) => Ok(PathBuf::from(glob::Pattern::escape(x))),
+        None => Err(Error::custom(format!(
+            "cannot resolve pattern '{pattern}' against '{}': path is not valid UTF-8",
+            path.display()
+        ))),
+    }
+}
+
+/// Replace `${VAR}` and `$VAR` in `path` with the values of the environment
+/// variables, as returned by `var`.
+fn expand_env<V>(path: &Path, var: V) -> Result<PathBuf, Error>
+where
+    V: Fn(&str) -> Result<String, env::VarError>,
+{
+    let Some(s) = path.to_str() else {
+        return Ok(path.to_path_buf());
+    };
+
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        let name = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced
+                    .find('}')
+                    .ok_or_else(|| Error::custom(format!("unterminated '${{' in import '{s}'")))?;
+
+                rest = &braced[end + 1..];
+                &braced[..end]
+            }
+            None => {
+                let end = rest.find(|c| !is_name(c)).unwrap_or(rest.len());
+                let (name, tail) = rest.split_at(end);
+
+                if name.is_empty() {
+                    out.push('$');
+                    continue;
+                }
+
+                rest = tail;
+                name
+            }
+        };
+
+        match var(name) {
+            Ok(x) => out.push_str(&x),
+            Err(env::VarError::NotPresent) => {
+                return Err(Error::custom(format!(
+                    "environment variable '{name}' of import '{s}' is not defined"
+                )));
+            }
+            Err(e) => {
+                return Err(Error::custom(format!(
+                    "environment variable '{name}' of import '{s}': {e}"
+                )));
+            }
+        }
+    }
+
+    out.push_str(rest);
+    Ok(PathBuf::from(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolver(base_dir: Option<&str>) -> Resolver {
+        Resolver {
+            base_dir: base_dir.map(PathBuf::from),
+            ..Resolver::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_import() {
+        let resolver = Resolver::default();
+
+        let p = resolver
+            .resolve(Path::new("/etc/app/config.toml"), Path::new("base.toml"))
+            .unwrap();
+        assert_eq!(p, Path::new("/etc/app/base.toml"));
+
+        let p = resolver
+            .resolve(Path::new("/etc/app/config.toml"), Path::new("/base.toml"))
+            .unwrap();
+        assert_eq!(p, Path::new("/base.toml"));
+    }
+
+    #[test]
+    fn test_resolve_import_parentless() {
+        let resolver = resolver(Some("/srv"));
+
+        for importer in ["/", "config.toml"] {
+            let p = resolver
+                .resolve(Path::new(importer), Path::new("base.toml"))
+                .unwrap();
+            assert_eq!(p, Path::new("/srv/base.toml"), "importer: {importer}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_import_no_base() {
+        let resolver = resolver(None);
+
+        let err = resolver
+            .resolve(Path::new("/"), Path::new("base.toml"))
+            .unwrap_err();
+
+        let msg = err.to_string();
+        assert!(msg.contains("base.toml"), "{msg}");
+        assert!(msg.contains("'/'"), "{msg}");
+
+        let p = resolver
+            .resolve(Path::new("/"), Path::new("/base.toml"))
+            .unwrap();
+        assert_eq!(p, Path::new("/base.toml"));
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let var = |name: &str| match name {
+            "APP" => Ok("app".to_owned()),
+            _ => Err(env::VarError::NotPresent),
+        };
+        let expand = |x: &str| expand_env(Path::new(x), var).map(|x| x.display().to_string());
+
+        assert_eq!(expand("/${APP}/a").unwrap(), "/app/a");
+        assert_eq!(expand("$APP.d/$APP").unwrap(), "app.d/app");
+        assert_eq!(expand("a$/b$").unwrap(), "a$/b$");
+        assert_eq!(expand("a/b").unwrap(), "a/b");
+
+        let msg = expand("${APP").unwrap_err().to_string();
+        assert!(msg.contains("unterminated"), "{msg}");
+
+        let msg = expand("$APPS/a").unwrap_err().to_string();
+        assert!(msg.contains("'APPS'"), "{msg}");
+    }
+}
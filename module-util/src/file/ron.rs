@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::Path;
+
+use module::Error;
+use ron::error::Position;
+use ron::extensions::Extensions;
+use serde::de::DeserializeOwned;
+
+use super::format::syntax_error;
+use super::{Format, Module, ModuleSeed};
+
+/// A [`Format`] for [RON] modules.
+///
+/// Uses [`ron`] under the hood.
+///
+/// Modules are maps whose keys are bare identifiers, like
+/// `{ imports: ["base.ron"], port: 8080 }`. The keys of the module are read
+/// like the fields of a struct, so optional values can be written without
+/// `Some(...)`. Nested structures, like dir imports, are written as structs
+/// too: `imports: [(dir: "conf.d")]`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{File, Ron};
+/// let ron = Ron::builder().recursion_limit(Some(16)).build();
+///
+/// let mut file = File::<i32, _>::ron_with(ron);
+/// file.read("config.ron").unwrap();
+/// ```
+///
+/// [RON]: https://github.com/ron-rs/ron
+#[derive(Debug, Clone, Copy)]
+pub struct Ron {
+    recursion_limit: Option<usize>,
+}
+
+impl Default for Ron {
+    fn default() -> Self {
+        Self {
+            recursion_limit: Some(128),
+        }
+    }
+}
+
+impl Ron {
+    /// Create a new [`RonBuilder`].
+    ///
+    /// The builder starts out with the default options.
+    pub fn builder() -> RonBuilder {
+        RonBuilder::default()
+    }
+
+    /// Get the maximum nesting depth of a module.
+    pub fn recursion_limit(&self) -> Option<usize> {
+        self.recursion_limit
+    }
+}
+
+/// A builder for [`Ron`].
+///
+/// See: [`Ron::builder`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RonBuilder {
+    inner: Ron,
+}
+
+impl RonBuilder {
+    /// Set the maximum nesting depth of a module.
+    ///
+    /// Modules that nest deeper are rejected while being parsed. `None`
+    /// means no limit. Defaults to `Some(128)`, the default of [`ron`].
+    pub fn recursion_limit(mut self, max: Option<usize>) -> Self {
+        self.inner.recursion_limit = max;
+        self
+    }
+
+    /// Build the [`Ron`] format.
+    pub fn build(self) -> Ron {
+        self.inner
+    }
+}
+
+impl Format for Ron {
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let data = fs::read_to_string(path).map_err(Error::from)?;
+        self.read_str(path, &data, seed)
+    }
+
+    fn read_str<T>(
+        &mut self,
+        _name: &Path,
+        data: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        // `imports` is a newtype, which RON would otherwise expect to be
+        // written as `Imports([...])`. Optional values are written without
+        // `Some(...)`, like in the other formats.
+        let options = ron::Options::default()
+            .with_default_extension(Extensions::UNWRAP_NEWTYPES | Extensions::IMPLICIT_SOME);
+        let options = match self.recursion_limit {
+            Some(max) => options.with_recursion_limit(max),
+            None => options.without_recursion_limit(),
+        };
+
+        let module = options.from_str_seed(data, seed).map_err(error)?;
+        Ok(module)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// Convert `e` to an [`Error`], moving its location to the note.
+fn error(e: ron::error::SpannedError) -> Error {
+    match e.position {
+        Position { line: 0, col: 0 } => Error::custom(e.code),
+        Position { line, col } => syntax_error(e.code, line, col),
+    }
+}
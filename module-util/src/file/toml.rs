@@ -2,25 +2,148 @@ use std::fs;
 use std::path::Path;
 
 use module::Error;
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, DeserializeSeed};
 
-use super::{Format, Module};
+use super::format::syntax_error;
+use super::{Format, Module, ModuleSeed};
 
 /// A [`Format`] for [TOML] modules.
 ///
 /// Uses [`toml`] under the hood.
 ///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{File, Toml};
+/// let toml = Toml::builder().spans(true).build();
+///
+/// let mut file = File::<i32, _>::toml_with(toml);
+/// file.read("config.toml").unwrap();
+/// ```
+///
 /// [TOML]: https://toml.io/en/
 #[derive(Debug, Default, Clone, Copy)]
-pub struct Toml;
+pub struct Toml {
+    spans: bool,
+}
+
+impl Toml {
+    /// Create a new [`TomlBuilder`].
+    ///
+    /// The builder starts out with the default options.
+    pub fn builder() -> TomlBuilder {
+        TomlBuilder::default()
+    }
+
+    /// Check whether errors show the lines of the module around them.
+    pub fn spans(&self) -> bool {
+        self.spans
+    }
+}
+
+/// A builder for [`Toml`].
+///
+/// See: [`Toml::builder`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TomlBuilder {
+    inner: Toml,
+}
+
+impl TomlBuilder {
+    /// Show the lines of the module around errors.
+    ///
+    /// Errors always report their location as their [note]. By default, the
+    /// message is a single line, which is easier to process further. When
+    /// this is enabled, the message is instead rendered by [`toml`] over
+    /// multiple lines, along with a snippet of the module.
+    ///
+    /// Note that errors about the type of a value of the module, as opposed to
+    /// syntax errors, may not carry a location.
+    ///
+    /// [note]: Error::note
+    ///
+    /// Defaults to `false`.
+    pub fn spans(mut self, enable: bool) -> Self {
+        self.inner.spans = enable;
+        self
+    }
+
+    /// Build the [`Toml`] format.
+    pub fn build(self) -> Toml {
+        self.inner
+    }
+}
 
 impl Format for Toml {
-    fn read<T>(&mut self, path: &Path) -> Result<Module<T>, Error>
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let data = fs::read_to_string(path).map_err(Error::from)?;
+        self.read_str(path, &data, seed)
+    }
+
+    fn read_str<T>(
+        &mut self,
+        _name: &Path,
+        data: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
     where
         T: DeserializeOwned,
     {
-        let data = fs::read_to_string(path).map_err(Error::custom)?;
-        let module = toml::from_str(&data).map_err(Error::custom)?;
-        Ok(module)
+        seed.deserialize(toml::Deserializer::new(data))
+            .map_err(|e| error(e, data, self.spans))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["toml"]
+    }
+}
+
+/// Convert `e` to an [`Error`], moving its location to the note.
+fn error(e: toml::de::Error, data: &str, spans: bool) -> Error {
+    let message = match spans {
+        true => e.to_string(),
+        false => e.message().trim().replace('\n', ", "),
+    };
+
+    match e.span() {
+        Some(span) => {
+            let (line, column) = location(data, span.start);
+            syntax_error(message, line, column)
+        }
+        None => Error::custom(message),
+    }
+}
+
+/// Get the 1-based line and column of the byte at `offset`.
+fn location(data: &str, offset: usize) -> (usize, usize) {
+    let before = &data[..offset.min(data.len())];
+
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |x| &before[x + 1..])
+        .chars()
+        .count()
+        + 1;
+
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location() {
+        let data = "a = 1\nbb = 2\n";
+
+        assert_eq!(location(data, 0), (1, 1));
+        assert_eq!(location(data, 4), (1, 5));
+        assert_eq!(location(data, 6), (2, 1));
+        assert_eq!(location(data, 11), (2, 6));
+        assert_eq!(location(data, 100), (3, 1));
     }
 }
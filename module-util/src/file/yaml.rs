@@ -1,32 +1,482 @@
-use std::fs;
-use std::io;
+use std::fmt;
 use std::path::Path;
 
 use module::Error;
-use serde::de::DeserializeOwned;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use serde_yaml::value::{Tag, TaggedValue};
+use serde_yaml::{Mapping, Value};
 
-use super::{Format, Module};
+use super::format::{check_size, read_limited, syntax_error};
+use super::{Format, Module, ModuleSeed};
 
 /// A [`Format`] for [YAML] modules.
 ///
 /// Uses [`serde_yaml`] under the hood.
 ///
+/// By default, modules are read without any limits other than those of
+/// [`serde_yaml`] itself. Limits on the size and nesting depth of modules can
+/// be set with [`Yaml::builder`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module_util::file::{File, Yaml};
+/// let yaml = Yaml::builder()
+///     .max_size(Some(64 * 1024))
+///     .max_depth(Some(16))
+///     .build();
+///
+/// let mut file = File::<i32, _>::yaml_with(yaml);
+/// file.read("config.yaml").unwrap();
+/// ```
+///
 /// [YAML]: https://yaml.org/
 #[derive(Debug, Default, Clone, Copy)]
-pub struct Yaml;
+pub struct Yaml {
+    max_size: Option<u64>,
+    max_depth: Option<usize>,
+    implicit_bools: bool,
+}
+
+impl Yaml {
+    /// Create a new [`YamlBuilder`].
+    ///
+    /// The builder starts out with the default options.
+    pub fn builder() -> YamlBuilder {
+        YamlBuilder::default()
+    }
+
+    /// Get the maximum size of a module in bytes.
+    pub fn max_size(&self) -> Option<u64> {
+        self.max_size
+    }
+
+    /// Get the maximum nesting depth of a module.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Check whether YAML 1.1 booleans like `yes` and `no` are accepted.
+    pub fn implicit_bools(&self) -> bool {
+        self.implicit_bools
+    }
+}
+
+/// A builder for [`Yaml`].
+///
+/// See: [`Yaml::builder`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct YamlBuilder {
+    inner: Yaml,
+}
+
+impl YamlBuilder {
+    /// Set the maximum size of a module in bytes.
+    ///
+    /// Larger modules are rejected before being parsed. Defaults to `None`,
+    /// which means no limit.
+    pub fn max_size(mut self, max: Option<u64>) -> Self {
+        self.inner.max_size = max;
+        self
+    }
+
+    /// Set the maximum nesting depth of a module.
+    ///
+    /// Every sequence or mapping adds one level of nesting. The top-level
+    /// mapping of a module is at depth `1`. Defaults to `None`, which means no
+    /// limit.
+    pub fn max_depth(mut self, max: Option<usize>) -> Self {
+        self.inner.max_depth = max;
+        self
+    }
+
+    /// Accept YAML 1.1 booleans like `yes`, `no`, `on` and `off`.
+    ///
+    /// YAML 1.2 only treats `true` and `false` as booleans, so by default
+    /// `no` is just a string and reading it into a `bool` fails. When this
+    /// is enabled, such values are accepted wherever a `bool` is expected.
+    /// They are still strings everywhere else.
+    ///
+    /// Defaults to `false`.
+    pub fn implicit_bools(mut self, enable: bool) -> Self {
+        self.inner.implicit_bools = enable;
+        self
+    }
+
+    /// Build the [`Yaml`] format.
+    pub fn build(self) -> Yaml {
+        self.inner
+    }
+}
+
+impl Yaml {
+    /// Check whether modules can be deserialized directly, without going
+    /// through a [`Value`] first.
+    fn is_direct(&self) -> bool {
+        self.max_depth.is_none() && !self.implicit_bools
+    }
+}
 
 impl Format for Yaml {
-    fn read<T>(&mut self, path: &Path) -> Result<Module<T>, Error>
+    fn read<T>(&mut self, path: &Path, seed: ModuleSeed<T>) -> Result<Module<T>, Error>
     where
         T: DeserializeOwned,
     {
-        let reader = fs::File::options()
-            .read(true)
-            .open(path)
-            .map(io::BufReader::new)
-            .map_err(Error::custom)?;
+        let data = read_limited(path, self.max_size)?;
+        self.read_str(path, &data, seed)
+    }
+
+    fn read_str<T>(
+        &mut self,
+        _name: &Path,
+        data: &str,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        check_size(data, self.max_size)?;
+
+        let de = serde_yaml::Deserializer::from_str(data);
+        if self.is_direct() {
+            return seed.deserialize(de).map_err(error);
+        }
+
+        let limited = Limited {
+            depth: 0,
+            max: self.max_depth,
+        };
+        let value = limited.deserialize(de).map_err(error)?;
+
+        let module = match self.implicit_bools {
+            true => seed.deserialize(ImplicitBools(value)),
+            false => seed.deserialize(value),
+        };
+        module.map_err(error)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["yaml", "yml"]
+    }
+}
+
+/// Convert `e` to an [`Error`], moving its location to the note.
+fn error(e: serde_yaml::Error) -> Error {
+    let Some(location) = e.location() else {
+        return Error::custom(e);
+    };
+
+    let (line, column) = (location.line(), location.column());
+    let msg = e
+        .to_string()
+        .replacen(&format!(" at line {line} column {column}"), "", 1);
+
+    syntax_error(msg, line, column)
+}
+
+/// A [`DeserializeSeed`] for [`Value`] that fails as soon as the value is
+/// nested deeper than `max`.
+///
+/// Every sequence or mapping adds one level of nesting.
+#[derive(Clone, Copy)]
+struct Limited {
+    depth: usize,
+    max: Option<usize>,
+}
+
+impl Limited {
+    fn nested<E>(self) -> Result<Self, E>
+    where
+        E: de::Error,
+    {
+        let depth = self.depth + 1;
+        match self.max {
+            Some(max) if depth > max => Err(E::custom(format!(
+                "module exceeds the maximum nesting depth of {max}"
+            ))),
+            max => Ok(Self { depth, max }),
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Limited {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Limited {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any YAML value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Value::Number(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Value::Number(v.into()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(Value::Number(v.into()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Value::String(v.to_owned()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let nested = self.nested()?;
+
+        let mut x = Vec::new();
+        while let Some(value) = seq.next_element_seed(nested)? {
+            x.push(value);
+        }
+
+        Ok(Value::Sequence(x))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let nested = self.nested()?;
+
+        let mut x = Mapping::new();
+        while let Some(key) = map.next_key_seed(nested)? {
+            if x.contains_key(&key) {
+                return Err(de::Error::custom(DuplicateKey(key)));
+            }
+
+            let value = map.next_value_seed(nested)?;
+            x.insert(key, value);
+        }
+
+        Ok(Value::Mapping(x))
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (tag, variant) = data.variant::<String>()?;
+        let value = variant.newtype_variant_seed(self)?;
+
+        Ok(Value::Tagged(Box::new(TaggedValue {
+            tag: Tag::new(tag),
+            value,
+        })))
+    }
+}
+
+/// The error of a key that appears twice in a mapping, as [`serde_yaml`]
+/// reports it.
+struct DuplicateKey(Value);
+
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("duplicate entry ")?;
+        match &self.0 {
+            Value::Null => f.write_str("with null key"),
+            Value::Bool(x) => write!(f, "with key `{x}`"),
+            Value::Number(x) => write!(f, "with key {x}"),
+            Value::String(x) => write!(f, "with key {x:?}"),
+            Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => {
+                f.write_str("in YAML map")
+            }
+        }
+    }
+}
+
+fn parse_implicit_bool(x: &str) -> Option<bool> {
+    match x {
+        "y" | "Y" | "yes" | "Yes" | "YES" | "on" | "On" | "ON" => Some(true),
+        "n" | "N" | "no" | "No" | "NO" | "off" | "Off" | "OFF" => Some(false),
+        _ => None,
+    }
+}
+
+/// A [`Deserializer`] for [`Value`] that accepts YAML 1.1 booleans.
+///
+/// [`Deserializer`]: de::Deserializer
+struct ImplicitBools(Value);
+
+impl<'de> IntoDeserializer<'de, serde_yaml::Error> for ImplicitBools {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ImplicitBools {
+    type Error = serde_yaml::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Sequence(x) => {
+                let mut seq = de::value::SeqDeserializer::new(x.into_iter().map(ImplicitBools));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Mapping(x) => {
+                let mut map = de::value::MapDeserializer::new(
+                    x.into_iter()
+                        .map(|(k, v)| (ImplicitBools(k), ImplicitBools(v))),
+                );
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            x => x.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::String(x) if let Some(x) = parse_implicit_bool(&x) => visitor.visit_bool(x),
+            x => x.deserialize_bool(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            x => visitor.visit_some(ImplicitBools(x)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::Deserialize;
+
+    fn limited(data: &str, max: usize) -> Result<Value, serde_yaml::Error> {
+        let limited = Limited {
+            depth: 0,
+            max: Some(max),
+        };
+        limited.deserialize(serde_yaml::Deserializer::from_str(data))
+    }
+
+    #[test]
+    fn test_limited() {
+        let value = limited("a: { b: [1, [2]] }", 4).unwrap();
+        assert_eq!(value, serde_yaml::from_str::<Value>("a: { b: [1, [2]] }").unwrap());
+
+        let msg = limited("a: { b: [1, [2]] }", 3).unwrap_err().to_string();
+        assert!(msg.contains("maximum nesting depth of 3"), "{msg}");
+
+        assert!(limited("{ [[1]]: 1 }", 2).is_err());
+        assert!(limited("!tag [1]", 1).is_ok());
+        assert!(limited("1", 0).is_ok());
+
+        let msg = limited("a: 1\na: 2", 1).unwrap_err().to_string();
+        assert!(msg.contains("duplicate entry with key \"a\""), "{msg}");
+    }
+
+    #[test]
+    fn test_implicit_bools() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Flags {
+            a: bool,
+            b: Option<bool>,
+            c: String,
+            d: Vec<bool>,
+        }
+
+        let value: Value = serde_yaml::from_str("{ a: yes, b: off, c: no, d: [Y, false] }").unwrap();
+
+        let flags = Flags::deserialize(ImplicitBools(value.clone())).unwrap();
+        assert_eq!(
+            flags,
+            Flags {
+                a: true,
+                b: Some(false),
+                c: "no".into(),
+                d: vec![true, false],
+            }
+        );
 
-        let module = serde_yaml::from_reader(reader).map_err(Error::custom)?;
-        Ok(module)
+        assert!(serde_yaml::from_value::<Flags>(value).is_err());
     }
 }
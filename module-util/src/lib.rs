@@ -5,4 +5,13 @@
 #![cfg_attr(module_nightly, feature(doc_auto_cfg))]
 #![forbid(unsafe_code)]
 
+#[cfg(feature = "cli")]
+pub mod cli;
+
 pub mod file;
+
+#[cfg(feature = "json")]
+pub mod preview;
+
+#[cfg(feature = "json")]
+pub mod subtree;
@@ -0,0 +1,675 @@
+//! Previewing what a module would change.
+//!
+//! Before deploying a new module, it is useful to know what it would change
+//! in a configuration that has already been evaluated. [`preview()`] reads a
+//! candidate module, merges it onto a copy of the current value and reports
+//! the differences, without touching the current value.
+//!
+//! Values are compared in their [`serde_json::Value`] form. Paths are
+//! `.`-separated lists of object keys, like `http.port`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # use module::Merge;
+//! # use serde::{Deserialize, Serialize};
+//! use module_util::file::Json;
+//! use module_util::preview::preview;
+//!
+//! #[derive(Clone, Serialize, Deserialize, Merge)]
+//! struct Config {
+//!     hosts: Vec<String>,
+//! }
+//!
+//! let current: Config = module_util::file::json("config.json").unwrap();
+//!
+//! let preview = preview(&current, "override.json", Json::default()).unwrap();
+//! println!("{}", preview.summary());
+//!
+//! for change in preview.changes() {
+//!     println!("{change}");
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use module::eval::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::file::{
+    DisplayPath, File, Format, Imported, Importer, Imports, Module, ModuleSeed, Resolver,
+    check_depth,
+};
+
+/// Options of [`preview_with()`] and [`preview_file()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    max_depth: Option<usize>,
+    provenance: bool,
+}
+
+impl Options {
+    /// Only follow imports up to `depth` levels deep.
+    ///
+    /// A depth of 0 reads only the candidate module. Imports that are deeper
+    /// are not read and are counted in [`Preview::skipped_imports`]. Unlike
+    /// [`File::set_max_depth`], this is not an error.
+    ///
+    /// Defaults to following all imports.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Record which module caused each change.
+    ///
+    /// This serializes the value after every module, so it is slower for
+    /// large values.
+    ///
+    /// See: [`Change::module`].
+    ///
+    /// Defaults to `false`.
+    pub fn provenance(mut self, enable: bool) -> Self {
+        self.provenance = enable;
+        self
+    }
+}
+
+/// Preview what merging the module at `candidate` into `current` would change.
+///
+/// Equivalent to: `preview_with(current, candidate, format, Options::default())`
+///
+/// See: [`preview_with()`].
+pub fn preview<T, F>(
+    current: &T,
+    candidate: impl AsRef<Path>,
+    format: F,
+) -> Result<Preview<T>, Error>
+where
+    T: Merge + Clone + Serialize + DeserializeOwned,
+    F: Format,
+{
+    preview_with(current, candidate, format, Options::default())
+}
+
+/// Preview what merging the module at `candidate` into `current` would change.
+///
+/// The candidate and its imports are read just like `File::new(format)`
+/// would read them and are merged onto a clone of `current`. To read them
+/// with the options of a configured [`File`] instead, use [`preview_file()`].
+///
+/// The preview is lenient. Errors of the candidate's imports or of merging
+/// do not stop it, they are collected in [`Preview::errors`] instead. If a
+/// value of a module fails to merge, only that value is dropped and the rest
+/// of the module is still merged. If a module cannot be merged at all, for
+/// example because it cannot be deserialized without that value, the whole
+/// module is dropped.
+///
+/// # Errors
+///
+/// If the candidate itself cannot be read, or if a value cannot be
+/// serialized.
+pub fn preview_with<T, F>(
+    current: &T,
+    candidate: impl AsRef<Path>,
+    format: F,
+    options: Options,
+) -> Result<Preview<T>, Error>
+where
+    T: Merge + Clone + Serialize + DeserializeOwned,
+    F: Format,
+{
+    run(current, candidate.as_ref(), &mut File::new(format), options)
+}
+
+/// Preview what merging the module at `candidate` into the value of `file`
+/// would change.
+///
+/// The candidate and its imports are read with the format of `file` and its
+/// options, like its [base directory], [import order] or [maximum depth].
+/// Modules that `file` has already read are read again, and `file` itself is
+/// not changed.
+///
+/// See: [`preview_with()`].
+///
+/// # Errors
+///
+/// If `file` has not read any module yet, if the candidate itself cannot be
+/// read, or if a value cannot be serialized.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use module::Merge;
+/// # use serde::{Deserialize, Serialize};
+/// use module_util::file::File;
+/// use module_util::preview::{Options, preview_file};
+///
+/// #[derive(Clone, Serialize, Deserialize, Merge)]
+/// struct Config {
+///     hosts: Vec<String>,
+/// }
+///
+/// let mut file = File::<Config, _>::json().with_expand_env(true);
+/// file.read("config.json").unwrap();
+///
+/// let preview = preview_file(&mut file, "override.json", Options::default()).unwrap();
+/// println!("{}", preview.summary());
+/// ```
+///
+/// [base directory]: File::base_dir
+/// [import order]: File::set_import_order
+/// [maximum depth]: File::set_max_depth
+pub fn preview_file<T, F>(
+    file: &mut File<T, F>,
+    candidate: impl AsRef<Path>,
+    options: Options,
+) -> Result<Preview<T>, Error>
+where
+    T: Merge + Clone + Serialize + DeserializeOwned,
+    F: Format,
+{
+    let current = file
+        .value()
+        .cloned()
+        .ok_or_else(|| Error::custom("no module was read"))?;
+
+    run(&current, candidate.as_ref(), file, options)
+}
+
+fn run<T, F>(
+    current: &T,
+    candidate: &Path,
+    file: &mut File<T, F>,
+    options: Options,
+) -> Result<Preview<T>, Error>
+where
+    T: Merge + Clone + Serialize + DeserializeOwned,
+    F: Format,
+{
+    let before = to_value(current)?;
+
+    let mut walker = Walker {
+        file,
+        options,
+        value: current.clone(),
+        errors: Vec::new(),
+        skipped: 0,
+        stack: Vec::new(),
+        evaluated: VisitedSet::new(),
+        depth: 0,
+        last: options.provenance.then(|| before.clone()),
+        origins: HashMap::new(),
+    };
+
+    if !walker.read(candidate, DisplayPath(candidate.to_path_buf()), 0) {
+        return Err(walker
+            .errors
+            .pop()
+            .unwrap_or_else(|| Error::custom("failed to read the candidate")));
+    }
+
+    let after = to_value(&walker.value)?;
+
+    let mut changes = Vec::new();
+    diff(&before, &after, &mut Vec::new(), &mut changes);
+
+    for change in &mut changes {
+        change.module = walker.origins.remove(&change.path);
+    }
+
+    Ok(Preview {
+        value: walker.value,
+        changes,
+        errors: walker.errors,
+        skipped: walker.skipped,
+    })
+}
+
+fn to_value<T>(value: &T) -> Result<Value, Error>
+where
+    T: Serialize,
+{
+    serde_json::to_value(value).map_err(Error::custom)
+}
+
+/// The result of [`preview()`].
+#[derive(Debug)]
+pub struct Preview<T> {
+    value: T,
+    changes: Vec<Change>,
+    errors: Vec<Error>,
+    skipped: usize,
+}
+
+impl<T> Preview<T> {
+    /// Get the value that merging the candidate would produce.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Destruct this [`Preview`] and get the value that merging the
+    /// candidate would produce.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Get the changes to the current value, ordered by path.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Get the change at `path`, if any.
+    pub fn change(&self, path: &str) -> Option<&Change> {
+        self.changes.iter().find(|x| x.path == path)
+    }
+
+    /// Get the errors that occurred while reading and merging the candidate.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Get the number of imports that were not read because of
+    /// [`Options::max_depth`].
+    pub fn skipped_imports(&self) -> usize {
+        self.skipped
+    }
+
+    /// Check whether the candidate changes nothing and merges without errors.
+    pub fn is_noop(&self) -> bool {
+        self.changes.is_empty() && self.errors.is_empty()
+    }
+
+    /// Get a short report of the preview.
+    ///
+    /// Like: `3 values changed, 1 added, 1 collision`.
+    pub fn summary(&self) -> String {
+        let count = |kind| self.changes.iter().filter(|x| x.kind == kind).count();
+        let changed = count(ChangeKind::Changed);
+        let added = count(ChangeKind::Added);
+        let removed = count(ChangeKind::Removed);
+
+        let collisions = self.errors.iter().filter(|x| x.kind.is_collision()).count();
+        let other = self.errors.len() - collisions;
+
+        let mut parts = vec![
+            format!("{changed} {} changed", plural(changed, "value", "values")),
+            format!("{added} added"),
+        ];
+
+        if removed != 0 {
+            parts.push(format!("{removed} removed"));
+        }
+
+        if collisions != 0 {
+            parts.push(format!(
+                "{collisions} {}",
+                plural(collisions, "collision", "collisions")
+            ));
+        }
+
+        if other != 0 {
+            parts.push(format!("{other} {}", plural(other, "error", "errors")));
+        }
+
+        parts.join(", ")
+    }
+}
+
+fn plural<'a>(n: usize, one: &'a str, many: &'a str) -> &'a str {
+    if n == 1 { one } else { many }
+}
+
+/// The kind of a [`Change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// The value did not exist or was `null`.
+    Added,
+    /// The value existed and was overridden.
+    Changed,
+    /// The value existed and is now `null`.
+    Removed,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added => f.write_str("added"),
+            Self::Changed => f.write_str("changed"),
+            Self::Removed => f.write_str("removed"),
+        }
+    }
+}
+
+/// A difference between the current value and the value of a [`Preview`].
+///
+/// Objects are compared key by key. All other values, including arrays, are
+/// compared as a whole.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    path: String,
+    kind: ChangeKind,
+    before: Option<Value>,
+    after: Option<Value>,
+    module: Option<PathBuf>,
+}
+
+impl Change {
+    /// Get the path of the value.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the kind of the change.
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// Get the current value, if it exists.
+    pub fn before(&self) -> Option<&Value> {
+        self.before.as_ref()
+    }
+
+    /// Get the value after the change, if it exists.
+    pub fn after(&self) -> Option<&Value> {
+        self.after.as_ref()
+    }
+
+    /// Get the module that last changed the value.
+    ///
+    /// Only available with [`Options::provenance`].
+    pub fn module(&self) -> Option<&Path> {
+        self.module.as_deref()
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' {}", self.path, self.kind)?;
+
+        if let Some(ref module) = self.module {
+            write!(f, " by {}", module.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Push the differences between `before` and `after` to `out`.
+fn diff(before: &Value, after: &Value, path: &mut Vec<String>, out: &mut Vec<Change>) {
+    if let (Value::Object(a), Value::Object(b)) = (before, after) {
+        let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            path.push(key.clone());
+            diff(
+                a.get(key).unwrap_or(&Value::Null),
+                b.get(key).unwrap_or(&Value::Null),
+                path,
+                out,
+            );
+            path.pop();
+        }
+
+        return;
+    }
+
+    let kind = match (before, after) {
+        _ if before == after => return,
+        (Value::Null, _) => ChangeKind::Added,
+        (_, Value::Null) => ChangeKind::Removed,
+        _ => ChangeKind::Changed,
+    };
+
+    let some = |x: &Value| (!x.is_null()).then(|| x.clone());
+
+    out.push(Change {
+        path: path.join("."),
+        kind,
+        before: some(before),
+        after: some(after),
+        module: None,
+    });
+}
+
+/// Remove the value at `path` from `value`.
+///
+/// `path` are the components of the path of the value in an [`Error`].
+///
+/// Returns whether anything was removed.
+fn remove(value: &mut Value, path: &[String]) -> bool {
+    let Some((last, parents)) = path.split_last() else {
+        return false;
+    };
+
+    let Some(parent) = parents.iter().try_fold(value, |value, component| {
+        match Component::parse(component) {
+            Component::Key(x) => value.as_object_mut()?.get_mut(&x),
+            Component::Index(i) => value.as_array_mut()?.get_mut(i),
+        }
+    }) else {
+        return false;
+    };
+
+    match (Component::parse(last), parent) {
+        (Component::Key(x), Value::Object(parent)) => parent.remove(&x).is_some(),
+        (Component::Index(i), Value::Array(parent)) if i < parent.len() => {
+            parent.remove(i);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// A component of the path of a value in an [`Error`].
+enum Component {
+    /// A field of a struct, or a key of a map.
+    Key(String),
+    /// An element of a sequence.
+    Index(usize),
+}
+
+impl Component {
+    /// Parse `x`. Keys of maps are quoted, fields of structs are not and
+    /// elements are written like `[3]`.
+    fn parse(x: &str) -> Self {
+        if let Some(i) = x
+            .strip_prefix('[')
+            .and_then(|x| x.strip_suffix(']'))
+            .and_then(|x| x.parse().ok())
+        {
+            return Self::Index(i);
+        }
+
+        if x.starts_with('"')
+            && let Ok(x) = serde_json::from_str(x)
+        {
+            return Self::Key(x);
+        }
+
+        Self::Key(x.to_owned())
+    }
+}
+
+struct Walker<'a, T, F> {
+    /// Only used for its format and options.
+    file: &'a mut File<T, F>,
+    options: Options,
+    value: T,
+    errors: Vec<Error>,
+    skipped: usize,
+    /// The modules being read, outermost first.
+    stack: Vec<String>,
+    evaluated: VisitedSet<OsString>,
+    depth: usize,
+    /// The value after the last module, for provenance.
+    last: Option<Value>,
+    /// The module that last changed each path.
+    origins: HashMap<String, PathBuf>,
+}
+
+impl<T, F> Walker<'_, T, F>
+where
+    T: Merge + Clone + Serialize + DeserializeOwned,
+    F: Format,
+{
+    /// Record `error` with the modules being read as its backtrace.
+    fn record(&mut self, mut error: Error) {
+        for module in self.stack.iter().rev() {
+            error.modules.push(module.clone());
+        }
+        self.errors.push(error);
+    }
+
+    /// Read the module at `path` and its imports.
+    ///
+    /// Returns whether the module itself was read.
+    fn read<D>(&mut self, path: &Path, display: D, offset: isize) -> bool
+    where
+        D: fmt::Display,
+    {
+        self.stack.push(display.to_string());
+        let r = match self._read(path, offset) {
+            Ok(()) => true,
+            Err(e) => {
+                self.record(e);
+                false
+            }
+        };
+        self.stack.pop();
+        r
+    }
+
+    fn _read(&mut self, path: &Path, offset: isize) -> Result<(), Error> {
+        check_depth(self.file.max_depth(), self.depth)?;
+
+        self.depth += 1;
+        let r = self.eval(path, offset);
+        self.depth -= 1;
+        r
+    }
+
+    fn eval(&mut self, path: &Path, offset: isize) -> Result<(), Error> {
+        let path = fs::canonicalize(path).map_err(Error::from)?;
+
+        let key = self.file.path_identity().key(&path);
+
+        // Just like `File`, a module is evaluated at most once.
+        if self.evaluated.enter(key.clone())? == Visit::Completed {
+            return Ok(());
+        }
+
+        let import_key = self.file.import_key();
+        let format = self.file.format_mut();
+        let module = format.read::<Value>(&path, ModuleSeed::new(import_key));
+
+        let Module { imports, value } = match module {
+            Ok(x) => x,
+            Err(e) => {
+                self.evaluated.abandon(&key);
+                return Err(e);
+            }
+        };
+
+        // Errors are recorded instead, so the walk always succeeds.
+        let r = self.file.import_order().walk(
+            self,
+            |this| {
+                this.merge(&path, value, offset);
+                Ok(())
+            },
+            |this| {
+                this.read_imports(&path, imports);
+                Ok(())
+            },
+        );
+
+        self.evaluated.complete(&key);
+        r?
+    }
+
+    fn read_imports(&mut self, path: &Path, imports: Imports) {
+        for import in imports.0 {
+            if self.options.max_depth.is_some_and(|x| self.depth > x) {
+                self.skipped += 1;
+                continue;
+            }
+
+            if let Err(e) = self.read_import(path, import) {
+                self.record(e);
+            }
+        }
+    }
+
+    /// Merge `value`, dropping the values that fail to merge.
+    fn merge(&mut self, path: &Path, mut value: Value, offset: isize) {
+        loop {
+            let mut x = match serde_json::from_value::<T>(value.clone()) {
+                Ok(x) => x,
+                Err(e) => return self.record(Error::custom(e)),
+            };
+            self.file.layer(&mut x, offset);
+
+            let mut merged = self.value.clone();
+            match merged.merge_ref(x) {
+                Ok(()) => {
+                    self.value = merged;
+                    break;
+                }
+                Err(e) => {
+                    let components: Vec<String> =
+                        e.value.components().map(|x| x.to_string()).collect();
+                    let removed = remove(&mut value, &components);
+
+                    self.record(e);
+                    if !removed {
+                        return;
+                    }
+                }
+            }
+        }
+
+        if let Some(ref last) = self.last {
+            let after = match to_value(&self.value) {
+                Ok(x) => x,
+                Err(e) => return self.record(e),
+            };
+
+            let mut changes = Vec::new();
+            diff(last, &after, &mut Vec::new(), &mut changes);
+            for change in changes {
+                self.origins.insert(change.path, path.to_path_buf());
+            }
+
+            self.last = Some(after);
+        }
+    }
+}
+
+impl<T, F> Importer for Walker<'_, T, F>
+where
+    T: Merge + Clone + Serialize + DeserializeOwned,
+    F: Format,
+{
+    fn resolver(&self) -> &Resolver {
+        self.file.resolver()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.file.format().extensions()
+    }
+
+    fn import(&mut self, import: Imported) -> Result<(), Error> {
+        let module = import.module(&import.path);
+        self.read(&import.path, module, import.offset);
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+//! Operations on subtrees of configuration values.
+//!
+//! This module allows treating a part of a configuration value as its own
+//! mergeable unit, without knowing its concrete type. Values are handled in
+//! their [`serde_json::Value`] form.
+//!
+//! Paths are `.`-separated lists of object keys, like `http.tls`. The empty
+//! path refers to the whole value.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use module_util::subtree::{self, GraftPolicy};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     http: Http,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Http {
+//!     port: u16,
+//!     hosts: Vec<String>,
+//! }
+//!
+//! let mut config = Config {
+//!     http: Http { port: 80, hosts: vec!["a".into()] },
+//! };
+//!
+//! let hosts: Vec<String> = subtree::subtree(&config, "http.hosts").unwrap();
+//! assert_eq!(hosts, &["a"]);
+//!
+//! subtree::apply_subtree(&mut config, "http.hosts", vec!["b"], GraftPolicy::Merge).unwrap();
+//! assert_eq!(config.http.hosts, &["a", "b"]);
+//! ```
+
+use module::{Context, Error, Merge};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// How [`graft()`] combines the new subtree with the existing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraftPolicy {
+    /// Replace the existing subtree.
+    Replace,
+    /// Merge the new subtree into the existing one.
+    ///
+    /// This uses the [`Merge`] implementation of [`Value`]: objects are merged
+    /// key by key, arrays are concatenated, `null` is replaced by the other
+    /// value and everything else collides.
+    Merge,
+}
+
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('.').filter(|x| !x.is_empty())
+}
+
+/// Add `path` to the value context of `r`.
+fn with_path<T>(r: Result<T, Error>, path: &[&str]) -> Result<T, Error> {
+    path.iter()
+        .rev()
+        .fold(r, |r, component| r.value(String::from(*component)))
+}
+
+/// Get a copy of the subtree of `value` at `path`.
+///
+/// Returns [`None`] if `path` does not exist.
+pub fn extract(value: &Value, path: &str) -> Option<Value> {
+    components(path)
+        .try_fold(value, |value, component| value.as_object()?.get(component))
+        .cloned()
+}
+
+/// Put `sub` at `path` inside `value` according to `policy`.
+///
+/// Objects along `path` that do not exist are created.
+///
+/// # Errors
+///
+/// * If a value along `path` is not an object.
+/// * If merging fails.
+///
+/// All errors carry the path to the offending value.
+pub fn graft(value: &mut Value, path: &str, sub: Value, policy: GraftPolicy) -> Result<(), Error> {
+    let path: Vec<&str> = components(path).collect();
+
+    let mut current = value;
+    for (i, component) in path.iter().enumerate() {
+        let Value::Object(map) = current else {
+            let r = Err(Error::custom(format!(
+                "expected an object, found {}",
+                kind(current)
+            )));
+            return with_path(r, &path[..i]);
+        };
+
+        current = map
+            .entry(*component)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    match policy {
+        GraftPolicy::Replace => {
+            *current = sub;
+            Ok(())
+        }
+        GraftPolicy::Merge => with_path(current.merge_ref(sub), &path),
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Get the subtree of `config` at `path` as `Sub`.
+///
+/// # Errors
+///
+/// * If `config` cannot be serialized.
+/// * If `path` does not exist.
+/// * If the subtree cannot be deserialized as `Sub`.
+pub fn subtree<T, Sub>(config: &T, path: &str) -> Result<Sub, Error>
+where
+    T: Serialize,
+    Sub: DeserializeOwned,
+{
+    let path_components: Vec<&str> = components(path).collect();
+
+    let value = serde_json::to_value(config).map_err(Error::custom)?;
+    let sub = extract(&value, path).ok_or_else(|| Error::custom("no such path"));
+    let sub = with_path(sub, &path_components)?;
+
+    with_path(
+        serde_json::from_value(sub).map_err(Error::custom),
+        &path_components,
+    )
+}
+
+/// Put `sub` at `path` inside `config` according to `policy`.
+///
+/// This serializes `config`, [`graft()`]s `sub` into it and deserializes the
+/// result back into `config`. On error, `config` is left unchanged.
+///
+/// # Errors
+///
+/// * If `config` or `sub` cannot be serialized.
+/// * If [`graft()`] fails.
+/// * If the result cannot be deserialized as `T`.
+pub fn apply_subtree<T, Sub>(
+    config: &mut T,
+    path: &str,
+    sub: Sub,
+    policy: GraftPolicy,
+) -> Result<(), Error>
+where
+    T: Serialize + DeserializeOwned,
+    Sub: Serialize,
+{
+    let path_components: Vec<&str> = components(path).collect();
+
+    let mut value = serde_json::to_value(&*config).map_err(Error::custom)?;
+    let sub = with_path(
+        serde_json::to_value(sub).map_err(Error::custom),
+        &path_components,
+    )?;
+
+    graft(&mut value, path, sub, policy)?;
+
+    *config = with_path(
+        serde_json::from_value(value).map_err(Error::custom),
+        &path_components,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use module::merge::ErrorKind;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    fn trace(err: &Error) -> Vec<String> {
+        err.value.components().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_extract() {
+        let value = json!({ "http": { "tls": { "port": 443 } } });
+
+        assert_eq!(extract(&value, "http.tls"), Some(json!({ "port": 443 })));
+        assert_eq!(extract(&value, ""), Some(value.clone()));
+        assert_eq!(extract(&value, "http.missing"), None);
+        assert_eq!(extract(&value, "http.tls.port.x"), None);
+    }
+
+    #[test]
+    fn test_graft_replace() {
+        let mut value = json!({ "http": { "hosts": ["a"], "port": 80 } });
+
+        graft(
+            &mut value,
+            "http",
+            json!({ "hosts": ["b"] }),
+            GraftPolicy::Replace,
+        )
+        .unwrap();
+        assert_eq!(value, json!({ "http": { "hosts": ["b"] } }));
+
+        graft(&mut value, "new.nested", json!(1), GraftPolicy::Replace).unwrap();
+        assert_eq!(value["new"]["nested"], json!(1));
+    }
+
+    #[test]
+    fn test_graft_merge() {
+        let mut value = json!({ "http": { "hosts": ["a"], "port": 80 } });
+
+        let mut sub = extract(&value, "http").unwrap();
+        sub.merge_ref(json!({ "hosts": ["b"] })).unwrap();
+
+        graft(&mut value, "http", sub, GraftPolicy::Replace).unwrap();
+        graft(
+            &mut value,
+            "http",
+            json!({ "hosts": ["c"], "tls": true }),
+            GraftPolicy::Merge,
+        )
+        .unwrap();
+
+        assert_eq!(
+            value,
+            json!({ "http": { "hosts": ["a", "b", "c"], "port": 80, "tls": true } })
+        );
+    }
+
+    #[test]
+    fn test_graft_merge_errors() {
+        let mut value = json!({ "http": { "port": 80, "hosts": ["a"] } });
+
+        let err = graft(
+            &mut value,
+            "http",
+            json!({ "port": 8080 }),
+            GraftPolicy::Merge,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+        assert_eq!(trace(&err), &["http", "\"port\""]);
+
+        let err = graft(
+            &mut value,
+            "http",
+            json!({ "hosts": "b" }),
+            GraftPolicy::Merge,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+        assert_eq!(trace(&err), &["http", "\"hosts\""]);
+
+        // `null` never collides.
+        graft(
+            &mut value,
+            "http",
+            json!({ "port": null }),
+            GraftPolicy::Merge,
+        )
+        .unwrap();
+        assert_eq!(value["http"]["port"], json!(80));
+
+        let err = graft(&mut value, "http.port.x", json!(1), GraftPolicy::Replace).unwrap_err();
+        assert_eq!(err.kind.to_string(), "expected an object, found a number");
+        assert_eq!(trace(&err), &["http", "port"]);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        http: Http,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Http {
+        port: u16,
+        hosts: Vec<String>,
+    }
+
+    fn config() -> Config {
+        Config {
+            http: Http {
+                port: 80,
+                hosts: vec!["a".into()],
+            },
+        }
+    }
+
+    #[test]
+    fn test_subtree() {
+        let config = config();
+
+        let http: Http = subtree(&config, "http").unwrap();
+        assert_eq!(http, config.http);
+
+        let err = subtree::<_, Http>(&config, "http.hosts").unwrap_err();
+        assert_eq!(trace(&err), &["http", "hosts"]);
+
+        let err = subtree::<_, Http>(&config, "https").unwrap_err();
+        assert_eq!(err.kind.to_string(), "no such path");
+        assert_eq!(trace(&err), &["https"]);
+    }
+
+    #[test]
+    fn test_apply_subtree() {
+        let mut config = config();
+
+        let mut http: Http = subtree(&config, "http").unwrap();
+        http.port = 8080;
+        apply_subtree(&mut config, "http", http, GraftPolicy::Replace).unwrap();
+        assert_eq!(config.http.port, 8080);
+
+        apply_subtree(&mut config, "http.hosts", ["b"], GraftPolicy::Merge).unwrap();
+        assert_eq!(config.http.hosts, &["a", "b"]);
+    }
+
+    #[test]
+    fn test_apply_subtree_type_mismatch() {
+        let mut config = config();
+
+        let err =
+            apply_subtree(&mut config, "http.port", "http", GraftPolicy::Replace).unwrap_err();
+        assert!(err.kind.is_custom());
+        assert_eq!(trace(&err), &["http", "port"]);
+        assert_eq!(config, self::config());
+    }
+}
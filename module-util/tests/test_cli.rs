@@ -0,0 +1,120 @@
+#![allow(missing_docs)]
+
+//! Checks the exit codes of [`exit_on_error`] by running this test binary
+//! again as a child process that calls it.
+
+use module::Merge;
+use serde::Deserialize;
+use std::env;
+use std::process::Command;
+
+use module_util::cli::exit_on_error;
+use module_util::file::json;
+
+/// The environment variable that tells a child process which file to read.
+const CHILD: &str = "MODULE_UTIL_TEST_CLI_CHILD";
+
+#[derive(Deserialize, Merge)]
+struct Config {
+    value: Option<i32>,
+}
+
+/// Run as a child process by [`run`], does nothing otherwise.
+#[test]
+fn child() {
+    let Some(path) = env::var_os(CHILD) else {
+        return;
+    };
+
+    let config: Config = exit_on_error(json(path));
+    assert_eq!(config.value, Some(1));
+}
+
+/// Run [`child`] in a new process with `files` and return its exit code and
+/// standard error.
+fn run(files: &[(&str, &str)], root: &str) -> (Option<i32>, String) {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, contents) in files {
+        std::fs::write(dir.path().join(name), contents).unwrap();
+    }
+
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "child", "--nocapture", "--test-threads=1"])
+        .env(CHILD, dir.path().join(root))
+        .env_remove("NO_COLOR")
+        .output()
+        .unwrap();
+
+    (
+        output.status.code(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_cli_ok() {
+    let (code, _) = run(&[("a.json", r#"{ "value": 1 }"#)], "a.json");
+    assert_eq!(code, Some(0));
+}
+
+#[test]
+fn test_cli_collision() {
+    let (code, stderr) = run(
+        &[
+            ("a.json", r#"{ "imports": ["b.json"], "value": 1 }"#),
+            ("b.json", r#"{ "value": 2 }"#),
+        ],
+        "a.json",
+    );
+
+    assert_eq!(code, Some(65));
+    assert!(
+        stderr.contains("error: value collision ("),
+        "unexpected output: {stderr}"
+    );
+    assert!(
+        stderr.contains(") while evaluating 'value'"),
+        "unexpected output: {stderr}"
+    );
+    assert!(
+        !stderr.contains('\x1b'),
+        "stderr is not a terminal, so there should be no colors: {stderr}"
+    );
+}
+
+#[test]
+fn test_cli_cycle() {
+    let (code, stderr) = run(
+        &[("a.json", r#"{ "imports": ["a.json"], "value": 1 }"#)],
+        "a.json",
+    );
+
+    assert_eq!(code, Some(65));
+    assert!(
+        stderr.contains("hint: the modules import each other"),
+        "unexpected output: {stderr}"
+    );
+}
+
+#[test]
+fn test_cli_not_found() {
+    let (code, _) = run(&[], "missing.json");
+    assert_eq!(code, Some(66));
+}
+
+#[test]
+fn test_cli_custom() {
+    let (code, _) = run(&[("a.json", r#"{ "value": "1" }"#)], "a.json");
+    assert_eq!(code, Some(78));
+}
+
+#[test]
+fn test_cli_syntax_error() {
+    let (code, stderr) = run(&[("a.json", "{\n  \"value\": 1,\n}")], "a.json");
+
+    assert_eq!(code, Some(78));
+    assert!(
+        stderr.contains("(at line 3, column 1)"),
+        "unexpected output: {stderr}"
+    );
+}
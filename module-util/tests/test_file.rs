@@ -2,11 +2,17 @@
 
 use module::Merge;
 use module::merge::ErrorKind;
-use module::types::Overridable;
+use module::types::{Candidates, Overridable};
 use serde::Deserialize;
+use std::fs;
+use std::ops::ControlFlow;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
 
-use module_util::file::json;
+use module_util::file::{
+    ContentHash, DirImport, File, Format, ImportOrder, Json, Manifest, Module, ModuleSeed,
+    PathIdentity, Remap, json, read_namespaced, read_namespaced_with,
+};
 
 fn path(p: &str) -> PathBuf {
     Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(p)
@@ -64,3 +70,1412 @@ fn test_file_cycle2() {
     let err = json::<Cycle>(path("json/cycle2.json")).unwrap_err();
     assert_eq!(err.kind, ErrorKind::Cycle);
 }
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Merge)]
+struct Speculative {
+    key: Option<String>,
+    items: Option<Vec<i32>>,
+}
+
+#[test]
+fn test_file_diamond() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    // main -> a -> shared
+    //      -> b -> shared
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["a.json", "b.json"], "items": [1] }"#,
+        ),
+        ("a.json", r#"{ "imports": ["shared.json"], "items": [2] }"#),
+        ("b.json", r#"{ "imports": ["shared.json"], "items": [3] }"#),
+        ("shared.json", r#"{ "items": [4] }"#),
+    ]);
+
+    let mut file = File::<Items, _>::json();
+    file.read(dir.path().join("main.json")).unwrap();
+    assert_eq!(file.evaluated().len(), 4);
+
+    // `shared.json` is merged only once.
+    assert_eq!(file.finish().unwrap().items, &[1, 2, 4, 3]);
+}
+
+#[test]
+fn test_file_read_dir() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Simple {
+        key: Option<String>,
+        items: Vec<i32>,
+    }
+
+    let mut file = File::<Simple, _>::json();
+    file.read_dir(path("json/drop-ins")).unwrap();
+
+    let evaluated: Vec<_> = file.evaluated().filter_map(|x| x.file_name()).collect();
+    assert_eq!(
+        evaluated,
+        [
+            "10-base.json",
+            "20-extra.json",
+            "simple2.json",
+            "9-late.json"
+        ]
+    );
+
+    let x = file.finish().unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items, &[1, 2, 3, 6, 0, 9]);
+
+    let mut import = DirImport::new(path("json/drop-ins"));
+    import.recursive = true;
+
+    let mut file = File::<Simple, _>::json();
+    file.read_dir_with(&import).unwrap();
+    assert_eq!(file.finish().unwrap().items, &[1, 2, 3, 6, 0, 9, 30]);
+}
+
+#[test]
+fn test_file_read_dir_error() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let dir = tree(&[
+        ("a.json", r#"{ "items": [1] }"#),
+        ("b.json", r#"{ "items": 2 }"#),
+    ]);
+
+    let mut file = File::<Items, _>::json();
+    let err = file.read_dir(dir.path()).unwrap_err();
+
+    let modules: Vec<String> = err.modules.iter().map(|x| x.to_string()).collect();
+    assert_eq!(modules.len(), 1);
+    assert!(modules[0].ends_with("b.json"), "{modules:?}");
+}
+
+#[test]
+fn test_file_speculate() {
+    let mut file = File::<Speculative, _>::json();
+    let before = file.snapshot();
+
+    let inside = file.speculate(|file| {
+        file.read(path("json/simple1.json")).unwrap();
+        file.value().cloned().unwrap()
+    });
+    assert_eq!(inside.key.as_deref(), Some("424242"));
+    assert_eq!(inside.items.as_deref(), Some([1, 3, 6, 0].as_slice()));
+
+    assert_eq!(file.snapshot(), before);
+    assert!(file.value().is_none());
+
+    // The speculatively read modules were forgotten, so this is not skipped.
+    file.read(path("json/simple2.json")).unwrap();
+
+    let x = file.finish().unwrap();
+    assert_eq!(x.key, None);
+    assert_eq!(x.items.as_deref(), Some([3, 6, 0].as_slice()));
+}
+
+#[test]
+fn test_file_speculate_panic() {
+    let mut file = File::<Speculative, _>::json();
+    let before = file.snapshot();
+
+    let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        file.speculate(|file| {
+            file.read(path("json/simple1.json")).unwrap();
+            assert!(file.value().is_some());
+            panic!("oops");
+        })
+    }));
+    assert!(r.is_err());
+
+    assert_eq!(file.snapshot(), before);
+    assert!(file.value().is_none());
+}
+
+#[test]
+fn test_file_restore() {
+    let mut file = File::<Speculative, _>::json();
+    let empty = file.snapshot();
+
+    file.read(path("json/simple1.json")).unwrap();
+    assert!(file.value().is_some());
+
+    file.restore(empty);
+    assert!(file.value().is_none());
+
+    // All modules were forgotten, so this is not skipped anymore.
+    file.read(path("json/simple1.json")).unwrap();
+    assert!(file.value().is_some());
+}
+
+/// Write `files` into a new temporary directory.
+fn tree(files: &[(&str, &str)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, contents) in files {
+        let p = dir.path().join(name);
+        fs::create_dir_all(p.parent().unwrap()).unwrap();
+        fs::write(p, contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_file_case_variant_cycle() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["sub/Base.json"], "items": [1] }"#,
+        ),
+        (
+            "sub/Base.json",
+            r#"{ "imports": ["../MAIN.json"], "items": [2] }"#,
+        ),
+        ("MAIN.json", r#"{ "items": [3] }"#),
+    ]);
+
+    let mut file = File::<Items, _>::json();
+    file.set_path_identity(PathIdentity::case_insensitive());
+
+    let err = file.read(dir.path().join("main.json")).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Cycle);
+
+    // Case-sensitive filesystems see 3 distinct files.
+    if cfg!(not(any(windows, target_os = "macos"))) {
+        let mut file = File::<Items, _>::json();
+        file.set_path_identity(PathIdentity::case_sensitive());
+
+        file.read(dir.path().join("main.json")).unwrap();
+        assert_eq!(file.finish().unwrap().items, &[1, 2, 3]);
+    }
+}
+
+#[test]
+fn test_file_case_variant_reimport() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let dir = tree(&[
+        ("main.json", r#"{ "imports": ["base.json"], "items": [1] }"#),
+        ("base.json", r#"{ "items": [2] }"#),
+        ("Base.json", r#"{ "items": [2] }"#),
+    ]);
+
+    let mut file = File::<Items, _>::json();
+    file.set_path_identity(PathIdentity::case_insensitive());
+
+    file.read(dir.path().join("main.json")).unwrap();
+
+    // The variant is recognized as an already evaluated module instead of
+    // being merged a second time.
+    file.read(dir.path().join("Base.json")).unwrap();
+    assert_eq!(file.finish().unwrap().items, &[1, 2]);
+}
+
+#[test]
+fn test_file_import_key() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+        imports: Option<Vec<String>>,
+    }
+
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "extends": ["base.json"], "imports": ["not-an-import"], "items": [1] }"#,
+        ),
+        (
+            "base.json",
+            r#"{ "extends": ["inner.json"], "items": [2] }"#,
+        ),
+        ("inner.json", r#"{ "items": [3] }"#),
+    ]);
+
+    let mut file = File::<Items, _>::json().with_import_key("extends");
+    assert_eq!(file.import_key(), "extends");
+
+    file.read(dir.path().join("main.json")).unwrap();
+    file.read_str("inline", r#"{ "extends": [], "items": [4] }"#)
+        .unwrap();
+
+    let x = file.finish().unwrap();
+    assert_eq!(x.items, &[1, 2, 3, 4]);
+    assert_eq!(x.imports.unwrap(), ["not-an-import"]);
+
+    // With the default key, `main.json` imports a module that does not exist.
+    let mut file = File::<Items, _>::json();
+    assert_eq!(file.import_key(), "imports");
+
+    let err = file.read(dir.path().join("main.json")).unwrap_err();
+    let source = std::error::Error::source(&err).unwrap();
+    let source = source.downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_file_expand_env() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let shared = tree(&[("base.json", r#"{ "items": [2] }"#)]);
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["${MODULE_UTIL_TEST_SHARED}/base.json"], "items": [1] }"#,
+        ),
+        (
+            "undefined.json",
+            r#"{ "imports": ["$MODULE_UTIL_TEST_UNDEFINED/base.json"], "items": [] }"#,
+        ),
+    ]);
+
+    // SAFETY: No other test reads or writes these variables.
+    unsafe {
+        std::env::set_var("MODULE_UTIL_TEST_SHARED", shared.path());
+        std::env::remove_var("MODULE_UTIL_TEST_UNDEFINED");
+    }
+
+    let mut file = File::<Items, _>::json().with_expand_env(true);
+    assert!(file.expands_env());
+
+    file.read(dir.path().join("main.json")).unwrap();
+    assert_eq!(file.finish().unwrap().items, &[1, 2]);
+
+    let mut file = File::<Items, _>::json().with_expand_env(true);
+    let err = file.read(dir.path().join("undefined.json")).unwrap_err();
+    let msg = err.kind.to_string();
+    assert!(msg.contains("MODULE_UTIL_TEST_UNDEFINED"), "{msg}");
+
+    // Expansion is off by default.
+    let mut file = File::<Items, _>::json();
+    assert!(file.read(dir.path().join("main.json")).is_err());
+}
+
+#[derive(Debug, Deserialize, Merge)]
+#[serde(deny_unknown_fields)]
+struct Remapped {
+    http: Option<Http>,
+    features: Option<Features>,
+}
+
+#[derive(Debug, Deserialize, Merge)]
+#[serde(deny_unknown_fields)]
+struct Http {
+    port: Option<Overridable<u16>>,
+    hosts: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Merge)]
+#[serde(deny_unknown_fields)]
+struct Features {
+    new_flag: Option<Overridable<bool>>,
+}
+
+fn remap() -> Remap {
+    let mut remap = Remap::new();
+    remap.push("net.http.*", "http.*").unwrap();
+    remap.push("old.flag", "features.new_flag").unwrap();
+    remap
+}
+
+#[test]
+fn test_file_remap() {
+    let mut file = File::<Remapped, _>::json().with_remap(remap());
+    file.read(path("json/remap/old.json")).unwrap();
+
+    let applied: Vec<String> = file
+        .format()
+        .applied()
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+    let module = fs::canonicalize(path("json/remap/old.json")).unwrap();
+    assert_eq!(
+        applied,
+        &[
+            format!("{}: 'net.http.*' has moved to 'http.*'", module.display()),
+            format!(
+                "{}: 'old.flag' has moved to 'features.new_flag'",
+                module.display()
+            ),
+        ]
+    );
+
+    let x = file.finish().unwrap();
+    let http = x.http.unwrap();
+    assert_eq!(http.port.as_deref().copied(), Some(8080));
+    assert_eq!(
+        http.hosts.as_deref(),
+        Some(["example.com".to_owned()].as_slice())
+    );
+    assert_eq!(x.features.unwrap().new_flag.as_deref().copied(), Some(true));
+}
+
+#[test]
+fn test_file_remap_untouched() {
+    let mut file = File::<Remapped, _>::json().with_remap(remap());
+    file.read(path("json/remap/new.json")).unwrap();
+
+    assert!(file.format().applied().is_empty());
+    assert!(file.finish().unwrap().http.is_some());
+}
+
+#[test]
+fn test_file_remap_conflict() {
+    let mut file = File::<Remapped, _>::json().with_remap(remap());
+    let err = file.read(path("json/remap/conflict.json")).unwrap_err();
+
+    assert_eq!(
+        err.kind.to_string(),
+        "cannot move 'net.http.port' to 'http.port': destination already exists"
+    );
+
+    let module = fs::canonicalize(path("json/remap/conflict.json")).unwrap();
+    let modules: Vec<String> = err.modules.iter().map(|x| x.to_string()).collect();
+    assert_eq!(modules, &[module.display().to_string()]);
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn test_file_remap_from_toml() {
+    let remap = Remap::from_toml(
+        r#"
+        [[remap]]
+        from = "net.http.*"
+        to = "http.*"
+
+        [[remap]]
+        from = "old.flag"
+        to = "features.new_flag"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(remap, self::remap());
+}
+
+#[test]
+fn test_file_jsonc() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Simple {
+        key: Option<String>,
+        items: Option<Vec<i32>>,
+    }
+
+    let jsonc = Json::builder()
+        .allow_comments(true)
+        .allow_trailing_commas(true)
+        .build();
+
+    let mut file = File::<Simple, _>::json_with(jsonc);
+    file.read(path("json/jsonc.json")).unwrap();
+    let x = file.finish().unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([1, 3, 6, 0].as_slice()));
+
+    assert!(json::<Simple>(path("json/jsonc.json")).is_err());
+
+    // `Json` is still a value with the default options.
+    let mut file = File::<Simple, _>::new(Json::default());
+    assert!(!file.format().allows_comments());
+    assert!(file.read(path("json/jsonc.json")).is_err());
+
+    let comments_only = Json::builder().allow_comments(true).build();
+    let mut file = File::<Simple, _>::json_with(comments_only);
+    assert!(file.read(path("json/jsonc.json")).is_err());
+
+    // Options can also be changed after the fact.
+    *file.format_mut() = jsonc;
+    file.read(path("json/jsonc.json")).unwrap();
+}
+
+#[derive(Debug, Deserialize, Merge)]
+struct Locked {
+    items: Vec<i32>,
+}
+
+fn locked_tree() -> tempfile::TempDir {
+    tree(&[
+        ("main.json", r#"{ "imports": ["a.json"], "items": [1] }"#),
+        ("a.json", r#"{ "items": [2] }"#),
+        ("b.json", r#"{ "items": [3] }"#),
+    ])
+}
+
+#[test]
+fn test_file_manifest() {
+    let dir = locked_tree();
+    let main = fs::canonicalize(dir.path().join("main.json")).unwrap();
+    let a = fs::canonicalize(dir.path().join("a.json")).unwrap();
+
+    let mut file = File::<Locked, _>::json().with_record_manifest(true);
+    file.read(&main).unwrap();
+    let manifest = file.record_manifest();
+
+    assert_eq!(manifest.roots(), std::slice::from_ref(&main));
+    let paths: Vec<&Path> = manifest.entries().iter().map(|x| x.path()).collect();
+    assert_eq!(paths, &[main.as_path(), a.as_path()]);
+
+    // Round-trip through the lockfile.
+    let manifest = Manifest::from_lockfile(&manifest.to_lockfile().unwrap()).unwrap();
+
+    let mut file = File::<Locked, _>::json().with_manifest(manifest.clone());
+    file.read(&main).unwrap();
+    // Nothing is recorded unless asked to.
+    assert!(file.record_manifest().entries().is_empty());
+    assert_eq!(file.finish().unwrap().items, &[1, 2]);
+
+    let mut file = File::<Locked, _>::json()
+        .with_manifest(manifest.clone())
+        .with_record_manifest(true);
+    file.read(&main).unwrap();
+    assert!(manifest.diff(&file.record_manifest()).is_empty());
+}
+
+#[test]
+fn test_file_manifest_changed() {
+    let dir = locked_tree();
+    let main = dir.path().join("main.json");
+    let a = fs::canonicalize(dir.path().join("a.json")).unwrap();
+
+    let mut file = File::<Locked, _>::json().with_record_manifest(true);
+    file.read(&main).unwrap();
+    let manifest = file.record_manifest();
+
+    fs::write(&a, r#"{ "items": [4] }"#).unwrap();
+
+    let mut file = File::<Locked, _>::json().with_manifest(manifest.clone());
+    let err = file.read(&main).unwrap_err();
+
+    let old = ContentHash::of(br#"{ "items": [2] }"#);
+    let new = ContentHash::of(br#"{ "items": [4] }"#);
+    assert_eq!(
+        err.kind.to_string(),
+        format!(
+            "'{}' has changed since the manifest was recorded (expected {old}, found {new})",
+            a.display()
+        )
+    );
+
+    // The changed module never made it into the value.
+    assert_eq!(
+        file.value().map(|x| x.items.as_slice()),
+        Some([1].as_slice())
+    );
+
+    let mut file = File::<Locked, _>::json().with_record_manifest(true);
+    file.read(&main).unwrap();
+    let diff = manifest.diff(&file.record_manifest());
+    assert_eq!(diff.changed(), &[(a, old, new)]);
+}
+
+#[test]
+fn test_file_manifest_unexpected_import() {
+    let dir = locked_tree();
+    let main = fs::canonicalize(dir.path().join("main.json")).unwrap();
+    let b = fs::canonicalize(dir.path().join("b.json")).unwrap();
+
+    fs::write(
+        &main,
+        r#"{ "imports": ["a.json", "b.json"], "items": [1] }"#,
+    )
+    .unwrap();
+
+    // A manifest that accounts for the new contents of `main.json`, but not
+    // for `b.json`.
+    let mut file = File::<Locked, _>::json().with_record_manifest(true);
+    file.read(&main).unwrap();
+    let lockfile = file.record_manifest().to_lockfile().unwrap();
+    let lockfile: String = lockfile
+        .lines()
+        .filter(|x| !x.ends_with("b.json"))
+        .map(|x| format!("{x}\n"))
+        .collect();
+    let manifest = Manifest::from_lockfile(&lockfile).unwrap();
+
+    let mut file = File::<Locked, _>::json().with_manifest(manifest);
+    let err = file.read(&main).unwrap_err();
+
+    assert_eq!(
+        err.kind.to_string(),
+        format!(
+            "'{}' (imported by '{}') is not in the manifest",
+            b.display(),
+            main.display()
+        )
+    );
+    assert_eq!(
+        file.value().map(|x| x.items.as_slice()),
+        Some([1, 2].as_slice())
+    );
+}
+
+#[test]
+fn test_file_manifest_unexpected_root() {
+    let dir = locked_tree();
+
+    let mut file = File::<Locked, _>::json().with_manifest(Manifest::new());
+    let err = file.read(dir.path().join("b.json")).unwrap_err();
+    assert!(
+        err.kind
+            .to_string()
+            .ends_with("b.json' is not in the manifest")
+    );
+}
+
+/// Counts how many times each file is parsed.
+#[derive(Default)]
+struct Counting {
+    inner: Json,
+    reads: std::collections::HashMap<PathBuf, usize>,
+}
+
+impl Format for &mut Counting {
+    fn read<T>(
+        &mut self,
+        path: &Path,
+        seed: ModuleSeed<T>,
+    ) -> Result<Module<T>, module::merge::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        *self.reads.entry(path.to_path_buf()).or_default() += 1;
+        self.inner.read(path, seed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Merge)]
+struct Tenant {
+    name: Option<String>,
+    #[serde(default)]
+    plugins: Vec<String>,
+}
+
+fn tenant_tree() -> tempfile::TempDir {
+    tree(&[
+        ("base.json", r#"{ "plugins": ["base"] }"#),
+        (
+            "acme.json",
+            r#"{ "imports": ["base.json"], "name": "acme", "plugins": ["a"] }"#,
+        ),
+        (
+            "globex.json",
+            r#"{ "imports": ["base.json"], "name": "globex" }"#,
+        ),
+        (
+            "initech.json",
+            r#"{ "imports": ["base.json", "initech2.json"], "name": "initech" }"#,
+        ),
+        ("initech2.json", r#"{ "name": "initech2" }"#),
+    ])
+}
+
+#[test]
+fn test_file_namespaced() {
+    let dir = tenant_tree();
+    let mut format = Counting::default();
+
+    let tenants = read_namespaced::<Tenant, _, _, _, _>(
+        [
+            ("acme", dir.path().join("acme.json")),
+            ("globex", dir.path().join("globex.json")),
+        ],
+        &mut format,
+    )
+    .unwrap();
+
+    assert_eq!(tenants.len(), 2);
+    assert_eq!(tenants["acme"].name.as_deref(), Some("acme"));
+    assert_eq!(tenants["acme"].plugins, ["a", "base"]);
+    assert_eq!(tenants["globex"].name.as_deref(), Some("globex"));
+    assert_eq!(tenants["globex"].plugins, ["base"]);
+
+    let base = fs::canonicalize(dir.path().join("base.json")).unwrap();
+    assert_eq!(format.reads[&base], 1, "base.json should be parsed once");
+}
+
+#[test]
+fn test_file_namespaced_error() {
+    let dir = tenant_tree();
+
+    let err = read_namespaced::<Tenant, _, _, _, _>(
+        [
+            ("acme", dir.path().join("acme.json")),
+            ("initech", dir.path().join("initech.json")),
+            ("globex", dir.path().join("globex.json")),
+        ],
+        Json::default(),
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "1 of 3 namespaces failed to evaluate: initech"
+    );
+
+    let (name, e) = err.errors().next().unwrap();
+    assert_eq!(name, "initech");
+    assert!(e.kind.is_collision());
+    assert_eq!(
+        e.modules.iter().next().map(|x| x.to_string()).as_deref(),
+        Some("namespace 'initech'")
+    );
+
+    let acme = err.get("acme").unwrap().as_ref().unwrap();
+    assert_eq!(acme.name.as_deref(), Some("acme"));
+    assert!(err.get("globex").unwrap().is_ok(), "globex should succeed");
+
+    let strict = err.into_strict();
+    assert!(strict.kind.is_collision());
+}
+
+#[test]
+fn test_file_namespaced_merge_roots() {
+    let dir = tree(&[
+        ("a.json", r#"{ "plugins": ["a"] }"#),
+        ("b.json", r#"{ "name": "x", "plugins": ["b"] }"#),
+    ]);
+
+    let tenants = read_namespaced::<Tenant, _, _, _, _>(
+        [
+            ("x", dir.path().join("a.json")),
+            ("x", dir.path().join("b.json")),
+        ],
+        Json::default(),
+    )
+    .unwrap();
+
+    assert_eq!(tenants.len(), 1);
+    assert_eq!(tenants["x"].plugins, ["a", "b"]);
+}
+
+#[test]
+fn test_file_namespaced_with() {
+    let dir = tree(&[
+        (
+            "acme.json",
+            r#"{ "include": ["base.json"], "name": "acme", "plugins": ["a"] }"#,
+        ),
+        ("base.json", r#"{ "plugins": ["base"] }"#),
+    ]);
+
+    let mut file = File::json().with_import_key("include");
+    file.set_import_order(ImportOrder::ImportsFirst);
+
+    let tenants =
+        read_namespaced_with::<Tenant, _, _, _, _>([("acme", dir.path().join("acme.json"))], file)
+            .unwrap();
+
+    assert_eq!(tenants["acme"].plugins, ["base", "a"]);
+}
+
+#[test]
+fn test_file_namespaced_with_options() {
+    let dir = tenant_tree();
+
+    // The hook stops each namespace on its own.
+    let file = File::json().on_merge(|x: &Tenant| match x.name {
+        Some(_) => ControlFlow::Break(()),
+        None => ControlFlow::Continue(()),
+    });
+
+    let tenants = read_namespaced_with::<Tenant, _, _, _, _>(
+        [
+            ("acme", dir.path().join("acme.json")),
+            ("globex", dir.path().join("globex.json")),
+        ],
+        file,
+    )
+    .unwrap();
+
+    assert_eq!(tenants["acme"].plugins, ["a"]);
+    assert!(tenants["globex"].plugins.is_empty());
+
+    // Only the modules of `acme` are in the manifest.
+    let mut file = File::<Tenant, _>::json().with_record_manifest(true);
+    file.read(dir.path().join("acme.json")).unwrap();
+    let file = File::json().with_manifest(file.record_manifest());
+
+    let err = read_namespaced_with::<Tenant, _, _, _, _>(
+        [
+            ("acme", dir.path().join("acme.json")),
+            ("globex", dir.path().join("globex.json")),
+        ],
+        file,
+    )
+    .unwrap_err();
+
+    assert!(err.get("acme").unwrap().is_ok(), "acme should succeed");
+    let (name, e) = err.errors().next().unwrap();
+    assert_eq!(name, "globex");
+    assert!(
+        e.kind
+            .to_string()
+            .ends_with("globex.json' is not in the manifest"),
+        "{e}"
+    );
+}
+
+#[test]
+fn test_file_saturation() {
+    let dir = tree(&[
+        (
+            "a.json",
+            r#"{ "imports": ["b.json", "c.json", "d.json"], "plugins": ["a"] }"#,
+        ),
+        ("b.json", r#"{ "imports": ["c.json"], "plugins": ["b"] }"#),
+        ("c.json", r#"{ "plugins": ["c"] }"#),
+        ("d.json", r#"{ "plugins": ["d"] }"#),
+        ("e.json", r#"{ "plugins": ["e"] }"#),
+    ]);
+
+    let mut format = Counting::default();
+
+    let mut merged = 0;
+    let mut file = File::new(&mut format).on_merge(move |_: &Tenant| {
+        merged += 1;
+        if merged >= 2 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+
+    file.read(dir.path().join("a.json")).unwrap();
+    assert!(file.is_saturated());
+    // The import of `b.json` and the remaining imports of `a.json`.
+    assert_eq!(file.skipped_modules(), 3);
+
+    file.read(dir.path().join("e.json")).unwrap();
+    assert_eq!(file.skipped_modules(), 4);
+
+    let x = file.finish().unwrap();
+    assert_eq!(x.plugins, ["a", "b"]);
+    assert_eq!(format.reads.len(), 2, "only 2 modules should be parsed");
+}
+
+#[derive(Debug, Deserialize, Merge)]
+#[merge(resolve)]
+struct Resolved {
+    #[merge(resolve)]
+    jobs: Option<Candidates<u32>>,
+}
+
+#[test]
+fn test_file_read_resolved() {
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["a.json", "b.json"], "jobs": 4 }"#,
+        ),
+        ("a.json", r#"{ "jobs": { "value": 8, "priority": 100 } }"#),
+        ("b.json", r#"{ "jobs": { "value": 2, "priority": 200 } }"#),
+    ]);
+
+    let x: Resolved = File::json()
+        .read_resolved(dir.path().join("main.json"))
+        .unwrap();
+
+    let jobs = x.jobs.unwrap();
+    assert_eq!(jobs.resolved(), Some(&8));
+    assert!(
+        jobs.candidates()[0].origin().unwrap().ends_with("a.json"),
+        "the origin should be the module the value came from"
+    );
+}
+
+#[test]
+fn test_file_read_resolved_tie() {
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["a.json", "b.json", "c.json"], "jobs": 4 }"#,
+        ),
+        ("a.json", r#"{ "jobs": { "value": 8, "priority": 100 } }"#),
+        ("b.json", r#"{ "jobs": { "value": 2, "priority": 100 } }"#),
+        ("c.json", r#"{ "jobs": { "value": 1, "priority": 100 } }"#),
+    ]);
+
+    let err = File::<Resolved, _>::json()
+        .read_resolved(dir.path().join("main.json"))
+        .unwrap_err();
+
+    let msg = err.to_string();
+    assert!(
+        msg.starts_with("3 values have the same priority 100, in "),
+        "unexpected error: {msg}"
+    );
+    assert!(
+        msg.contains("while evaluating 'jobs'"),
+        "unexpected error: {msg}"
+    );
+    for x in ["a.json", "b.json", "c.json"] {
+        assert!(msg.contains(x), "{x} should be listed: {msg}");
+    }
+}
+
+#[derive(Debug, Deserialize, Merge)]
+struct Layered {
+    #[serde(default)]
+    items: Vec<i32>,
+    value: Option<Overridable<i32>>,
+}
+
+#[test]
+fn test_file_dir_import_order() {
+    let dir = tree(&[
+        (
+            "root.json",
+            r#"{ "imports": [{ "dir": "conf.d" }], "items": [0] }"#,
+        ),
+        ("conf.d/50-site.json", r#"{ "items": [50] }"#),
+        ("conf.d/10-base.json", r#"{ "items": [10] }"#),
+        ("conf.d/99-local.json", r#"{ "items": [99] }"#),
+        ("conf.d/B.json", r#"{ "items": [2] }"#),
+        ("conf.d/a.json", r#"{ "items": [3] }"#),
+        ("conf.d/README.md", "not a module"),
+        ("conf.d/sub/20-nested.json", r#"{ "items": [20] }"#),
+    ]);
+
+    let x: Layered = json(dir.path().join("root.json")).unwrap();
+    assert_eq!(x.items, [0, 10, 50, 99, 2, 3]);
+}
+
+#[test]
+fn test_file_dir_import_layered() {
+    let files = |layered: bool| {
+        let root = format!(r#"{{ "imports": [{{ "dir": "conf.d", "layered": {layered} }}] }}"#);
+        tree(&[
+            ("root.json", &root),
+            ("conf.d/10-base.json", r#"{ "value": 1 }"#),
+            ("conf.d/50-site.json", r#"{ "value": 2 }"#),
+        ])
+    };
+
+    let read = |path: PathBuf| {
+        let mut file = File::json().on_layer(|x: &mut Layered, offset| {
+            if let Some(value) = &mut x.value {
+                value.shift_default(offset);
+            }
+        });
+        file.read(path).map(|()| file.finish().unwrap())
+    };
+
+    let dir = files(false);
+    let err = read(dir.path().join("root.json")).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Collision);
+
+    let dir = files(true);
+    let x = read(dir.path().join("root.json")).unwrap();
+    assert_eq!(x.value.as_deref(), Some(&2));
+
+    // Without the hook, the priorities are left alone.
+    let err = json::<Layered>(dir.path().join("root.json")).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Collision);
+
+    // Explicit priorities are not shifted.
+    fs::write(
+        dir.path().join("conf.d/10-base.json"),
+        r#"{ "value": { "value": 1, "priority": 100 } }"#,
+    )
+    .unwrap();
+    let x = read(dir.path().join("root.json")).unwrap();
+    assert_eq!(x.value.as_deref(), Some(&1));
+}
+
+#[test]
+fn test_file_dir_import_recursive() {
+    let dir = tree(&[
+        (
+            "root.json",
+            r#"{ "imports": [{ "dir": "conf.d", "recursive": true }] }"#,
+        ),
+        ("conf.d/10-base.json", r#"{ "items": [10] }"#),
+        ("conf.d/30-site.json", r#"{ "items": [30] }"#),
+        ("conf.d/20/10-nested.json", r#"{ "items": [2010] }"#),
+        ("conf.d/20/deeper/x.json", r#"{ "items": [2099] }"#),
+    ]);
+
+    let x: Layered = json(dir.path().join("root.json")).unwrap();
+    assert_eq!(x.items, [10, 2010, 2099, 30]);
+}
+
+#[test]
+fn test_file_dir_import_missing() {
+    let dir = tree(&[
+        ("required.json", r#"{ "imports": [{ "dir": "conf.d" }] }"#),
+        (
+            "optional.json",
+            r#"{ "imports": [{ "dir": "conf.d", "optional": true }], "items": [1] }"#,
+        ),
+        (
+            "empty.json",
+            r#"{ "imports": [{ "dir": "empty" }], "items": [2] }"#,
+        ),
+    ]);
+    fs::create_dir(dir.path().join("empty")).unwrap();
+
+    let err = json::<Layered>(dir.path().join("required.json")).unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::NotFound));
+
+    let x: Layered = json(dir.path().join("optional.json")).unwrap();
+    assert_eq!(x.items, [1]);
+
+    let x: Layered = json(dir.path().join("empty.json")).unwrap();
+    assert_eq!(x.items, [2]);
+}
+
+#[test]
+fn test_file_dir_import_trace() {
+    let dir = tree(&[
+        ("root.json", r#"{ "imports": [{ "dir": "conf.d" }] }"#),
+        ("conf.d/50-site.json", r#"{ "items": "oops" }"#),
+    ]);
+
+    let err = json::<Layered>(dir.path().join("root.json")).unwrap_err();
+    let modules: Vec<String> = err.modules.iter().map(|x| x.to_string()).collect();
+
+    let root = fs::canonicalize(dir.path().join("root.json")).unwrap();
+    let site = fs::canonicalize(dir.path().join("conf.d/50-site.json")).unwrap();
+    assert_eq!(
+        modules,
+        [
+            root.display().to_string(),
+            format!("{} (via dir import in {})", site.display(), root.display()),
+        ]
+    );
+}
+
+#[test]
+fn test_file_read_collecting() {
+    #[derive(Debug, Clone, Deserialize, Merge)]
+    struct Config {
+        port: Option<i32>,
+        name: Option<String>,
+        items: Option<Vec<i32>>,
+    }
+
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["a.json", "b.json", "c.json"], "port": 1, "items": [1] }"#,
+        ),
+        ("a.json", r#"{ "port": 2 }"#),
+        ("b.json", r#"{ "imports": ["d.json"], "items": [2] }"#),
+        ("c.json", r#"{ "name": "c", "items": [3], "port": 3 }"#),
+        ("d.json", r#"{ "name": "d" }"#),
+    ]);
+
+    let mut file = File::<Config, _>::json();
+    let errors = file
+        .read_collecting(dir.path().join("main.json"))
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors.errors().iter().all(|e| e.kind.is_collision()));
+
+    let main = fs::canonicalize(dir.path().join("main.json")).unwrap();
+    let modules: Vec<Vec<String>> = errors
+        .errors()
+        .iter()
+        .map(|e| e.modules.iter().map(|x| x.to_string()).collect())
+        .collect();
+
+    for (modules, name) in modules.iter().zip(["a.json", "c.json"]) {
+        let module = fs::canonicalize(dir.path().join(name)).unwrap();
+        assert_eq!(
+            *modules,
+            [main.display().to_string(), module.display().to_string()]
+        );
+    }
+
+    // The failing modules were dropped as a whole.
+    let x = file.finish().unwrap();
+    assert_eq!(x.port, Some(1));
+    assert_eq!(x.name.as_deref(), Some("d"));
+    assert_eq!(x.items.unwrap(), [1, 2]);
+}
+
+#[test]
+fn test_file_collision_blame() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Config {
+        port: Option<i32>,
+        items: Option<Vec<i32>>,
+    }
+
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["a.json", "b.json", "c.json"] }"#,
+        ),
+        ("a.json", r#"{ "port": 1 }"#),
+        ("b.json", r#"{ "items": [1] }"#),
+        ("c.json", r#"{ "port": 2, "items": [2] }"#),
+    ]);
+
+    let main = fs::canonicalize(dir.path().join("main.json")).unwrap();
+    let a = fs::canonicalize(dir.path().join("a.json")).unwrap();
+    let c = fs::canonicalize(dir.path().join("c.json")).unwrap();
+
+    let mut file = File::<Config, _>::json();
+    let err = file.read(dir.path().join("main.json")).unwrap_err();
+    assert!(err.kind.is_collision());
+    assert_eq!(
+        err.note().unwrap().to_string(),
+        format!("also defined in {}", a.display())
+    );
+
+    // The trace starts at the module that failed to merge.
+    let modules: Vec<String> = err.modules.iter().rev().map(|x| x.to_string()).collect();
+    assert_eq!(
+        modules,
+        [c.display().to_string(), main.display().to_string()]
+    );
+
+    let message = err.to_string();
+    assert!(message.contains("a.json"), "{message}");
+    assert!(message.contains("c.json"), "{message}");
+}
+
+#[test]
+fn test_file_collision_blame_map() {
+    let dir = tree(&[
+        ("main.json", r#"{ "imports": ["a.json", "b.json"] }"#),
+        ("a.json", r#"{ "port": 1 }"#),
+        ("b.json", r#"{ "port": 2 }"#),
+    ]);
+
+    let a = fs::canonicalize(dir.path().join("a.json")).unwrap();
+
+    let mut file = File::<std::collections::HashMap<String, i32>, _>::json();
+    let err = file.read(dir.path().join("main.json")).unwrap_err();
+    assert!(err.kind.is_collision());
+    assert_eq!(
+        err.note().unwrap().to_string(),
+        format!("also defined in {}", a.display())
+    );
+}
+
+#[test]
+fn test_file_finish_lossy() {
+    #[derive(Debug, Clone, Deserialize, Merge)]
+    struct Config {
+        port: Option<i32>,
+        items: Option<Vec<i32>>,
+    }
+
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["a.json", "missing.json", "b.json"], "port": 1 }"#,
+        ),
+        ("a.json", r#"{ "port": 2, "items": [1] }"#),
+        ("b.json", r#"{ "items": [2] }"#),
+        ("extra.json", r#"{ "items": [3] }"#),
+    ]);
+
+    let mut file = File::<Config, _>::json();
+    file.read_lossy(dir.path().join("main.json"));
+    file.read_lossy(dir.path().join("extra.json"));
+
+    let (value, errors) = file.finish_lossy();
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].kind.is_collision());
+    assert_eq!(errors[1].io_kind(), Some(std::io::ErrorKind::NotFound));
+
+    // `a.json` was dropped as a whole, but `b.json` was still read.
+    let value = value.unwrap();
+    assert_eq!(value.port, Some(1));
+    assert_eq!(value.items.unwrap(), [2, 3]);
+}
+
+#[test]
+fn test_file_read_lossy_imports() {
+    #[derive(Debug, Clone, Deserialize, Merge)]
+    struct Config {
+        items: Vec<i32>,
+    }
+
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["broken.json", "a.json"], "items": [0] }"#,
+        ),
+        ("broken.json", r#"{ "imports": ["b.json"], "items": "#),
+        ("a.json", r#"{ "imports": ["b.json"], "items": [1] }"#),
+        ("b.json", r#"{ "items": [2] }"#),
+    ]);
+
+    let mut file = File::<Config, _>::json();
+    file.read_lossy(dir.path().join("main.json"));
+
+    let (value, errors) = file.finish_lossy();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+
+    let main = fs::canonicalize(dir.path().join("main.json")).unwrap();
+    let broken = fs::canonicalize(dir.path().join("broken.json")).unwrap();
+    let modules: Vec<String> = errors[0].modules.iter().map(|x| x.to_string()).collect();
+    assert_eq!(
+        modules,
+        [main.display().to_string(), broken.display().to_string()]
+    );
+
+    // The import after the broken one was read, along with its own imports.
+    assert_eq!(value.unwrap().items, [0, 1, 2]);
+}
+
+#[test]
+fn test_file_speculate_lossy() {
+    #[derive(Debug, Clone, Deserialize, Merge)]
+    struct Config {
+        port: Option<i32>,
+    }
+
+    let dir = tree(&[
+        ("a.json", r#"{ "port": 1 }"#),
+        ("b.json", r#"{ "port": 2 }"#),
+    ]);
+
+    let mut file = File::<Config, _>::json();
+    file.read(dir.path().join("a.json")).unwrap();
+
+    file.speculate(|file| file.read_lossy(dir.path().join("b.json")));
+
+    // The error kept while speculating was forgotten with everything else.
+    let (value, errors) = file.finish_lossy();
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(value.unwrap().port, Some(1));
+}
+
+#[test]
+fn test_file_read_collecting_panic() {
+    #[derive(Debug, Clone, Deserialize, Merge)]
+    struct Config {
+        port: Option<i32>,
+    }
+
+    let dir = tree(&[
+        ("a.json", r#"{ "port": 1 }"#),
+        ("b.json", r#"{ "port": 2 }"#),
+    ]);
+
+    let mut panicking = true;
+    let mut file = File::<Config, _>::json().on_merge(move |_: &Config| {
+        if std::mem::take(&mut panicking) {
+            panic!("oops");
+        }
+        ControlFlow::Continue(())
+    });
+
+    let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        file.read_collecting(dir.path().join("a.json"))
+    }));
+    assert!(r.is_err());
+
+    // The panic stopped collecting, so merge errors are returned again.
+    assert_eq!(file.value().unwrap().port, Some(1));
+    let err = file.read(dir.path().join("b.json")).unwrap_err();
+    assert!(err.kind.is_collision());
+}
+
+#[test]
+fn test_file_max_depth() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let dir = tree(&[
+        ("main.json", r#"{ "imports": ["a.json"], "items": [0] }"#),
+        ("a.json", r#"{ "imports": ["b.json"], "items": [1] }"#),
+        ("b.json", r#"{ "imports": ["c.json"], "items": [2] }"#),
+        ("c.json", r#"{ "items": [3] }"#),
+    ]);
+
+    let mut file = File::<Items, _>::json().with_max_depth(Some(2));
+    let err = file.read(dir.path().join("main.json")).unwrap_err();
+    assert_eq!(err.kind.to_string(), "import depth exceeded");
+    assert_eq!(err.note().unwrap().to_string(), "the limit is 2");
+
+    let c = fs::canonicalize(dir.path().join("c.json")).unwrap();
+    let modules: Vec<String> = err.modules.iter().map(|x| x.to_string()).collect();
+    assert_eq!(modules.len(), 4);
+    assert_eq!(modules[3], c.display().to_string());
+
+    let mut file = File::<Items, _>::json().with_max_depth(Some(3));
+    file.read(dir.path().join("main.json")).unwrap();
+    assert_eq!(file.finish().unwrap().items, &[0, 1, 2, 3]);
+}
+
+#[test]
+fn test_file_evaluated() {
+    #[derive(Debug, Clone, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["a.json", "b.json"], "items": [0] }"#,
+        ),
+        ("a.json", r#"{ "imports": ["c.json"], "items": [1] }"#),
+        ("b.json", r#"{ "items": [2] }"#),
+        ("c.json", r#"{ "items": [3] }"#),
+        ("extra.json", r#"{ "items": [4] }"#),
+    ]);
+
+    let canonical = |name: &str| fs::canonicalize(dir.path().join(name)).unwrap();
+
+    let mut file = File::<Items, _>::json();
+    file.read(dir.path().join("main.json")).unwrap();
+
+    let evaluated: Vec<PathBuf> = file.evaluated().map(Path::to_path_buf).collect();
+    let expected: Vec<PathBuf> = ["main.json", "a.json", "c.json", "b.json"]
+        .into_iter()
+        .map(canonical)
+        .collect();
+    assert_eq!(evaluated, expected);
+
+    file.speculate(|file| {
+        file.read(dir.path().join("extra.json")).unwrap();
+        assert_eq!(file.evaluated().len(), 5);
+    });
+    assert_eq!(file.evaluated().len(), 4);
+}
+
+#[test]
+fn test_file_import_order() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Config {
+        items: Vec<i32>,
+        port: Option<Overridable<u16>>,
+    }
+
+    let dir = tree(&[
+        (
+            "main.json",
+            r#"{ "imports": ["a.json", "b.json"], "items": [0] }"#,
+        ),
+        ("a.json", r#"{ "imports": ["c.json"], "items": [1] }"#),
+        ("b.json", r#"{ "items": [2] }"#),
+        ("c.json", r#"{ "items": [3] }"#),
+        (
+            "port.json",
+            r#"{ "imports": ["port-a.json"], "items": [], "port": 1 }"#,
+        ),
+        ("port-a.json", r#"{ "items": [], "port": 2 }"#),
+    ]);
+
+    let read = |order, name: &str| {
+        let mut file = File::<Config, _>::json();
+        file.set_import_order(order);
+        file.read(dir.path().join(name))
+            .map(|()| file.finish().unwrap())
+    };
+
+    let x = read(ImportOrder::ImporterFirst, "main.json").unwrap();
+    assert_eq!(x.items, [0, 1, 3, 2]);
+
+    let x = read(ImportOrder::ImportsFirst, "main.json").unwrap();
+    assert_eq!(x.items, [3, 1, 2, 0]);
+
+    // Priorities still decide, so equal priorities collide in either order.
+    for order in [ImportOrder::ImporterFirst, ImportOrder::ImportsFirst] {
+        let err = read(order, "port.json").unwrap_err();
+        assert!(err.kind.is_collision());
+    }
+}
+
+#[test]
+fn test_file_import_order_failed_merge() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Config {
+        port: Option<u16>,
+    }
+
+    let dir = tree(&[
+        ("base.json", r#"{ "port": 1 }"#),
+        ("bad.json", r#"{ "port": 2 }"#),
+        ("main.json", r#"{ "imports": ["bad.json"] }"#),
+    ]);
+
+    for order in [ImportOrder::ImporterFirst, ImportOrder::ImportsFirst] {
+        let mut file = File::<Config, _>::json();
+        file.set_import_order(order);
+        file.read(dir.path().join("base.json")).unwrap();
+
+        let err = file.read(dir.path().join("bad.json")).unwrap_err();
+        assert!(err.kind.is_collision(), "{order:?}: {err}");
+        assert_eq!(file.evaluated().len(), 1, "{order:?}");
+
+        // `bad.json` was never merged, so importing it again is not skipped.
+        let err = file.read(dir.path().join("main.json")).unwrap_err();
+        assert!(err.kind.is_collision(), "{order:?}: {err}");
+    }
+}
+
+#[test]
+fn test_file_read_str() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let dir = tree(&[("base.json", r#"{ "items": [2] }"#)]);
+
+    let mut file = File::<Items, _>::json();
+    file.set_base_dir(Some(dir.path()));
+
+    file.read_str("inline", r#"{ "imports": ["base.json"], "items": [1] }"#)
+        .unwrap();
+    assert_eq!(file.evaluated().len(), 1);
+
+    // Modules are identified by their name.
+    file.read_str("inline", r#"{ "items": [3] }"#).unwrap();
+
+    let err = file.read_str("broken", r#"{ "items": 3 }"#).unwrap_err();
+    let modules: Vec<String> = err.modules.iter().map(|x| x.to_string()).collect();
+    assert_eq!(modules, ["broken"]);
+
+    assert_eq!(file.finish().unwrap().items, &[1, 2]);
+
+    // Formats that only read files.
+    let mut format = Counting::default();
+    let mut file = File::<Items, _>::new(&mut format);
+    let err = file.read_str("inline", r#"{ "items": [1] }"#).unwrap_err();
+    assert!(err.kind.is_custom());
+}
+
+#[test]
+fn test_file_location() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let mut file = File::<Items, _>::json();
+    let err = file
+        .read_str("inline.json", "{\n  \"items\": [1,\n  x]\n}")
+        .unwrap_err();
+    assert_eq!(err.kind.to_string(), "expected value");
+    assert_eq!(err.note().unwrap().to_string(), "at line 3, column 3");
+}
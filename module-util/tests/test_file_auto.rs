@@ -0,0 +1,49 @@
+#![allow(missing_docs)]
+
+use module::Merge;
+use module_util::file::{AnyFormat, File, Format, read_auto};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+fn path(p: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(p)
+}
+
+#[derive(Debug, Deserialize, Merge)]
+struct Simple {
+    key: Option<String>,
+    items: Option<Vec<i32>>,
+}
+
+#[test]
+fn test_file_auto_mixed() {
+    let x: Simple = read_auto(path("auto/main.toml")).unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([1, 2, 3].as_slice()));
+}
+
+#[test]
+fn test_file_auto_unknown_extension() {
+    let err = read_auto::<Simple>(path("auto/unknown.toml")).unwrap_err();
+    let msg = err.kind.to_string();
+    assert!(msg.contains("config.xml"), "{msg}");
+    assert!(msg.contains("json, toml"), "{msg}");
+}
+
+#[test]
+fn test_file_auto_read_str() {
+    let mut file = File::<Simple, _>::auto();
+    file.read_str("inline.json", r#"{ "items": [1] }"#).unwrap();
+    file.read_str("inline.toml", "items = [2]").unwrap();
+    assert!(file.read_str("inline", "items = [3]").is_err());
+
+    let x = file.finish().unwrap();
+    assert_eq!(x.items.as_deref(), Some([1, 2].as_slice()));
+}
+
+#[test]
+fn test_file_auto_extensions() {
+    let format = AnyFormat::default();
+    assert!(format.extensions().contains(&"json"));
+    assert!(format.extensions().contains(&"toml"));
+}
@@ -0,0 +1,94 @@
+#![allow(missing_docs)]
+
+use module::Merge;
+use module_util::file::{File, Ini, ini};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+fn path(p: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(p)
+}
+
+#[derive(Debug, Deserialize, Merge)]
+struct Simple {
+    key: Option<String>,
+    items: Option<Vec<i32>>,
+    server: Option<Server>,
+}
+
+#[derive(Debug, Deserialize, Merge)]
+struct Server {
+    name: Option<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    tls: Option<Tls>,
+}
+
+#[derive(Debug, Deserialize, Merge)]
+struct Tls {
+    ciphers: Option<Vec<String>>,
+}
+
+#[test]
+fn test_file_format_ini_simple() {
+    let x: Simple = ini(path("ini/simple1.ini")).unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([1, 3, 6, 0].as_slice()));
+
+    let server = x.server.unwrap();
+    assert_eq!(server.name.as_deref(), Some("main"));
+    assert_eq!(server.host.as_deref(), Some("localhost, or not"));
+    assert_eq!(server.port, Some(8080));
+
+    let ciphers = server.tls.unwrap().ciphers.unwrap();
+    assert_eq!(ciphers, ["a, b", "c"]);
+}
+
+#[test]
+fn test_file_format_ini_syntax_error() {
+    let err = ini::<Simple>(path("ini/invalid.ini")).unwrap_err();
+    assert_eq!(err.kind.to_string(), "expected `]`");
+    assert_eq!(err.note().unwrap().to_string(), "at line 3, column 10");
+
+    let mut file = File::<Simple, _>::ini();
+    let err = file
+        .read_str("inline.ini", "[server]\nport = 1\nport = 2\n")
+        .unwrap_err();
+    assert_eq!(err.kind.to_string(), "duplicate key `port`");
+    assert_eq!(err.note().unwrap().to_string(), "at line 3, column 1");
+}
+
+#[test]
+fn test_file_format_ini_infer_types() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(untagged)]
+    enum Any {
+        Bool(bool),
+        Int(i64),
+        Str(String),
+    }
+
+    #[derive(Deserialize, Merge)]
+    struct Untyped {
+        #[merge(skip)]
+        #[serde(flatten)]
+        rest: HashMap<String, Any>,
+    }
+
+    let data = "a = true\nb = 42\nc = \"42\"\nd = hello\n";
+
+    let mut file = File::<Untyped, _>::ini();
+    file.read_str("inline.ini", data).unwrap();
+    let x = file.finish().unwrap().rest;
+    assert_eq!(x["a"], Any::Bool(true));
+    assert_eq!(x["b"], Any::Int(42));
+    assert_eq!(x["c"], Any::Str("42".into()));
+    assert_eq!(x["d"], Any::Str("hello".into()));
+
+    let mut file = File::<Untyped, _>::ini_with(Ini::builder().infer_types(false).build());
+    file.read_str("inline.ini", data).unwrap();
+    let x = file.finish().unwrap().rest;
+    assert_eq!(x["a"], Any::Str("true".into()));
+    assert_eq!(x["b"], Any::Str("42".into()));
+}
@@ -0,0 +1,64 @@
+#![allow(missing_docs)]
+
+use module::Merge;
+use module_util::file::{File, Json5, json, json5};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+fn path(p: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(p)
+}
+
+#[derive(Debug, Deserialize, Merge)]
+struct Simple {
+    key: Option<String>,
+    items: Option<Vec<i32>>,
+}
+
+#[test]
+fn test_file_format_json5_simple() {
+    let x: Simple = json5(path("json5/simple1.json5")).unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([1, 3, 6, 0].as_slice()));
+}
+
+#[test]
+fn test_file_format_json5_not_json() {
+    // Strict JSON does not allow comments, trailing commas or unquoted keys.
+    assert!(json::<Simple>(path("json5/simple1.json5")).is_err());
+    assert!(json::<Simple>(path("json5/simple2.json5")).is_err());
+}
+
+#[test]
+fn test_file_format_json5_max_size() {
+    let json5 = Json5::builder().max_size(Some(8)).build();
+    let mut file = File::<Simple, _>::json5_with(json5);
+    let err = file.read(path("json5/simple2.json5")).unwrap_err();
+    assert!(
+        err.kind
+            .to_string()
+            .contains("exceeds the limit of 8 bytes")
+    );
+
+    let json5 = Json5::builder().max_size(Some(1024)).build();
+    let mut file = File::<Simple, _>::json5_with(json5);
+    file.read(path("json5/simple2.json5")).unwrap();
+}
+
+#[test]
+fn test_file_format_json5_location() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let mut file = File::<Items, _>::json5();
+    let err = file
+        .read_str("inline.json5", "{\n  items: [1,\n  x]\n}")
+        .unwrap_err();
+    assert_eq!(
+        err.kind.to_string(),
+        "expected array, boolean, null, number, object, or string"
+    );
+    assert_eq!(err.note().unwrap().to_string(), "at line 3, column 3");
+}
@@ -0,0 +1,61 @@
+#![allow(missing_docs)]
+
+use module::Merge;
+use module::types::Last;
+use module_util::file::{File, Ron, ron};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+fn path(p: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(p)
+}
+
+#[test]
+fn test_file_format_ron_simple() {
+    #[derive(Deserialize, Merge)]
+    struct Simple {
+        key: Option<String>,
+        items: Option<Vec<i32>>,
+    }
+
+    let x: Simple = ron(path("ron/simple1.ron")).unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([1, 3, 6, 0].as_slice()));
+}
+
+type Nested = Vec<Vec<Vec<Vec<i32>>>>;
+
+#[derive(Debug, Deserialize, Merge)]
+struct Deep {
+    key: Option<String>,
+    items: Option<Vec<i32>>,
+    nested: Option<Last<Nested>>,
+}
+
+#[test]
+fn test_file_format_ron_recursion_limit() {
+    let x: Deep = ron(path("ron/deep.ron")).unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([2].as_slice()));
+    assert_eq!(x.nested.unwrap().0, [[[[1]]]]);
+
+    let ron = Ron::builder().recursion_limit(Some(4)).build();
+    let mut file = File::<Deep, _>::ron_with(ron);
+    let err = file.read(path("ron/deep.ron")).unwrap_err();
+    assert!(err.kind.to_string().contains("recursion limit"), "{err}");
+}
+
+#[test]
+fn test_file_format_ron_location() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let mut file = File::<Items, _>::ron();
+    let err = file
+        .read_str("inline.ron", "{\n  items: [1,\n  2,,]\n}")
+        .unwrap_err();
+    assert_eq!(err.kind.to_string(), "Expected integer");
+    assert_eq!(err.note().unwrap().to_string(), "at line 3, column 5");
+}
@@ -1,7 +1,7 @@
 #![allow(missing_docs)]
 
 use module::Merge;
-use module_util::file::toml;
+use module_util::file::{File, Toml, toml};
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
 
@@ -21,3 +21,78 @@ fn test_file_format_toml_simple() {
     assert_eq!(x.key.as_deref(), Some("424242"));
     assert_eq!(x.items.as_deref(), Some([1, 3, 6, 0].as_slice()));
 }
+
+#[test]
+fn test_file_format_toml_manifest() {
+    #[derive(Deserialize, Merge)]
+    struct Simple {
+        key: Option<String>,
+        items: Option<Vec<i32>>,
+    }
+
+    let mut file = File::<Simple, _>::toml().with_record_manifest(true);
+    file.read(path("toml/simple1.toml")).unwrap();
+    assert_eq!(file.record_manifest().entries().len(), 2);
+
+    let x = file.finish().unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([1, 3, 6, 0].as_slice()));
+}
+
+#[test]
+fn test_file_format_toml_spans() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Simple {
+        key: Option<String>,
+        items: Option<Vec<i32>>,
+    }
+
+    let err = toml::<Simple>(path("toml/invalid.toml")).unwrap_err();
+    assert_eq!(err.kind.to_string(), "invalid array, expected `]`");
+    assert_eq!(err.note().unwrap().to_string(), "at line 3, column 3");
+
+    let toml = Toml::builder().spans(true).build();
+    let mut file = File::<Simple, _>::toml_with(toml);
+    let err = file.read(path("toml/invalid.toml")).unwrap_err();
+    assert!(err.kind.to_string().contains('\n'));
+    assert_eq!(err.note().unwrap().to_string(), "at line 3, column 3");
+}
+
+#[test]
+fn test_file_format_toml_optional_import() {
+    #[derive(Deserialize, Merge)]
+    struct Simple {
+        key: Option<String>,
+        items: Option<Vec<i32>>,
+    }
+
+    // `local.toml` does not exist, but `simple2.toml` is still read.
+    let x: Simple = toml(path("toml/optional_import.toml")).unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([3, 6, 0].as_slice()));
+}
+
+#[test]
+#[cfg(feature = "glob")]
+fn test_file_format_toml_glob() {
+    #[derive(Deserialize, Merge)]
+    struct Simple {
+        key: Option<String>,
+        items: Option<Vec<i32>>,
+    }
+
+    let mut file = File::<Simple, _>::toml().with_glob(true);
+    file.read(path("toml/glob.toml")).unwrap();
+
+    // `10-first.toml` is imported directly too, but only read once.
+    let evaluated: Vec<_> = file.evaluated().filter_map(|x| x.file_name()).collect();
+    assert_eq!(evaluated, ["glob.toml", "10-first.toml", "20-second.toml"]);
+
+    let x = file.finish().unwrap();
+    assert_eq!(x.key.as_deref(), Some("424242"));
+    assert_eq!(x.items.as_deref(), Some([1, 2, 3].as_slice()));
+
+    // Without globs, the pattern is a path that does not exist.
+    let mut file = File::<Simple, _>::toml();
+    assert!(file.read(path("toml/glob.toml")).is_err());
+}
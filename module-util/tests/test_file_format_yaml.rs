@@ -1,8 +1,10 @@
 #![allow(missing_docs)]
 
 use module::Merge;
-use module_util::file::yaml;
+use module::types::Last;
+use module_util::file::{File, Yaml, yaml};
 use serde::Deserialize;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 fn path(p: &str) -> PathBuf {
@@ -21,3 +23,87 @@ fn test_file_format_yaml_simple() {
     assert_eq!(x.key.as_deref(), Some("424242"));
     assert_eq!(x.items.as_deref(), Some([1, 3, 6, 0].as_slice()));
 }
+
+#[derive(Debug, Deserialize, Merge)]
+struct Deep {
+    key: Option<String>,
+    nested: Option<Last<serde_yaml::Value>>,
+}
+
+#[test]
+fn test_file_format_yaml_max_depth() {
+    let module = fs::canonicalize(path("yaml/deep.yaml")).unwrap();
+
+    let yaml = Yaml::builder().max_depth(Some(4)).build();
+    let mut file = File::<Deep, _>::yaml_with(yaml);
+    let err = file.read(&module).unwrap_err();
+
+    assert_eq!(
+        err.kind.to_string(),
+        "nested.a.b[0]: module exceeds the maximum nesting depth of 4"
+    );
+    let modules: Vec<String> = err.modules.iter().map(|x| x.to_string()).collect();
+    assert_eq!(modules, &[module.display().to_string()]);
+
+    let yaml = Yaml::builder().max_depth(Some(5)).build();
+    let mut file = File::<Deep, _>::yaml_with(yaml);
+    file.read(&module).unwrap();
+    assert_eq!(file.finish().unwrap().key.as_deref(), Some("424242"));
+}
+
+#[test]
+fn test_file_format_yaml_max_size() {
+    let yaml = Yaml::builder().max_size(Some(8)).build();
+    let mut file = File::<Deep, _>::yaml_with(yaml);
+    let err = file.read(path("yaml/deep.yaml")).unwrap_err();
+    assert!(
+        err.kind
+            .to_string()
+            .contains("exceeds the limit of 8 bytes")
+    );
+
+    let yaml = Yaml::builder().max_size(Some(1024)).build();
+    let mut file = File::<Deep, _>::yaml_with(yaml);
+    file.read(path("yaml/deep.yaml")).unwrap();
+}
+
+#[test]
+fn test_file_format_yaml_implicit_bools() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Flags {
+        enabled: Option<Last<bool>>,
+        name: Option<String>,
+    }
+
+    assert!(yaml::<Flags>(path("yaml/flags.yaml")).is_err());
+
+    let yaml = Yaml::builder().implicit_bools(true).build();
+    let mut file = File::<Flags, _>::yaml_with(yaml);
+    file.read(path("yaml/flags.yaml")).unwrap();
+
+    let x = file.finish().unwrap();
+    assert_eq!(x.enabled.map(|x| x.0), Some(false));
+    assert_eq!(x.name.as_deref(), Some("no"));
+
+    let mut file = File::<Flags, _>::yaml_with(yaml);
+    file.read_str("inline", "enabled: on").unwrap();
+    assert_eq!(file.finish().unwrap().enabled.map(|x| x.0), Some(true));
+}
+
+#[test]
+fn test_file_format_yaml_location() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Items {
+        items: Vec<i32>,
+    }
+
+    let mut file = File::<Items, _>::yaml();
+    let err = file
+        .read_str("inline.yaml", "items:\n  - 1\nother:\n  - [\n")
+        .unwrap_err();
+    assert_eq!(
+        err.kind.to_string(),
+        "did not find expected node content, while parsing a flow node"
+    );
+    assert_eq!(err.note().unwrap().to_string(), "at line 5, column 1");
+}
@@ -0,0 +1,121 @@
+#![allow(missing_docs)]
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use module::Merge;
+use module::types::Last;
+use module_util::file::{File, Toml};
+use serde::Deserialize;
+
+fn path(p: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(p)
+}
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct User {
+    name: Option<Last<String>>,
+    email: Option<Last<String>>,
+}
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct Credential {
+    helper: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct Include {
+    path: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize, Merge)]
+#[serde(default)]
+struct Config {
+    user: User,
+    credential: Credential,
+    #[serde(rename = "includeIf")]
+    include_if: HashMap<String, Include>,
+}
+
+/// Read git-style layers, processing the conditional includes of each layer
+/// before moving on to the next.
+fn read(gitdir: &Path) -> Config {
+    let dir = path("toml/layers");
+
+    let mut file = File::<Config, Toml>::toml();
+    let mut included = HashSet::new();
+
+    let mut found = Vec::new();
+    for layer in ["system.toml", "global.toml", "local.toml"] {
+        found.push(file.read_optional(dir.join(layer)).unwrap());
+
+        let Some(config) = file.value() else {
+            continue;
+        };
+
+        let pending: Vec<PathBuf> = config
+            .include_if
+            .iter()
+            .filter(|(condition, _)| {
+                let dir = condition.strip_prefix("gitdir:").unwrap();
+                gitdir.starts_with(dir)
+            })
+            .flat_map(|(_, x)| x.path.iter().cloned())
+            .filter(|x| included.insert(x.clone()))
+            .collect();
+
+        for p in pending {
+            file.read(dir.join(p)).unwrap();
+        }
+    }
+
+    assert_eq!(
+        found,
+        &[false, true, true],
+        "only the system layer should be missing"
+    );
+    file.finish().unwrap()
+}
+
+#[test]
+fn test_file_layers() {
+    let config = read(Path::new("/work/app"));
+
+    // `local.toml` overrides `global.toml` and its includes.
+    assert_eq!(
+        config.user.name.as_deref().map(|x| x.as_str()),
+        Some("J. Doe")
+    );
+    // The matching conditional include overrides the layer that included it.
+    assert_eq!(
+        config.user.email.as_deref().map(|x| x.as_str()),
+        Some("jane@work.example")
+    );
+    assert_eq!(config.credential.helper, &["store", "work-sso", "cache"]);
+}
+
+#[test]
+fn test_file_layers_no_match() {
+    let config = read(Path::new("/home/jane/app"));
+
+    assert_eq!(
+        config.user.email.as_deref().map(|x| x.as_str()),
+        Some("jane@home.example")
+    );
+    assert_eq!(config.credential.helper, &["store", "cache"]);
+}
+
+#[test]
+fn test_file_read_optional_missing_import() {
+    #[derive(Debug, Deserialize, Merge)]
+    struct Empty {}
+
+    let mut file = File::<Empty, Toml>::toml();
+    let err = file
+        .read_optional(path("toml/missing_import.toml"))
+        .unwrap_err();
+    assert!(!err.modules.is_empty());
+}
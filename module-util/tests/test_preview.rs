@@ -0,0 +1,280 @@
+#![allow(missing_docs)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use module::Merge;
+use module::merge::ErrorKind;
+use module_util::file::{File, ImportOrder, Json};
+use module_util::preview::{ChangeKind, Options, preview, preview_file, preview_with};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Merge)]
+struct Config {
+    port: Option<u16>,
+    hosts: Vec<String>,
+    name: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+fn current() -> Config {
+    Config {
+        port: Some(80),
+        hosts: vec!["a".into()],
+        name: None,
+        tags: Vec::new(),
+    }
+}
+
+/// Write `files` into a new temporary directory.
+fn tree(files: &[(&str, &str)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, contents) in files {
+        let p = dir.path().join(name);
+        fs::create_dir_all(p.parent().unwrap()).unwrap();
+        fs::write(p, contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_preview() {
+    let dir = tree(&[(
+        "candidate.json",
+        r#"{ "port": 8080, "hosts": ["b"], "name": "edge" }"#,
+    )]);
+
+    let current = current();
+    let preview = preview(&current, dir.path().join("candidate.json"), Json::default()).unwrap();
+
+    let changes: Vec<(&str, ChangeKind)> = preview
+        .changes()
+        .iter()
+        .map(|x| (x.path(), x.kind()))
+        .collect();
+    assert_eq!(
+        changes,
+        [("hosts", ChangeKind::Changed), ("name", ChangeKind::Added)]
+    );
+
+    let hosts = preview.change("hosts").unwrap();
+    assert_eq!(hosts.before(), Some(&json!(["a"])));
+    assert_eq!(hosts.after(), Some(&json!(["a", "b"])));
+    assert_eq!(hosts.module(), None);
+
+    let name = preview.change("name").unwrap();
+    assert_eq!(name.before(), None);
+    assert_eq!(name.after(), Some(&json!("edge")));
+
+    assert_eq!(preview.errors().len(), 1);
+    assert_eq!(preview.errors()[0].kind, ErrorKind::Collision);
+    assert_eq!(preview.errors()[0].value.to_string(), "'port'");
+
+    assert_eq!(preview.summary(), "1 value changed, 1 added, 1 collision");
+
+    // The current value is untouched and the colliding value is dropped.
+    assert_eq!(current, self::current());
+    assert_eq!(preview.value().port, Some(80));
+    assert_eq!(preview.value().hosts, ["a", "b"]);
+}
+
+#[test]
+fn test_preview_provenance() {
+    let dir = tree(&[
+        (
+            "candidate.json",
+            r#"{ "imports": ["extra.json"], "hosts": ["b"] }"#,
+        ),
+        ("extra.json", r#"{ "hosts": ["c"], "tags": ["x"] }"#),
+    ]);
+
+    let candidate = dir.path().join("candidate.json");
+    let extra = fs::canonicalize(dir.path().join("extra.json")).unwrap();
+
+    let preview = preview_with(
+        &current(),
+        &candidate,
+        Json::default(),
+        Options::default().provenance(true),
+    )
+    .unwrap();
+
+    assert!(preview.errors().is_empty());
+    assert_eq!(preview.summary(), "2 values changed, 0 added");
+    assert_eq!(preview.value().hosts, ["a", "b", "c"]);
+
+    // `hosts` was changed by both modules, `extra.json` was the last.
+    let module = |path: &str| preview.change(path).unwrap().module();
+    assert_eq!(module("hosts"), Some(extra.as_path()));
+    assert_eq!(module("tags"), Some(extra.as_path()));
+}
+
+#[test]
+fn test_preview_max_depth() {
+    let dir = tree(&[
+        (
+            "candidate.json",
+            r#"{ "imports": ["extra.json"], "hosts": ["b"] }"#,
+        ),
+        ("extra.json", r#"{ "hosts": ["c"] }"#),
+    ]);
+
+    let preview = preview_with(
+        &current(),
+        dir.path().join("candidate.json"),
+        Json::default(),
+        Options::default().max_depth(0),
+    )
+    .unwrap();
+
+    assert_eq!(preview.skipped_imports(), 1);
+    assert_eq!(preview.value().hosts, ["a", "b"]);
+}
+
+#[test]
+fn test_preview_errors() {
+    let dir = tree(&[
+        (
+            "candidate.json",
+            r#"{ "imports": ["missing.json"], "hosts": [], "name": "x" }"#,
+        ),
+        ("noop.json", r#"{ "hosts": [] }"#),
+    ]);
+
+    let x = preview(
+        &current(),
+        dir.path().join("candidate.json"),
+        Json::default(),
+    )
+    .unwrap();
+    assert_eq!(x.summary(), "0 values changed, 1 added, 1 error");
+    assert_eq!(x.errors()[0].io_kind(), Some(std::io::ErrorKind::NotFound));
+
+    let x = preview(&current(), dir.path().join("noop.json"), Json::default()).unwrap();
+    assert!(x.is_noop());
+
+    let err = preview(&current(), Path::new("/nonexistent.json"), Json::default()).unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::NotFound));
+}
+
+#[test]
+fn test_preview_prune_nested() {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Merge)]
+    struct Host {
+        port: Option<u16>,
+        name: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Merge)]
+    struct Hosts {
+        hosts: HashMap<String, Host>,
+    }
+
+    let dir = tree(&[(
+        "candidate.json",
+        r#"{ "hosts": { "a": { "port": 2, "name": "x" }, "b": { "port": 3 } } }"#,
+    )]);
+
+    let current = Hosts {
+        hosts: HashMap::from([(
+            "a".to_owned(),
+            Host {
+                port: Some(1),
+                name: None,
+            },
+        )]),
+    };
+
+    let x = preview(&current, dir.path().join("candidate.json"), Json::default()).unwrap();
+
+    // Only `hosts."a".port` collided, so only it was dropped.
+    assert_eq!(x.errors().len(), 1);
+    assert_eq!(x.errors()[0].kind, ErrorKind::Collision);
+    assert_eq!(
+        x.value().hosts["a"],
+        Host {
+            port: Some(1),
+            name: Some("x".into()),
+        }
+    );
+    assert_eq!(x.value().hosts["b"].port, Some(3));
+    assert_eq!(x.summary(), "0 values changed, 2 added, 1 collision");
+}
+
+#[test]
+fn test_preview_file() {
+    let dir = tree(&[
+        ("current.json", r#"{ "port": 80, "hosts": ["a"] }"#),
+        (
+            "candidate.json",
+            r#"{ "include": ["extra.json"], "hosts": ["b"] }"#,
+        ),
+        (
+            "extra.json",
+            r#"{ "include": ["deep.json"], "hosts": ["c"] }"#,
+        ),
+        ("deep.json", r#"{ "hosts": ["d"] }"#),
+    ]);
+
+    let mut file = File::<Config, _>::json()
+        .with_import_key("include")
+        .with_max_depth(Some(1));
+    file.set_import_order(ImportOrder::ImportsFirst);
+
+    let err = preview_file(
+        &mut file,
+        dir.path().join("candidate.json"),
+        Options::default(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("no module was read"), "{err}");
+
+    file.read(dir.path().join("current.json")).unwrap();
+
+    let preview = preview_file(
+        &mut file,
+        dir.path().join("candidate.json"),
+        Options::default(),
+    )
+    .unwrap();
+
+    // The imports are merged first and `deep.json` is too deep.
+    assert_eq!(preview.value().hosts, ["a", "c", "b"]);
+    assert_eq!(preview.errors().len(), 1);
+    assert_eq!(
+        preview.errors()[0].kind.to_string(),
+        "import depth exceeded"
+    );
+
+    assert_eq!(file.value().unwrap().hosts, ["a"]);
+}
+
+#[cfg(feature = "glob")]
+#[test]
+fn test_preview_file_glob() {
+    let dir = tree(&[
+        (
+            "candidate.json",
+            r#"{ "imports": ["conf.d/*.json"], "hosts": [] }"#,
+        ),
+        ("conf.d/1.json", r#"{ "hosts": ["b"] }"#),
+        ("conf.d/2.json", r#"{ "hosts": ["c"] }"#),
+    ]);
+
+    let mut file = File::<Config, _>::json().with_glob(true);
+    file.read_str("current.json", r#"{ "port": 80, "hosts": ["a"] }"#)
+        .unwrap();
+
+    let preview = preview_file(
+        &mut file,
+        dir.path().join("candidate.json"),
+        Options::default(),
+    )
+    .unwrap();
+
+    assert!(preview.errors().is_empty());
+    assert_eq!(preview.value().hosts, ["a", "b", "c"]);
+}
@@ -0,0 +1,41 @@
+//! Compare merging many small values one by one against reserving upfront.
+//!
+//! Run with: `cargo bench -p module --bench merge`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use module::merge::IteratorExt;
+use module::types::Lines;
+
+const FRAGMENTS: usize = 10_000;
+const ROUNDS: u32 = 50;
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+
+    println!("{name:<24} {elapsed:>12.2?}");
+    elapsed
+}
+
+fn lines() -> impl Iterator<Item = Lines> {
+    (0..FRAGMENTS).map(|i| Lines::new(format!("fragment {i}")))
+}
+
+fn vecs() -> impl Iterator<Item = Vec<u64>> {
+    (0..FRAGMENTS as u64).map(|i| vec![i; 4])
+}
+
+fn main() {
+    println!("merging {FRAGMENTS} fragments, {ROUNDS} rounds");
+
+    bench("lines/naive", || lines().try_merge());
+    bench("lines/reserved", || lines().try_merge_reserved());
+
+    bench("vec/naive", || vecs().try_merge());
+    bench("vec/reserved", || vecs().try_merge_reserved());
+}
@@ -0,0 +1,114 @@
+//! Compare merging wide, sparsely populated structs with and without
+//! `#[merge(trim_neutral)]`.
+//!
+//! Run with: `cargo bench -p module --bench trim_neutral --features derive`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use module::Merge;
+use module::types::Last;
+
+const MODULES: usize = 3_000;
+const ROUNDS: u32 = 50;
+
+macro_rules! wide {
+    ($(#[$attr:meta])* $name:ident { $($opt:ident),* ; $($vec:ident),* }) => {
+        #[derive(Default, Clone, Merge)]
+        $(#[$attr])*
+        struct $name {
+            $($opt: Option<Last<u64>>,)*
+            $($vec: Vec<u64>,)*
+        }
+
+        impl $name {
+            /// Create a value where only the `i`-th and `j`-th fields are set.
+            fn sparse(i: usize, j: usize) -> Self {
+                let mut x = Self::default();
+                let mut n = 0;
+
+                $(
+                    if n == i || n == j {
+                        x.$opt = Some(Last(n as u64));
+                    }
+                    n += 1;
+                )*
+
+                $(
+                    if n == i || n == j {
+                        x.$vec = vec![n as u64];
+                    }
+                    n += 1;
+                )*
+
+                let _ = n;
+                x
+            }
+        }
+    };
+}
+
+macro_rules! fields {
+    ($($(#[$attr:meta])* $name:ident),*) => {
+        $(
+            wide! {
+                $(#[$attr])*
+                $name {
+                    o00, o01, o02, o03, o04, o05, o06, o07, o08, o09,
+                    o10, o11, o12, o13, o14, o15, o16, o17, o18, o19,
+                    o20, o21, o22, o23, o24, o25, o26, o27, o28, o29;
+                    v00, v01, v02, v03, v04, v05, v06, v07, v08, v09,
+                    v10, v11, v12, v13, v14, v15, v16, v17, v18, v19,
+                    v20, v21, v22, v23, v24, v25, v26, v27, v28, v29
+                }
+            }
+        )*
+    };
+}
+
+fields! {
+    Plain,
+    #[merge(trim_neutral)]
+    Trimmed
+}
+
+const FIELDS: usize = 60;
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+
+    println!(
+        "{name:<24} {elapsed:>12.2?} ({:.2?}/module)",
+        elapsed / MODULES as u32
+    );
+    elapsed
+}
+
+fn modules<T>(sparse: fn(usize, usize) -> T) -> Vec<T> {
+    (0..MODULES)
+        .map(|i| sparse(i % FIELDS, (i * 7 + 3) % FIELDS))
+        .collect()
+}
+
+fn merge_all<T: Merge + Default>(modules: Vec<T>) -> T {
+    let mut acc = T::default();
+    for x in modules {
+        acc.merge_ref(x).unwrap();
+    }
+    acc
+}
+
+fn main() {
+    println!("merging {MODULES} modules of {FIELDS} fields, {ROUNDS} rounds");
+
+    // Cloning the inputs costs the same for both and is included in the timings.
+    let plain = modules(Plain::sparse);
+    bench("plain", || merge_all(plain.clone()));
+
+    let trimmed = modules(Trimmed::sparse);
+    bench("trim_neutral", || merge_all(trimmed.clone()));
+}
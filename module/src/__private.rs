@@ -0,0 +1,98 @@
+//! Items used by code generated from `#[derive(Merge)]`. Not public API.
+
+/// Marker for field types usable with `#[merge(skip(deny_other))]`.
+#[diagnostic::on_unimplemented(
+    message = "`#[merge(skip(deny_other))]` requires `{Self}: PartialEq + Default`",
+    label = "`{Self}` cannot be compared against its default value"
+)]
+pub trait Unset {
+    fn is_unset(&self) -> bool;
+}
+
+impl<T> Unset for T
+where
+    T: PartialEq + Default,
+{
+    #[inline]
+    fn is_unset(&self) -> bool {
+        *self == T::default()
+    }
+}
+
+/// Check whether `x` has its default value.
+#[inline]
+pub fn is_unset<T>(x: &T) -> bool
+where
+    T: Unset,
+{
+    x.is_unset()
+}
+
+/// Decide whether the value of an `overridable` field of `#[overridable_struct]`
+/// should be taken from `other`.
+///
+/// [`None`] means the field was not set.
+#[inline]
+pub fn take_overridable(
+    this: Option<crate::types::overridable::Priority>,
+    other: Option<crate::types::overridable::Priority>,
+) -> Result<bool, crate::Error> {
+    match (this, other) {
+        (_, None) => Ok(false),
+        (None, Some(_)) => Ok(true),
+        (Some(a), Some(b)) if a == b => Err(crate::types::overridable::collision(a)),
+        (Some(a), Some(b)) => Ok(a > b),
+    }
+}
+
+/// Add the field `names[idx]` to the value of `e`.
+///
+/// This is the shared error path of `#[merge(compact_names)]`. It is
+/// deliberately not generic and not inlined, so that it exists only once in
+/// the binary.
+#[inline(never)]
+pub fn push_value_idx(
+    mut e: crate::Error,
+    names: &'static [&'static str],
+    idx: u16,
+) -> crate::Error {
+    if let Some(name) = names.get(usize::from(idx)) {
+        e.value.push(*name);
+    }
+    e
+}
+
+/// The built-in strategies of `#[merge(strategy = ...)]`, in the shape
+/// expected by `#[merge(with = ...)]`.
+pub mod strategy {
+    /// Like [`Last`](crate::types::Last).
+    pub mod last {
+        #[inline]
+        pub fn merge_ref<T>(this: &mut T, other: T) -> Result<(), crate::Error> {
+            *this = other;
+            Ok(())
+        }
+    }
+
+    /// Like [`First`](crate::types::First).
+    pub mod first {
+        #[inline]
+        pub fn merge_ref<T>(_: &mut T, _: T) -> Result<(), crate::Error> {
+            Ok(())
+        }
+    }
+
+    /// Like [`Lines`](crate::types::Lines).
+    pub mod lines {
+        use alloc::string::String;
+
+        use crate::Merge;
+        use crate::types::Lines;
+
+        pub fn merge_ref(this: &mut String, other: String) -> Result<(), crate::Error> {
+            let merged = Lines::new(core::mem::take(this)).merge(Lines::new(other))?;
+            *this = merged.into_string();
+            Ok(())
+        }
+    }
+}
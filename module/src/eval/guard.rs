@@ -0,0 +1,197 @@
+//! Attaching modules to errors.
+//!
+//! See: [`ModuleGuard`].
+
+use core::fmt::Display;
+
+use alloc::string::String;
+
+use crate::merge::{Context, Error};
+
+/// Attaches a module to all errors produced while evaluating it.
+///
+/// All errors returned from [`run()`] get the module added to their
+/// backtrace with [`Context::module`]. Because [`ModuleGuard`]s of imported
+/// modules run inside the [`ModuleGuard`] of their importer, the backtrace
+/// is built in the correct order: from the module where the error occurred,
+/// up until the root module.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::eval::ModuleGuard;
+/// # use module::Error;
+/// let r: Result<(), Error> = ModuleGuard::new("main").run(|| {
+///     ModuleGuard::new("extra").run(|| Err(Error::collision()))
+/// });
+///
+/// let err = r.unwrap_err();
+/// assert_eq!(err.to_string(),
+/// r#"value collision
+///
+///     in extra
+///   from main
+/// "#);
+/// ```
+///
+/// # Current module
+///
+/// With the `current-module` feature, the module of the innermost [`run()`]
+/// on the current thread is available through [`current_module()`]. Values
+/// can use it to record which module they came from while they are
+/// deserialized. This formats the module every time a guard runs, so it is
+/// disabled by default.
+///
+/// [`run()`]: ModuleGuard::run
+#[derive(Debug, Clone)]
+pub struct ModuleGuard<D> {
+    module: D,
+}
+
+impl<D> ModuleGuard<D>
+where
+    D: Display + Send + Sync + 'static,
+{
+    /// Create a new [`ModuleGuard`] for `module`.
+    pub fn new(module: D) -> Self {
+        Self { module }
+    }
+
+    /// Get the module of this guard.
+    pub fn module(&self) -> &D {
+        &self.module
+    }
+
+    /// Run `f` and attach the module to the error it returns, if any.
+    ///
+    /// While `f` runs, the module is the [`current_module()`].
+    pub fn run<T>(self, f: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+        #[cfg(feature = "current-module")]
+        let r = imp::enter(&self.module, f);
+        #[cfg(not(feature = "current-module"))]
+        let r = f();
+
+        r.module(self.module)
+    }
+}
+
+/// Get the module of the innermost [`ModuleGuard`] running on the current
+/// thread.
+///
+/// Returns [`None`] outside of any [`ModuleGuard`], or always without the
+/// `current-module` feature.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::eval::{ModuleGuard, current_module};
+/// # use module::Error;
+/// assert_eq!(current_module(), None);
+///
+/// ModuleGuard::new("main").run(|| {
+/// #   #[cfg(feature = "current-module")]
+///     assert_eq!(current_module().as_deref(), Some("main"));
+///     Ok::<_, Error>(())
+/// })
+/// .unwrap();
+/// ```
+pub fn current_module() -> Option<String> {
+    #[cfg(feature = "current-module")]
+    return imp::current();
+
+    #[cfg(not(feature = "current-module"))]
+    None
+}
+
+#[cfg(feature = "current-module")]
+mod imp {
+    use core::cell::RefCell;
+    use core::fmt::Display;
+
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    std::thread_local! {
+        static STACK: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    pub(super) fn enter<D, R>(module: &D, f: impl FnOnce() -> R) -> R
+    where
+        D: Display,
+    {
+        struct Pop;
+
+        impl Drop for Pop {
+            fn drop(&mut self) {
+                STACK.with_borrow_mut(|x| x.pop());
+            }
+        }
+
+        STACK.with_borrow_mut(|x| x.push(module.to_string()));
+        let _pop = Pop;
+        f()
+    }
+
+    pub(super) fn current() -> Option<String> {
+        STACK.with_borrow(|x| x.last().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    fn modules(e: &Error) -> Vec<String> {
+        e.modules.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_ok() {
+        let r = ModuleGuard::new("a").run(|| Ok::<_, Error>(42));
+        assert_eq!(r.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_err() {
+        let e = ModuleGuard::new("a")
+            .run(|| Err::<(), _>(Error::collision()))
+            .unwrap_err();
+
+        assert_eq!(modules(&e), ["a"]);
+    }
+
+    #[test]
+    #[cfg(feature = "current-module")]
+    fn test_current_module() {
+        let r = ModuleGuard::new("a").run(|| {
+            ModuleGuard::new("b").run(|| {
+                assert_eq!(current_module().as_deref(), Some("b"));
+                Ok::<_, Error>(())
+            })?;
+
+            assert_eq!(current_module().as_deref(), Some("a"));
+            Err::<(), _>(Error::collision())
+        });
+
+        assert!(r.is_err(), "the error should be returned");
+        assert_eq!(current_module(), None);
+    }
+
+    #[test]
+    fn test_nested() {
+        let e = ModuleGuard::new("a")
+            .run(|| {
+                ModuleGuard::new("b")
+                    .run(|| ModuleGuard::new("c").run(|| Err::<(), _>(Error::cycle())))
+            })
+            .unwrap_err();
+
+        // Innermost module first.
+        let mut m = modules(&e);
+        m.reverse();
+        assert_eq!(m, ["c", "b", "a"]);
+    }
+}
@@ -0,0 +1,144 @@
+//! Building blocks for evaluators.
+//!
+//! An evaluator reads modules from some source, merges their values and
+//! follows their imports. `module-util` provides evaluators for files, but
+//! nothing stops you from writing one for any other source. The [`prelude`]
+//! exports everything an evaluator usually needs.
+//!
+//! # Conventions
+//!
+//! Evaluators should follow these rules so that errors look the same no
+//! matter where the modules come from.
+//!
+//! 1. **Identify modules canonically.** Decide on a key that uniquely
+//!    identifies a module, like a canonical path, _before_ entering it into
+//!    a [`VisitedSet`]. Two different keys for the same module hide cycles.
+//!
+//! 2. **Enter a module before reading it, complete it after its imports.**
+//!    A module is [in progress] while its imports are evaluated, so an import
+//!    that leads back to it is reported as a [cycle]. Modules reached again
+//!    through a different path, "diamonds", are reported as
+//!    [`Visit::Completed`].
+//!
+//! 3. **Attach the module to every error.** Evaluate each module, including
+//!    its imports, inside a [`ModuleGuard`]. Guards of imported modules run
+//!    inside the guard of their importer, so the module backtrace goes from
+//!    the module where the error occurred up to the root module.
+//!
+//! 4. **Leave values to [`Merge`].** [`Merge`] implementations, derived or
+//!    not, add the value path to their errors. Evaluators only deal with
+//!    modules.
+//!
+//! 5. **Choose how to accumulate.** Use [`merge_opt`] to stop at the first
+//!    error and know which module caused it. Use [`MergeCell`] to merge
+//!    everything and look at the result at the end.
+//!
+//! # Example
+//!
+//! An evaluator for modules stored in a [`BTreeMap`]. Each module is a list
+//! of lines. Lines of the form `import <name>` import another module, all
+//! other lines are the values of the module.
+//!
+//! ```rust
+//! use std::collections::BTreeMap;
+//!
+//! use module::eval::prelude::*;
+//!
+//! struct MapEvaluator<'a> {
+//!     source: &'a BTreeMap<String, String>,
+//!     visited: VisitedSet<String>,
+//!     value: Option<Vec<String>>,
+//! }
+//!
+//! impl MapEvaluator<'_> {
+//!     fn read(&mut self, name: &str) -> Result<(), Error> {
+//!         // Every error from here on, even errors from imports, is
+//!         // attributed to `name`.
+//!         ModuleGuard::new(name.to_owned()).run(|| self.read_inner(name))
+//!     }
+//!
+//!     fn read_inner(&mut self, name: &str) -> Result<(), Error> {
+//!         // Module names are already canonical. An evaluator for files would
+//!         // canonicalize the path here.
+//!         if self.visited.enter(name.to_owned())? == Visit::Completed {
+//!             // Already merged through another importer.
+//!             return Ok(());
+//!         }
+//!
+//!         let text = self
+//!             .source
+//!             .get(name)
+//!             .ok_or_else(|| Error::custom("no such module"))?;
+//!
+//!         let mut imports = Vec::new();
+//!         let mut value = Vec::new();
+//!         for line in text.lines() {
+//!             match line.strip_prefix("import ") {
+//!                 Some(import) => imports.push(import),
+//!                 None => value.push(line.to_owned()),
+//!             }
+//!         }
+//!
+//!         merge_opt(&mut self.value, value)?;
+//!
+//!         for import in imports {
+//!             self.read(import)?;
+//!         }
+//!
+//!         self.visited.complete(name);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let source = BTreeMap::from([
+//!     ("main".to_owned(), "import net\nimport log\na".to_owned()),
+//!     ("net".to_owned(), "import base\nb".to_owned()),
+//!     ("log".to_owned(), "import base\nc".to_owned()),
+//!     ("base".to_owned(), "d".to_owned()),
+//!     ("loop".to_owned(), "import main\nimport loop".to_owned()),
+//! ]);
+//!
+//! let mut eval = MapEvaluator {
+//!     source: &source,
+//!     visited: VisitedSet::new(),
+//!     value: None,
+//! };
+//! eval.read("main").unwrap();
+//! assert_eq!(eval.value.unwrap(), ["a", "b", "d", "c"]);
+//!
+//! let mut eval = MapEvaluator {
+//!     source: &source,
+//!     visited: VisitedSet::new(),
+//!     value: None,
+//! };
+//! let err = eval.read("loop").unwrap_err();
+//! assert!(err.kind.is_cycle());
+//! assert_eq!(err.to_string(),
+//! r#"cyclic imports
+//!
+//!     in loop (x2)
+//! "#);
+//! ```
+//!
+//! [in progress]: VisitedSet::is_in_progress
+//! [cycle]: crate::merge::ErrorKind::Cycle
+//! [`BTreeMap`]: alloc::collections::BTreeMap
+
+mod guard;
+mod visited;
+
+pub use self::guard::{ModuleGuard, current_module};
+pub use self::visited::{Visit, VisitedSet};
+
+#[doc(no_inline)]
+pub use crate::merge::{Context, Error, Merge, MergeCell, merge_opt};
+
+/// Everything needed to write an evaluator.
+///
+/// ```rust
+/// use module::eval::prelude::*;
+/// ```
+pub mod prelude {
+    #[doc(no_inline)]
+    pub use super::{Context, Error, Merge, MergeCell, ModuleGuard, Visit, VisitedSet, merge_opt};
+}
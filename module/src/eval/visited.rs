@@ -0,0 +1,214 @@
+//! Tracking which modules have been evaluated.
+//!
+//! See: [`VisitedSet`].
+
+use alloc::collections::BTreeSet;
+use core::borrow::Borrow;
+
+use crate::merge::Error;
+
+/// The result of [`VisitedSet::enter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visit {
+    /// The module has never been entered before.
+    New,
+    /// The module has already been evaluated completely.
+    ///
+    /// This happens when more than one module imports the same module, a
+    /// "diamond". It is up to the evaluator to decide whether this is an
+    /// error or whether the module should simply be skipped.
+    Completed,
+}
+
+/// The set of modules an evaluator has visited.
+///
+/// Each module is either _in progress_ or _completed_. A module is in
+/// progress from the moment it is [entered] until it is [completed], which
+/// should happen after all of its imports have been evaluated. Entering a
+/// module that is in progress means that the module (indirectly) imports
+/// itself, which is an [import cycle].
+///
+/// Modules are identified by keys of type `K`. It is up to the evaluator to
+/// make sure that keys are canonical, for example by canonicalizing paths,
+/// so that the same module always maps to the same key.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::eval::{Visit, VisitedSet};
+/// let mut visited = VisitedSet::new();
+///
+/// assert_eq!(visited.enter("a").unwrap(), Visit::New);
+/// // `a` imports `b`
+/// assert_eq!(visited.enter("b").unwrap(), Visit::New);
+/// // `b` imports `a`
+/// assert!(visited.enter("a").unwrap_err().kind.is_cycle());
+/// ```
+///
+/// [entered]: VisitedSet::enter
+/// [completed]: VisitedSet::complete
+/// [import cycle]: crate::merge::ErrorKind::Cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisitedSet<K> {
+    in_progress: BTreeSet<K>,
+    completed: BTreeSet<K>,
+}
+
+impl<K> VisitedSet<K> {
+    /// Create a new empty [`VisitedSet`].
+    pub const fn new() -> Self {
+        Self {
+            in_progress: BTreeSet::new(),
+            completed: BTreeSet::new(),
+        }
+    }
+
+    /// Check whether no module has been entered.
+    pub fn is_empty(&self) -> bool {
+        self.in_progress.is_empty() && self.completed.is_empty()
+    }
+}
+
+impl<K> VisitedSet<K>
+where
+    K: Ord,
+{
+    /// Enter the module identified by `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [import cycle] error if the module is in progress.
+    ///
+    /// [import cycle]: crate::merge::ErrorKind::Cycle
+    pub fn enter(&mut self, key: K) -> Result<Visit, Error> {
+        if self.in_progress.contains(&key) {
+            return Err(Error::cycle());
+        }
+
+        if self.completed.contains(&key) {
+            return Ok(Visit::Completed);
+        }
+
+        self.in_progress.insert(key);
+        Ok(Visit::New)
+    }
+
+    /// Mark the module identified by `key` as completed.
+    ///
+    /// Does nothing if the module is not in progress.
+    pub fn complete<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if let Some(key) = self.in_progress.take(key) {
+            self.completed.insert(key);
+        }
+    }
+
+    /// Forget that the module identified by `key` was entered.
+    ///
+    /// This is meant for modules that failed to evaluate in a way that left
+    /// no trace, so that they may be entered again later.
+    ///
+    /// Does nothing if the module is not in progress.
+    pub fn abandon<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.in_progress.remove(key);
+    }
+
+    /// Check whether the module identified by `key` is in progress.
+    pub fn is_in_progress<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.in_progress.contains(key)
+    }
+
+    /// Check whether the module identified by `key` is completed.
+    pub fn is_completed<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.completed.contains(key)
+    }
+
+    /// Check whether the module identified by `key` has been entered.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.is_in_progress(key) || self.is_completed(key)
+    }
+}
+
+impl<K> Default for VisitedSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle() {
+        let mut v = VisitedSet::new();
+
+        assert_eq!(v.enter("a").unwrap(), Visit::New);
+        assert_eq!(v.enter("b").unwrap(), Visit::New);
+        assert!(v.enter("a").unwrap_err().kind.is_cycle());
+        assert!(v.enter("b").unwrap_err().kind.is_cycle());
+    }
+
+    #[test]
+    fn test_diamond() {
+        // a -> b -> d
+        //   -> c -> d
+        let mut v = VisitedSet::new();
+
+        assert_eq!(v.enter("a").unwrap(), Visit::New);
+        assert_eq!(v.enter("b").unwrap(), Visit::New);
+        assert_eq!(v.enter("d").unwrap(), Visit::New);
+        v.complete("d");
+        v.complete("b");
+        assert_eq!(v.enter("c").unwrap(), Visit::New);
+        assert_eq!(v.enter("d").unwrap(), Visit::Completed);
+        v.complete("c");
+        v.complete("a");
+
+        assert!(v.is_completed("a"));
+        assert!(!v.is_in_progress("a"));
+        assert_eq!(v.enter("a").unwrap(), Visit::Completed);
+    }
+
+    #[test]
+    fn test_abandon() {
+        let mut v = VisitedSet::new();
+
+        assert_eq!(v.enter("a").unwrap(), Visit::New);
+        v.abandon("a");
+        assert!(!v.contains("a"));
+        assert!(v.is_empty());
+        assert_eq!(v.enter("a").unwrap(), Visit::New);
+
+        // Completed modules are not affected.
+        v.complete("a");
+        v.abandon("a");
+        assert!(v.is_completed("a"));
+    }
+
+    #[test]
+    fn test_complete_not_entered() {
+        let mut v = VisitedSet::<&str>::new();
+        v.complete("a");
+        assert!(!v.contains("a"));
+    }
+}
@@ -0,0 +1,39 @@
+//! Matching strings against simple patterns.
+
+/// Check whether `s` matches `pattern`.
+///
+/// `*` in `pattern` matches any sequence of characters, including none. All
+/// other characters match only themselves.
+pub(crate) fn glob(pattern: &str, s: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == s,
+        Some((prefix, rest)) => {
+            let Some(s) = s.strip_prefix(prefix) else {
+                return false;
+            };
+
+            (0..=s.len())
+                .filter(|&i| s.is_char_boundary(i))
+                .any(|i| glob(rest, &s[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        assert!(glob("abc", "abc"));
+        assert!(!glob("abc", "abcd"));
+        assert!(glob("*", ""));
+        assert!(glob("*.password", "db.password"));
+        assert!(glob("*.password", "a.b.password"));
+        assert!(!glob("*.password", "password"));
+        assert!(glob("db.*", "db.password"));
+        assert!(glob("a*c*e", "abcde"));
+        assert!(!glob("a*c*e", "abcd"));
+        assert!(glob("*é", "café"));
+    }
+}
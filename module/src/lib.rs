@@ -23,9 +23,21 @@ pub use self::merge::{Context, Error, Merge, merge};
 
 #[doc(inline)]
 #[cfg(feature = "derive")]
-pub use module_derive::Merge;
+pub use module_derive::{Merge, overridable_struct};
 
 pub mod types;
 
+pub mod eval;
+
+pub mod redact;
+
+mod glob;
+
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+#[doc(hidden)]
+pub mod __private;
+
 #[cfg(test)]
 mod test;
@@ -1,7 +1,12 @@
+use core::fmt::{self, Debug};
 use core::mem::replace;
+use core::ops::ControlFlow;
+
+use alloc::boxed::Box;
 
 use super::Merge;
 use super::error::Error;
+use super::reserve::ReserveHint;
 
 /// A memory location that allows repeated merging.
 ///
@@ -25,10 +30,39 @@ use super::error::Error;
 /// let merged = cell.try_finish().unwrap().unwrap();
 /// assert_eq!(merged, &[1, 2, 0, 4, 8]);
 /// ```
-#[derive(Debug)]
+///
+/// # Hooks
+///
+/// [`on_merge()`] observes every value the cell accumulates and can mark the
+/// cell as [saturated], after which it ignores further values. [`map_err()`]
+/// decorates the error of a failed merge. Cells without hooks do not pay for
+/// them.
+///
+/// [`on_merge()`]: MergeCell::on_merge
+/// [`map_err()`]: MergeCell::map_err
+/// [saturated]: MergeCell::is_saturated
 pub struct MergeCell<T> {
     value: Option<T>,
     result: Result<(), Error>,
+    saturated: bool,
+    on_merge: Option<OnMerge<T>>,
+    map_err: Option<MapErr>,
+}
+
+type OnMerge<T> = Box<dyn FnMut(&T) -> ControlFlow<()>>;
+type MapErr = Box<dyn FnMut(Error) -> Error>;
+
+impl<T> Debug for MergeCell<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MergeCell")
+            .field("value", &self.value)
+            .field("result", &self.result)
+            .field("saturated", &self.saturated)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<T> Default for MergeCell<T> {
@@ -43,6 +77,9 @@ impl<T> MergeCell<T> {
         Self {
             value: None,
             result: Ok(()),
+            saturated: false,
+            on_merge: None,
+            map_err: None,
         }
     }
 
@@ -50,10 +87,92 @@ impl<T> MergeCell<T> {
     pub fn new(value: T) -> Self {
         Self {
             value: Some(value),
-            result: Ok(()),
+            ..Self::empty()
         }
     }
 
+    /// Call `f` after every successful [`merge()`].
+    ///
+    /// `f` is given the value accumulated so far, including the value that
+    /// fills an empty cell. If it returns [`ControlFlow::Break`], the cell
+    /// becomes [saturated] and [`merge()`] ignores any further values.
+    ///
+    /// This replaces any previous hook.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::MergeCell;
+    /// use core::ops::ControlFlow;
+    ///
+    /// // Stop once there are at least 3 items.
+    /// let mut cell = MergeCell::empty().on_merge(|x: &Vec<i32>| {
+    ///     if x.len() >= 3 {
+    ///         ControlFlow::Break(())
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// });
+    ///
+    /// cell.merge(vec![1, 2]);
+    /// assert!(!cell.is_saturated());
+    /// cell.merge(vec![3]);
+    /// assert!(cell.is_saturated());
+    /// cell.merge(vec![4]);
+    ///
+    /// assert_eq!(cell.finish().unwrap(), [1, 2, 3]);
+    /// ```
+    ///
+    /// [`merge()`]: MergeCell::merge
+    /// [saturated]: MergeCell::is_saturated
+    pub fn on_merge<H>(mut self, f: H) -> Self
+    where
+        H: FnMut(&T) -> ControlFlow<()> + 'static,
+    {
+        self.on_merge = Some(Box::new(f));
+        self
+    }
+
+    /// Pass the error of a failed [`merge()`] through `f`.
+    ///
+    /// `f` is called once, when the error occurs, and its return value is the
+    /// error reported by [`finish()`]. This replaces any previous decorator.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::{Error, MergeCell};
+    /// let mut cell = MergeCell::empty().map_err(|mut e: Error| {
+    ///     e.modules.push("overrides");
+    ///     e
+    /// });
+    ///
+    /// cell.merge(1i32);
+    /// cell.merge(2i32);
+    ///
+    /// let err = cell.finish().unwrap_err();
+    /// assert!(err.kind.is_collision());
+    /// assert_eq!(err.modules.iter().next().unwrap().to_string(), "overrides");
+    /// ```
+    ///
+    /// [`merge()`]: MergeCell::merge
+    /// [`finish()`]: MergeCell::finish
+    pub fn map_err<H>(mut self, f: H) -> Self
+    where
+        H: FnMut(Error) -> Error + 'static,
+    {
+        self.map_err = Some(Box::new(f));
+        self
+    }
+
+    /// Check whether the [`on_merge()`] hook has stopped the cell from
+    /// accepting more values.
+    ///
+    /// [`on_merge()`]: MergeCell::on_merge
+    pub fn is_saturated(&self) -> bool {
+        self.saturated
+    }
+
     /// Check whether the cell is empty.
     ///
     /// The cell is empty if and only if it was created with [`empty()`] and no
@@ -72,6 +191,63 @@ impl<T> MergeCell<T> {
         self.result.is_err()
     }
 
+    /// Get the value accumulated so far, or [`None`] if the cell is empty.
+    ///
+    /// After a failed [`merge()`], this is the value as it was left by the
+    /// failed merge.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::MergeCell;
+    /// let mut cell = MergeCell::empty();
+    /// assert_eq!(cell.as_ref(), None);
+    ///
+    /// cell.merge(vec![1, 2]);
+    /// assert_eq!(cell.as_ref(), Some(&vec![1, 2]));
+    /// ```
+    ///
+    /// [`merge()`]: MergeCell::merge
+    pub fn as_ref(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Get the value accumulated so far mutably, or [`None`] if the cell is
+    /// empty.
+    ///
+    /// Changing the value does not call the [`on_merge()`] hook.
+    ///
+    /// [`on_merge()`]: MergeCell::on_merge
+    pub fn as_mut(&mut self) -> Option<&mut T> {
+        self.value.as_mut()
+    }
+
+    /// Destruct the [`MergeCell`] into the value accumulated so far and the
+    /// result of all of the [`merge()`] operations on the cell.
+    ///
+    /// Unlike [`try_finish()`], this gives back the value even if a merge has
+    /// failed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::MergeCell;
+    /// let mut cell = MergeCell::empty();
+    ///
+    /// cell.merge(Some(1));
+    /// cell.merge(Some(2));
+    ///
+    /// let (value, result) = cell.into_parts();
+    /// assert_eq!(value, Some(Some(1)));
+    /// assert!(result.unwrap_err().kind.is_collision());
+    /// ```
+    ///
+    /// [`merge()`]: MergeCell::merge
+    /// [`try_finish()`]: MergeCell::try_finish
+    pub fn into_parts(self) -> (Option<T>, Result<(), Error>) {
+        (self.value, self.result)
+    }
+
     /// Destruct the [`MergeCell`] and get back the final merged value.
     ///
     /// Returns the result of all of the [`merge()`] operations on the cell.
@@ -138,14 +314,143 @@ where
     /// Merge `other` into the cell.
     ///
     /// This function will fill the cell if it is empty.
+    ///
+    /// Does nothing if the cell is [saturated].
+    ///
+    /// [saturated]: MergeCell::is_saturated
     pub fn merge(&mut self, other: T) {
-        match self.value {
+        if self.saturated {
+            return;
+        }
+
+        let value = match self.value {
             Some(ref mut value) => {
                 let r = replace(&mut self.result, Ok(()));
-                self.result = r.and_then(|()| value.merge_ref(other));
+
+                self.result = match r {
+                    Ok(()) => value.merge_ref(other).map_err(|e| match self.map_err {
+                        Some(ref mut f) => f(e),
+                        None => e,
+                    }),
+                    Err(e) => Err(e),
+                };
+
+                if self.result.is_err() {
+                    return;
+                }
+
+                value
             }
 
-            None => self.value = Some(other),
+            None => self.value.insert(other),
+        };
+
+        if let Some(ref mut f) = self.on_merge {
+            self.saturated = f(value).is_break();
         }
     }
+
+    /// Merge `other` into the cell and report a failure right away.
+    ///
+    /// This is like [`merge()`], but it returns the error of the cell if this
+    /// or any previous merge has failed. Callers can use it to stop at the
+    /// first failure instead of waiting for [`finish()`]. The error stays in
+    /// the cell and is still returned by [`finish()`].
+    ///
+    /// # Errors
+    ///
+    /// If this or any previous merge has failed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::MergeCell;
+    /// let mut cell = MergeCell::empty();
+    ///
+    /// for x in [Some(1), None, Some(2), Some(3)] {
+    ///     if let Err(e) = cell.try_merge(x) {
+    ///         assert!(e.kind.is_collision());
+    ///         break;
+    ///     }
+    /// }
+    ///
+    /// assert!(cell.finish().is_err());
+    /// ```
+    ///
+    /// [`merge()`]: MergeCell::merge
+    /// [`finish()`]: MergeCell::finish
+    pub fn try_merge(&mut self, other: T) -> Result<(), &Error> {
+        self.merge(other);
+        self.result.as_ref().copied()
+    }
+}
+
+/// The most units [`MergeCell::merge_reserved`] reserves upfront.
+const MAX_RESERVE_HINT: usize = 1 << 16;
+
+impl<T> MergeCell<T>
+where
+    T: Merge + ReserveHint,
+{
+    /// Reserve capacity in the cell's value for `additional_hint` more units.
+    ///
+    /// Does nothing if the cell is empty.
+    ///
+    /// See: [`ReserveHint`].
+    pub fn reserve(&mut self, additional_hint: usize) {
+        if let Some(ref mut value) = self.value {
+            value.reserve_for(additional_hint);
+        }
+    }
+
+    /// Merge all values of `iter` into the cell.
+    ///
+    /// This produces the exact same result as calling [`merge()`] for each
+    /// value, but it reserves capacity upfront. The amount reserved is
+    /// estimated from the [`size_hint()`] of the iterator and the
+    /// [`len_hint()`] of its first item, and is capped at 65536 units. Neither
+    /// is trusted beyond that, the value grows as usual if it needs more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::MergeCell;
+    /// let mut cell = MergeCell::empty();
+    ///
+    /// cell.merge_reserved((0..100).map(|x| vec![x]));
+    ///
+    /// let merged = cell.finish().unwrap();
+    /// assert_eq!(merged.len(), 100);
+    /// ```
+    ///
+    /// [`merge()`]: MergeCell::merge
+    /// [`size_hint()`]: Iterator::size_hint
+    /// [`len_hint()`]: ReserveHint::len_hint
+    pub fn merge_reserved<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut items = iter.into_iter();
+
+        if self.is_empty() {
+            match items.next() {
+                Some(first) => self.merge(first),
+                None => return,
+            }
+        }
+
+        let Some(sample) = items.next() else {
+            return;
+        };
+
+        let (remaining, _) = items.size_hint();
+        let additional = sample
+            .len_hint()
+            .saturating_mul(remaining.saturating_add(1))
+            .min(MAX_RESERVE_HINT);
+        self.reserve(additional);
+
+        self.merge(sample);
+        items.for_each(|x| self.merge(x));
+    }
 }
@@ -9,7 +9,19 @@ use self::private::Sealed;
 
 /// Extension trait for [`Result`].
 ///
-/// Adds methods for adding context to an [`Error`].
+/// Adds methods for adding context to an [`Error`]. It is also implemented
+/// for [`Error`] itself and for [`Option<Error>`], for errors that are not
+/// inside a [`Result`].
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::{Context, Error};
+/// let err = Error::collision().value("port").module("config.json");
+///
+/// assert_eq!(err.value.to_string(), "'port'");
+/// assert_eq!(err.modules.len(), 1);
+/// ```
 ///
 /// This trait is **sealed** and cannot be implemented for any other types.
 ///
@@ -48,6 +60,37 @@ pub trait Context: Sealed {
     where
         D: Display + Send + Sync + 'static,
         Self: Sized;
+
+    /// Add the position of the element of a sequence to the context of the
+    /// error.
+    ///
+    /// This is like [`Context::value`], but the position displays as `[i]`,
+    /// right after the name of the sequence, like `'settings.items[3]'`.
+    ///
+    /// See: [`Value::push_index`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::{Context, Error};
+    /// let r: Result<(), Error> = Err(Error::collision())
+    ///     .value("port")
+    ///     .index(3)
+    ///     .value("items")
+    ///     .value("settings");
+    ///
+    /// assert_eq!(r.unwrap_err().value.to_string(), "'settings.items[3].port'");
+    /// ```
+    ///
+    /// [`Value::push_index`]: super::error::Value::push_index
+    fn index(self, i: usize) -> Self
+    where
+        Self: Sized;
+
+    /// The same as [`Context::index`] but lazily-evaluated.
+    fn with_index(self, f: impl FnOnce() -> usize) -> Self
+    where
+        Self: Sized;
 }
 
 impl<T> Sealed for core::result::Result<T, Error> {}
@@ -89,4 +132,157 @@ impl<T> Context for core::result::Result<T, Error> {
             e
         })
     }
+
+    fn index(self, i: usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.with_index(|| i)
+    }
+
+    fn with_index(self, f: impl FnOnce() -> usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.map_err(|mut e| {
+            e.value.push_index(f());
+            e
+        })
+    }
+}
+
+impl Sealed for Error {}
+
+impl Context for Error {
+    fn module<D>(self, name: D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+        Self: Sized,
+    {
+        self.with_module(|| name)
+    }
+
+    fn with_module<D>(mut self, f: impl FnOnce() -> D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        self.modules.push(f());
+        self
+    }
+
+    fn value<D>(self, name: D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+        Self: Sized,
+    {
+        self.with_value(|| name)
+    }
+
+    fn with_value<D>(mut self, f: impl FnOnce() -> D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+        Self: Sized,
+    {
+        self.value.push(f());
+        self
+    }
+
+    fn index(self, i: usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.with_index(|| i)
+    }
+
+    fn with_index(mut self, f: impl FnOnce() -> usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.value.push_index(f());
+        self
+    }
+}
+
+impl Sealed for Option<Error> {}
+
+impl Context for Option<Error> {
+    fn module<D>(self, name: D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+        Self: Sized,
+    {
+        self.with_module(|| name)
+    }
+
+    fn with_module<D>(self, f: impl FnOnce() -> D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        self.map(|e| e.with_module(f))
+    }
+
+    fn value<D>(self, name: D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+        Self: Sized,
+    {
+        self.with_value(|| name)
+    }
+
+    fn with_value<D>(self, f: impl FnOnce() -> D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+        Self: Sized,
+    {
+        self.map(|e| e.with_value(f))
+    }
+
+    fn index(self, i: usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.with_index(|| i)
+    }
+
+    fn with_index(self, f: impl FnOnce() -> usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.map(|e| e.with_index(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_error() {
+        let err = Error::collision()
+            .value("port")
+            .index(2)
+            .value("servers")
+            .module("a.json")
+            .module("main.json");
+
+        let chained = Err::<(), _>(Error::collision())
+            .value("port")
+            .index(2)
+            .value("servers")
+            .module("a.json")
+            .module("main.json")
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), chained.to_string());
+    }
+
+    #[test]
+    fn test_option() {
+        let err = Some(Error::collision()).value("port").module("a.json");
+        assert_eq!(err.unwrap().value.to_string(), "'port'");
+
+        assert!(None.value("port").module("a.json").is_none());
+    }
 }
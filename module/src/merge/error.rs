@@ -9,6 +9,8 @@ use core::mem::discriminant;
 
 use alloc::boxed::Box;
 use alloc::collections::linked_list::{self, LinkedList};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
 /// Kind of [`Error`].
 #[non_exhaustive]
@@ -39,8 +41,20 @@ pub enum ErrorKind {
 
     /// A custom error that occurred during merging or evaluating.
     ///
-    /// Contains a [`Box`]ed error object.
+    /// Contains a [`Box`]ed error object. If the [`Error`] was created with
+    /// [`Error::custom_error`], the original error is also available as its
+    /// [`source()`].
+    ///
+    /// [`source()`]: core::error::Error::source
     Custom(Box<dyn Display + Send + Sync + 'static>),
+
+    /// Many errors combined into one.
+    ///
+    /// Contains the combined errors, in order. Each of them keeps its own
+    /// value and module traces.
+    ///
+    /// See: [`Error::and`].
+    Multiple(Vec<Error>),
 }
 
 impl ErrorKind {
@@ -58,6 +72,11 @@ impl ErrorKind {
     pub fn is_custom(&self) -> bool {
         matches!(self, Self::Custom(_))
     }
+
+    /// Check whether `self` is [`ErrorKind::Multiple`].
+    pub fn is_multiple(&self) -> bool {
+        matches!(self, Self::Multiple(_))
+    }
 }
 
 impl Debug for ErrorKind {
@@ -66,6 +85,7 @@ impl Debug for ErrorKind {
             Self::Collision => write!(f, "Collision"),
             Self::Cycle => write!(f, "Cycle"),
             Self::Custom(x) => write!(f, "Custom(\"{x}\")"),
+            Self::Multiple(x) => f.debug_tuple("Multiple").field(x).finish(),
         }
     }
 }
@@ -76,6 +96,21 @@ impl Display for ErrorKind {
             Self::Collision => write!(f, "value collision"),
             Self::Cycle => write!(f, "cyclic imports"),
             Self::Custom(x) => x.fmt(f),
+            Self::Multiple(x) => write!(f, "{} errors", x.len()),
+        }
+    }
+}
+
+/// Cloning [`ErrorKind::Custom`] renders the error object into a [`String`].
+/// The clone displays exactly the same, but it no longer holds the original
+/// error object.
+impl Clone for ErrorKind {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Collision => Self::Collision,
+            Self::Cycle => Self::Cycle,
+            Self::Custom(x) => Self::Custom(Box::new(x.to_string())),
+            Self::Multiple(x) => Self::Multiple(x.clone()),
         }
     }
 }
@@ -90,9 +125,34 @@ impl Eq for ErrorKind {}
 
 type BoxedDisplay = Box<dyn Display + Send + Sync + 'static>;
 
+/// A module in the backtrace, along with how many times it was repeated.
+#[derive(Clone)]
+struct ModuleEntry {
+    rendered: String,
+    count: usize,
+}
+
+impl Display for ModuleEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rendered)?;
+
+        if self.count > 1 {
+            write!(f, " (x{})", self.count)?;
+        }
+
+        Ok(())
+    }
+}
+
 /// The module backtrace.
+///
+/// Consecutive modules that display the same are collapsed into one entry,
+/// which is annotated with the number of repetitions, like `config.json (x3)`.
+/// This keeps the backtrace readable when the same module is added many times,
+/// for example by an evaluator that retries.
+#[derive(Clone)]
 pub struct Modules {
-    list: LinkedList<BoxedDisplay>,
+    list: LinkedList<ModuleEntry>,
 }
 
 impl Modules {
@@ -114,11 +174,48 @@ impl Modules {
     }
 
     /// Push `module`.
+    ///
+    /// If `module` displays the same as the last pushed module, the
+    /// repetition count of that module is incremented instead.
     pub fn push<D>(&mut self, module: D)
     where
         D: Display + Send + Sync + 'static,
     {
-        self.list.push_front(Box::new(module));
+        let rendered = module.to_string();
+
+        match self.list.front_mut() {
+            Some(last) if last.rendered == rendered => last.count += 1,
+            _ => self.list.push_front(ModuleEntry { rendered, count: 1 }),
+        }
+    }
+
+    /// Collapse all runs of consecutive modules that display the same.
+    ///
+    /// [`push`] already does this for each new module. This is only needed
+    /// for backtraces that were assembled some other way.
+    ///
+    /// [`push`]: Modules::push
+    pub fn dedup(&mut self) {
+        let mut list: LinkedList<ModuleEntry> = LinkedList::new();
+
+        while let Some(entry) = self.list.pop_front() {
+            match list.back_mut() {
+                Some(last) if last.rendered == entry.rendered => last.count += entry.count,
+                _ => list.push_back(entry),
+            }
+        }
+
+        self.list = list;
+    }
+
+    /// Move all modules of `other` into `self`.
+    ///
+    /// The modules of `other` are placed as if they had been pushed before
+    /// all modules of `self`. This is useful for evaluators that assemble a
+    /// backtrace from multiple sources. No deduplication is performed, see:
+    /// [`Modules::dedup`].
+    pub fn append(&mut self, other: &mut Self) {
+        self.list.append(&mut other.list);
     }
 
     /// Get an iterator over all modules in the backtrace.
@@ -149,6 +246,57 @@ impl Modules {
     }
 }
 
+/// Add modules in the order of [`Modules::iter`].
+///
+/// Each module is added after all modules already in the backtrace, as if it
+/// had been pushed before them. So collecting [`Modules::iter`] into a new
+/// [`Modules`] gives the same order back. Like with [`Modules::push`], a
+/// module that displays the same as the one before it increments its
+/// repetition count instead.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::error::Modules;
+/// let modules: Modules = ["main.json", "a.json", "a.json"].into_iter().collect();
+///
+/// let modules: Vec<String> = modules.iter().map(|x| x.to_string()).collect();
+/// assert_eq!(modules, ["main.json", "a.json (x2)"]);
+/// ```
+impl<D> Extend<D> for Modules
+where
+    D: Display + Send + Sync + 'static,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = D>,
+    {
+        for module in iter {
+            let rendered = module.to_string();
+
+            match self.list.back_mut() {
+                Some(last) if last.rendered == rendered => last.count += 1,
+                _ => self.list.push_back(ModuleEntry { rendered, count: 1 }),
+            }
+        }
+    }
+}
+
+/// Collects modules in the order of [`Modules::iter`], like [`Extend`].
+impl<D> FromIterator<D> for Modules
+where
+    D: Display + Send + Sync + 'static,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = D>,
+    {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
 impl Debug for Modules {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list()
@@ -159,7 +307,7 @@ impl Debug for Modules {
 
 /// Borrowing iterator for [`Modules`].
 pub struct ModulesIter<'a> {
-    iter: linked_list::Iter<'a, BoxedDisplay>,
+    iter: linked_list::Iter<'a, ModuleEntry>,
 }
 
 impl Debug for ModulesIter<'_> {
@@ -172,7 +320,7 @@ impl<'a> Iterator for ModulesIter<'a> {
     type Item = &'a (dyn Display + Send + Sync + 'static);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(AsRef::as_ref)
+        self.iter.next().map(|x| x as _)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -182,7 +330,7 @@ impl<'a> Iterator for ModulesIter<'a> {
 
 impl DoubleEndedIterator for ModulesIter<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.iter.next_back().map(AsRef::as_ref)
+        self.iter.next_back().map(|x| x as _)
     }
 }
 
@@ -194,9 +342,40 @@ impl ExactSizeIterator for ModulesIter<'_> {
 
 impl FusedIterator for ModulesIter<'_> {}
 
-/// The module backtrace.
+/// The path of a value.
+///
+/// Each component of the path is either the name of a field, added with
+/// [`Value::push`], or the position of an element of a sequence, added with
+/// [`Value::push_index`]. The path displays with names separated by `.` and
+/// positions attached to the component before them, like
+/// `'settings.items[3].count'`.
 pub struct Value {
-    list: LinkedList<BoxedDisplay>,
+    list: LinkedList<Component>,
+}
+
+/// A component of a [`Value`].
+enum Component {
+    Name(BoxedDisplay),
+    Index(Index),
+}
+
+impl Component {
+    fn as_display(&self) -> &(dyn Display + Send + Sync + 'static) {
+        match self {
+            Self::Name(x) => x.as_ref(),
+            Self::Index(x) => x,
+        }
+    }
+}
+
+/// The position of an element of a sequence, like `[3]`.
+#[derive(Clone, Copy)]
+struct Index(usize);
+
+impl Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.0)
+    }
 }
 
 impl Value {
@@ -222,7 +401,28 @@ impl Value {
     where
         D: Display + Send + Sync + 'static,
     {
-        self.list.push_front(Box::new(component));
+        self.list.push_front(Component::Name(Box::new(component)));
+    }
+
+    /// Push the position `i` of an element of a sequence.
+    ///
+    /// The component displays as `[i]` and is attached to the previous
+    /// component without a `.`, like `'items[3]'`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::error::Value;
+    /// let mut value = Value::new();
+    ///
+    /// value.push("name");
+    /// value.push_index(3);
+    /// value.push("items");
+    ///
+    /// assert_eq!(value.to_string(), "'items[3].name'");
+    /// ```
+    pub fn push_index(&mut self, i: usize) {
+        self.list.push_front(Component::Index(Index(i)));
     }
 
     /// Get an iterator over all components of the value.
@@ -253,14 +453,76 @@ impl Value {
     }
 }
 
+/// Add components in the order of [`Value::components`].
+///
+/// Each component is added after all components already in the path, as if
+/// it had been pushed before them. So collecting [`Value::components`] into a
+/// new [`Value`] gives the same path back.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::error::Value;
+/// let value: Value = ["server", "tls", "cert"].into_iter().collect();
+///
+/// assert_eq!(value.to_string(), "'server.tls.cert'");
+/// ```
+impl<D> Extend<D> for Value
+where
+    D: Display + Send + Sync + 'static,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = D>,
+    {
+        for component in iter {
+            self.list.push_back(Component::Name(Box::new(component)));
+        }
+    }
+}
+
+/// Collects components in the order of [`Value::components`], like [`Extend`].
+impl<D> FromIterator<D> for Value
+where
+    D: Display + Send + Sync + 'static,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = D>,
+    {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+/// Cloning a [`Value`] renders its components into [`String`]s.
+impl Clone for Value {
+    fn clone(&self) -> Self {
+        Self {
+            list: self
+                .list
+                .iter()
+                .map(|x| match x {
+                    Component::Name(x) => Component::Name(Box::new(x.to_string())),
+                    Component::Index(x) => Component::Index(*x),
+                })
+                .collect(),
+        }
+    }
+}
+
 impl Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "'")?;
 
-        let mut iter = self.components();
+        let mut iter = self.list.iter();
         if let Some(first) = iter.next() {
-            write!(f, "{first}")?;
-            iter.try_for_each(|x| write!(f, ".{x}"))?;
+            write!(f, "{}", first.as_display())?;
+            iter.try_for_each(|x| match x {
+                Component::Name(x) => write!(f, ".{x}"),
+                Component::Index(x) => write!(f, "{x}"),
+            })?;
         }
 
         write!(f, "'")?;
@@ -276,7 +538,7 @@ impl Display for Value {
 
 /// Borrowing iterator for [`Value`].
 pub struct Components<'a> {
-    iter: linked_list::Iter<'a, BoxedDisplay>,
+    iter: linked_list::Iter<'a, Component>,
 }
 
 impl Debug for Components<'_> {
@@ -289,7 +551,7 @@ impl<'a> Iterator for Components<'a> {
     type Item = &'a (dyn Display + Send + Sync + 'static);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(AsRef::as_ref)
+        self.iter.next().map(Component::as_display)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -299,7 +561,7 @@ impl<'a> Iterator for Components<'a> {
 
 impl DoubleEndedIterator for Components<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.iter.next_back().map(AsRef::as_ref)
+        self.iter.next_back().map(Component::as_display)
     }
 }
 
@@ -342,7 +604,34 @@ impl FusedIterator for Components<'_> {}
 /// information publically accessible. This way you can write another
 /// [`Display`] implementation that fits more inline with your vision.
 ///
+/// # IO errors
+///
+/// With the `std` feature, [`Error`] can be passed through interfaces that
+/// only speak [`std::io::Error`]. Converting an [`Error`] into an
+/// [`std::io::Error`] keeps the whole error as its inner error, so it can be
+/// recovered intact on the other side with [`Error::from_io_chain`].
+///
+/// The [`std::io::ErrorKind`] of the converted error is chosen as follows:
+///
+/// | [`ErrorKind`]                        | [`std::io::ErrorKind`]             |
+/// | ------------------------------------ | ---------------------------------- |
+/// | `Collision`                          | `InvalidData`                      |
+/// | `Cycle`                              | `InvalidInput`                     |
+/// | `Custom` created from an IO error    | the kind of the original IO error  |
+/// | any other `Custom`                   | `Other`                            |
+/// | `Multiple`                           | `Other`                            |
+///
+/// # Clone
+///
+/// Cloning an [`Error`] renders everything it holds into [`String`]s: the
+/// object of [`ErrorKind::Custom`], the note and the components of the
+/// value. So the clone displays exactly the same and has the same
+/// [`ErrorKind`], but the original objects are not kept. This makes it
+/// possible to store errors, for example to report them again later, without
+/// evaluating again. The [`source()`] of the error is not kept either.
+///
 /// [`Merge`]: crate::Merge
+/// [`source()`]: core::error::Error::source
 #[derive(Debug)]
 #[allow(clippy::manual_non_exhaustive)]
 pub struct Error {
@@ -363,6 +652,69 @@ pub struct Error {
     /// error. The path is stored as a list of components and can be accessed as
     /// an [`Iterator`].
     pub value: Value,
+
+    note: Option<Note>,
+
+    source: Option<BoxedError>,
+
+    #[cfg(feature = "std")]
+    io_kind: Option<std::io::ErrorKind>,
+}
+
+type BoxedError = Box<dyn core::error::Error + Send + Sync + 'static>;
+
+impl Clone for Error {
+    fn clone(&self) -> Self {
+        Self {
+            _priv: (),
+            kind: self.kind.clone(),
+            modules: self.modules.clone(),
+            value: self.value.clone(),
+            note: self.note.clone(),
+            source: None,
+            #[cfg(feature = "std")]
+            io_kind: self.io_kind,
+        }
+    }
+}
+
+/// Errors are compared by their [`ErrorKind`] only. The value and module
+/// traces, the note and the source are ignored, just like the object of
+/// [`ErrorKind::Custom`].
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::{Context, Error};
+/// let err = Err::<(), _>(Error::collision())
+///     .value("port")
+///     .module("config.json")
+///     .unwrap_err();
+///
+/// assert_eq!(err, Error::collision());
+/// assert_ne!(err, Error::cycle());
+/// ```
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl Eq for Error {}
+
+/// Additional information about an [`Error`].
+struct Note(BoxedDisplay);
+
+impl Clone for Note {
+    fn clone(&self) -> Self {
+        Self(Box::new(self.0.to_string()))
+    }
+}
+
+impl Debug for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.0)
+    }
 }
 
 impl From<ErrorKind> for Error {
@@ -393,20 +745,241 @@ impl Error {
         Self::with_kind(ErrorKind::Custom(Box::new(msg)))
     }
 
+    /// Raised when another error occurs while merging 2 values.
+    ///
+    /// This is like [`Error::custom`], but `error` is also kept as the
+    /// [`source()`] of the returned error, so error reporters can show the
+    /// whole chain of causes. The [`ErrorKind::Custom`] displays the same as
+    /// `error`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::Error;
+    /// use core::error::Error as _;
+    /// use core::fmt;
+    ///
+    /// let err = Error::custom_error(fmt::Error);
+    ///
+    /// assert!(err.kind.is_custom());
+    /// assert!(err.source().unwrap().is::<fmt::Error>());
+    /// ```
+    ///
+    /// [`source()`]: core::error::Error::source
+    pub fn custom_error<E>(error: E) -> Self
+    where
+        E: core::error::Error + Send + Sync + 'static,
+    {
+        let mut this = Self::custom(error.to_string());
+        this.source = Some(Box::new(error));
+        this
+    }
+
+    /// Create a new [`ErrorBuilder`].
+    ///
+    /// This is useful to create an error with all of its context at once,
+    /// instead of adding context to a failed [`Result`] with [`Context`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::{Error, ErrorKind};
+    /// let err = Error::builder()
+    ///     .kind(ErrorKind::Custom(Box::new("expected a version")))
+    ///     .value("version")
+    ///     .value("package")
+    ///     .module("Cargo.toml")
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "expected a version while evaluating 'package.version'\n\
+    ///     \n\
+    ///     \x20   in Cargo.toml\n"
+    /// );
+    /// ```
+    ///
+    /// [`Context`]: crate::merge::Context
+    pub fn builder() -> ErrorBuilder {
+        ErrorBuilder {
+            error: Self::collision(),
+        }
+    }
+
+    /// Attach `note` to the error.
+    ///
+    /// The note explains the error further and is displayed right after the
+    /// [`ErrorKind`], like `value collision (both priority 500)`. Attaching
+    /// another note replaces the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::Error;
+    /// let err = Error::collision().with_note("both priority 500");
+    ///
+    /// assert!(err.kind.is_collision());
+    /// assert_eq!(err.note().unwrap().to_string(), "both priority 500");
+    /// assert_eq!(err.to_string(), "value collision (both priority 500)\n");
+    /// ```
+    pub fn with_note<D>(mut self, note: D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        self.note = Some(Note(Box::new(note)));
+        self
+    }
+
+    /// Get the note attached to the error, if any.
+    ///
+    /// See: [`Error::with_note`].
+    pub fn note(&self) -> Option<&(dyn Display + Send + Sync + 'static)> {
+        self.note.as_ref().map(|x| x.0.as_ref())
+    }
+
+    /// Collapse all runs of consecutive modules in the backtrace that display
+    /// the same.
+    ///
+    /// See: [`Modules::dedup`].
+    pub fn dedup_modules(&mut self) {
+        self.modules.dedup();
+    }
+
+    /// Combine `self` and `other` into one error.
+    ///
+    /// The combined error is an [`ErrorKind::Multiple`] that holds both
+    /// errors, each with its own value and module traces. Combining an error
+    /// that is already combined adds to it instead of nesting, so errors can
+    /// be accumulated in a loop.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::{Context, Error};
+    /// let a = Err::<(), _>(Error::collision()).value("port").unwrap_err();
+    /// let b = Error::custom("missing value").with_note("set it in main.json");
+    ///
+    /// let err = a.and(b).and(Error::cycle());
+    ///
+    /// assert!(err.kind.is_multiple());
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "3 errors\n\
+    ///     \n\
+    ///     value collision while evaluating 'port'\n\
+    ///     \n\
+    ///     missing value (set it in main.json)\n\
+    ///     \n\
+    ///     cyclic imports\n"
+    /// );
+    /// ```
+    pub fn and(self, other: Self) -> Self {
+        let mut errors = self.into_errors();
+        errors.extend(other.into_errors());
+        Self::with_kind(ErrorKind::Multiple(errors))
+    }
+
+    /// Get the errors combined in `self`, or just `self` if it is not a bare
+    /// [`ErrorKind::Multiple`].
+    fn into_errors(self) -> Vec<Self> {
+        let bare = self.modules.is_empty() && self.value.is_empty() && self.note.is_none();
+
+        match self.kind {
+            ErrorKind::Multiple(errors) if bare => errors,
+            _ => alloc::vec![self],
+        }
+    }
+
     fn with_kind(kind: ErrorKind) -> Self {
         Self {
             _priv: (),
             kind,
             modules: Modules::new(),
             value: Value::new(),
+            note: None,
+            source: None,
+            #[cfg(feature = "std")]
+            io_kind: None,
         }
     }
 }
 
+/// A builder for [`Error`].
+///
+/// Context is added in the same order as with [`Context`]: values and modules
+/// from the innermost to the outermost. The [`ErrorKind`] is
+/// [`ErrorKind::Collision`] unless set otherwise.
+///
+/// See: [`Error::builder`].
+///
+/// [`Context`]: crate::merge::Context
+#[derive(Debug)]
+pub struct ErrorBuilder {
+    error: Error,
+}
+
+impl ErrorBuilder {
+    /// Set the [`ErrorKind`] of the error.
+    pub fn kind(mut self, kind: ErrorKind) -> Self {
+        self.error.kind = kind;
+        self
+    }
+
+    /// Add the name of a value.
+    ///
+    /// See: [`Value::push`].
+    pub fn value<D>(mut self, name: D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        self.error.value.push(name);
+        self
+    }
+
+    /// Add the position of an element of a sequence.
+    ///
+    /// See: [`Value::push_index`].
+    pub fn index(mut self, i: usize) -> Self {
+        self.error.value.push_index(i);
+        self
+    }
+
+    /// Add the name of a module.
+    ///
+    /// See: [`Modules::push`].
+    pub fn module<D>(mut self, name: D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        self.error.modules.push(name);
+        self
+    }
+
+    /// Attach a note.
+    ///
+    /// See: [`Error::with_note`].
+    pub fn note<D>(mut self, note: D) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        self.error = self.error.with_note(note);
+        self
+    }
+
+    /// Build the [`Error`].
+    pub fn build(self) -> Error {
+        self.error
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.kind)?;
 
+        if let Some(ref note) = self.note {
+            write!(f, " ({})", note.0)?;
+        }
+
         if !self.value.is_empty() {
             write!(f, " while evaluating {}", self.value)?;
         }
@@ -420,11 +993,208 @@ impl Display for Error {
             modules.try_for_each(|x| writeln!(f, "  from {x}"))?;
         }
 
+        if let ErrorKind::Multiple(ref errors) = self.kind {
+            errors.iter().try_for_each(|x| write!(f, "\n{x}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        self.source.as_deref().map(|x| x as _)
+    }
+}
+
+/// Many [`Error`]s.
+///
+/// This is returned by operations that keep going after an error, so that all
+/// errors can be reported at once.
+///
+/// # Display
+///
+/// Displays every error, in order, separated by an empty line.
+#[derive(Debug, Default)]
+pub struct MultiError {
+    errors: Vec<Error>,
+}
+
+impl MultiError {
+    /// Create a new empty [`MultiError`].
+    pub fn new() -> Self {
+        Self { errors: Vec::new() }
+    }
+
+    /// Get the number of errors.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Check whether there are no errors.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Add `error` to the end.
+    pub fn push(&mut self, error: Error) {
+        self.errors.push(error);
+    }
+
+    /// Get all errors, in the order they occurred.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Destruct the [`MultiError`] and get all errors.
+    pub fn into_errors(self) -> Vec<Error> {
+        self.errors
+    }
+}
+
+impl From<Vec<Error>> for MultiError {
+    fn from(errors: Vec<Error>) -> Self {
+        Self { errors }
+    }
+}
+
+impl From<Error> for MultiError {
+    fn from(error: Error) -> Self {
+        Self {
+            errors: alloc::vec![error],
+        }
+    }
+}
+
+impl IntoIterator for MultiError {
+    type Item = Error;
+    type IntoIter = alloc::vec::IntoIter<Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MultiError {
+    type Item = &'a Error;
+    type IntoIter = core::slice::Iter<'a, Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.iter()
+    }
+}
+
+impl Display for MultiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut errors = self.errors.iter();
+
+        if let Some(first) = errors.next() {
+            write!(f, "{first}")?;
+            errors.try_for_each(|x| write!(f, "\n{x}"))?;
+        }
+
         Ok(())
     }
 }
 
-impl core::error::Error for Error {}
+impl core::error::Error for MultiError {}
+
+#[cfg(feature = "std")]
+mod io_impls {
+    use super::{Error, ErrorKind};
+    use std::io;
+
+    impl Error {
+        /// Recover an [`Error`] from an [`io::Error`].
+        ///
+        /// This follows the chain of [`io::Error`]s nested inside `error`. If
+        /// the chain ends in an [`Error`], that [`Error`] is returned intact,
+        /// along with its value and module traces. Otherwise, `error` is
+        /// wrapped in a [`ErrorKind::Custom`] and its kind is recorded, see:
+        /// [`Error::io_kind`].
+        ///
+        /// See: [IO errors](Error#io-errors).
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # use module::merge::{Context, Error};
+        /// use std::io;
+        ///
+        /// fn load() -> io::Result<Vec<u8>> {
+        ///     let r: Result<_, Error> = Err(Error::collision()).value("port");
+        ///     Ok(r?)
+        /// }
+        ///
+        /// let err = Error::from_io_chain(load().unwrap_err());
+        /// assert!(err.kind.is_collision());
+        /// assert_eq!(err.value.to_string(), "'port'");
+        /// ```
+        pub fn from_io_chain(error: io::Error) -> Self {
+            let kind = error.kind();
+
+            // `into_inner` consumes `error`, so check first whether it is
+            // worth it. Otherwise, the message of `error` would be lost.
+            let nested = error
+                .get_ref()
+                .is_some_and(|x| x.is::<Self>() || x.is::<io::Error>());
+
+            if !nested {
+                return Self::io(kind, error);
+            }
+
+            let Some(inner) = error.into_inner() else {
+                return Self::io(kind, kind.into());
+            };
+
+            match inner.downcast::<Self>() {
+                Ok(x) => *x,
+                Err(inner) => match inner.downcast::<io::Error>() {
+                    Ok(x) => Self::from_io_chain(*x),
+                    Err(inner) => Self::io(kind, io::Error::new(kind, inner)),
+                },
+            }
+        }
+
+        /// Get the [`io::ErrorKind`] of the [`io::Error`] this error was
+        /// created from.
+        ///
+        /// Returns [`None`] if this error was not created from an
+        /// [`io::Error`].
+        ///
+        /// See: [`Error::from_io_chain`].
+        pub fn io_kind(&self) -> Option<io::ErrorKind> {
+            self.io_kind
+        }
+
+        fn io(kind: io::ErrorKind, error: io::Error) -> Self {
+            let mut this = Self::custom_error(error);
+            this.io_kind = Some(kind);
+            this
+        }
+    }
+
+    impl From<io::Error> for Error {
+        /// See: [`Error::from_io_chain`].
+        fn from(error: io::Error) -> Self {
+            Self::from_io_chain(error)
+        }
+    }
+
+    impl From<Error> for io::Error {
+        fn from(error: Error) -> Self {
+            let kind = match (&error.kind, error.io_kind) {
+                (ErrorKind::Collision, _) => io::ErrorKind::InvalidData,
+                (ErrorKind::Cycle, _) => io::ErrorKind::InvalidInput,
+                (ErrorKind::Custom(_), Some(kind)) => kind,
+                (ErrorKind::Custom(_), None) => io::ErrorKind::Other,
+                (ErrorKind::Multiple(_), _) => io::ErrorKind::Other,
+            };
+
+            io::Error::new(kind, error)
+        }
+    }
+}
 
 struct DisplayToDebug<T>(T);
 
@@ -436,3 +1206,317 @@ where
         self.0.fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::merge::Context;
+    use alloc::vec::Vec;
+
+    fn modules(err: &Error) -> Vec<String> {
+        err.modules.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_push_repeated() {
+        let mut err = Error::collision();
+        err.modules.push("c.json");
+        err.modules.push("b.json");
+        err.modules.push("b.json");
+        err.modules.push("a.json");
+        err.modules.push("b.json");
+
+        assert_eq!(
+            modules(&err),
+            &["b.json", "a.json", "b.json (x2)", "c.json"]
+        );
+    }
+
+    #[test]
+    fn test_retry_loop() {
+        fn attempt() -> Result<(), Error> {
+            Err(Error::collision()).value("port")
+        }
+
+        let mut r = attempt().module("user.json");
+        for _ in 0..3 {
+            r = r.or_else(|_| attempt().module("user.json"));
+            r = r.module("user.json");
+        }
+        let err = r.module("config.json").unwrap_err();
+
+        assert_eq!(err.modules.len(), 2);
+        assert_eq!(
+            err.to_string(),
+            r#"value collision while evaluating 'port'
+
+    in user.json (x2)
+  from config.json
+"#
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut inner = Modules::new();
+        inner.push("c.json");
+        inner.push("b.json");
+
+        let mut err = Error::collision();
+        err.modules.push("b.json");
+        err.modules.push("b.json");
+        err.modules.push("a.json");
+        err.modules.append(&mut inner);
+
+        assert_eq!(
+            modules(&err),
+            &["a.json", "b.json (x2)", "b.json", "c.json"]
+        );
+
+        err.dedup_modules();
+        assert_eq!(modules(&err), &["a.json", "b.json (x3)", "c.json"]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_round_trip() {
+        use std::io;
+
+        fn load() -> io::Result<Vec<u8>> {
+            let r: Result<_, Error> = Err(Error::collision())
+                .value("port")
+                .value("http")
+                .value("services")
+                .module("user.json")
+                .module("config.json");
+
+            Ok(r?)
+        }
+
+        let err = load().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Pass it through another layer of `io::Error`.
+        let err = io::Error::other(err);
+
+        let err = Error::from_io_chain(err);
+        assert!(err.kind.is_collision());
+        assert_eq!(err.value.to_string(), "'services.http.port'");
+        assert_eq!(modules(&err), &["config.json", "user.json"]);
+        assert_eq!(err.io_kind(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_kind() {
+        use std::io;
+
+        let err = Error::from_io_chain(io::Error::new(io::ErrorKind::NotFound, "no config"));
+        assert!(err.kind.is_custom());
+        assert_eq!(err.kind.to_string(), "no config");
+        assert_eq!(err.io_kind(), Some(io::ErrorKind::NotFound));
+        assert!(core::error::Error::source(&err).unwrap().is::<io::Error>());
+
+        let mut err = err;
+        err.modules.push("config.json");
+        let err = io::Error::from(err);
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let err = Error::from(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(err.io_kind(), Some(io::ErrorKind::PermissionDenied));
+
+        let kind = |err: Error| io::Error::from(err).kind();
+        assert_eq!(kind(Error::cycle()), io::ErrorKind::InvalidInput);
+        assert_eq!(kind(Error::custom("x")), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn test_multi_error_display() {
+        let mut errors = MultiError::new();
+        assert!(errors.is_empty());
+        assert_eq!(errors.to_string(), "");
+
+        errors.push(Err::<(), _>(Error::collision()).value("port").unwrap_err());
+        errors.push(Err::<(), _>(Error::collision()).value("host").unwrap_err());
+        assert_eq!(errors.len(), 2);
+
+        assert_eq!(
+            errors.to_string(),
+            r#"value collision while evaluating 'port'
+
+value collision while evaluating 'host'
+"#
+        );
+    }
+
+    #[test]
+    fn test_and() {
+        let a = Err::<(), _>(Error::collision())
+            .value("port")
+            .module("a.json")
+            .unwrap_err();
+        let b = Err::<(), _>(Error::custom("bad host"))
+            .value("host")
+            .unwrap_err();
+
+        let err = a.and(b);
+        assert!(err.kind.is_multiple());
+        assert_eq!(
+            err.to_string(),
+            r#"2 errors
+
+value collision while evaluating 'port'
+
+    in a.json
+
+bad host while evaluating 'host'
+"#
+        );
+
+        // Combined errors are flattened, unless they have traces of their own.
+        let err = err.and(Error::cycle());
+        let ErrorKind::Multiple(ref errors) = err.kind else {
+            panic!("expected multiple errors");
+        };
+        assert_eq!(errors.len(), 3);
+
+        let err = Err::<(), _>(err).module("main.json").unwrap_err();
+        let err = err.and(Error::cycle());
+        let ErrorKind::Multiple(ref errors) = err.kind else {
+            panic!("expected multiple errors");
+        };
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].kind.is_multiple());
+    }
+
+    #[test]
+    fn test_from_iter_round_trip() {
+        let mut err = Error::collision();
+        err.modules.push("c.json");
+        err.modules.push("b.json");
+        err.modules.push("a.json");
+        err.value.push("port");
+        err.value.push("server");
+
+        let copy: Modules = modules(&err).into_iter().collect();
+        assert_eq!(copy.len(), 3);
+        assert_eq!(
+            copy.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+            modules(&err)
+        );
+
+        let copy: Value = err.value.components().map(|x| x.to_string()).collect();
+        assert_eq!(copy.to_string(), "'server.port'");
+        assert_eq!(copy.to_string(), err.value.to_string());
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut modules = Modules::new();
+        modules.push("b.json");
+        modules.push("a.json");
+        modules.extend(["b.json", "c.json"]);
+
+        let modules: Vec<String> = modules.iter().map(|x| x.to_string()).collect();
+        assert_eq!(modules, ["a.json", "b.json (x2)", "c.json"]);
+
+        let mut value = Value::new();
+        value.push("server");
+        value.extend(["tls", "cert"]);
+        assert_eq!(value.to_string(), "'server.tls.cert'");
+    }
+
+    #[test]
+    fn test_builder() {
+        let built = Error::builder()
+            .kind(ErrorKind::Cycle)
+            .value("port")
+            .index(1)
+            .value("servers")
+            .module("b.json")
+            .module("a.json")
+            .note("note")
+            .build();
+
+        let chained = Err::<(), _>(Error::cycle().with_note("note"))
+            .value("port")
+            .index(1)
+            .value("servers")
+            .module("b.json")
+            .module("a.json")
+            .unwrap_err();
+
+        assert_eq!(built, chained);
+        assert_eq!(built.to_string(), chained.to_string());
+        assert!(Error::builder().build().kind.is_collision());
+    }
+
+    #[test]
+    fn test_eq() {
+        let a = Err::<(), _>(Error::collision())
+            .value("port")
+            .module("a.json")
+            .unwrap_err();
+        let b = Err::<(), _>(Error::collision().with_note("both priority 500"))
+            .value("host")
+            .module("b.json")
+            .unwrap_err();
+
+        assert_eq!(a, b);
+        assert_eq!(Error::custom("a"), Error::custom("b"));
+        assert_ne!(a, Error::cycle());
+    }
+
+    #[test]
+    fn test_clone() {
+        let err = Err::<(), _>(Error::custom(42).with_note("note"))
+            .value("port")
+            .value("http")
+            .module("a.json")
+            .module("a.json")
+            .unwrap_err();
+
+        let clone = err.clone();
+        assert_eq!(clone.kind, err.kind);
+        assert_eq!(clone.kind, ErrorKind::Custom(Box::new("other")));
+        assert_eq!(clone.to_string(), err.to_string());
+        assert_eq!(clone.note().unwrap().to_string(), "note");
+        assert_eq!(modules(&clone), ["a.json (x2)"]);
+        assert_eq!(format!("{clone:?}"), format!("{err:?}"));
+    }
+
+    #[test]
+    fn test_index() {
+        let err = Err::<(), _>(Error::collision())
+            .index(1)
+            .value("port")
+            .index(0)
+            .value("services")
+            .unwrap_err();
+
+        assert_eq!(err.value.to_string(), "'services[0].port[1]'");
+        assert_eq!(err.clone().value.to_string(), "'services[0].port[1]'");
+
+        let components: Vec<String> = err.value.components().map(|x| x.to_string()).collect();
+        assert_eq!(components, ["services", "[0]", "port", "[1]"]);
+
+        let err = Err::<(), _>(Error::collision()).index(2).unwrap_err();
+        assert_eq!(err.value.to_string(), "'[2]'");
+    }
+
+    #[test]
+    fn test_source() {
+        use core::error::Error as _;
+
+        let err = Error::custom_error(fmt::Error);
+        assert_eq!(
+            err.to_string(),
+            "an error occurred when formatting an argument\n"
+        );
+        assert!(err.source().unwrap().is::<fmt::Error>());
+
+        assert!(Error::custom("oops").source().is_none());
+        assert!(err.clone().source().is_none());
+    }
+}
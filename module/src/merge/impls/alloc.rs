@@ -2,7 +2,7 @@ use core::cmp::Ord;
 use core::fmt::Display;
 
 use alloc::boxed::Box;
-use alloc::collections::{BTreeMap, BTreeSet, LinkedList};
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
 use alloc::vec::Vec;
 
 use super::prelude::*;
@@ -13,8 +13,14 @@ unmergeable! {
     alloc::string::String
 }
 
+/// Concatenates the slices, like [`Vec`].
 impl<T> Merge for Box<[T]> {
-    unmergeable!();
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        let mut x = core::mem::take(self).into_vec();
+        x.append(&mut other.into_vec());
+        *self = x.into_boxed_slice();
+        Ok(())
+    }
 }
 
 impl<T> Merge for alloc::borrow::Cow<'_, T>
@@ -44,6 +50,29 @@ where
     }
 }
 
+/// Merges into the value behind `self`, cloning it first if it is shared. The
+/// value of `other` is cloned too, unless `other` is its only owner.
+impl<T> Merge for alloc::rc::Rc<T>
+where
+    T: Merge + Clone,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        Self::make_mut(self).merge_ref(Self::unwrap_or_clone(other))
+    }
+}
+
+/// Merges into the value behind `self`, cloning it first if it is shared. The
+/// value of `other` is cloned too, unless `other` is its only owner.
+#[cfg(target_has_atomic = "ptr")]
+impl<T> Merge for alloc::sync::Arc<T>
+where
+    T: Merge + Clone,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        Self::make_mut(self).merge_ref(Self::unwrap_or_clone(other))
+    }
+}
+
 impl<T> Merge for Vec<T> {
     fn merge_ref(&mut self, mut other: Self) -> Result<(), Error> {
         self.append(&mut other);
@@ -51,6 +80,13 @@ impl<T> Merge for Vec<T> {
     }
 }
 
+impl<T> Merge for VecDeque<T> {
+    fn merge_ref(&mut self, mut other: Self) -> Result<(), Error> {
+        self.append(&mut other);
+        Ok(())
+    }
+}
+
 impl<T> Merge for LinkedList<T> {
     fn merge_ref(&mut self, mut other: Self) -> Result<(), Error> {
         self.append(&mut other);
@@ -58,6 +94,16 @@ impl<T> Merge for LinkedList<T> {
     }
 }
 
+impl<T> Merge for BinaryHeap<T>
+where
+    T: Ord,
+{
+    fn merge_ref(&mut self, mut other: Self) -> Result<(), Error> {
+        self.append(&mut other);
+        Ok(())
+    }
+}
+
 impl<K, V> Merge for BTreeMap<K, V>
 where
     K: Ord + Display,
@@ -107,6 +153,41 @@ mod tests {
         assert!((*c).0);
     }
 
+    #[test]
+    fn test_boxed_slice() {
+        let a: Box<[i32]> = Box::new([1, 2]);
+        let b: Box<[i32]> = Box::new([3]);
+
+        let c = a.merge(b).unwrap();
+        assert_eq!(c, Box::from([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_rc() {
+        use alloc::rc::Rc;
+
+        let a = Rc::new(vec![1, 2]);
+        let shared = Rc::clone(&a);
+        let b = Rc::new(vec![3]);
+
+        let c = a.merge(b).unwrap();
+        assert_eq!(*c, [1, 2, 3]);
+        assert_eq!(*shared, [1, 2], "shared value should not change");
+    }
+
+    #[test]
+    fn test_arc() {
+        use alloc::sync::Arc;
+
+        let a = Arc::new(vec![1, 2]);
+        let b = Arc::new(vec![3]);
+        let shared = Arc::clone(&b);
+
+        let c = a.merge(b).unwrap();
+        assert_eq!(*c, [1, 2, 3]);
+        assert_eq!(*shared, [3]);
+    }
+
     #[test]
     fn test_vec() {
         use alloc::vec;
@@ -118,6 +199,15 @@ mod tests {
         assert_eq!(c, &[1, 2, 5, 7, 0, 2, 8, 9, 10]);
     }
 
+    #[test]
+    fn test_vec_deque() {
+        let a: VecDeque<i32> = [1, 2, 5, 7, 0].into_iter().collect();
+        let b: VecDeque<i32> = [2, 8, 9, 10].into_iter().collect();
+
+        let c = a.merge(b).unwrap();
+        assert!(c.iter().eq(&[1, 2, 5, 7, 0, 2, 8, 9, 10]));
+    }
+
     #[test]
     fn test_linked_list() {
         let a: LinkedList<i32> = [1, 2, 5, 7, 0].into_iter().collect();
@@ -167,4 +257,13 @@ mod tests {
         c.sort_unstable();
         assert_eq!(c, &[0, 1, 2, 5, 7, 8, 9, 10]);
     }
+
+    #[test]
+    fn test_binary_heap() {
+        let a: BinaryHeap<i32> = [1, 2, 5, 7, 0, 10].into_iter().collect();
+        let b: BinaryHeap<i32> = [2, 8, 9, 10, 5].into_iter().collect();
+
+        let c = a.merge(b).unwrap().into_sorted_vec();
+        assert_eq!(c, &[0, 1, 2, 2, 5, 5, 7, 8, 9, 10, 10]);
+    }
 }
@@ -11,13 +11,27 @@ unmergeable! {
     core::net::IpAddr, core::net::Ipv4Addr, core::net::Ipv6Addr,
     core::num::NonZeroI8, core::num::NonZeroI16, core::num::NonZeroI32, core::num::NonZeroI64, core::num::NonZeroIsize,
     core::num::NonZeroU8, core::num::NonZeroU16, core::num::NonZeroU32, core::num::NonZeroU64, core::num::NonZeroUsize,
-    core::num::Saturating<i8>, core::num::Saturating<i16>, core::num::Saturating<i32>, core::num::Saturating<i64>, core::num::Saturating<isize>,
-    core::num::Saturating<u8>, core::num::Saturating<u16>, core::num::Saturating<u32>, core::num::Saturating<u64>, core::num::Saturating<usize>,
     core::net::SocketAddr, core::net::SocketAddrV4, core::net::SocketAddrV6,
     core::num::Wrapping<i8>, core::num::Wrapping<i16>, core::num::Wrapping<i32>, core::num::Wrapping<i64>, core::num::Wrapping<isize>,
     core::num::Wrapping<u8>, core::num::Wrapping<u16>, core::num::Wrapping<u32>, core::num::Wrapping<u64>, core::num::Wrapping<usize>
 }
 
+/// Adds the 2 values, saturating at the numeric bounds.
+///
+/// [`Wrapping`] is unmergeable, because wrapping around is rarely what is
+/// meant when merging configuration.
+///
+/// [`Wrapping`]: core::num::Wrapping
+impl<T> Merge for core::num::Saturating<T>
+where
+    Self: core::ops::AddAssign,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        *self += other;
+        Ok(())
+    }
+}
+
 impl Merge for () {
     fn merge(self, _other: Self) -> Result<Self, Error> {
         Ok(())
@@ -63,6 +77,26 @@ impl<T, E> Merge for core::result::Result<T, E> {
     unmergeable!();
 }
 
+/// Merges the 2 values when both are [`Some`].
+///
+/// For values that cannot be merged, like paths, this collides. To keep the
+/// first [`Some`] instead, use [`Coalesce`], which does not need `T` to
+/// implement [`Merge`].
+///
+/// ```rust
+/// # use module::merge::Merge;
+/// # use module::types::Coalesce;
+/// use std::path::PathBuf;
+///
+/// let a = Some(PathBuf::from("/etc/app"));
+/// let b = Some(PathBuf::from("/opt/app"));
+/// assert!(a.clone().merge(b.clone()).unwrap_err().kind.is_collision());
+///
+/// let merged = Coalesce(a).merge(Coalesce(b)).unwrap();
+/// assert_eq!(merged.into_option().unwrap(), PathBuf::from("/etc/app"));
+/// ```
+///
+/// [`Coalesce`]: crate::types::Coalesce
 impl<T> Merge for Option<T>
 where
     T: Merge,
@@ -79,9 +113,52 @@ where
     }
 }
 
+impl<T, const N: usize> Merge for [T; N]
+where
+    T: Merge,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        self.iter_mut()
+            .zip(other)
+            .enumerate()
+            .try_for_each(|(i, (a, b))| a.merge_ref(b).index(i))
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($T:ident $idx:tt),+) => {
+        impl<$($T),+> Merge for ($($T,)+)
+        where
+            $($T: Merge,)+
+        {
+            fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+                $(self.$idx.merge_ref(other.$idx).index($idx)?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_tuple!(T0 0);
+impl_tuple!(T0 0, T1 1);
+impl_tuple!(T0 0, T1 1, T2 2);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);
+
 #[cfg(test)]
 mod tests {
     use crate::test::*;
+    use crate::types::Overridable;
+
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
 
     #[test]
     fn test_option() {
@@ -93,4 +170,65 @@ mod tests {
         assert_eq!(Some(42).merge(None).unwrap(), Some(42));
         assert_eq!(Option::<i32>::None.merge(None).unwrap(), None);
     }
+
+    #[test]
+    fn test_saturating() {
+        use core::num::{Saturating, Wrapping};
+
+        assert_eq!(Saturating(1u8).merge(Saturating(2)).unwrap(), Saturating(3));
+        assert_eq!(
+            Saturating(u8::MAX).merge(Saturating(10)).unwrap(),
+            Saturating(u8::MAX)
+        );
+        assert_eq!(
+            Saturating(i32::MIN).merge(Saturating(-1)).unwrap(),
+            Saturating(i32::MIN)
+        );
+        assert_eq!(
+            Wrapping(1u8).merge(Wrapping(2)).unwrap_err().kind,
+            ErrorKind::Collision
+        );
+    }
+
+    #[test]
+    fn test_array() {
+        let x = |p: [isize; 3]| p.map(|p| Overridable::with_priority(p, p));
+
+        let merged: [Overridable<isize>; 3] = x([1, 5, 3]).merge(x([2, 4, 6])).unwrap();
+        assert_eq!(merged.map(|x| *x), [1, 4, 3]);
+
+        let err = x([1, 5, 3]).merge(x([2, 4, 3])).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+        assert_eq!(
+            err.value
+                .components()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>(),
+            ["[2]"]
+        );
+
+        assert!(<[i32; 0]>::merge([], []).is_ok());
+    }
+
+    #[test]
+    fn test_tuple() {
+        let merged = (vec![1], Some(2), Merged(false))
+            .merge((vec![3], None, Merged(false)))
+            .unwrap();
+        assert_eq!(merged.0, [1, 3]);
+        assert_eq!(merged.1, Some(2));
+        assert!(merged.2.0);
+
+        let err = (vec![1], Some(2), Merged(false))
+            .merge((vec![3], Some(4), Merged(false)))
+            .unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+        assert_eq!(
+            err.value
+                .components()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>(),
+            ["[1]"]
+        );
+    }
 }
@@ -0,0 +1,120 @@
+use core::cmp::Eq;
+use core::fmt::Display;
+use core::hash::{BuildHasher, Hash};
+
+use indexmap::{IndexMap, IndexSet};
+
+use super::prelude::*;
+
+/// Merges the values of keys present in both maps, in place. Keys only in
+/// `other` are added at the end, in their order in `other`.
+impl<K, V, S> Merge for IndexMap<K, V, S>
+where
+    K: Eq + Hash + Display,
+    V: Merge,
+    S: BuildHasher,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        use indexmap::map::Entry;
+
+        for (k, b) in other {
+            match self.entry(k) {
+                Entry::Vacant(x) => {
+                    x.insert(b);
+                }
+                Entry::Occupied(mut x) => {
+                    let r = x.get_mut().merge_ref(b);
+                    r.with_value(|| format!("\"{}\"", x.key()))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds the values only in `other` at the end, in their order in `other`.
+impl<T, S> Merge for IndexSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        self.extend(other);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use core::hash::BuildHasherDefault;
+
+    #[derive(Default)]
+    struct Hasher(u64);
+
+    impl core::hash::Hasher for Hasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for x in bytes {
+                self.0 = self.0.wrapping_mul(31).wrapping_add(u64::from(*x));
+            }
+        }
+    }
+
+    type Map<K, V> = IndexMap<K, V, BuildHasherDefault<Hasher>>;
+    type Set<T> = IndexSet<T, BuildHasherDefault<Hasher>>;
+
+    #[test]
+    fn test_index_map() {
+        fn from_keys(keys: &[&'static str]) -> Map<&'static str, Merged> {
+            keys.iter()
+                .copied()
+                .map(|k| (k, Merged::default()))
+                .collect()
+        }
+
+        let a = from_keys(&["key3", "key1", "key2"]);
+        let b = from_keys(&["key5", "key1", "key4", "key3"]);
+
+        let c = a.merge(b).unwrap();
+
+        let expected = [
+            ("key3", true),
+            ("key1", true),
+            ("key2", false),
+            ("key5", false),
+            ("key4", false),
+        ];
+
+        let c: Vec<_> = c.into_iter().map(|(k, v)| (k, v.0)).collect();
+        assert_eq!(c, expected);
+    }
+
+    #[test]
+    fn test_index_map_collision() {
+        let a: Map<&str, i32> = [("port", 1)].into_iter().collect();
+        let b: Map<&str, i32> = [("port", 2)].into_iter().collect();
+
+        let err = a.merge(b).unwrap_err();
+        assert!(err.kind.is_collision());
+        assert_eq!(err.value.to_string(), "'\"port\"'");
+    }
+
+    #[test]
+    fn test_index_set() {
+        let a: Set<i32> = [7, 1, 5].into_iter().collect();
+        let b: Set<i32> = [2, 5, 9, 7].into_iter().collect();
+
+        let c: Vec<i32> = a.merge(b).unwrap().into_iter().collect();
+        assert_eq!(c, &[7, 1, 5, 2, 9]);
+    }
+}
@@ -0,0 +1,96 @@
+use serde_json::{Map, Value};
+
+use alloc::string::String;
+
+use super::prelude::*;
+
+/// Merges objects recursively and concatenates arrays. `null` merged with
+/// anything is the other value. Any other values collide, as do values of
+/// different types.
+impl Merge for Value {
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        match (self, other) {
+            (Value::Object(a), Value::Object(b)) => a.merge_ref(b),
+            (Value::Array(a), Value::Array(b)) => a.merge_ref(b),
+            (_, Value::Null) => Ok(()),
+            (a @ Value::Null, b) => {
+                *a = b;
+                Ok(())
+            }
+            _ => Err(Error::collision()),
+        }
+    }
+}
+
+/// Merges the values of keys present in both objects. Keys only in `other`
+/// are added.
+impl Merge for Map<String, Value> {
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        for (k, b) in other {
+            match self.get_mut(&k) {
+                Some(a) => a.merge_ref(b).with_value(|| format!("\"{k}\""))?,
+                None => {
+                    self.insert(k, b);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+    use serde_json::json;
+
+    #[test]
+    fn test_object() {
+        let a = json!({
+            "name": "a",
+            "server": { "host": "localhost", "tls": { "cert": "a.pem" } },
+        });
+        let b = json!({
+            "server": { "port": 8080, "tls": { "key": "a.key" } },
+        });
+
+        let expected = json!({
+            "name": "a",
+            "server": {
+                "host": "localhost",
+                "port": 8080,
+                "tls": { "cert": "a.pem", "key": "a.key" },
+            },
+        });
+
+        assert_eq!(a.merge(b).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_array() {
+        let a = json!({ "items": [1, 2] });
+        let b = json!({ "items": [3, "four"] });
+
+        assert_eq!(a.merge(b).unwrap(), json!({ "items": [1, 2, 3, "four"] }));
+    }
+
+    #[test]
+    fn test_null() {
+        assert_eq!(json!(null).merge(json!(1)).unwrap(), json!(1));
+        assert_eq!(json!(1).merge(json!(null)).unwrap(), json!(1));
+    }
+
+    #[test]
+    fn test_collision() {
+        let err = json!({ "server": { "port": 1 } })
+            .merge(json!({ "server": { "port": 1 } }))
+            .unwrap_err();
+        assert!(err.kind.is_collision());
+        assert_eq!(err.value.to_string(), "'\"server\".\"port\"'");
+
+        let err = json!([1]).merge(json!({ "y": 1 })).unwrap_err();
+        assert!(err.kind.is_collision());
+    }
+}
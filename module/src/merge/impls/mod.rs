@@ -4,6 +4,15 @@ mod core;
 #[cfg(feature = "std")]
 mod std;
 
+#[cfg(feature = "indexmap")]
+mod indexmap;
+
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg(feature = "toml")]
+mod toml;
+
 mod prelude {
     pub(super) use crate::{Context, Error, Merge};
 
@@ -0,0 +1,103 @@
+use toml::{Table, Value};
+
+use super::prelude::*;
+
+/// Merges tables recursively and concatenates arrays. Any other values
+/// collide, as do values of different types.
+impl Merge for Value {
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        match (self, other) {
+            (Value::Table(a), Value::Table(b)) => a.merge_ref(b),
+            (Value::Array(a), Value::Array(b)) => a.merge_ref(b),
+            _ => Err(Error::collision()),
+        }
+    }
+}
+
+/// Merges the values of keys present in both tables. Keys only in `other` are
+/// added.
+impl Merge for Table {
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        for (k, b) in other {
+            match self.get_mut(&k) {
+                Some(a) => a.merge_ref(b).with_value(|| format!("\"{k}\""))?,
+                None => {
+                    self.insert(k, b);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    fn parse(s: &str) -> Value {
+        Value::Table(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_table() {
+        let a = parse(
+            r#"
+            name = "a"
+
+            [server]
+            host = "localhost"
+
+            [server.tls]
+            cert = "a.pem"
+            "#,
+        );
+        let b = parse(
+            r#"
+            [server]
+            port = 8080
+
+            [server.tls]
+            key = "a.key"
+            "#,
+        );
+
+        let expected = parse(
+            r#"
+            name = "a"
+
+            [server]
+            host = "localhost"
+            port = 8080
+
+            [server.tls]
+            cert = "a.pem"
+            key = "a.key"
+            "#,
+        );
+
+        assert_eq!(a.merge(b).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_array() {
+        let a = parse("items = [1, 2]");
+        let b = parse(r#"items = [3, "four"]"#);
+
+        assert_eq!(a.merge(b).unwrap(), parse(r#"items = [1, 2, 3, "four"]"#));
+    }
+
+    #[test]
+    fn test_collision() {
+        let err = parse("[server]\nport = 1")
+            .merge(parse("[server]\nport = 1"))
+            .unwrap_err();
+        assert!(err.kind.is_collision());
+        assert_eq!(err.value.to_string(), "'\"server\".\"port\"'");
+
+        let err = parse("x = [1]").merge(parse("x = { y = 1 }")).unwrap_err();
+        assert!(err.kind.is_collision());
+    }
+}
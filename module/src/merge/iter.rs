@@ -1,4 +1,5 @@
 use super::cell::MergeCell;
+use super::reserve::ReserveHint;
 use super::{Error, Merge};
 
 /// Extension trait for [`Iterator`].
@@ -57,6 +58,92 @@ pub trait IteratorExt: Iterator {
     where
         Self::Item: Merge,
         Self: Sized;
+
+    /// Takes an iterator and merges its items into `base`.
+    ///
+    /// Each item is merged into `base` with [`Merge::merge_ref`], in order.
+    /// Merging stops at the first error. Items merged before the error stay
+    /// merged into `base`.
+    ///
+    /// This is useful when there already is a value to start from, so there
+    /// is no empty case to handle like with [`try_merge()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::IteratorExt;
+    /// // Loaded from the config file.
+    /// let mut paths = vec!["/usr/share/app".to_owned()];
+    ///
+    /// // Given on the command line.
+    /// let args = ["--path", "/opt/app", "--path", "~/.app"];
+    /// let overrides = args
+    ///     .chunks(2)
+    ///     .map(|x| vec![x[1].to_owned()]);
+    ///
+    /// overrides.merge_into(&mut paths).unwrap();
+    ///
+    /// assert_eq!(paths, ["/usr/share/app", "/opt/app", "~/.app"]);
+    /// ```
+    ///
+    /// [`try_merge()`]: Self::try_merge
+    fn merge_into(self, base: &mut Self::Item) -> Result<(), Error>
+    where
+        Self::Item: Merge,
+        Self: Sized;
+
+    /// Takes an iterator and merges together its items, reserving all needed
+    /// capacity upfront.
+    ///
+    /// This produces the exact same result as [`try_merge()`]. It is useful
+    /// when merging many small values, where repeatedly growing the
+    /// accumulator would otherwise dominate.
+    ///
+    /// See: [`ReserveHint`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::IteratorExt;
+    /// # use module::types::Lines;
+    /// let iter = (0..3).map(|x| Lines::new(format!("line{x}")));
+    ///
+    /// let merged = iter.try_merge_reserved().unwrap().unwrap();
+    ///
+    /// assert_eq!(*merged, "line0\nline1\nline2");
+    /// ```
+    ///
+    /// [`try_merge()`]: Self::try_merge
+    fn try_merge_reserved(self) -> Option<Result<Self::Item, Error>>
+    where
+        Self::Item: Merge + ReserveHint,
+        Self: Sized;
+
+    /// Takes an iterator and merges together its items with `f`.
+    ///
+    /// This is like [`try_merge()`], but the items are merged with `f`
+    /// instead of their [`Merge`] implementation. It works like
+    /// [`Iterator::reduce`], except that `f` can fail. Merging stops at the
+    /// first error.
+    ///
+    /// Returns [`None`] if the iterator yields no items.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::merge::{Error, IteratorExt};
+    /// let sum = [1, 2, 3, 4]
+    ///     .into_iter()
+    ///     .merge_by(|a: i32, b| a.checked_add(b).ok_or_else(|| Error::custom("overflow")));
+    ///
+    /// assert_eq!(sum.unwrap().unwrap(), 10);
+    /// ```
+    ///
+    /// [`try_merge()`]: Self::try_merge
+    fn merge_by<F>(self, f: F) -> Option<Result<Self::Item, Error>>
+    where
+        F: FnMut(Self::Item, Self::Item) -> Result<Self::Item, Error>,
+        Self: Sized;
 }
 
 impl<I> IteratorExt for I
@@ -85,4 +172,31 @@ where
 
         cell.try_finish()
     }
+
+    fn merge_into(mut self, base: &mut Self::Item) -> Result<(), Error>
+    where
+        Self::Item: Merge,
+        Self: Sized,
+    {
+        self.try_for_each(|x| base.merge_ref(x))
+    }
+
+    fn try_merge_reserved(self) -> Option<Result<Self::Item, Error>>
+    where
+        Self::Item: Merge + ReserveHint,
+        Self: Sized,
+    {
+        let mut cell = MergeCell::empty();
+        cell.merge_reserved(self);
+        cell.try_finish()
+    }
+
+    fn merge_by<F>(mut self, f: F) -> Option<Result<Self::Item, Error>>
+    where
+        F: FnMut(Self::Item, Self::Item) -> Result<Self::Item, Error>,
+        Self: Sized,
+    {
+        let first = self.next()?;
+        Some(self.try_fold(first, f))
+    }
 }
@@ -4,6 +4,10 @@ mod cell;
 mod context;
 mod impls;
 mod iter;
+mod neutral;
+mod reserve;
+mod resolve;
+mod transaction;
 
 #[cfg(test)]
 mod tests;
@@ -13,8 +17,12 @@ pub mod error;
 pub use self::cell::MergeCell;
 pub use self::context::Context;
 #[doc(inline)]
-pub use self::error::{Error, ErrorKind};
+pub use self::error::{Error, ErrorKind, MultiError};
 pub use self::iter::IteratorExt;
+pub use self::neutral::Neutral;
+pub use self::reserve::ReserveHint;
+pub use self::resolve::Resolve;
+pub use self::transaction::Transaction;
 
 /// A value that may be merged.
 ///
@@ -71,6 +79,43 @@ pub trait Merge: Sized {
     /// assert_eq!(a, &[1, 3, 4, 7, 2, 0]);
     /// ```
     fn merge_ref(&mut self, other: Self) -> Result<(), Error>;
+
+    /// Merge together all values of `iter`.
+    ///
+    /// Returns `Ok(None)` if `iter` yields no values. Stops at the first
+    /// error.
+    ///
+    /// This is like [`IteratorExt::try_merge`], but it works with anything
+    /// that can be turned into an iterator and does not need the extension
+    /// trait in scope.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::Merge;
+    /// assert_eq!(Vec::<i32>::merge_all([]).unwrap(), None);
+    ///
+    /// assert_eq!(Vec::merge_all([vec![1]]).unwrap(), Some(vec![1]));
+    ///
+    /// let items = vec![vec![1, 2], vec![], vec![3]];
+    /// assert_eq!(Vec::merge_all(items).unwrap(), Some(vec![1, 2, 3]));
+    /// ```
+    fn merge_all<I>(iter: I) -> Result<Option<Self>, Error>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let mut cell = MergeCell::empty();
+
+        for x in iter {
+            cell.merge(x);
+
+            if cell.has_errored() {
+                break;
+            }
+        }
+
+        cell.try_finish().transpose()
+    }
 }
 
 /// Merge `this` and `other`.
@@ -83,3 +128,67 @@ where
 {
     this.merge(other)
 }
+
+/// Merge `this` and `other` with `f`.
+///
+/// `f` merges its second argument into its first, like [`Merge::merge_ref`].
+/// This is useful in hand-written [`Merge`] implementations, to merge a field
+/// in some ad-hoc way without writing a module for `#[merge(with = ...)]` or
+/// a newtype.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::{Error, Merge, merge_with};
+/// struct Stats {
+///     hits: u64,
+/// }
+///
+/// impl Merge for Stats {
+///     fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+///         self.hits = merge_with(self.hits, other.hits, |a, b| Ok(*a += b))?;
+///         Ok(())
+///     }
+/// }
+///
+/// let merged = Stats { hits: 3 }.merge(Stats { hits: 4 }).unwrap();
+/// assert_eq!(merged.hits, 7);
+/// ```
+#[inline]
+pub fn merge_with<T, F>(mut this: T, other: T, f: F) -> Result<T, Error>
+where
+    F: FnOnce(&mut T, T) -> Result<(), Error>,
+{
+    f(&mut this, other)?;
+    Ok(this)
+}
+
+/// Merge `other` into `this`, filling `this` if it is empty.
+///
+/// This is the usual way evaluators accumulate the values of modules when
+/// they need to know which module caused a merge error, as opposed to
+/// [`MergeCell`], which defers errors for later.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::merge_opt;
+/// let mut acc = None;
+///
+/// merge_opt(&mut acc, vec![1, 2]).unwrap();
+/// merge_opt(&mut acc, vec![3]).unwrap();
+///
+/// assert_eq!(acc.unwrap(), &[1, 2, 3]);
+/// ```
+pub fn merge_opt<T>(this: &mut Option<T>, other: T) -> Result<(), Error>
+where
+    T: Merge,
+{
+    match this {
+        Some(x) => x.merge_ref(other),
+        None => {
+            *this = Some(other);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use core::marker::PhantomData;
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::vec::Vec;
+
+/// A value that may be neutral with respect to merging.
+///
+/// A value is _neutral_ if merging it into any other value does nothing. For
+/// example, [`None`] and empty collections are neutral.
+///
+/// This trait is used by `#[merge(trim_neutral)]` to skip merging fields that
+/// would not change anything anyway.
+///
+/// # Law
+///
+/// Implementations must uphold the following for all values `a` and `b`:
+///
+/// ```text
+/// b.is_neutral()  =>  a.merge(b) == Ok(a)
+/// ```
+///
+/// In other words, skipping the merge of a neutral value must never change the
+/// result. The converse is not required, `is_neutral()` may return `false`
+/// for values that are in fact neutral.
+pub trait Neutral {
+    /// Check whether merging `self` into another value does nothing.
+    fn is_neutral(&self) -> bool;
+}
+
+impl<T> Neutral for Option<T> {
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<T> Neutral for Box<T>
+where
+    T: Neutral,
+{
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        T::is_neutral(self)
+    }
+}
+
+impl Neutral for () {
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        true
+    }
+}
+
+impl<T> Neutral for PhantomData<T> {
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        true
+    }
+}
+
+macro_rules! empty_is_neutral {
+    ($($t:ident<$($tp:ident),*>),*) => {
+        $(
+            impl<$($tp),*> Neutral for $t<$($tp),*> {
+                #[inline]
+                fn is_neutral(&self) -> bool {
+                    self.is_empty()
+                }
+            }
+        )*
+    };
+}
+
+empty_is_neutral! {
+    Vec<T>, VecDeque<T>, LinkedList<T>, BinaryHeap<T>, BTreeMap<K, V>, BTreeSet<T>
+}
+
+#[cfg(feature = "std")]
+mod std_impls {
+    use super::Neutral;
+
+    use std::collections::{HashMap, HashSet};
+
+    impl<K, V, S> Neutral for HashMap<K, V, S> {
+        #[inline]
+        fn is_neutral(&self) -> bool {
+            self.is_empty()
+        }
+    }
+
+    impl<T, S> Neutral for HashSet<T, S> {
+        #[inline]
+        fn is_neutral(&self) -> bool {
+            self.is_empty()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    use core::fmt::Debug;
+
+    /// Check the law of [`Neutral`] for all combinations of `values`.
+    fn check_law<T>(values: &[T])
+    where
+        T: Neutral + Merge + Clone + PartialEq + Debug,
+    {
+        for b in values.iter().filter(|x| x.is_neutral()) {
+            for a in values {
+                let merged = a.clone().merge(b.clone());
+                assert_eq!(merged.ok().as_ref(), Some(a), "a: {a:?}, b: {b:?}");
+
+                let mut merged = a.clone();
+                merged.merge_ref(b.clone()).unwrap();
+                assert_eq!(&merged, a, "a: {a:?}, b: {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_option() {
+        check_law(&[None, Some(1), Some(2)]);
+        check_law(&[None, Some(vec![1]), Some(vec![])]);
+        assert!(!Some(()).is_neutral());
+    }
+
+    #[test]
+    fn test_box() {
+        check_law(&[Box::new(vec![]), Box::new(vec![1, 2])]);
+    }
+
+    #[test]
+    fn test_unit() {
+        check_law(&[()]);
+        check_law(&[PhantomData::<i32>]);
+    }
+
+    #[test]
+    fn test_collections() {
+        check_law(&[vec![], vec![1], vec![1, 2]]);
+        check_law(&[
+            LinkedList::new(),
+            [1].into_iter().collect(),
+            [1, 2].into_iter().collect(),
+        ]);
+        check_law(&[
+            BTreeMap::new(),
+            [("a", Merged(false))].into_iter().collect(),
+            [("a", Merged(true)), ("b", Merged(false))]
+                .into_iter()
+                .collect(),
+        ]);
+        check_law(&[BTreeSet::new(), [1].into_iter().collect()]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_std_collections() {
+        use std::collections::{HashMap, HashSet};
+
+        check_law(&[
+            HashMap::new(),
+            [("a", Merged(false))].into_iter().collect(),
+            [("a", Merged(true)), ("b", Merged(false))]
+                .into_iter()
+                .collect(),
+        ]);
+        check_law(&[HashSet::new(), [1].into_iter().collect()]);
+    }
+}
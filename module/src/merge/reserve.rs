@@ -0,0 +1,154 @@
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A value that can reserve capacity ahead of being merged into.
+///
+/// Merging many small values one by one into an accumulator makes it grow in
+/// small steps. Types implementing this trait can instead be told upfront how
+/// much data is about to be merged into them and reserve it all at once.
+///
+/// This trait is used by [`MergeCell::merge_reserved`] and
+/// [`IteratorExt::try_merge_reserved`]. Reserving is purely an optimization and
+/// must never change the result of a merge.
+///
+/// [`MergeCell::merge_reserved`]: super::MergeCell::merge_reserved
+/// [`IteratorExt::try_merge_reserved`]: super::IteratorExt::try_merge_reserved
+pub trait ReserveHint {
+    /// Estimate how much capacity merging `self` into another value requires.
+    ///
+    /// The unit is up to the implementation, but it must be the same unit
+    /// [`reserve_for`] expects.
+    ///
+    /// [`reserve_for`]: ReserveHint::reserve_for
+    fn len_hint(&self) -> usize;
+
+    /// Reserve capacity for at least `additional_hint` more units.
+    fn reserve_for(&mut self, additional_hint: usize);
+}
+
+impl<T> ReserveHint for Vec<T> {
+    #[inline]
+    fn len_hint(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn reserve_for(&mut self, additional_hint: usize) {
+        self.reserve(additional_hint);
+    }
+}
+
+impl<T> ReserveHint for VecDeque<T> {
+    #[inline]
+    fn len_hint(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn reserve_for(&mut self, additional_hint: usize) {
+        self.reserve(additional_hint);
+    }
+}
+
+impl<T> ReserveHint for BinaryHeap<T> {
+    #[inline]
+    fn len_hint(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn reserve_for(&mut self, additional_hint: usize) {
+        self.reserve(additional_hint);
+    }
+}
+
+impl ReserveHint for String {
+    #[inline]
+    fn len_hint(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn reserve_for(&mut self, additional_hint: usize) {
+        self.reserve(additional_hint);
+    }
+}
+
+impl<T> ReserveHint for Option<T>
+where
+    T: ReserveHint,
+{
+    #[inline]
+    fn len_hint(&self) -> usize {
+        self.as_ref().map_or(0, T::len_hint)
+    }
+
+    #[inline]
+    fn reserve_for(&mut self, additional_hint: usize) {
+        if let Some(x) = self {
+            x.reserve_for(additional_hint);
+        }
+    }
+}
+
+macro_rules! no_reserve {
+    ($($t:ident<$($tp:ident),*>),*) => {
+        $(
+            impl<$($tp),*> ReserveHint for $t<$($tp),*> {
+                #[inline]
+                fn len_hint(&self) -> usize {
+                    self.len()
+                }
+
+                #[inline]
+                fn reserve_for(&mut self, _: usize) {}
+            }
+        )*
+    };
+}
+
+no_reserve! {
+    BTreeMap<K, V>, BTreeSet<T>, LinkedList<T>
+}
+
+#[cfg(feature = "std")]
+mod std_impls {
+    use super::ReserveHint;
+
+    use core::hash::{BuildHasher, Hash};
+
+    use std::collections::{HashMap, HashSet};
+
+    impl<K, V, S> ReserveHint for HashMap<K, V, S>
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        #[inline]
+        fn len_hint(&self) -> usize {
+            self.len()
+        }
+
+        #[inline]
+        fn reserve_for(&mut self, additional_hint: usize) {
+            self.reserve(additional_hint);
+        }
+    }
+
+    impl<T, S> ReserveHint for HashSet<T, S>
+    where
+        T: Eq + Hash,
+        S: BuildHasher,
+    {
+        #[inline]
+        fn len_hint(&self) -> usize {
+            self.len()
+        }
+
+        #[inline]
+        fn reserve_for(&mut self, additional_hint: usize) {
+            self.reserve(additional_hint);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::context::Context;
+use super::error::Error;
+
+/// A value that defers some decisions until after merging.
+///
+/// Some types, like [`Candidates`], do not decide which value wins while they
+/// are being merged. Instead, they keep every value and decide once all
+/// modules have been merged. [`Resolve`] is how that decision is made.
+///
+/// [`Resolve`] can be derived for structs with `#[merge(resolve)]`, see:
+/// [`Merge`](macro@crate::Merge).
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::{Merge, Resolve};
+/// # use module::types::Candidates;
+/// let a = Candidates::with_priority("foo", 10);
+/// let b = Candidates::with_priority("bar", 5);
+///
+/// let mut merged: Option<Candidates<&str>> = Some(a.merge(b).unwrap());
+/// merged.resolve_in_place().unwrap();
+///
+/// assert_eq!(merged.unwrap().resolved(), Some(&"bar"));
+/// ```
+///
+/// [`Candidates`]: crate::types::Candidates
+pub trait Resolve {
+    /// Make all deferred decisions in `self`.
+    ///
+    /// # Errors
+    ///
+    /// If a decision cannot be made, for example because of a tie.
+    fn resolve_in_place(&mut self) -> Result<(), Error>;
+}
+
+impl<T> Resolve for Option<T>
+where
+    T: Resolve,
+{
+    fn resolve_in_place(&mut self) -> Result<(), Error> {
+        match self {
+            Some(x) => x.resolve_in_place(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> Resolve for Box<T>
+where
+    T: Resolve,
+{
+    fn resolve_in_place(&mut self) -> Result<(), Error> {
+        T::resolve_in_place(self)
+    }
+}
+
+impl<T> Resolve for Vec<T>
+where
+    T: Resolve,
+{
+    fn resolve_in_place(&mut self) -> Result<(), Error> {
+        self.iter_mut()
+            .enumerate()
+            .try_for_each(|(i, x)| x.resolve_in_place().index(i))
+    }
+}
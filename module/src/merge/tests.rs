@@ -65,6 +65,129 @@ fn test_derive_merge_rename() {
     assert_eq!(iter.next().as_deref(), Some("foo"));
 }
 
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_index_path() {
+    use alloc::string::ToString;
+
+    #[derive(Debug, Default, Merge)]
+    struct Item {
+        count: Option<i32>,
+    }
+
+    #[derive(Debug, Default, Merge)]
+    struct Settings {
+        items: [Item; 2],
+    }
+
+    #[derive(Debug, Default, Merge)]
+    struct Config {
+        settings: Settings,
+    }
+
+    let config = |count| Config {
+        settings: Settings {
+            items: [Item::default(), Item { count: Some(count) }],
+        },
+    };
+
+    let err = config(1).merge(config(2)).unwrap_err();
+    assert_eq!(err.value.to_string(), "'settings.items[1].count'");
+    assert_eq!(err.value.components().len(), 4);
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_flatten() {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Default, Merge)]
+    struct Inner {
+        a: i32,
+    }
+
+    #[derive(Debug, Default, Merge)]
+    struct MyType {
+        #[merge(flatten)]
+        global: Inner,
+        nested: Inner,
+    }
+
+    let value = |err: Error| {
+        err.value
+            .components()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let mut a = MyType::default();
+    let err = a.merge_ref(MyType::default()).unwrap_err();
+    assert_eq!(value(err), &["a"]);
+
+    let a = MyType::default();
+    let b = MyType {
+        global: Inner { a: 1 },
+        nested: Inner { a: 1 },
+    };
+    let err = a.merge(b).unwrap_err();
+    assert_eq!(value(err), &["a"]);
+}
+
+#[test]
+#[cfg(all(feature = "derive", feature = "serde"))]
+fn test_derive_merge_serde_rename() {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, Deserialize, Merge)]
+    #[serde(rename_all = "PascalCase")]
+    struct MyType {
+        host_name: Option<i32>,
+        #[serde(rename = "User")]
+        user_name: Option<i32>,
+        #[serde(rename(serialize = "a", deserialize = "b"))]
+        port: Option<i32>,
+        #[serde(rename = "Ignored")]
+        #[merge(rename = "explicit")]
+        other: Option<i32>,
+    }
+
+    #[derive(Debug, Deserialize, Merge)]
+    #[serde(rename_all = "lowercase")]
+    enum MyEnum {
+        #[serde(rename_all = "kebab-case")]
+        Variant { some_field: i32 },
+    }
+
+    let value = |err: Error| {
+        err.value
+            .components()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let collide = |x: fn(&mut MyType)| {
+        let mut a = MyType::default();
+        x(&mut a);
+        let mut b = MyType::default();
+        x(&mut b);
+        value(a.merge(b).unwrap_err())
+    };
+
+    assert_eq!(collide(|x| x.host_name = Some(1)), &["HostName"]);
+    assert_eq!(collide(|x| x.user_name = Some(1)), &["User"]);
+    assert_eq!(collide(|x| x.port = Some(1)), &["b"]);
+    assert_eq!(collide(|x| x.other = Some(1)), &["explicit"]);
+
+    let err = MyEnum::Variant { some_field: 1 }
+        .merge(MyEnum::Variant { some_field: 2 })
+        .unwrap_err();
+    assert_eq!(value(err), &["some-field"]);
+}
+
 #[test]
 #[cfg(feature = "derive")]
 fn test_derive_merge_skip() {
@@ -86,23 +209,154 @@ fn test_derive_merge_skip() {
     assert!(merged.c.0);
 }
 
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_skip_if() {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    fn is_populated(x: &[i32]) -> bool {
+        !x.is_empty()
+    }
+
+    #[derive(Debug, Default, Merge)]
+    struct MyType {
+        #[merge(skip_if = is_populated)]
+        cache: Vec<i32>,
+        #[merge(skip_if = Option::is_some, rename = "renamed")]
+        value: Option<i32>,
+    }
+
+    let merged = MyType::default()
+        .merge(MyType {
+            cache: vec![1],
+            value: None,
+        })
+        .unwrap()
+        .merge(MyType {
+            cache: vec![2],
+            value: Some(1),
+        })
+        .unwrap()
+        .merge(MyType {
+            cache: vec![3],
+            value: Some(2),
+        })
+        .unwrap();
+
+    assert_eq!(merged.cache, [1]);
+    assert_eq!(merged.value, Some(1));
+
+    #[derive(Debug, Default, Merge)]
+    struct Never {
+        #[merge(skip_if = Option::is_none, rename = "renamed")]
+        value: Option<i32>,
+    }
+
+    let err = Never { value: Some(1) }
+        .merge(Never { value: Some(2) })
+        .unwrap_err();
+    assert_eq!(
+        err.value
+            .components()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>(),
+        &["renamed"]
+    );
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_skip_deny_other() {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Default, Merge)]
+    struct MyType {
+        #[merge(skip(deny_other))]
+        a: Option<i32>,
+        #[merge(skip(deny_other), rename = "renamed")]
+        b: Vec<i32>,
+        c: Merged,
+    }
+
+    let a = MyType {
+        a: Some(1),
+        ..Default::default()
+    };
+    let merged = a.merge(MyType::default()).unwrap();
+    assert_eq!(merged.a, Some(1));
+    assert!(merged.c.0);
+
+    let mut a = MyType::default();
+    a.merge_ref(MyType::default()).unwrap();
+    assert!(a.c.0);
+
+    let b = MyType {
+        a: Some(2),
+        ..Default::default()
+    };
+    let err = MyType::default().merge(b).unwrap_err();
+    assert!(err.kind.is_custom());
+    assert_eq!(
+        err.kind.to_string(),
+        "field `a` cannot be set here (it is managed internally)"
+    );
+    assert_eq!(
+        err.value
+            .components()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>(),
+        &["a"]
+    );
+
+    let b = MyType {
+        b: vec![1],
+        ..Default::default()
+    };
+    let mut a = MyType::default();
+    let err = a.merge_ref(b).unwrap_err();
+    assert_eq!(
+        err.kind.to_string(),
+        "field `b` cannot be set here (it is managed internally)"
+    );
+    assert_eq!(
+        err.value
+            .components()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>(),
+        &["renamed"]
+    );
+    assert!(!a.c.0);
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_skip_keeps_other_silently() {
+    #[derive(Default, Merge)]
+    struct MyType {
+        #[merge(skip)]
+        a: Option<i32>,
+    }
+
+    let b = MyType { a: Some(2) };
+    let merged = MyType::default().merge(b).unwrap();
+    assert_eq!(merged.a, None);
+}
+
 #[test]
 #[cfg(feature = "derive")]
 fn test_derive_merge_with() {
     mod custom {
         use super::*;
 
-        pub fn merge(a: i32, b: i32) -> Result<i32, Error> {
-            Ok(a + b)
-        }
-
         pub fn merge_ref(a: &mut i32, b: i32) -> Result<(), Error> {
             *a += b;
             Ok(())
         }
 
         pub mod nested {
-            pub use super::{merge, merge_ref};
+            pub use super::merge_ref;
         }
     }
 
@@ -122,3 +376,697 @@ fn test_derive_merge_with() {
     assert_eq!(merged.a, 3);
     assert_eq!(merged.b, 54);
 }
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_strategy() {
+    use alloc::string::String;
+
+    #[derive(Merge)]
+    struct MyType {
+        #[merge(strategy = last)]
+        last: i32,
+        #[merge(strategy = first)]
+        first: i32,
+        #[merge(strategy = lines)]
+        lines: String,
+    }
+
+    let a = || MyType {
+        last: 1,
+        first: 1,
+        lines: "a".into(),
+    };
+    let b = || MyType {
+        last: 2,
+        first: 2,
+        lines: "b".into(),
+    };
+
+    let merged = a().merge(b()).unwrap();
+    assert_eq!(merged.last, 2);
+    assert_eq!(merged.first, 1);
+    assert_eq!(merged.lines, "a\nb");
+
+    let mut merged = a();
+    merged.merge_ref(b()).unwrap();
+    assert_eq!(merged.last, 2);
+    assert_eq!(merged.first, 1);
+    assert_eq!(merged.lines, "a\nb");
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_crate() {
+    mod framework {
+        pub mod config {
+            pub use crate::*;
+        }
+    }
+
+    #[derive(Default, Merge)]
+    #[merge(crate = framework::config, trim_neutral)]
+    struct MyType<T> {
+        a: Option<T>,
+        #[merge(strategy = last)]
+        b: Option<i32>,
+        #[merge(skip(deny_other))]
+        c: Option<i32>,
+    }
+
+    #[derive(Merge)]
+    #[merge(crate = framework::config)]
+    enum MyEnum {
+        A(Merged),
+    }
+
+    let merged = MyType {
+        a: Some(Merged(false)),
+        b: Some(1),
+        c: None,
+    }
+    .merge(MyType {
+        a: Some(Merged(false)),
+        b: Some(2),
+        c: None,
+    })
+    .unwrap();
+    assert_eq!(merged.a, Some(Merged(true)));
+    assert_eq!(merged.b, Some(2));
+
+    let MyEnum::A(merged) = MyEnum::A(Merged(false))
+        .merge(MyEnum::A(Merged(false)))
+        .unwrap();
+    assert!(merged.0);
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_bounds() {
+    use core::marker::PhantomData;
+
+    struct NotMerge;
+
+    #[derive(Merge)]
+    struct Skipped<T, U> {
+        a: U,
+        #[merge(skip)]
+        _marker: PhantomData<T>,
+    }
+
+    #[derive(Merge)]
+    #[merge(bound = "U: Merge + Copy")]
+    struct Bound<T, U> {
+        a: U,
+        #[merge(strategy = last)]
+        b: Option<T>,
+    }
+
+    let merged = Skipped::<NotMerge, _> {
+        a: Merged(false),
+        _marker: PhantomData,
+    }
+    .merge(Skipped {
+        a: Merged(false),
+        _marker: PhantomData,
+    })
+    .unwrap();
+    assert!(merged.a.0);
+
+    let merged = Bound::<NotMerge, _> {
+        a: Merged(false),
+        b: None,
+    }
+    .merge(Bound {
+        a: Merged(false),
+        b: Some(NotMerge),
+    })
+    .unwrap();
+    assert!(merged.a.0);
+    assert!(merged.b.is_some());
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_enum() {
+    #[derive(Debug, Merge)]
+    enum MyType {
+        Unit,
+        Tuple(Merged, #[merge(skip)] Merged),
+        Named { a: Merged, b: Merged },
+    }
+
+    let merged = MyType::Unit.merge(MyType::Unit).unwrap();
+    assert!(matches!(merged, MyType::Unit));
+
+    let merged = MyType::Tuple(Merged(false), Merged(false))
+        .merge(MyType::Tuple(Merged(false), Merged(false)))
+        .unwrap();
+    assert!(matches!(merged, MyType::Tuple(Merged(true), Merged(false))));
+
+    let mut a = MyType::Named {
+        a: Merged(false),
+        b: Merged(false),
+    };
+    a.merge_ref(MyType::Named {
+        a: Merged(false),
+        b: Merged(false),
+    })
+    .unwrap();
+    assert!(matches!(
+        a,
+        MyType::Named {
+            a: Merged(true),
+            b: Merged(true)
+        }
+    ));
+
+    let err = MyType::Unit
+        .merge(MyType::Tuple(Merged(false), Merged(false)))
+        .unwrap_err();
+    assert!(err.kind.is_collision());
+    assert!(err.value.is_empty());
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_enum_value() {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Merge)]
+    enum MyType {
+        Tuple(i32),
+        Named {
+            a: Merged,
+            #[merge(rename = "renamed")]
+            b: i32,
+        },
+    }
+
+    let value = |err: Error| {
+        err.value
+            .components()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let err = MyType::Tuple(1).merge(MyType::Tuple(2)).unwrap_err();
+    assert!(err.kind.is_collision());
+    assert_eq!(value(err), &["0"]);
+
+    let named = || MyType::Named {
+        a: Merged(false),
+        b: 1,
+    };
+    let err = named().merge(named()).unwrap_err();
+    assert!(err.kind.is_collision());
+    assert_eq!(value(err), &["renamed"]);
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_trim_neutral() {
+    use crate::types::Last;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Clone, PartialEq, Merge)]
+    struct Plain {
+        a: Option<Last<i32>>,
+        b: Vec<i32>,
+        #[merge(skip)]
+        c: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Merge)]
+    #[merge(trim_neutral)]
+    struct Trimmed {
+        a: Option<Last<i32>>,
+        b: Vec<i32>,
+        #[merge(skip)]
+        c: i32,
+    }
+
+    let values = [
+        (None, vec![], 0),
+        (Some(1), vec![], 1),
+        (None, vec![1, 2], 2),
+        (Some(2), vec![3], 3),
+    ];
+
+    for &(a1, ref b1, c1) in &values {
+        for &(a2, ref b2, c2) in &values {
+            let plain = |a: Option<i32>, b: &Vec<i32>, c| Plain {
+                a: a.map(Last),
+                b: b.clone(),
+                c,
+            };
+            let trimmed = |a: Option<i32>, b: &Vec<i32>, c| Trimmed {
+                a: a.map(Last),
+                b: b.clone(),
+                c,
+            };
+
+            let expected = plain(a1, b1, c1).merge(plain(a2, b2, c2)).unwrap();
+            let merged = trimmed(a1, b1, c1).merge(trimmed(a2, b2, c2)).unwrap();
+            assert_eq!(
+                (&merged.a, &merged.b, merged.c),
+                (&expected.a, &expected.b, expected.c)
+            );
+
+            let mut merged = trimmed(a1, b1, c1);
+            merged.merge_ref(trimmed(a2, b2, c2)).unwrap();
+            assert_eq!(
+                (&merged.a, &merged.b, merged.c),
+                (&expected.a, &expected.b, expected.c)
+            );
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_merge_trim_neutral_keeps_errors() {
+    #[derive(Debug, Merge)]
+    #[merge(trim_neutral)]
+    struct MyType {
+        a: Option<i32>,
+    }
+
+    let err = MyType { a: Some(1) }
+        .merge(MyType { a: Some(2) })
+        .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Collision);
+
+    let merged = MyType { a: Some(1) }.merge(MyType { a: None }).unwrap();
+    assert_eq!(merged.a, Some(1));
+}
+
+#[test]
+fn test_merge_reserved_lines() {
+    use crate::merge::IteratorExt;
+    use crate::types::Lines;
+
+    let fragments = || {
+        (0..10_000).map(|i| match i % 3 {
+            0 => Lines::new(format!("line{i}")),
+            1 => Lines::new(format!("line{i}\n")),
+            _ => Lines::new(""),
+        })
+    };
+
+    let naive = fragments().try_merge().unwrap().unwrap();
+    let reserved = fragments().try_merge_reserved().unwrap().unwrap();
+
+    assert_eq!(
+        naive.as_bytes(),
+        reserved.as_bytes(),
+        "reserved merge should be byte-identical"
+    );
+}
+
+#[test]
+fn test_merge_reserved_vec() {
+    use crate::merge::{IteratorExt, MergeCell};
+    use alloc::vec::Vec;
+
+    let fragments = || (0..10_000).map(|i| vec![i; i % 4]);
+
+    let naive: Vec<usize> = fragments().try_merge().unwrap().unwrap();
+    let reserved: Vec<usize> = fragments().try_merge_reserved().unwrap().unwrap();
+    assert_eq!(naive, reserved);
+
+    let mut cell = MergeCell::new(vec![42]);
+    cell.merge_reserved(fragments());
+    let reserved = cell.finish().unwrap();
+
+    assert_eq!(reserved[0], 42);
+    assert_eq!(&reserved[1..], naive);
+}
+
+#[test]
+fn test_merge_reserved_inflated_hint() {
+    use crate::merge::MergeCell;
+    use alloc::vec::Vec;
+
+    /// An iterator that claims to have far more items than it does.
+    struct Inflated<I>(I);
+
+    impl<I: Iterator> Iterator for Inflated<I> {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.next()
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (usize::MAX / 2, None)
+        }
+    }
+
+    let items = [vec![0u8; 1 << 20], vec![1; 1 << 20], vec![2]];
+
+    let mut cell = MergeCell::new(Vec::new());
+    cell.merge_reserved(Inflated(items.into_iter()));
+    let merged = cell.finish().unwrap();
+
+    assert_eq!(merged.len(), (2 << 20) + 1);
+    assert!(merged.capacity() <= 4 << 20, "{}", merged.capacity());
+}
+
+#[test]
+fn test_merge_reserved_empty() {
+    use crate::merge::{IteratorExt, MergeCell};
+    use alloc::vec::Vec;
+
+    assert!(
+        core::iter::empty::<Vec<i32>>()
+            .try_merge_reserved()
+            .is_none()
+    );
+
+    let mut cell = MergeCell::<Vec<i32>>::empty();
+    cell.merge_reserved([]);
+    assert!(cell.is_empty());
+}
+
+#[test]
+fn test_merge_cell_hooks() {
+    use crate::merge::{Context, MergeCell};
+    use alloc::rc::Rc;
+    use alloc::string::ToString;
+    use core::cell::Cell;
+    use core::ops::ControlFlow;
+
+    let calls = Rc::new(Cell::new(0));
+    let mut cell = MergeCell::empty().on_merge({
+        let calls = calls.clone();
+        move |x: &i32| {
+            calls.set(calls.get() + 1);
+            if *x == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    });
+
+    cell.merge(2);
+    assert!(cell.is_saturated());
+    cell.merge(3);
+    assert_eq!(calls.get(), 1, "a saturated cell should ignore values");
+    assert_eq!(cell.finish().unwrap(), 2);
+
+    let decorated = Rc::new(Cell::new(0));
+    let mut cell = MergeCell::empty().map_err({
+        let decorated = decorated.clone();
+        move |e: Error| {
+            decorated.set(decorated.get() + 1);
+            Err::<(), _>(e).module("overrides.json").unwrap_err()
+        }
+    });
+
+    cell.merge(1);
+    cell.merge(1);
+    cell.merge(1);
+    assert!(cell.has_errored());
+
+    let err = cell.finish().unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Collision);
+    assert_eq!(
+        err.modules
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<alloc::vec::Vec<_>>(),
+        ["overrides.json"]
+    );
+    assert_eq!(decorated.get(), 1, "the decorator should run once");
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_resolve() {
+    use crate::merge::Resolve;
+    use crate::types::Candidates;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Merge)]
+    #[merge(resolve)]
+    struct Inner {
+        #[merge(resolve)]
+        level: Candidates<i32>,
+        other: Vec<i32>,
+    }
+
+    #[derive(Debug, Merge)]
+    #[merge(resolve)]
+    struct Outer {
+        #[merge(resolve)]
+        inner: Inner,
+        #[merge(resolve)]
+        name: Option<Candidates<&'static str>>,
+    }
+
+    let x = |level: isize, name: &'static str| Outer {
+        inner: Inner {
+            level: Candidates::with_priority(level as i32, level),
+            other: vec![1],
+        },
+        name: Some(Candidates::with_priority(name, level)),
+    };
+
+    let mut merged = x(20, "a").merge(x(10, "b")).unwrap();
+    merged.resolve_in_place().unwrap();
+
+    assert_eq!(merged.inner.level.resolved(), Some(&10));
+    assert_eq!(merged.inner.other, [1, 1]);
+    assert_eq!(merged.name.unwrap().resolved(), Some(&"b"));
+
+    let mut merged = x(10, "a").merge(x(10, "b")).unwrap();
+    let err = merged.resolve_in_place().unwrap_err();
+    assert_eq!(err.value.to_string(), "'inner.level'");
+}
+
+#[test]
+#[cfg(all(feature = "derive", feature = "serde"))]
+fn test_overridable_struct() {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use serde::Deserialize;
+
+    #[crate::overridable_struct]
+    #[derive(Debug, Deserialize, Merge)]
+    struct Config {
+        #[merge(overridable)]
+        port: u16,
+        #[merge(overridable)]
+        #[serde(rename = "host-name")]
+        host: String,
+        hosts: Vec<String>,
+    }
+
+    let read = |json: &str| serde_json::from_str::<Config>(json).unwrap();
+
+    let a = read(r#"{ "port": 80, "hosts": ["a"] }"#);
+    let b = read(r#"{ "port": { "value": 8080, "priority": 10 }, "hosts": ["b"] }"#);
+    let c = read(r#"{ "port": 443, "host-name": "c", "hosts": [] }"#);
+
+    assert_eq!(a.port, 80);
+    assert_eq!(a.host, "");
+
+    let merged = a.merge(b).unwrap().merge(c).unwrap();
+    assert_eq!(merged.port, 8080);
+    assert_eq!(merged.host, "c");
+    assert_eq!(merged.hosts, ["a", "b"]);
+
+    let a = read(r#"{ "port": 80, "hosts": [] }"#);
+    let b = read(r#"{ "port": 443, "hosts": [] }"#);
+    let err = a.merge(b).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Collision);
+    assert_eq!(err.value.to_string(), "'port'");
+}
+
+#[test]
+#[cfg(all(feature = "derive", feature = "serde"))]
+fn test_overridable_struct_crate() {
+    use serde::Deserialize;
+
+    mod framework {
+        pub mod config {
+            pub use crate::*;
+        }
+    }
+
+    #[crate::overridable_struct]
+    #[derive(Debug, Deserialize, Merge)]
+    #[merge(crate = framework::config)]
+    struct Config {
+        #[merge(overridable)]
+        port: u16,
+    }
+
+    let read = |json: &str| serde_json::from_str::<Config>(json).unwrap();
+
+    let a = read(r#"{ "port": 80 }"#);
+    let b = read(r#"{ "port": { "value": 8080, "priority": 10 } }"#);
+    assert_eq!(a.merge(b).unwrap().port, 8080);
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn test_derive_compact_names() {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    macro_rules! config {
+        ($name:ident, $inner:ident, $tuple:ident $(, $attr:meta)?) => {
+            #[derive(Debug, Default, Merge)]
+            $(#[$attr])?
+            struct $inner {
+                #[merge(rename = "Port")]
+                port: Option<u16>,
+                hosts: Vec<String>,
+            }
+
+            #[derive(Debug, Default, Merge)]
+            $(#[$attr])?
+            struct $tuple($inner);
+
+            #[derive(Debug, Default, Merge)]
+            $(#[$attr])?
+            struct $name {
+                enabled: Option<bool>,
+                #[merge(skip)]
+                _skipped: u8,
+                #[merge(rename = alloc::format!("{}-{}", "in", "ner"))]
+                nested: $inner,
+                inner: $inner,
+                tuple: $tuple,
+            }
+        };
+    }
+
+    config!(Normal, NormalInner, NormalTuple);
+    config!(Compact, CompactInner, CompactTuple, merge(compact_names));
+
+    fn render(r: Result<(), Error>) -> String {
+        let e = r.unwrap_err();
+        alloc::format!("{:?} at {}", e.kind, e.value)
+    }
+
+    macro_rules! check {
+        ($($field:tt).+ = $a:expr, $b:expr) => {{
+            let (mut n1, mut n2) = (Normal::default(), Normal::default());
+            let (mut c1, mut c2) = (Compact::default(), Compact::default());
+            n1.$($field).+ = $a;
+            n2.$($field).+ = $b;
+            c1.$($field).+ = $a;
+            c2.$($field).+ = $b;
+
+            let normal = render(n1.merge(n2).map(drop));
+            let compact = render(c1.merge(c2).map(drop));
+            assert_eq!(normal, compact);
+
+            let (mut n1, mut n2) = (Normal::default(), Normal::default());
+            let (mut c1, mut c2) = (Compact::default(), Compact::default());
+            n1.$($field).+ = $a;
+            n2.$($field).+ = $b;
+            c1.$($field).+ = $a;
+            c2.$($field).+ = $b;
+
+            let compact_ref = render(c1.merge_ref(c2));
+            assert_eq!(render(n1.merge_ref(n2)), compact_ref);
+            assert_eq!(compact, compact_ref);
+
+            compact
+        }};
+    }
+
+    assert_eq!(
+        check!(enabled = Some(true), Some(false)),
+        "Collision at 'enabled'"
+    );
+    assert_eq!(
+        check!(inner.port = Some(1), Some(2)),
+        "Collision at 'inner.Port'"
+    );
+    assert_eq!(
+        check!(nested.port = Some(1), Some(2)),
+        "Collision at 'in-ner.Port'"
+    );
+    assert_eq!(
+        check!(tuple.0.port = Some(1), Some(2)),
+        "Collision at 'tuple.0.Port'"
+    );
+
+    let merged = Compact {
+        enabled: Some(true),
+        ..Default::default()
+    }
+    .merge(Compact {
+        inner: CompactInner {
+            hosts: vec!["a".to_string()],
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(merged.enabled, Some(true));
+    assert_eq!(merged.inner.hosts, ["a"]);
+}
+
+#[test]
+fn test_merge_cell_peek() {
+    use crate::merge::MergeCell;
+
+    let mut cell = MergeCell::empty();
+    assert!(cell.as_ref().is_none());
+
+    for i in 0.. {
+        cell.merge(vec![i]);
+
+        // Stop reading modules once there are enough items.
+        if cell.as_ref().unwrap().len() >= 3 {
+            break;
+        }
+    }
+
+    cell.as_mut().unwrap().push(42);
+    assert!(!cell.has_errored());
+    assert_eq!(cell.finish().unwrap(), [0, 1, 2, 42]);
+}
+
+#[test]
+fn test_merge_cell_into_parts() {
+    use crate::merge::MergeCell;
+
+    let (value, result) = MergeCell::<Option<i32>>::empty().into_parts();
+    assert!(value.is_none());
+    assert!(result.is_ok());
+
+    let mut cell = MergeCell::empty();
+    cell.merge(Some(1));
+    cell.merge(Some(2));
+    cell.merge(None);
+
+    let (value, result) = cell.into_parts();
+    assert_eq!(value, Some(Some(1)));
+    assert_eq!(result.unwrap_err().kind, ErrorKind::Collision);
+}
+
+#[test]
+fn test_merge_cell_try_merge() {
+    use crate::merge::MergeCell;
+
+    let mut cell = MergeCell::empty();
+    assert!(cell.try_merge(Some(1)).is_ok());
+    assert!(cell.try_merge(None).is_ok());
+    assert!(cell.try_merge(Some(2)).unwrap_err().kind.is_collision());
+
+    // The cell keeps failing, even for values that would merge cleanly.
+    assert!(cell.try_merge(None).unwrap_err().kind.is_collision());
+    assert_eq!(cell.as_ref(), Some(&Some(1)));
+    assert!(cell.finish().unwrap_err().kind.is_collision());
+}
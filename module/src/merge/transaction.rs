@@ -0,0 +1,255 @@
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use super::Merge;
+use super::cell::MergeCell;
+use super::error::Error;
+
+/// A group of [`MergeCell`]s that succeed or fail together.
+///
+/// A [`Transaction`] owns up to 4 cells of possibly different types, its
+/// _slots_. Values are merged into each slot with `merge_0()`, `merge_1()`
+/// and so on. Errors are deferred, just like with [`MergeCell`].
+///
+/// [`commit()`] gives back the values of all slots, but only if every slot
+/// has a value and none of them has errored. Otherwise it returns the errors
+/// of all slots, so nothing is lost by failing early.
+///
+/// [`commit_into()`] merges the values into existing targets instead. The
+/// merges are staged on clones of the targets, so if merging into any of
+/// them fails, none of the targets are changed.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::{MergeCell, Transaction};
+/// let mut tx = Transaction::new((MergeCell::empty(), MergeCell::empty()));
+///
+/// tx.merge_0(vec![1, 2]);
+/// tx.merge_1(Some(42));
+/// tx.merge_0(vec![3]);
+///
+/// let (a, b): (Vec<i32>, Option<i32>) = tx.commit().unwrap();
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(b, Some(42));
+/// ```
+///
+/// [`commit()`]: Transaction::commit
+/// [`commit_into()`]: Transaction::commit_into
+#[derive(Debug, Default)]
+pub struct Transaction<C> {
+    cells: C,
+}
+
+impl<C> Transaction<C> {
+    /// Create a new [`Transaction`] over a tuple of [`MergeCell`]s.
+    pub fn new(cells: C) -> Self {
+        Self { cells }
+    }
+
+    /// Get the cells of the transaction.
+    pub fn cells(&self) -> &C {
+        &self.cells
+    }
+
+    /// Get the cells of the transaction mutably.
+    pub fn cells_mut(&mut self) -> &mut C {
+        &mut self.cells
+    }
+}
+
+/// Take the value out of `cell`, recording its error if it has one.
+fn take<T>(cell: MergeCell<T>, slot: usize, errors: &mut Vec<Error>) -> Option<T> {
+    match cell.try_finish() {
+        Some(Ok(x)) => Some(x),
+        Some(Err(e)) => {
+            errors.push(e);
+            None
+        }
+        None => {
+            errors.push(Error::custom(EmptySlot(slot)));
+            None
+        }
+    }
+}
+
+/// Merge `value` into a clone of `target`, recording the error if it fails.
+fn stage<T>(target: &T, value: T, errors: &mut Vec<Error>) -> Option<T>
+where
+    T: Merge + Clone,
+{
+    match target.clone().merge(value) {
+        Ok(x) => Some(x),
+        Err(e) => {
+            errors.push(e);
+            None
+        }
+    }
+}
+
+struct EmptySlot(usize);
+
+impl fmt::Display for EmptySlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nothing was merged into slot {}", self.0)
+    }
+}
+
+macro_rules! impl_transaction {
+    ($($T:ident $idx:tt $merge:ident $x:ident),+) => {
+        impl<$($T),+> Transaction<($(MergeCell<$T>,)+)> {
+            $(
+                #[doc = concat!("Merge `value` into slot ", stringify!($idx), ".")]
+                ///
+                /// See: [`MergeCell::merge`].
+                pub fn $merge(&mut self, value: $T)
+                where
+                    $T: Merge,
+                {
+                    self.cells.$idx.merge(value);
+                }
+            )+
+
+            /// Check whether merging into any slot has failed.
+            pub fn has_errored(&self) -> bool {
+                $(self.cells.$idx.has_errored())||+
+            }
+
+            /// Get the values of all slots.
+            ///
+            /// # Errors
+            ///
+            /// If any slot is empty or has errored. The errors of all slots
+            /// are returned, in slot order.
+            pub fn commit(self) -> Result<($($T,)+), Vec<Error>> {
+                let mut errors = Vec::new();
+                $(let $x = take(self.cells.$idx, $idx, &mut errors);)+
+
+                match ($($x,)+) {
+                    ($(Some($x),)+) if errors.is_empty() => Ok(($($x,)+)),
+                    _ => Err(errors),
+                }
+            }
+
+            /// Merge the values of all slots into `targets`.
+            ///
+            /// The targets are only changed if every merge succeeds.
+            ///
+            /// # Errors
+            ///
+            /// If [`commit()`] fails, or if merging into any of the targets
+            /// fails. In both cases, all errors are returned and none of the
+            /// targets are changed.
+            ///
+            /// [`commit()`]: Transaction::commit
+            pub fn commit_into(self, targets: ($(&mut $T,)+)) -> Result<(), Vec<Error>>
+            where
+                $($T: Merge + Clone,)+
+            {
+                let values = self.commit()?;
+
+                let mut errors = Vec::new();
+                $(let $x = stage(&*targets.$idx, values.$idx, &mut errors);)+
+
+                match ($($x,)+) {
+                    ($(Some($x),)+) if errors.is_empty() => {
+                        $(*targets.$idx = $x;)+
+                        Ok(())
+                    }
+                    _ => Err(errors),
+                }
+            }
+        }
+    };
+}
+
+impl_transaction!(A 0 merge_0 a);
+impl_transaction!(A 0 merge_0 a, B 1 merge_1 b);
+impl_transaction!(A 0 merge_0 a, B 1 merge_1 b, C 2 merge_2 c);
+impl_transaction!(A 0 merge_0 a, B 1 merge_1 b, C 2 merge_2 c, D 3 merge_3 d);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    type Tx = Transaction<(MergeCell<Vec<i32>>, MergeCell<i32>, MergeCell<Option<i32>>)>;
+
+    fn tx() -> Tx {
+        Transaction::new((MergeCell::empty(), MergeCell::empty(), MergeCell::empty()))
+    }
+
+    #[test]
+    fn test_commit() {
+        let mut tx = tx();
+        tx.merge_0(vec![1]);
+        tx.merge_1(42);
+        tx.merge_2(None);
+        tx.merge_0(vec![2]);
+        tx.merge_2(Some(7));
+
+        assert!(!tx.has_errored());
+        assert_eq!(tx.commit().unwrap(), (vec![1, 2], 42, Some(7)));
+    }
+
+    #[test]
+    fn test_commit_errors() {
+        let mut tx = tx();
+        tx.merge_0(vec![1]);
+        tx.merge_1(1);
+        tx.merge_1(2);
+        tx.merge_2(Some(1));
+        tx.merge_2(Some(2));
+
+        assert!(tx.has_errored());
+
+        let errors = tx.commit().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.kind.is_collision()));
+    }
+
+    #[test]
+    fn test_commit_empty() {
+        let mut tx = tx();
+        tx.merge_0(vec![1]);
+        tx.merge_2(Some(1));
+
+        let errors = tx.commit().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind.to_string(), "nothing was merged into slot 1");
+    }
+
+    #[test]
+    fn test_commit_into() {
+        let mut a = vec![0];
+        let mut b = Some(1);
+
+        let mut tx = Transaction::new((MergeCell::empty(), MergeCell::empty()));
+        tx.merge_0(vec![1]);
+        tx.merge_1(None);
+
+        tx.commit_into((&mut a, &mut b)).unwrap();
+        assert_eq!(a, [0, 1]);
+        assert_eq!(b, Some(1));
+    }
+
+    #[test]
+    fn test_commit_into_untouched() {
+        let mut a = vec![0];
+        let mut b = Some(1);
+
+        // Slot 1 merges cleanly on its own, but collides with its target.
+        let mut tx = Transaction::new((MergeCell::empty(), MergeCell::empty()));
+        tx.merge_0(vec![1]);
+        tx.merge_1(Some(2));
+
+        let errors = tx.commit_into((&mut a, &mut b)).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].kind.is_collision());
+
+        assert_eq!(a, [0]);
+        assert_eq!(b, Some(1));
+    }
+}
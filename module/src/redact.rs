@@ -0,0 +1,327 @@
+//! Hiding sensitive values from output.
+//!
+//! Anything that shows values to the user, keyed by their path, must render
+//! them through [`render_value`]. It consults the active [`Policy`] and hides
+//! the values it matches.
+//!
+//! With the `std` feature, a [`Policy`] can be made active for the whole
+//! process with [`set_policy`], or for the duration of a closure on the
+//! current thread with [`with_policy`]. The latter takes precedence. Without
+//! the `std` feature, no policy is ever active and all values are rendered
+//! as is.
+//!
+//! Currently, [`render_value`] is used by the assertions of [`test_util`].
+//!
+//! # Example
+//!
+//! ```rust
+//! # use module::redact::{self, Mode, Policy, Redacted};
+//! let policy = Policy::new(Mode::Mask).with_pattern("*.password");
+//!
+//! redact::with_policy(policy, || {
+//!     let x = redact::render_value("db.password", &"hunter2");
+//!     assert_eq!(x, Redacted::Masked);
+//!     assert_eq!(x.to_string(), "<redacted>");
+//!
+//!     let x = redact::render_value("db.user", &"admin");
+//!     assert_eq!(x.to_string(), "admin");
+//! });
+//! ```
+//!
+//! [`test_util`]: crate::test_util
+
+use core::fmt::{self, Display, Write};
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::glob::glob;
+
+/// How a [`Policy`] hides values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Replace the value with a placeholder.
+    Mask,
+    /// Replace the value with a hash of its contents.
+    ///
+    /// The hash is stable, so equal values can still be recognized as such.
+    /// This is only meant for correlating values, it gives no
+    /// confidentiality. The hash is an unkeyed 64-bit FNV-1a, so short or
+    /// guessable values, like ports, PINs or common passwords, are easily
+    /// recovered by hashing candidates until one matches. Use [`Mode::Mask`]
+    /// or [`Mode::Drop`] for values that must not be recovered.
+    Hash,
+    /// Leave the value out completely.
+    Drop,
+}
+
+/// A set of value paths that must be hidden.
+///
+/// Paths are matched against patterns where `*` matches any sequence of
+/// characters, including `.`. For example, `*.password` matches both
+/// `db.password` and `services.db.password`, but not a top-level `password`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    patterns: Vec<String>,
+    mode: Mode,
+}
+
+impl Policy {
+    /// Create a new [`Policy`] that hides values according to `mode`.
+    ///
+    /// The policy initially matches no paths.
+    pub fn new(mode: Mode) -> Self {
+        Self {
+            patterns: Vec::new(),
+            mode,
+        }
+    }
+
+    /// Add `pattern` to the paths hidden by this policy.
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.push(pattern);
+        self
+    }
+
+    /// Add `pattern` to the paths hidden by this policy.
+    pub fn push(&mut self, pattern: impl Into<String>) {
+        self.patterns.push(pattern.into());
+    }
+
+    /// Get the mode of this policy.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Check whether the value at `path` must be hidden.
+    pub fn matches(&self, path: &str) -> bool {
+        self.patterns.iter().any(|x| glob(x, path))
+    }
+
+    /// Render the value at `path` according to this policy.
+    pub fn render(&self, path: &str, value: &dyn Display) -> Redacted {
+        if !self.matches(path) {
+            return Redacted::Value(value.to_string());
+        }
+
+        match self.mode {
+            Mode::Mask => Redacted::Masked,
+            Mode::Hash => Redacted::Hashed(hash(value)),
+            Mode::Drop => Redacted::Dropped,
+        }
+    }
+}
+
+/// A value rendered by [`render_value`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Redacted {
+    /// The value is not hidden.
+    Value(String),
+    /// The value is hidden by [`Mode::Mask`].
+    Masked,
+    /// The value is hidden by [`Mode::Hash`].
+    ///
+    /// This is the hash of the value, which does not keep the value secret.
+    /// See [`Mode::Hash`].
+    Hashed(u64),
+    /// The value is hidden by [`Mode::Drop`].
+    ///
+    /// Whatever renders the value should leave it out, along with its path.
+    Dropped,
+}
+
+impl Redacted {
+    /// Check whether the value is [`Redacted::Dropped`].
+    pub fn is_dropped(&self) -> bool {
+        matches!(self, Self::Dropped)
+    }
+}
+
+impl Display for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(x) => f.write_str(x),
+            Self::Masked => f.write_str("<redacted>"),
+            Self::Hashed(x) => write!(f, "<redacted fnv1a64:{x:016x}>"),
+            Self::Dropped => Ok(()),
+        }
+    }
+}
+
+/// Render the value at `path` according to the active [`Policy`].
+///
+/// This is the single place where values are checked against the policy.
+/// See the [module-level docs](self).
+pub fn render_value(path: &str, value: &dyn Display) -> Redacted {
+    #[cfg(feature = "std")]
+    if let Some(x) = imp::render(path, value) {
+        return x;
+    }
+
+    #[cfg(not(feature = "std"))]
+    let _ = path;
+
+    Redacted::Value(value.to_string())
+}
+
+/// The 64-bit FNV-1a hash of the rendered `value`.
+fn hash(value: &dyn Display) -> u64 {
+    struct Fnv(u64);
+
+    impl Write for Fnv {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for b in s.bytes() {
+                self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3);
+            }
+            Ok(())
+        }
+    }
+
+    let mut h = Fnv(0xcbf2_9ce4_8422_2325);
+    let _ = write!(h, "{value}");
+    h.0
+}
+
+#[cfg(feature = "std")]
+pub use self::imp::{clear_policy, set_policy, with_policy};
+
+#[cfg(feature = "std")]
+mod imp {
+    use super::{Display, Policy, Redacted};
+
+    use std::cell::RefCell;
+    use std::sync::RwLock;
+    use std::vec::Vec;
+
+    static GLOBAL: RwLock<Option<Policy>> = RwLock::new(None);
+
+    std::thread_local! {
+        static SCOPED: RefCell<Vec<Policy>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Make `policy` active for the whole process.
+    ///
+    /// This replaces any policy previously set with this function. Policies
+    /// set by [`with_policy`] take precedence.
+    pub fn set_policy(policy: Policy) {
+        *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = Some(policy);
+    }
+
+    /// Remove the policy set by [`set_policy`].
+    pub fn clear_policy() {
+        *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Make `policy` active on the current thread while `f` runs.
+    ///
+    /// Calls to [`with_policy`] can be nested, the innermost policy wins.
+    pub fn with_policy<R>(policy: Policy, f: impl FnOnce() -> R) -> R {
+        struct Pop;
+
+        impl Drop for Pop {
+            fn drop(&mut self) {
+                SCOPED.with_borrow_mut(|x| x.pop());
+            }
+        }
+
+        SCOPED.with_borrow_mut(|x| x.push(policy));
+        let _pop = Pop;
+        f()
+    }
+
+    /// Render the value at `path` according to the active policy, if any.
+    pub(super) fn render(path: &str, value: &dyn Display) -> Option<Redacted> {
+        let scoped = SCOPED.with_borrow(|x| x.last().map(|p| p.render(path, value)));
+        if scoped.is_some() {
+            return scoped;
+        }
+
+        let global = GLOBAL.read().unwrap_or_else(|e| e.into_inner());
+        global.as_ref().map(|p| p.render(path, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_modes() {
+        let mask = Policy::new(Mode::Mask).with_pattern("*.password");
+        assert_eq!(mask.render("db.password", &"hunter2"), Redacted::Masked);
+        assert_eq!(
+            mask.render("db.user", &"admin"),
+            Redacted::Value("admin".into())
+        );
+        assert_eq!(
+            mask.render("password", &"hunter2"),
+            Redacted::Value("hunter2".into())
+        );
+
+        let drop = Policy::new(Mode::Drop).with_pattern("*.password");
+        assert!(drop.render("db.password", &"hunter2").is_dropped());
+        assert_eq!(drop.render("db.password", &"hunter2").to_string(), "");
+    }
+
+    #[test]
+    fn test_policy_hash() {
+        let policy = Policy::new(Mode::Hash).with_pattern("*.password");
+
+        let a = policy.render("db.password", &"hunter2");
+        let b = policy.render("cache.password", &"hunter2");
+        let c = policy.render("db.password", &"hunter3");
+
+        // Equal values hash the same, regardless of their path.
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        // Reference value of FNV-1a 64.
+        assert_eq!(
+            policy.render("x.password", &"a").to_string(),
+            "<redacted fnv1a64:af63dc4c8601ec8c>"
+        );
+        assert!(!a.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_scoped() {
+        let outer = Policy::new(Mode::Mask).with_pattern("*.password");
+        let inner = Policy::new(Mode::Drop).with_pattern("*.token");
+
+        assert_eq!(
+            render_value("db.password", &"x"),
+            Redacted::Value("x".into())
+        );
+
+        with_policy(outer, || {
+            assert_eq!(render_value("db.password", &"x"), Redacted::Masked);
+
+            with_policy(inner, || {
+                assert_eq!(
+                    render_value("db.password", &"x"),
+                    Redacted::Value("x".into())
+                );
+                assert!(render_value("api.token", &"x").is_dropped());
+            });
+
+            assert_eq!(render_value("db.password", &"x"), Redacted::Masked);
+        });
+
+        assert_eq!(
+            render_value("db.password", &"x"),
+            Redacted::Value("x".into())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_scoped_panic() {
+        let policy = Policy::new(Mode::Mask).with_pattern("*");
+
+        let r = std::panic::catch_unwind(|| with_policy(policy, || panic!("boom")));
+        assert!(r.is_err(), "the closure should have panicked");
+
+        assert_eq!(render_value("a", &"x"), Redacted::Value("x".into()));
+    }
+}
@@ -0,0 +1,196 @@
+//! Implementation details of the macros of [`test_util`].
+//!
+//! [`test_util`]: super
+
+use core::fmt::{Debug, Write};
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::merge::{Error, ErrorKind, Merge};
+use crate::redact::{Redacted, render_value};
+
+/// A rendered value.
+#[derive(Debug)]
+pub enum Rendered {
+    /// The whole value, rendered with [`Debug`].
+    Whole(String),
+
+    /// All leaves of the value, keyed by their path.
+    Leaves(BTreeMap<String, String>),
+}
+
+/// Wrapper used to pick the best way to render a value.
+///
+/// `(&Render(&x)).render()` resolves to [`ViaSerde`] if `x` implements
+/// `Serialize` and to [`ViaDebug`] otherwise.
+#[derive(Debug)]
+pub struct Render<'a, T>(pub &'a T);
+
+/// Render by serializing the value.
+pub trait ViaSerde {
+    fn render(&self) -> Rendered;
+}
+
+#[cfg(feature = "serde")]
+impl<T> ViaSerde for Render<'_, T>
+where
+    T: serde::Serialize,
+{
+    fn render(&self) -> Rendered {
+        match super::flatten::flatten(self.0) {
+            Ok(x) => Rendered::Leaves(x),
+            Err(e) => Rendered::Whole(format!("<failed to serialize: {e}>")),
+        }
+    }
+}
+
+/// Render with [`Debug`].
+pub trait ViaDebug {
+    fn render(&self) -> Rendered;
+}
+
+impl<T> ViaDebug for &Render<'_, T>
+where
+    T: Debug,
+{
+    fn render(&self) -> Rendered {
+        Rendered::Whole(format!("{:#?}", self.0))
+    }
+}
+
+/// Merge `b` into `a` with both [`Merge::merge`] and [`Merge::merge_ref`].
+///
+/// Panics if one of them fails and the other does not.
+pub fn merge_both<T>(a: T, b: T) -> Result<(T, T), Error>
+where
+    T: Merge + Clone,
+{
+    let merged = a.clone().merge(b.clone());
+
+    let mut merged_ref = a;
+    let r = merged_ref.merge_ref(b).map(|()| merged_ref);
+
+    match (merged, r) {
+        (Ok(x), Ok(y)) => Ok((x, y)),
+        (Err(e), Err(_)) => Err(e),
+        (Ok(_), Err(e)) => panic!("`merge` succeeded but `merge_ref` failed:\n\n{e}"),
+        (Err(e), Ok(_)) => panic!("`merge_ref` succeeded but `merge` failed:\n\n{e}"),
+    }
+}
+
+/// Panic with a message that shows how 2 values differ.
+pub fn values_differ(
+    message: &str,
+    (left_name, left): (&str, Rendered),
+    (right_name, right): (&str, Rendered),
+) -> ! {
+    let mut out = String::new();
+    let width = left_name.len().max(right_name.len());
+
+    let _ = writeln!(out, "{message}");
+
+    match (left, right) {
+        (Rendered::Leaves(left), Rendered::Leaves(right)) => {
+            let _ = writeln!(out, "differing values:");
+
+            let mut paths: Vec<&String> = left.keys().chain(right.keys()).collect();
+            paths.sort();
+            paths.dedup();
+
+            let mut hidden = 0;
+            for path in paths {
+                let l = left.get(path);
+                let r = right.get(path);
+                if l == r {
+                    continue;
+                }
+
+                let l = l.map(|x| render_value(path, x));
+                let r = r.map(|x| render_value(path, x));
+                if l.as_ref().is_some_and(Redacted::is_dropped)
+                    || r.as_ref().is_some_and(Redacted::is_dropped)
+                {
+                    hidden += 1;
+                    continue;
+                }
+
+                let path = if path.is_empty() { "(root)" } else { path };
+                let missing = Redacted::Value(String::from("<missing>"));
+
+                let _ = writeln!(out, "  {path}:");
+                let _ = writeln!(
+                    out,
+                    "    {left_name:>width$}: {}",
+                    l.as_ref().unwrap_or(&missing)
+                );
+                let _ = writeln!(
+                    out,
+                    "    {right_name:>width$}: {}",
+                    r.as_ref().unwrap_or(&missing)
+                );
+            }
+
+            if hidden != 0 {
+                let _ = writeln!(out, "  ({hidden} more hidden by the redaction policy)");
+            }
+        }
+        (left, right) => {
+            // The whole value is rendered as the value at the root.
+            let whole = |x: Rendered| {
+                let x = match x {
+                    Rendered::Whole(x) => x,
+                    Rendered::Leaves(x) => format!("{x:#?}"),
+                };
+                render_value("", &x)
+            };
+
+            let _ = writeln!(out, "{left_name:>width$}: {}", whole(left));
+            let _ = writeln!(out, "{right_name:>width$}: {}", whole(right));
+        }
+    }
+
+    panic!("{out}")
+}
+
+fn kind_name(kind: &ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Collision => "Collision",
+        ErrorKind::Cycle => "Cycle",
+        ErrorKind::Custom(_) => "Custom",
+        ErrorKind::Multiple(_) => "Multiple",
+    }
+}
+
+/// Check that the kind of `e` is the variant named `kind`.
+pub fn check_kind(e: &Error, kind: &str) {
+    let actual = kind_name(&e.kind);
+
+    if actual != kind {
+        panic!("error kind mismatch: expected `{kind}`, found `{actual}`\n\nerror:\n{e}");
+    }
+}
+
+/// Check that the value path of `e` is `path`.
+pub fn check_path(e: &Error, path: &str) {
+    let actual = e
+        .value
+        .components()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(".");
+
+    if actual != path {
+        panic!("error path mismatch: expected `{path}`, found `{actual}`\n\nerror:\n{e}");
+    }
+}
+
+/// Check that the module backtrace of `e` is `modules`, innermost first.
+pub fn check_modules(e: &Error, modules: &[&str]) {
+    let actual: Vec<String> = e.modules.iter().rev().map(|x| x.to_string()).collect();
+
+    if actual != modules {
+        panic!("error modules mismatch: expected {modules:?}, found {actual:?}\n\nerror:\n{e}");
+    }
+}
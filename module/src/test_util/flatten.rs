@@ -0,0 +1,549 @@
+//! Flatten a [`Serialize`] value into its leaves.
+//!
+//! Each leaf is keyed by its path, like `http.hosts[1]`, so 2 values can be
+//! compared leaf by leaf.
+
+use core::fmt::{self, Display};
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+
+pub type Leaves = BTreeMap<String, String>;
+
+pub fn flatten<T>(value: &T) -> Result<Leaves, FlattenError>
+where
+    T: Serialize + ?Sized,
+{
+    let mut out = Leaves::new();
+    value.serialize(Flattener {
+        out: &mut out,
+        path: String::new(),
+    })?;
+    Ok(out)
+}
+
+#[derive(Debug)]
+pub struct FlattenError(String);
+
+impl Display for FlattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl core::error::Error for FlattenError {}
+
+impl ser::Error for FlattenError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self(msg.to_string())
+    }
+}
+
+fn field(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+struct Flattener<'a> {
+    out: &'a mut Leaves,
+    path: String,
+}
+
+impl<'a> Flattener<'a> {
+    fn leaf(self, value: impl Display) -> Result<(), FlattenError> {
+        self.out.insert(self.path, value.to_string());
+        Ok(())
+    }
+
+    fn compound(self, empty: &'static str) -> Compound<'a> {
+        let before = self.out.len();
+
+        Compound {
+            out: self.out,
+            path: self.path,
+            index: 0,
+            before,
+            empty,
+            key: None,
+        }
+    }
+}
+
+impl<'a> ser::Serializer for Flattener<'a> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), FlattenError> {
+        self.leaf(format_args!("{v:?}"))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), FlattenError> {
+        self.leaf(format_args!("{v:?}"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), FlattenError> {
+        self.leaf(format_args!("{v:?}"))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), FlattenError> {
+        self.leaf(format_args!("{v:?}"))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), FlattenError> {
+        self.leaf(format_args!("{v:?}"))
+    }
+
+    fn serialize_none(self) -> Result<(), FlattenError> {
+        self.leaf("None")
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), FlattenError> {
+        self.leaf("()")
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), FlattenError> {
+        self.leaf(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), FlattenError> {
+        self.leaf(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(Flattener {
+            path: field(&self.path, variant),
+            out: self.out,
+        })
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound("[]"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound("()"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound("()"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FlattenError> {
+        let path = field(&self.path, variant);
+        Ok(Flattener {
+            out: self.out,
+            path,
+        }
+        .compound("()"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound("{}"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound("{}"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FlattenError> {
+        let path = field(&self.path, variant);
+        Ok(Flattener {
+            out: self.out,
+            path,
+        }
+        .compound("{}"))
+    }
+}
+
+/// Serializer for sequences, maps and structs.
+///
+/// If no leaves are added below it, the compound itself becomes a leaf with
+/// the value of `empty`. This keeps empty compounds distinguishable from
+/// missing ones.
+struct Compound<'a> {
+    out: &'a mut Leaves,
+    path: String,
+    index: usize,
+    before: usize,
+    empty: &'static str,
+    key: Option<String>,
+}
+
+impl Compound<'_> {
+    fn element<T>(&mut self, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let path = format!("{}[{}]", self.path, self.index);
+        self.index += 1;
+        self.child(path, value)
+    }
+
+    fn child<T>(&mut self, path: String, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(Flattener {
+            out: self.out,
+            path,
+        })
+    }
+
+    fn finish(self) -> Result<(), FlattenError> {
+        if self.out.len() == self.before {
+            self.out.insert(self.path, self.empty.to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| FlattenError("map value without a key".to_string()))?;
+
+        let path = field(&self.path, &key);
+        self.child(path, value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let path = field(&self.path, key);
+        self.child(path, value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let path = field(&self.path, key);
+        self.child(path, value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        self.finish()
+    }
+}
+
+/// Serializer for map keys.
+///
+/// Only scalar keys can be part of a path.
+struct KeySerializer;
+
+macro_rules! display_keys {
+    ($($method:ident: $t:ty),*) => {
+        $(
+            fn $method(self, v: $t) -> Result<String, FlattenError> {
+                Ok(v.to_string())
+            }
+        )*
+    };
+}
+
+macro_rules! unsupported_keys {
+    ($($method:ident($($arg:ty),*) -> $ret:ty),*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, FlattenError> {
+                Err(FlattenError("map keys must be scalars".to_string()))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = FlattenError;
+
+    type SerializeSeq = Impossible<String, FlattenError>;
+    type SerializeTuple = Impossible<String, FlattenError>;
+    type SerializeTupleStruct = Impossible<String, FlattenError>;
+    type SerializeTupleVariant = Impossible<String, FlattenError>;
+    type SerializeMap = Impossible<String, FlattenError>;
+    type SerializeStruct = Impossible<String, FlattenError>;
+    type SerializeStructVariant = Impossible<String, FlattenError>;
+
+    display_keys! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str
+    }
+
+    unsupported_keys! {
+        serialize_bytes(&[u8]) -> String,
+        serialize_none() -> String,
+        serialize_unit() -> String,
+        serialize_seq(Option<usize>) -> Self::SerializeSeq,
+        serialize_tuple(usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant,
+        serialize_map(Option<usize>) -> Self::SerializeMap,
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct,
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<String, FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<String, FlattenError> {
+        Ok(name.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, FlattenError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, FlattenError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(FlattenError("map keys must be scalars".to_string()))
+    }
+}
@@ -0,0 +1,309 @@
+//! Runtime support for [`merge_test_matrix`].
+//!
+//! [`merge_test_matrix`]: super::merge_test_matrix
+
+use core::fmt::{self, Debug};
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::merge::Merge;
+use crate::types::{First, Last, Overridable};
+
+/// Sample values used to test how a type merges.
+///
+/// See: [`merge_test_matrix`].
+///
+/// [`merge_test_matrix`]: super::merge_test_matrix
+pub trait TestValues: Sized {
+    /// A value as set by a module.
+    fn sample_a() -> Self;
+
+    /// A value as set by a module, different from [`sample_a()`].
+    ///
+    /// [`sample_a()`]: TestValues::sample_a
+    fn sample_b() -> Self;
+
+    /// The value of a field that a module does not set.
+    ///
+    /// This is usually the [`Default`] value.
+    fn unset() -> Self;
+}
+
+macro_rules! impl_test_values {
+    ($($t:ty: $a:expr, $b:expr, $unset:expr;)*) => {
+        $(
+            impl TestValues for $t {
+                fn sample_a() -> Self { $a }
+                fn sample_b() -> Self { $b }
+                fn unset() -> Self { $unset }
+            }
+        )*
+    };
+}
+
+impl_test_values! {
+    bool: true, false, false;
+    char: 'a', 'b', '\0';
+    u8: 1, 2, 0;
+    u16: 1, 2, 0;
+    u32: 1, 2, 0;
+    u64: 1, 2, 0;
+    u128: 1, 2, 0;
+    usize: 1, 2, 0;
+    i8: 1, 2, 0;
+    i16: 1, 2, 0;
+    i32: 1, 2, 0;
+    i64: 1, 2, 0;
+    i128: 1, 2, 0;
+    isize: 1, 2, 0;
+    String: String::from("a"), String::from("b"), String::new();
+}
+
+impl<T> TestValues for Option<T>
+where
+    T: TestValues,
+{
+    fn sample_a() -> Self {
+        Some(T::sample_a())
+    }
+
+    fn sample_b() -> Self {
+        Some(T::sample_b())
+    }
+
+    fn unset() -> Self {
+        None
+    }
+}
+
+impl<T> TestValues for Vec<T>
+where
+    T: TestValues,
+{
+    fn sample_a() -> Self {
+        vec![T::sample_a()]
+    }
+
+    fn sample_b() -> Self {
+        vec![T::sample_b()]
+    }
+
+    fn unset() -> Self {
+        Vec::new()
+    }
+}
+
+impl<T> TestValues for First<T>
+where
+    T: TestValues,
+{
+    fn sample_a() -> Self {
+        First(T::sample_a())
+    }
+
+    fn sample_b() -> Self {
+        First(T::sample_b())
+    }
+
+    fn unset() -> Self {
+        First(T::unset())
+    }
+}
+
+impl<T> TestValues for Last<T>
+where
+    T: TestValues,
+{
+    fn sample_a() -> Self {
+        Last(T::sample_a())
+    }
+
+    fn sample_b() -> Self {
+        Last(T::sample_b())
+    }
+
+    fn unset() -> Self {
+        Last(T::unset())
+    }
+}
+
+/// Both samples have a priority less than `DEFAULT`, with [`sample_a()`]
+/// having the least. [`unset()`] has the default priority.
+///
+/// [`sample_a()`]: TestValues::sample_a
+/// [`unset()`]: TestValues::unset
+impl<T, const DEFAULT: isize, S> TestValues for Overridable<T, DEFAULT, S>
+where
+    T: TestValues,
+{
+    fn sample_a() -> Self {
+        Self::with_priority(T::sample_a(), DEFAULT.saturating_sub(20))
+    }
+
+    fn sample_b() -> Self {
+        Self::with_priority(T::sample_b(), DEFAULT.saturating_sub(10))
+    }
+
+    fn unset() -> Self {
+        Self::new(T::unset())
+    }
+}
+
+/// The values merged by a test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// [`sample_a()`] merged with [`unset()`].
+    ///
+    /// [`sample_a()`]: TestValues::sample_a
+    /// [`unset()`]: TestValues::unset
+    SetUnset,
+    /// [`unset()`] merged with [`sample_a()`].
+    ///
+    /// [`sample_a()`]: TestValues::sample_a
+    /// [`unset()`]: TestValues::unset
+    UnsetSet,
+    /// [`sample_a()`] merged with itself.
+    ///
+    /// [`sample_a()`]: TestValues::sample_a
+    SetSetEqual,
+    /// [`sample_a()`] merged with [`sample_b()`].
+    ///
+    /// [`sample_a()`]: TestValues::sample_a
+    /// [`sample_b()`]: TestValues::sample_b
+    SetSetDifferent,
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::SetUnset => "(set, unset)",
+            Self::UnsetSet => "(unset, set)",
+            Self::SetSetEqual => "(set, set-equal)",
+            Self::SetSetDifferent => "(set, set-different)",
+        })
+    }
+}
+
+/// The outcome of merging 2 values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The result is the left value.
+    Kept,
+    /// The result is the right value.
+    Filled,
+    /// The result is neither of the values.
+    Merged,
+    /// Merging failed with a collision.
+    Collided,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Kept => "kept",
+            Self::Filled => "filled",
+            Self::Merged => "merged",
+            Self::Collided => "collided",
+        })
+    }
+}
+
+/// Check that merging the values of `case` has the `expected` outcome.
+///
+/// `field` is never called, it only selects the type of the values.
+///
+/// # Panics
+///
+/// * If merging fails with an error other than a collision.
+/// * If [`Merge::merge`] and [`Merge::merge_ref`] disagree.
+/// * If the outcome is not `expected`.
+pub fn check<S, F>(_field: fn(&S) -> &F, name: &str, case: Case, expected: Outcome)
+where
+    F: TestValues + Merge + Clone + PartialEq + Debug,
+{
+    let (left, right) = match case {
+        Case::SetUnset => (F::sample_a(), F::unset()),
+        Case::UnsetSet => (F::unset(), F::sample_a()),
+        Case::SetSetEqual => (F::sample_a(), F::sample_a()),
+        Case::SetSetDifferent => (F::sample_a(), F::sample_b()),
+    };
+
+    let actual = match super::__private::merge_both(left.clone(), right.clone()) {
+        Ok((merged, merged_ref)) => {
+            assert!(
+                merged == merged_ref,
+                "`{name}` {case}: `merge` and `merge_ref` disagree\n\n\
+                 merge: {merged:?}\nmerge_ref: {merged_ref:?}"
+            );
+
+            if merged == left {
+                Outcome::Kept
+            } else if merged == right {
+                Outcome::Filled
+            } else {
+                Outcome::Merged
+            }
+        }
+        Err(e) if e.kind.is_collision() => Outcome::Collided,
+        Err(e) => panic!("`{name}` {case}: merging failed unexpectedly:\n\n{e}"),
+    };
+
+    assert!(
+        actual == expected,
+        "`{name}` {case}: expected the values to be {expected}, but they were {actual}\n\n\
+         left: {left:?}\nright: {right:?}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sample {
+        a: Option<u16>,
+        b: Vec<i32>,
+        c: Overridable<String>,
+    }
+
+    #[test]
+    fn test_check() {
+        use Case::*;
+        use Outcome::*;
+
+        for (case, outcome) in [
+            (SetUnset, Kept),
+            (UnsetSet, Filled),
+            (SetSetEqual, Collided),
+            (SetSetDifferent, Collided),
+        ] {
+            check(|x: &Sample| &x.a, "a", case, outcome);
+        }
+
+        for (case, outcome) in [
+            (SetUnset, Kept),
+            (UnsetSet, Filled),
+            (SetSetEqual, Merged),
+            (SetSetDifferent, Merged),
+        ] {
+            check(|x: &Sample| &x.b, "b", case, outcome);
+        }
+
+        for (case, outcome) in [
+            (SetUnset, Kept),
+            (UnsetSet, Filled),
+            (SetSetEqual, Collided),
+            (SetSetDifferent, Kept),
+        ] {
+            check(|x: &Sample| &x.c, "c", case, outcome);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "`a` (set, unset): expected the values to be filled, but they were kept"
+    )]
+    fn test_check_mismatch() {
+        check(|x: &Sample| &x.a, "a", Case::SetUnset, Outcome::Filled);
+    }
+}
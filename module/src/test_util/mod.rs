@@ -0,0 +1,170 @@
+//! Assertions for testing [`Merge`] implementations.
+//!
+//! This module contains the [`assert_merged_eq!`] and [`assert_merge_err!`]
+//! macros, which take care of the boilerplate of testing how values merge.
+//!
+//! Both macros merge the values with [`Merge::merge`] and [`Merge::merge_ref`]
+//! and fail if the two disagree.
+//!
+//! With the `test-macros` feature, `merge_test_matrix` generates tests for
+//! every field of a struct from a table of expected outcomes. See: [`matrix`].
+//!
+//! [`Merge`]: crate::Merge
+//! [`Merge::merge`]: crate::Merge::merge
+//! [`Merge::merge_ref`]: crate::Merge::merge_ref
+
+#[cfg(feature = "serde")]
+mod flatten;
+
+pub mod matrix;
+
+#[doc(inline)]
+pub use self::matrix::TestValues;
+
+/// Generate tests for how the fields of a struct merge.
+///
+/// See the [derive crate](module_derive::merge_test_matrix) for the full
+/// documentation.
+#[cfg(feature = "test-macros")]
+pub use module_derive::merge_test_matrix;
+
+#[doc(hidden)]
+pub mod __private;
+
+/// Assert that merging 2 values results in the expected value.
+///
+/// * **Syntax:** `assert_merged_eq!(a, b => expected)`
+///
+/// Merges `b` into `a` and compares the result against `expected`. The type
+/// of the values must implement [`Merge`], [`Clone`], [`PartialEq`] and either
+/// [`Debug`] or [`Serialize`].
+///
+/// On failure, only the differing parts of the values are printed. This is
+/// only possible for types that implement [`Serialize`] and requires the
+/// `serde` feature. Otherwise, the values are printed in full with [`Debug`].
+///
+/// # Panics
+///
+/// * If merging fails.
+/// * If [`Merge::merge`] and [`Merge::merge_ref`] produce different results.
+/// * If the result is not equal to `expected`.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::test_util::assert_merged_eq;
+/// assert_merged_eq!(vec![1, 2], vec![3] => vec![1, 2, 3]);
+/// assert_merged_eq!(Some(1), None => Some(1));
+/// ```
+///
+/// [`Merge`]: crate::Merge
+/// [`Merge::merge`]: crate::Merge::merge
+/// [`Merge::merge_ref`]: crate::Merge::merge_ref
+/// [`Debug`]: core::fmt::Debug
+/// [`Serialize`]: serde::Serialize
+#[doc(inline)]
+pub use crate::__assert_merged_eq as assert_merged_eq;
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_merged_eq {
+    ($a:expr, $b:expr => $expected:expr $(,)?) => {{
+        use $crate::test_util::__private::{Render, merge_both, values_differ};
+        #[allow(unused_imports)]
+        use $crate::test_util::__private::{ViaDebug as _, ViaSerde as _};
+
+        let expected = $expected;
+
+        match merge_both($a, $b) {
+            ::core::result::Result::Ok((merged, merged_ref)) => {
+                if merged != merged_ref {
+                    values_differ(
+                        "`merge` and `merge_ref` disagree",
+                        ("merge", (&Render(&merged)).render()),
+                        ("merge_ref", (&Render(&merged_ref)).render()),
+                    );
+                }
+
+                if merged != expected {
+                    values_differ(
+                        "assertion `merged == expected` failed",
+                        ("merged", (&Render(&merged)).render()),
+                        ("expected", (&Render(&expected)).render()),
+                    );
+                }
+            }
+            ::core::result::Result::Err(e) => {
+                ::core::panic!("merging failed unexpectedly:\n\n{e}")
+            }
+        }
+    }};
+}
+
+/// Assert that merging 2 values fails.
+///
+/// * **Syntax:** `assert_merge_err!(a, b => kind: Collision, path: "http.port", modules: ["user.toml"])`
+///
+/// Merges `b` into `a` and checks the returned [`Error`] against each of the
+/// given matchers. All matchers are optional and can appear in any order.
+///
+/// * `kind`: The name of the [`ErrorKind`] variant, like `Collision`.
+/// * `path`: The path of the value, with components joined by `.`.
+/// * `modules`: The module backtrace, innermost module first.
+///
+/// # Panics
+///
+/// * If merging succeeds.
+/// * If [`Merge::merge`] and [`Merge::merge_ref`] do not both fail.
+/// * If any of the matchers does not match.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::test_util::assert_merge_err;
+/// assert_merge_err!(1, 2 => kind: Collision);
+/// assert_merge_err!(1, 2 => kind: Collision, path: "");
+/// ```
+///
+/// [`Error`]: crate::Error
+/// [`ErrorKind`]: crate::merge::ErrorKind
+/// [`Merge::merge`]: crate::Merge::merge
+/// [`Merge::merge_ref`]: crate::Merge::merge_ref
+#[doc(inline)]
+pub use crate::__assert_merge_err as assert_merge_err;
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_merge_err {
+    ($a:expr, $b:expr $(=> $($matchers:tt)*)?) => {{
+        match $crate::test_util::__private::merge_both($a, $b) {
+            ::core::result::Result::Ok(_) => {
+                ::core::panic!("expected merging to fail, but it succeeded")
+            }
+            ::core::result::Result::Err(e) => {
+                $( $crate::__assert_merge_err_matchers!(e; $($matchers)*); )?
+                let _ = e;
+            }
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_merge_err_matchers {
+    ($e:ident;) => {};
+    ($e:ident; kind: $kind:ident $(, $($rest:tt)*)?) => {
+        $crate::test_util::__private::check_kind(&$e, ::core::stringify!($kind));
+        $crate::__assert_merge_err_matchers!($e; $($($rest)*)?);
+    };
+    ($e:ident; path: $path:expr $(, $($rest:tt)*)?) => {
+        $crate::test_util::__private::check_path(&$e, $path);
+        $crate::__assert_merge_err_matchers!($e; $($($rest)*)?);
+    };
+    ($e:ident; modules: [$($module:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::test_util::__private::check_modules(&$e, &[$($module),*]);
+        $crate::__assert_merge_err_matchers!($e; $($($rest)*)?);
+    };
+}
+
+#[cfg(test)]
+mod tests;
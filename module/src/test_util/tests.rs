@@ -0,0 +1,227 @@
+use crate::test::*;
+
+use alloc::vec;
+
+use super::{assert_merge_err, assert_merged_eq};
+
+#[derive(Debug, Clone)]
+struct Failing;
+
+impl Merge for Failing {
+    fn merge_ref(&mut self, _: Self) -> Result<(), Error> {
+        Err(Error::collision())
+            .value("port")
+            .value("http")
+            .module("user.toml")
+            .module("config.toml")
+    }
+}
+
+/// Merges differently depending on whether `merge` or `merge_ref` is used.
+#[derive(Debug, Clone, PartialEq)]
+struct Inconsistent(i32);
+
+impl Merge for Inconsistent {
+    fn merge(self, _: Self) -> Result<Self, Error> {
+        Ok(Self(1))
+    }
+
+    fn merge_ref(&mut self, _: Self) -> Result<(), Error> {
+        self.0 = 2;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_merged_eq() {
+    assert_merged_eq!(vec![1, 2], vec![3] => vec![1, 2, 3]);
+    assert_merged_eq!(Some(1), None => Some(1));
+    assert_merged_eq!(Merged(false), Merged(false) => Merged(true));
+}
+
+#[test]
+#[should_panic(expected = "`merge` and `merge_ref` disagree")]
+fn test_merged_eq_inconsistent() {
+    assert_merged_eq!(Inconsistent(0), Inconsistent(0) => Inconsistent(1));
+}
+
+#[test]
+#[should_panic(expected = "merging failed unexpectedly")]
+fn test_merged_eq_error() {
+    assert_merged_eq!(1, 2 => 1);
+}
+
+#[test]
+#[should_panic(expected = "  merged: Merged(\n    true,\n)\nexpected: Merged(\n    false,\n)")]
+fn test_merged_eq_debug_fallback() {
+    assert_merged_eq!(Merged(false), Merged(false) => Merged(false));
+}
+
+#[test]
+#[cfg(all(feature = "serde", feature = "std"))]
+fn test_merged_eq_diff() {
+    use alloc::collections::BTreeMap;
+    use alloc::string::String;
+    use std::panic;
+
+    let map = |x: &[(&'static str, &[i32])]| -> BTreeMap<&'static str, alloc::vec::Vec<i32>> {
+        x.iter().map(|(k, v)| (*k, v.to_vec())).collect()
+    };
+
+    let a = map(&[("http", &[1]), ("tls", &[2])]);
+    let b = map(&[("tls", &[3])]);
+    let expected = map(&[("http", &[1]), ("tls", &[2, 4])]);
+
+    let err = panic::catch_unwind(|| assert_merged_eq!(a, b => expected)).unwrap_err();
+    let msg = err.downcast::<String>().unwrap();
+
+    assert_eq!(
+        *msg,
+        "assertion `merged == expected` failed
+differing values:
+  tls[1]:
+      merged: 3
+    expected: 4
+"
+    );
+}
+
+#[cfg(all(feature = "serde", feature = "std"))]
+fn redacted_diff(policy: crate::redact::Policy) -> alloc::string::String {
+    use alloc::collections::BTreeMap;
+    use alloc::string::String;
+    use std::panic;
+
+    type Config = BTreeMap<&'static str, BTreeMap<&'static str, alloc::vec::Vec<i32>>>;
+
+    let db = |password: &[i32], user: &[i32]| -> Config {
+        BTreeMap::from([(
+            "db",
+            BTreeMap::from([("password", password.to_vec()), ("user", user.to_vec())]),
+        )])
+    };
+
+    let a = db(&[1], &[2]);
+    let b = db(&[3], &[4]);
+    let expected = db(&[1, 5], &[2, 5]);
+
+    let err = crate::redact::with_policy(policy, || {
+        panic::catch_unwind(|| assert_merged_eq!(a, b => expected)).unwrap_err()
+    });
+    *err.downcast::<String>().unwrap()
+}
+
+#[test]
+#[cfg(all(feature = "serde", feature = "std"))]
+fn test_merged_eq_diff_redacted() {
+    use crate::redact::{Mode, Policy};
+
+    let policy = |mode| Policy::new(mode).with_pattern("*.password*");
+
+    assert_eq!(
+        redacted_diff(policy(Mode::Mask)),
+        "assertion `merged == expected` failed
+differing values:
+  db.password[1]:
+      merged: <redacted>
+    expected: <redacted>
+  db.user[1]:
+      merged: 4
+    expected: 5
+"
+    );
+
+    assert_eq!(
+        redacted_diff(policy(Mode::Drop)),
+        "assertion `merged == expected` failed
+differing values:
+  db.user[1]:
+      merged: 4
+    expected: 5
+  (1 more hidden by the redaction policy)
+"
+    );
+
+    let hashed = redacted_diff(policy(Mode::Hash));
+    assert!(
+        hashed.contains("      merged: <redacted fnv1a64:"),
+        "{hashed}"
+    );
+    // The digests are stable.
+    assert_eq!(hashed, redacted_diff(policy(Mode::Hash)));
+}
+
+#[test]
+fn test_merge_err() {
+    assert_merge_err!(1, 2);
+    assert_merge_err!(1, 2 => kind: Collision);
+    assert_merge_err!(Failing, Failing => kind: Collision, path: "http.port");
+    assert_merge_err!(Failing, Failing => path: "http.port", modules: ["user.toml", "config.toml"]);
+    assert_merge_err!(Failing, Failing => modules: ["user.toml", "config.toml"],);
+}
+
+#[test]
+#[should_panic(expected = "expected merging to fail, but it succeeded")]
+fn test_merge_err_success() {
+    assert_merge_err!(vec![1], vec![2] => kind: Collision);
+}
+
+#[test]
+#[should_panic(expected = "error kind mismatch: expected `Cycle`, found `Collision`")]
+fn test_merge_err_kind() {
+    assert_merge_err!(Failing, Failing => kind: Cycle);
+}
+
+#[test]
+#[should_panic(
+    expected = "error path mismatch: expected `http.host`, found `http.port`\n\n\
+                           error:\nvalue collision while evaluating 'http.port'"
+)]
+fn test_merge_err_path() {
+    assert_merge_err!(Failing, Failing => kind: Collision, path: "http.host");
+}
+
+#[test]
+#[should_panic(
+    expected = "error modules mismatch: expected [\"user.toml\"], found [\"user.toml\", \"config.toml\"]"
+)]
+fn test_merge_err_modules() {
+    assert_merge_err!(Failing, Failing => modules: ["user.toml"]);
+}
+
+#[cfg(feature = "test-macros")]
+#[super::merge_test_matrix]
+mod matrix {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use crate::types::{First, Last, Overridable};
+
+    #[allow(dead_code)]
+    struct Config {
+        port: u16,
+        host: Option<String>,
+        rustflags: Vec<String>,
+        jobs: Overridable<u32>,
+        name: First<String>,
+        target: Last<String>,
+        r#type: Option<bool>,
+    }
+
+    #[allow(dead_code)]
+    struct Empty {}
+
+    matrix! {
+        Config {
+            port: collide,
+            host: optional,
+            rustflags: append,
+            jobs: override_lowest,
+            name: first,
+            target: last,
+            r#type: (kept, filled, collided, collided),
+        }
+
+        Empty {}
+    }
+}
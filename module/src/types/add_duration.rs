@@ -0,0 +1,109 @@
+//! Durations that are added together when merged.
+//!
+//! See: [`AddDuration`].
+
+use core::fmt;
+use core::time::Duration;
+
+use super::prelude::*;
+
+/// Durations that are added together when merged.
+///
+/// [`Duration`] itself cannot be merged. This is useful for durations that
+/// every module adds to, like timeouts or retry budgets.
+///
+/// Unlike [`Sum<Duration>`], which panics, an overflowing sum is an error.
+///
+/// # Example
+///
+/// ```rust
+/// # use core::time::Duration;
+/// # use module::types::AddDuration;
+/// # use module::merge::Merge;
+/// let a = AddDuration(Duration::from_secs(1));
+/// let b = AddDuration(Duration::from_millis(500));
+///
+/// let merged = a.merge(b).unwrap();
+///
+/// assert_eq!(*merged, Duration::from_millis(1500));
+/// ```
+///
+/// # serde
+///
+/// This type deserializes like [`Duration`].
+///
+/// [`Sum<Duration>`]: crate::types::Sum
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct AddDuration(pub Duration);
+
+impl AddDuration {
+    /// Destruct this [`AddDuration`] and get the duration.
+    #[inline]
+    pub fn into_inner(self) -> Duration {
+        self.0
+    }
+}
+
+impl Merge for AddDuration {
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        self.0 = self
+            .0
+            .checked_add(other.0)
+            .ok_or_else(|| Error::custom(Overflow))?;
+        Ok(())
+    }
+}
+
+impl From<Duration> for AddDuration {
+    #[inline]
+    fn from(x: Duration) -> Self {
+        Self(x)
+    }
+}
+
+impl_wrapper!(AddDuration => Duration { .0 });
+
+struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("duration overflow")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_merge() {
+        let merged = AddDuration(Duration::from_secs(1))
+            .merge(AddDuration(Duration::from_millis(500)))
+            .unwrap();
+        assert_eq!(merged.into_inner(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let err = AddDuration(Duration::MAX)
+            .merge(AddDuration(Duration::from_nanos(1)))
+            .unwrap_err();
+        assert!(err.kind.is_custom());
+        assert_eq!(err.kind.to_string(), "duration overflow");
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: AddDuration = serde_json::from_str(r#"{ "secs": 1, "nanos": 500000000 }"#).unwrap();
+        assert_eq!(*x, Duration::from_millis(1500));
+    }
+}
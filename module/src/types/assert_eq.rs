@@ -0,0 +1,77 @@
+//! Values that may only be set to the same value.
+//!
+//! See: [`AssertEq`].
+
+use super::prelude::*;
+
+merge_thin_wrapper! {
+    /// A value that may be set more than once, but only to the same value.
+    ///
+    /// This is like [`NoMerge`], except that merging 2 equal values is not an
+    /// error. Modules that set the same value are harmless duplicates, while
+    /// modules that set different values are still a collision.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::types::AssertEq;
+    /// # use module::merge::{Merge, ErrorKind};
+    /// let merged = AssertEq("foo").merge(AssertEq("foo")).unwrap();
+    /// assert_eq!(*merged, "foo");
+    ///
+    /// let err = AssertEq("foo").merge(AssertEq("bar")).unwrap_err();
+    /// assert_eq!(err.kind, ErrorKind::Collision);
+    /// ```
+    ///
+    /// # serde
+    ///
+    /// This type deserializes like `T`.
+    ///
+    /// [`NoMerge`]: crate::types::NoMerge
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+    pub struct AssertEq;
+}
+
+impl<T> Merge for AssertEq<T>
+where
+    T: PartialEq,
+{
+    #[inline]
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        if self.0 == other.0 {
+            Ok(())
+        } else {
+            Err(Error::collision())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    #[test]
+    fn test_merge_equal() {
+        let merged = AssertEq(vec![1, 2]).merge(AssertEq(vec![1, 2])).unwrap();
+        assert_eq!(*merged, [1, 2]);
+    }
+
+    #[test]
+    fn test_merge_unequal() {
+        let err = AssertEq(42).merge(AssertEq(43)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: AssertEq<i32> = serde_json::from_str("42").unwrap();
+        assert_eq!(*x, 42);
+    }
+}
@@ -0,0 +1,378 @@
+//! Overridable values that are resolved after merging.
+//!
+//! See: [`Candidates`].
+
+use core::fmt;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::overridable::{Overridable, Priority};
+use super::prelude::*;
+use crate::merge::Resolve;
+
+/// A value contributed to [`Candidates`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate<T> {
+    value: T,
+    priority: Priority,
+    origin: Option<String>,
+}
+
+impl<T> Candidate<T> {
+    /// Get the value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Get the priority of the value.
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Get the module the value came from, if known.
+    pub fn origin(&self) -> Option<&str> {
+        self.origin.as_deref()
+    }
+
+    /// Destruct this [`Candidate`] and get the value.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+/// An overridable value that is resolved after merging.
+///
+/// This is like [`Overridable`], except that merging never decides which
+/// value wins. Instead, [`Candidates`] keeps every value, in the order they
+/// were merged, along with its priority and the module it came from. The
+/// value with the least priority is picked by [`resolve()`], once everything
+/// has been merged.
+///
+/// Because all values are known by then, a tie reports every value that has
+/// the least priority, not just the first 2 that happened to be merged.
+///
+/// The module of a value is recorded when it is deserialized, from
+/// [`current_module()`], if the `current-module` feature is enabled. It can
+/// also be set with [`with_origin()`].
+///
+/// # Example
+///
+/// ```rust
+/// # use module::types::Candidates;
+/// # use module::merge::Merge;
+/// let a = Candidates::with_priority("foo", 10).with_origin("a.json");
+/// let b = Candidates::with_priority("bar", 5).with_origin("b.json");
+/// let c = Candidates::with_priority("baz", 5).with_origin("c.json");
+///
+/// let merged: Candidates<&str> = a.merge(b).unwrap();
+/// assert_eq!(merged.clone().resolve().unwrap(), "bar");
+///
+/// let err = merged.merge(c).unwrap().resolve().unwrap_err();
+/// assert_eq!(
+///     err.kind.to_string(),
+///     "2 values have the same priority 5, in b.json and c.json"
+/// );
+/// ```
+///
+/// # serde
+///
+/// This type deserializes exactly like [`Overridable`].
+///
+/// [`resolve()`]: Candidates::resolve
+/// [`current_module()`]: crate::eval::current_module
+/// [`with_origin()`]: Candidates::with_origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidates<T, const DEFAULT: isize = 500> {
+    list: Vec<Candidate<T>>,
+}
+
+impl<T, const DEFAULT: isize> Candidates<T, DEFAULT> {
+    /// Create a new `value` with the default priority.
+    pub fn new(value: T) -> Self {
+        Self::with_priority(value, DEFAULT)
+    }
+
+    /// Create a new `value` with `priority`.
+    pub fn with_priority<P>(value: T, priority: P) -> Self
+    where
+        P: Into<Priority>,
+    {
+        Self {
+            list: vec![Candidate {
+                value,
+                priority: priority.into(),
+                origin: None,
+            }],
+        }
+    }
+
+    /// Set the module of all values whose module is not known.
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        let origin = origin.into();
+
+        self.list
+            .iter_mut()
+            .filter(|x| x.origin.is_none())
+            .for_each(|x| x.origin = Some(origin.clone()));
+
+        self
+    }
+
+    /// Get all values, in the order they were merged.
+    pub fn candidates(&self) -> &[Candidate<T>] {
+        &self.list
+    }
+
+    /// Get the value, if only one is left.
+    ///
+    /// This is the case for [`Candidates`] that were never merged, or after
+    /// [`Resolve::resolve_in_place`] succeeds.
+    pub fn resolved(&self) -> Option<&T> {
+        match self.list.as_slice() {
+            [x] => Some(&x.value),
+            _ => None,
+        }
+    }
+
+    /// Pick the value with the least priority.
+    ///
+    /// # Errors
+    ///
+    /// If more than one value has the least priority. The error lists all of
+    /// them.
+    pub fn resolve(self) -> Result<T, Error> {
+        self.resolve_candidate().map(Candidate::into_value)
+    }
+
+    fn resolve_candidate(mut self) -> Result<Candidate<T>, Error> {
+        let i = self.winner()?;
+        Ok(self.list.swap_remove(i))
+    }
+
+    /// Find the index of the value with the least priority.
+    fn winner(&self) -> Result<usize, Error> {
+        let min = self
+            .list
+            .iter()
+            .map(|x| x.priority)
+            .min()
+            .ok_or_else(|| Error::custom("no values to choose from"))?;
+
+        let mut tied = self
+            .list
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.priority == min);
+
+        let (i, _) = tied
+            .next()
+            .ok_or_else(|| Error::custom("no values to choose from"))?;
+
+        if tied.next().is_none() {
+            return Ok(i);
+        }
+
+        let origins = self
+            .list
+            .iter()
+            .filter(|x| x.priority == min)
+            .map(|x| x.origin.clone())
+            .collect();
+
+        Err(Error::custom(Tie {
+            priority: min,
+            origins,
+        }))
+    }
+}
+
+impl<T, const DEFAULT: isize> Merge for Candidates<T, DEFAULT> {
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        self.list.extend(other.list);
+        Ok(())
+    }
+}
+
+impl<T, const DEFAULT: isize> Resolve for Candidates<T, DEFAULT> {
+    fn resolve_in_place(&mut self) -> Result<(), Error> {
+        let i = self.winner()?;
+        self.list.swap(0, i);
+        self.list.truncate(1);
+        Ok(())
+    }
+}
+
+impl<T, const DEFAULT: isize> From<T> for Candidates<T, DEFAULT> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, const DEFAULT: isize> Default for Candidates<T, DEFAULT>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T, const DEFAULT: isize> From<Overridable<T, DEFAULT>> for Candidates<T, DEFAULT> {
+    fn from(x: Overridable<T, DEFAULT>) -> Self {
+        let priority = x.priority();
+        Self::with_priority(x.into_value(), priority)
+    }
+}
+
+impl<T, const DEFAULT: isize, S> TryFrom<Candidates<T, DEFAULT>> for Overridable<T, DEFAULT, S> {
+    type Error = Error;
+
+    /// See: [`Candidates::resolve`].
+    fn try_from(x: Candidates<T, DEFAULT>) -> Result<Self, Self::Error> {
+        let Candidate {
+            value, priority, ..
+        } = x.resolve_candidate()?;
+
+        Ok(Self::with_priority(value, priority))
+    }
+}
+
+/// The error of a tie between [`Candidates`].
+struct Tie {
+    priority: Priority,
+    origins: Vec<Option<String>>,
+}
+
+impl fmt::Display for Tie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} values have the same priority {:?}, in ",
+            self.origins.len(),
+            self.priority
+        )?;
+
+        let last = self.origins.len() - 1;
+        for (i, origin) in self.origins.iter().enumerate() {
+            match i {
+                0 => {}
+                _ if i == last => f.write_str(" and ")?,
+                _ => f.write_str(", ")?,
+            }
+
+            match origin {
+                Some(x) => f.write_str(x)?,
+                None => f.write_str("an unknown module")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use serde::Deserialize;
+    use serde::de::Deserializer;
+
+    impl<'de, T, const DEFAULT: isize> Deserialize<'de> for Candidates<T, DEFAULT>
+    where
+        T: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let x = Overridable::<T, DEFAULT>::deserialize(deserializer)?;
+
+            let mut this = Self::from(x);
+            this.list[0].origin = crate::eval::current_module();
+            Ok(this)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    #[inline]
+    fn x(value: i32, priority: isize, origin: &str) -> Candidates<i32> {
+        Candidates::with_priority(value, priority).with_origin(origin)
+    }
+
+    #[test]
+    fn test_resolve() {
+        let c = x(1, 10, "a").merge(x(2, 5, "b")).unwrap();
+        let c = c.merge(x(3, 7, "c")).unwrap();
+
+        assert_eq!(c.candidates().len(), 3);
+        assert_eq!(c.resolved(), None);
+        assert_eq!(c.resolve().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_resolve_tie() {
+        let c = x(1, 100, "a.json")
+            .merge(x(2, 100, "b.json"))
+            .unwrap()
+            .merge(x(3, 200, "c.json"))
+            .unwrap()
+            .merge(Candidates::with_priority(4, 100))
+            .unwrap();
+
+        let err = c.resolve().unwrap_err();
+        assert_eq!(
+            err.kind.to_string(),
+            "3 values have the same priority 100, in a.json, b.json and an unknown module"
+        );
+    }
+
+    #[test]
+    fn test_resolve_in_place() {
+        let mut c = x(1, 10, "a").merge(x(2, 5, "b")).unwrap();
+        c.resolve_in_place().unwrap();
+
+        assert_eq!(c.resolved(), Some(&2));
+        assert_eq!(c.candidates()[0].origin(), Some("b"));
+    }
+
+    #[test]
+    fn test_overridable() {
+        let c: Candidates<i32> = Overridable::with_priority(1, 10).into();
+        let c = c.merge(Candidates::with_priority(2, 20)).unwrap();
+
+        let o = Overridable::<i32>::try_from(c).unwrap();
+        assert_eq!(*o, 1);
+        assert_eq!(o.priority(), Priority::from(10));
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "current-module"))]
+    fn test_deserialize_origin() {
+        use crate::eval::ModuleGuard;
+
+        let read = |module: &'static str, json: &'static str| {
+            ModuleGuard::new(module)
+                .run(|| serde_json::from_str::<Candidates<i32>>(json).map_err(Error::custom))
+                .unwrap()
+        };
+
+        let c = read("a.json", "1")
+            .merge(read("b.json", r#"{ "value": 2, "priority": 500 }"#))
+            .unwrap()
+            .merge(read("c.json", r#"{ "value": 3 }"#))
+            .unwrap();
+
+        let err = c.resolve().unwrap_err();
+        assert_eq!(
+            err.kind.to_string(),
+            "3 values have the same priority 500, in a.json, b.json and c.json"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Optional values that keep the first one present.
+//!
+//! See: [`Coalesce`].
+
+use crate::merge::Neutral;
+
+use super::prelude::*;
+
+/// Optional values that keep the first one present.
+///
+/// [`Option`] merges the 2 values when both are [`Some`], which collides for
+/// values that cannot be merged. [`Coalesce`] keeps the first [`Some`] instead
+/// and ignores all later ones. `T` does not need to implement [`Merge`].
+///
+/// # Example
+///
+/// ```rust
+/// # use module::types::Coalesce;
+/// # use module::merge::Merge;
+/// let a = Coalesce(None);
+/// let b = Coalesce(Some("foo"));
+/// let c = Coalesce(Some("bar"));
+///
+/// let merged = a.merge(b).unwrap().merge(c).unwrap();
+///
+/// assert_eq!(*merged, Some("foo"));
+/// ```
+///
+/// # serde
+///
+/// This type deserializes like `Option<T>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Coalesce<T>(pub Option<T>);
+
+/// Another name for [`Coalesce`], for those looking for it next to
+/// [`Option`].
+pub type OptionCoalesce<T> = Coalesce<T>;
+
+impl<T> Coalesce<T> {
+    /// Create a new [`Coalesce`] with `value`.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self(Some(value))
+    }
+
+    /// Destruct this [`Coalesce`] and get the inner [`Option`].
+    #[inline]
+    pub fn into_option(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> Merge for Coalesce<T> {
+    #[inline]
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        if self.0.is_none() {
+            self.0 = other.0;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Neutral for Coalesce<T> {
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl<T> Default for Coalesce<T> {
+    #[inline]
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> From<Option<T>> for Coalesce<T> {
+    #[inline]
+    fn from(x: Option<T>) -> Self {
+        Self(x)
+    }
+}
+
+impl_wrapper!(Coalesce<T> => Option<T> { .0 });
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use serde::Deserialize;
+    use serde::de::Deserializer;
+
+    impl<'de, T> Deserialize<'de> for Coalesce<T>
+    where
+        T: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            <Option<T> as Deserialize>::deserialize(deserializer).map(Self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    #[test]
+    fn test_none_some() {
+        let merged = Coalesce(None).merge(Coalesce(Some(1))).unwrap();
+        assert_eq!(*merged, Some(1));
+    }
+
+    #[test]
+    fn test_some_some() {
+        let merged = Coalesce(Some(1)).merge(Coalesce(Some(2))).unwrap();
+        assert_eq!(*merged, Some(1));
+    }
+
+    #[test]
+    fn test_some_none() {
+        let merged = Coalesce(Some(1)).merge(Coalesce(None)).unwrap();
+        assert_eq!(*merged, Some(1));
+    }
+
+    #[test]
+    fn test_none_none() {
+        let merged = Coalesce::<i32>(None).merge(Coalesce(None)).unwrap();
+        assert_eq!(*merged, None);
+    }
+
+    #[test]
+    fn test_alias() {
+        let merged = OptionCoalesce::new("a")
+            .merge(OptionCoalesce::new("b"))
+            .unwrap();
+        assert_eq!(merged.into_option(), Some("a"));
+    }
+
+    #[test]
+    fn test_no_inner_merge() {
+        let merged = Coalesce::new(Merged(false))
+            .merge(Coalesce::new(Merged(false)))
+            .unwrap();
+        assert!(!merged.into_option().unwrap().0);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Coalesce<i32> = serde_json::from_str("42").unwrap();
+        assert_eq!(*x, Some(42));
+
+        let x: Coalesce<i32> = serde_json::from_str("null").unwrap();
+        assert_eq!(*x, None);
+    }
+}
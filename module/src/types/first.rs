@@ -2,6 +2,8 @@
 //!
 //! See: [`First`].
 
+use crate::merge::Neutral;
+
 use super::prelude::*;
 
 merge_thin_wrapper! {
@@ -42,6 +44,15 @@ impl<T> Merge for First<T> {
     }
 }
 
+impl<T> Neutral for First<T> {
+    /// Merging anything into a [`First`] discards it, so every value is
+    /// neutral.
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -12,6 +12,11 @@ merge_thin_wrapper! {
     ///
     /// The opposite of this is [`First`].
     ///
+    /// Use [`Last`] for values that simply keep the most recent setting, like
+    /// plain scalars. To stop a value that could be merged, like a nested
+    /// struct, from being merged, prefer [`Replace`], which does the same but
+    /// states the intent.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -30,6 +35,7 @@ merge_thin_wrapper! {
     /// This type deserializes like `T`.
     ///
     /// [`First`]: crate::types::First
+    /// [`Replace`]: crate::types::Replace
     #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
     pub struct Last;
 }
@@ -1,13 +1,57 @@
-//! Strings concatenated with `\n`.
+//! Strings concatenated with a separator.
 //!
-//! See: [`Lines`].
+//! See: [`Joined`], [`Lines`].
 
 use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
 
 use alloc::string::String;
 
+use crate::merge::ReserveHint;
+
 use super::prelude::*;
 
+/// The separator of a [`Joined`].
+///
+/// See: [`Joined`].
+pub trait Separator {
+    /// The string inserted between 2 merged strings.
+    const SEP: &'static str;
+}
+
+/// The [`Separator`] of [`Lines`], `\n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Newline;
+
+impl Separator for Newline {
+    const SEP: &'static str = "\n";
+}
+
+/// A [`Separator`] for comma separated lists, `, `.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Comma;
+
+impl Separator for Comma {
+    const SEP: &'static str = ", ";
+}
+
+/// A [`Separator`] for `PATH`-like lists, `:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Colon;
+
+impl Separator for Colon {
+    const SEP: &'static str = ":";
+}
+
+/// A [`Separator`] for lists like `PATH` on Windows, `;`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Semicolon;
+
+impl Separator for Semicolon {
+    const SEP: &'static str = ";";
+}
+
 /// Strings concatenated with `\n`.
 ///
 /// # Example
@@ -34,19 +78,48 @@ use super::prelude::*;
 /// # serde
 ///
 /// This type deserializes like [`String`].
-#[derive(Default, Clone, PartialEq, Eq, Hash)]
-pub struct Lines {
+pub type Lines = Joined<Newline>;
+
+/// Strings concatenated with the [`Separator`] `S`.
+///
+/// The separator is inserted between 2 merged strings, unless the first
+/// already ends with it. [`Lines`] is a [`Joined`] with [`Newline`]. For any
+/// other separator, implement [`Separator`] on a type of your own.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::types::Joined;
+/// # use module::types::lines::Colon;
+/// # use module::merge::Merge;
+/// type SearchPath = Joined<Colon>;
+///
+/// let a = SearchPath::new("/usr/bin");
+/// let b = SearchPath::new("/bin:");
+/// let c = SearchPath::new("/sbin");
+///
+/// let merged = a.merge(b).unwrap().merge(c).unwrap();
+///
+/// assert_eq!(*merged, "/usr/bin:/bin:/sbin");
+/// ```
+///
+/// # serde
+///
+/// This type deserializes like [`String`].
+pub struct Joined<S> {
     content: String,
+    _sep: PhantomData<fn() -> S>,
 }
 
-impl Lines {
-    /// Create a new [`Lines`] with `content`.
+impl<S> Joined<S> {
+    /// Create a new [`Joined`] with `content`.
     pub fn new<C>(content: C) -> Self
     where
         C: Into<String>,
     {
         Self {
             content: content.into(),
+            _sep: PhantomData,
         }
     }
 
@@ -56,12 +129,13 @@ impl Lines {
     }
 }
 
-impl Merge for Lines {
+impl<S> Merge for Joined<S>
+where
+    S: Separator,
+{
     fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
-        const SEP: char = '\n';
-
-        if !self.content.ends_with(SEP) {
-            self.content.push(SEP);
+        if !self.content.ends_with(S::SEP) {
+            self.content.push_str(S::SEP);
         }
 
         self.content.push_str(&other.content);
@@ -70,29 +144,76 @@ impl Merge for Lines {
     }
 }
 
-impl fmt::Debug for Lines {
+impl<S> ReserveHint for Joined<S>
+where
+    S: Separator,
+{
+    #[inline]
+    fn len_hint(&self) -> usize {
+        // Account for the separator that may be inserted before the content.
+        self.content.len() + S::SEP.len()
+    }
+
+    #[inline]
+    fn reserve_for(&mut self, additional_hint: usize) {
+        self.content.reserve(additional_hint);
+    }
+}
+
+impl<S> fmt::Debug for Joined<S>
+where
+    S: Separator,
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_list().entries(self.content.lines()).finish()
+        f.debug_list()
+            .entries(self.content.split_terminator(S::SEP))
+            .finish()
+    }
+}
+
+impl<S> Default for Joined<S> {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+impl<S> Clone for Joined<S> {
+    fn clone(&self) -> Self {
+        Self::new(self.content.clone())
+    }
+}
+
+impl<S> PartialEq for Joined<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content
+    }
+}
+
+impl<S> Eq for Joined<S> {}
+
+impl<S> Hash for Joined<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.content.hash(state);
     }
 }
 
-impl From<String> for Lines {
+impl<S> From<String> for Joined<S> {
     #[inline]
     fn from(content: String) -> Self {
         Self::new(content)
     }
 }
 
-impl From<Lines> for String {
+impl<S> From<Joined<S>> for String {
     #[inline]
-    fn from(lines: Lines) -> Self {
-        lines.into_string()
+    fn from(joined: Joined<S>) -> Self {
+        joined.into_string()
     }
 }
 
-impl_borrow!(Lines => str { .content });
-impl_as_ref!(Lines => str { .content });
-impl_wrapper!(Lines => String { .content });
+impl_borrow!(Joined<S> => str { .content });
+impl_as_ref!(Joined<S> => str { .content });
+impl_wrapper!(Joined<S> => String { .content });
 
 #[cfg(feature = "serde")]
 mod serde_impl {
@@ -101,7 +222,7 @@ mod serde_impl {
     use serde::Deserialize;
     use serde::de::Deserializer;
 
-    impl<'de> Deserialize<'de> for Lines {
+    impl<'de, S> Deserialize<'de> for Joined<S> {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
             D: Deserializer<'de>,
@@ -123,6 +244,28 @@ mod tests {
         let merged = a.merge(b).unwrap();
         assert_eq!(*merged, "line1\nline2");
     }
+
+    #[test]
+    fn test_merge_separator() {
+        type X = Joined<Comma>;
+
+        let merged = X::new("a").merge(X::new("b")).unwrap();
+        assert_eq!(*merged, "a, b");
+
+        let merged = X::new("a, ").merge(X::new("b")).unwrap();
+        assert_eq!(*merged, "a, b");
+
+        let merged = Joined::<Semicolon>::new("a;b")
+            .merge(Joined::new("c"))
+            .unwrap();
+        assert_eq!(*merged, "a;b;c");
+    }
+
+    #[test]
+    fn test_debug() {
+        let x = Joined::<Comma>::new("a, b, ");
+        assert_eq!(alloc::format!("{x:?}"), r#"["a", "b"]"#);
+    }
 }
 
 #[cfg(test)]
@@ -134,5 +277,8 @@ mod serde_tests {
     fn test_deserialize() {
         let x: Lines = serde_json::from_str("\"test1\\ntest2\"").unwrap();
         assert_eq!(*x, "test1\ntest2");
+
+        let x: Joined<Colon> = serde_json::from_str("\"/bin:/sbin\"").unwrap();
+        assert_eq!(*x, "/bin:/sbin");
     }
 }
@@ -0,0 +1,104 @@
+//! Values that cannot be changed once set.
+//!
+//! See: [`Locked`].
+
+use super::prelude::*;
+
+merge_thin_wrapper! {
+    /// A value that cannot be changed once set.
+    ///
+    /// Any attempt to merge another value into a [`Locked`] is a collision
+    /// with the note `value is locked`. This works exactly like [`NoMerge`],
+    /// but the error says why the value cannot be merged. It is meant for
+    /// security-sensitive settings that no other module may touch.
+    ///
+    /// Wrap it in an [`Option`], so that only the first module that sets the
+    /// value wins and modules that leave it unset are still allowed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::types::Locked;
+    /// # use module::merge::Merge;
+    /// let a = Some(Locked("/usr/bin/sudo"));
+    /// let b = None;
+    /// let c = Some(Locked("/tmp/sudo"));
+    ///
+    /// let merged = a.merge(b).unwrap();
+    /// let err = merged.merge(c).unwrap_err();
+    ///
+    /// assert!(err.kind.is_collision());
+    /// assert_eq!(err.to_string(), "value collision (value is locked)\n");
+    /// ```
+    ///
+    /// # serde
+    ///
+    /// This type deserializes like `T`.
+    ///
+    /// [`NoMerge`]: crate::types::NoMerge
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+    pub struct Locked;
+}
+
+impl<T> Locked<T> {
+    /// Destruct this [`Locked`] and get the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Merge for Locked<T> {
+    fn merge(self, _other: Self) -> Result<Self, Error> {
+        Err(locked())
+    }
+
+    fn merge_ref(&mut self, _other: Self) -> Result<(), Error> {
+        Err(locked())
+    }
+}
+
+fn locked() -> Error {
+    Error::collision().with_note("value is locked")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_merge() {
+        let err = Locked(42).merge(Locked(43)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+        assert_eq!(err.note().unwrap().to_string(), "value is locked");
+
+        let mut a = Locked(42);
+        let err = a.merge_ref(Locked(42)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+        assert_eq!(*a, 42);
+    }
+
+    #[test]
+    fn test_option() {
+        let merged = Some(Locked(1)).merge(None).unwrap();
+        assert_eq!(merged.unwrap().into_inner(), 1);
+
+        let merged = None.merge(Some(Locked(1))).unwrap();
+        assert_eq!(merged.unwrap().into_inner(), 1);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Locked<i32> = serde_json::from_str("42").unwrap();
+        assert_eq!(*x, 42);
+    }
+}
@@ -0,0 +1,448 @@
+//! Values with per-environment overrides.
+//!
+//! See: [`Matrix`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::glob::glob;
+
+use super::prelude::*;
+
+/// A base value plus overrides that apply to selected environments.
+///
+/// A [`Matrix`] holds a base `T` and an ordered list of deltas, each keyed by a
+/// selector string. Calling [`resolve()`] with the active selectors produces
+/// the final `T` by merging every matching delta on top of the base, in the
+/// order they were declared.
+///
+/// Deltas are merged with `T`'s own [`Merge`] implementation, so they are
+/// usually partial values. Fields that deltas should replace rather than
+/// extend must use a strategy that allows it, for example [`Last`] or an
+/// [`Overridable`] with a lower priority in the delta.
+///
+/// # Selectors
+///
+/// A selector matches if:
+///
+/// * it is exactly equal to one of the active selectors, or
+/// * it is a comma-separated list of tags, and every tag matches at least one
+///   of the active selectors.
+///
+/// Tags may contain `*`, which matches any sequence of characters. A lone `*`
+/// matches anything, even when there are no active selectors.
+///
+/// # Merge
+///
+/// Merging two [`Matrix`] values merges their bases, and merges deltas with
+/// the same selector. Deltas with new selectors are appended in order.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::collections::BTreeMap;
+/// # use module::types::{Last, Matrix};
+/// # use module::merge::Merge;
+/// type Config = BTreeMap<&'static str, Last<i32>>;
+///
+/// let mut a = Matrix::new(Config::from([("replicas", Last(1))]));
+/// a.push("prod", Config::from([("replicas", Last(3))])).unwrap();
+///
+/// let mut b = Matrix::new(Config::new());
+/// b.push("prod,eu-west", Config::from([("replicas", Last(5))])).unwrap();
+///
+/// let matrix = a.merge(b).unwrap();
+///
+/// assert_eq!(*matrix.resolve(&["dev"]).unwrap()["replicas"], 1);
+/// assert_eq!(*matrix.resolve(&["prod"]).unwrap()["replicas"], 3);
+/// assert_eq!(*matrix.resolve(&["prod", "eu-west"]).unwrap()["replicas"], 5);
+/// ```
+///
+/// # serde
+///
+/// This type deserializes like `T`, with an extra optional `matrix` key that
+/// maps selectors to deltas:
+///
+/// ```toml
+/// replicas = 1
+///
+/// [matrix.prod]
+/// replicas = 3
+///
+/// [matrix."prod,eu-west"]
+/// replicas = 5
+/// ```
+///
+/// Errors while deserializing a delta name its selector.
+///
+/// [`resolve()`]: Matrix::resolve
+/// [`Last`]: crate::types::Last
+/// [`Overridable`]: crate::types::Overridable
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    base: T,
+    entries: Vec<(String, T)>,
+}
+
+impl<T> Matrix<T> {
+    /// Create a new [`Matrix`] with `base` and no deltas.
+    pub fn new(base: T) -> Self {
+        Self {
+            base,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the base value.
+    pub fn base(&self) -> &T {
+        &self.base
+    }
+
+    /// Get a mutable reference to the base value.
+    pub fn base_mut(&mut self) -> &mut T {
+        &mut self.base
+    }
+
+    /// Get an iterator over all selectors and their deltas, in declaration
+    /// order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Get the delta for `selector`.
+    pub fn get(&self, selector: &str) -> Option<&T> {
+        self.entries
+            .iter()
+            .find_map(|(k, v)| (k == selector).then_some(v))
+    }
+
+    /// Destruct this [`Matrix`] and get the base value, discarding all
+    /// deltas.
+    pub fn into_base(self) -> T {
+        self.base
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Merge,
+{
+    /// Add `delta` under `selector`.
+    ///
+    /// If a delta with the same selector exists, `delta` is merged into it.
+    pub fn push<S>(&mut self, selector: S, delta: T) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let selector = selector.into();
+
+        match self.entries.iter_mut().find(|(k, _)| *k == selector) {
+            Some((_, x)) => x
+                .merge_ref(delta)
+                .with_value(|| format!("\"{selector}\""))
+                .value("matrix"),
+            None => {
+                self.entries.push((selector, delta));
+                Ok(())
+            }
+        }
+    }
+
+    /// Resolve the final value for the active `selectors`.
+    ///
+    /// Every delta whose selector matches is merged on top of the base in
+    /// declaration order. Errors carry the selector of the delta that failed
+    /// to merge.
+    pub fn resolve(&self, selectors: &[&str]) -> Result<T, Error>
+    where
+        T: Clone,
+    {
+        let mut value = self.base.clone();
+
+        for (selector, delta) in &self.entries {
+            if !matches(selector, selectors) {
+                continue;
+            }
+
+            value
+                .merge_ref(delta.clone())
+                .with_value(|| format!("\"{selector}\""))
+                .value("matrix")?;
+        }
+
+        Ok(value)
+    }
+}
+
+impl<T> Merge for Matrix<T>
+where
+    T: Merge,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        self.base.merge_ref(other.base)?;
+
+        other
+            .entries
+            .into_iter()
+            .try_for_each(|(selector, delta)| self.push(selector, delta))
+    }
+}
+
+impl<T> From<T> for Matrix<T> {
+    #[inline]
+    fn from(base: T) -> Self {
+        Self::new(base)
+    }
+}
+
+fn matches(selector: &str, active: &[&str]) -> bool {
+    if active.contains(&selector) {
+        return true;
+    }
+
+    selector
+        .split(',')
+        .map(str::trim)
+        .all(|tag| tag == "*" || active.iter().any(|x| glob(tag, x)))
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::Deserialize;
+    use serde::de::{self, Deserializer, MapAccess, Visitor};
+
+    struct Entries<T>(Vec<(String, T)>);
+
+    impl<T> Default for Entries<T> {
+        fn default() -> Self {
+            Self(Vec::new())
+        }
+    }
+
+    impl<'de, T> Deserialize<'de> for Entries<T>
+    where
+        T: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct EntriesVisitor<T>(PhantomData<T>);
+
+            impl<'de, T> Visitor<'de> for EntriesVisitor<T>
+            where
+                T: Deserialize<'de>,
+            {
+                type Value = Entries<T>;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a map of selectors to values")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: MapAccess<'de>,
+                {
+                    let mut entries = Vec::new();
+
+                    while let Some(selector) = map.next_key::<String>()? {
+                        let delta = map.next_value::<T>().map_err(|e| {
+                            de::Error::custom(format_args!("matrix.\"{selector}\": {e}"))
+                        })?;
+
+                        entries.push((selector, delta));
+                    }
+
+                    Ok(Entries(entries))
+                }
+            }
+
+            deserializer.deserialize_map(EntriesVisitor(PhantomData))
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(bound = "T: Deserialize<'de>")]
+    struct Repr<T> {
+        #[serde(flatten)]
+        base: T,
+
+        #[serde(default)]
+        matrix: Entries<T>,
+    }
+
+    impl<'de, T> Deserialize<'de> for Matrix<T>
+    where
+        T: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let Repr { base, matrix } = Repr::deserialize(deserializer)?;
+
+            Ok(Self {
+                base,
+                entries: matrix.0,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+    use crate::types::Last;
+
+    use alloc::collections::BTreeMap;
+    use alloc::string::ToString;
+
+    type Config = BTreeMap<&'static str, Last<i32>>;
+
+    fn config<const N: usize>(x: [(&'static str, i32); N]) -> Config {
+        x.into_iter().map(|(k, v)| (k, Last(v))).collect()
+    }
+
+    #[test]
+    fn test_merge_selectors() {
+        let mut a = Matrix::new(config([("replicas", 1)]));
+        a.push("prod", config([("replicas", 3)])).unwrap();
+
+        let mut b = Matrix::new(config([("timeout", 10)]));
+        b.push("dev", config([("timeout", 60)])).unwrap();
+
+        let c = a.merge(b).unwrap();
+
+        let selectors: Vec<&str> = c.entries().map(|(k, _)| k).collect();
+        assert_eq!(selectors, &["prod", "dev"]);
+        assert_eq!(*c.base(), config([("replicas", 1), ("timeout", 10)]));
+    }
+
+    #[test]
+    fn test_merge_overlapping_selectors() {
+        let mut a = Matrix::new(vec![0]);
+        a.push("prod", vec![1]).unwrap();
+
+        let mut b = Matrix::new(vec![]);
+        b.push("prod", vec![2]).unwrap();
+
+        let c = a.merge(b).unwrap();
+
+        assert_eq!(c.entries().count(), 1);
+        assert_eq!(c.get("prod").unwrap(), &[1, 2]);
+        assert_eq!(c.resolve(&["prod"]).unwrap(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_resolve_order() {
+        let mut a = Matrix::new(config([("replicas", 1)]));
+        a.push("prod", config([("replicas", 3)])).unwrap();
+        a.push("eu-west", config([("replicas", 5)])).unwrap();
+
+        let mut b = Matrix::new(config([("replicas", 1)]));
+        b.push("eu-west", config([("replicas", 5)])).unwrap();
+        b.push("prod", config([("replicas", 3)])).unwrap();
+
+        assert_eq!(*a.resolve(&["prod", "eu-west"]).unwrap()["replicas"], 5);
+        assert_eq!(*b.resolve(&["prod", "eu-west"]).unwrap()["replicas"], 3);
+        assert_eq!(*a.resolve(&[]).unwrap()["replicas"], 1);
+    }
+
+    #[test]
+    fn test_resolve_and() {
+        let mut a = Matrix::new(vec![0]);
+        a.push("prod,eu-west", vec![1]).unwrap();
+
+        assert_eq!(a.resolve(&["prod"]).unwrap(), &[0]);
+        assert_eq!(a.resolve(&["eu-west"]).unwrap(), &[0]);
+        assert_eq!(a.resolve(&["eu-west", "prod"]).unwrap(), &[0, 1]);
+        assert_eq!(a.resolve(&["prod,eu-west"]).unwrap(), &[0, 1]);
+    }
+
+    #[test]
+    fn test_resolve_wildcard() {
+        let mut a = Matrix::new(vec![0]);
+        a.push("*", vec![1]).unwrap();
+        a.push("prod,eu-*", vec![2]).unwrap();
+        a.push("*-central", vec![3]).unwrap();
+
+        assert_eq!(a.resolve(&[]).unwrap(), &[0, 1]);
+        assert_eq!(a.resolve(&["prod", "eu-west"]).unwrap(), &[0, 1, 2]);
+        assert_eq!(a.resolve(&["prod", "us-west"]).unwrap(), &[0, 1]);
+        assert_eq!(a.resolve(&["prod", "eu-central"]).unwrap(), &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_resolve_error_trace() {
+        let mut a = Matrix::new(BTreeMap::from([("replicas", 1)]));
+        a.push("prod", BTreeMap::from([("replicas", 3)])).unwrap();
+
+        let err = a.resolve(&["prod"]).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+
+        let path: Vec<_> = err.value.components().map(|x| x.to_string()).collect();
+        assert_eq!(path, &["matrix", "\"prod\"", "\"replicas\""]);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+    use crate::types::Last;
+
+    use alloc::collections::BTreeMap;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Matrix<BTreeMap<String, Last<i32>>> = serde_json::from_str(
+            r#"{
+                "replicas": 1,
+                "matrix": {
+                    "prod": { "replicas": 3 },
+                    "prod,eu-west": { "replicas": 5 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(*x.base()["replicas"], 1);
+
+        let selectors: Vec<&str> = x.entries().map(|(k, _)| k).collect();
+        assert_eq!(selectors, &["prod", "prod,eu-west"]);
+
+        assert_eq!(*x.resolve(&["prod", "eu-west"]).unwrap()["replicas"], 5);
+    }
+
+    #[test]
+    fn test_deserialize_no_matrix() {
+        let x: Matrix<BTreeMap<String, i32>> = serde_json::from_str(r#"{ "a": 1 }"#).unwrap();
+        assert_eq!(x.base()["a"], 1);
+        assert_eq!(x.entries().count(), 0);
+    }
+
+    #[test]
+    fn test_deserialize_invalid_delta() {
+        #[derive(Debug, serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        #[allow(dead_code)]
+        struct Config {
+            replicas: Option<i32>,
+        }
+
+        let err =
+            serde_json::from_str::<Matrix<Config>>(r#"{ "matrix": { "prod": { "replica": 3 } } }"#)
+                .unwrap_err();
+
+        let err = err.to_string();
+        assert!(err.contains("matrix.\"prod\""), "{err}");
+        assert!(err.contains("replica"), "{err}");
+    }
+}
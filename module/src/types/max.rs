@@ -0,0 +1,91 @@
+//! Values that keep the largest one when merged.
+//!
+//! See: [`Max`].
+
+use super::prelude::*;
+
+merge_thin_wrapper! {
+    /// Values that keep the largest one when merged.
+    ///
+    /// Merging 2 values of this type keeps the greater one, as decided by
+    /// [`Ord`]. Equal values merge cleanly and keep the value of `self`, so
+    /// there are no collisions.
+    ///
+    /// The opposite of this is [`Min`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::types::Max;
+    /// # use module::merge::Merge;
+    /// let a = Max(10);
+    /// let b = Max(30);
+    ///
+    /// let merged = a.merge(b).unwrap();
+    ///
+    /// assert_eq!(*merged, 30);
+    /// ```
+    ///
+    /// # serde
+    ///
+    /// This type deserializes like `T`.
+    ///
+    /// [`Min`]: crate::types::Min
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+    pub struct Max;
+}
+
+impl<T> Merge for Max<T>
+where
+    T: Ord,
+{
+    #[inline]
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        if other.0 > self.0 {
+            self.0 = other.0;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_ab() {
+        let a = Max(42);
+        let b = Max(43);
+
+        let merged = a.merge(b).unwrap();
+        assert_eq!(*merged, 43);
+    }
+
+    #[test]
+    fn test_merge_ba() {
+        let a = Max(42);
+        let b = Max(43);
+
+        let merged = b.merge(a).unwrap();
+        assert_eq!(*merged, 43);
+    }
+
+    #[test]
+    fn test_merge_equal() {
+        let merged = Max(42).merge(Max(42)).unwrap();
+        assert_eq!(*merged, 42);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Max<i32> = serde_json::from_str("42").unwrap();
+        assert_eq!(*x, 42);
+    }
+}
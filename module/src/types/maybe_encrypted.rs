@@ -0,0 +1,515 @@
+//! Values that may or may not be encrypted.
+//!
+//! See: [`MaybeEncrypted`].
+
+use core::borrow::Borrow;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+use alloc::string::String;
+
+use super::last::Last;
+use super::prelude::*;
+
+/// The prefix that marks a value as encrypted.
+///
+/// See: [`MaybeEncrypted`].
+pub trait Prefix {
+    /// The prefix of encrypted values.
+    const PREFIX: &'static str;
+}
+
+/// The default [`Prefix`], `enc:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Enc;
+
+impl Prefix for Enc {
+    const PREFIX: &'static str = "enc:";
+}
+
+/// Whether a [`MaybeEncrypted`] holds an encrypted value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Classification {
+    /// The value starts with the [`Prefix`].
+    Encrypted,
+    /// The value does not start with the [`Prefix`].
+    Plaintext,
+}
+
+impl Classification {
+    /// Classify `value` according to `P`.
+    pub fn of<P>(value: &str) -> Self
+    where
+        P: Prefix,
+    {
+        if value.starts_with(P::PREFIX) {
+            Self::Encrypted
+        } else {
+            Self::Plaintext
+        }
+    }
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encrypted => f.write_str("encrypted"),
+            Self::Plaintext => f.write_str("plaintext"),
+        }
+    }
+}
+
+/// A string value that may or may not be encrypted.
+///
+/// Values starting with the [`Prefix`] `P` (`enc:` by default) are classified
+/// as [encrypted], all other values as [plaintext]. The decision of which
+/// value is kept is delegated to the strategy `S`, for example
+/// [`Overridable<String>`] or [`Last<String>`].
+///
+/// Merging fails if the value that is kept and the value that is discarded
+/// have a different classification, regardless of which one is kept. This
+/// catches a module silently replacing an encrypted value with a plaintext
+/// one, or the other way around. The check can be disabled for a value by
+/// setting `allow_downgrade`, in which case it is allowed to replace values
+/// of any classification.
+///
+/// The [`Debug`] and [`Display`] implementations and all merge errors show
+/// only the classification of the value, never its contents.
+///
+/// Note that the check is done by [`MaybeEncrypted`] itself, so it must be the
+/// outermost type. `Overridable<MaybeEncrypted>` never merges the values it
+/// holds and thus never checks anything.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::types::{MaybeEncrypted, Overridable};
+/// # use module::merge::Merge;
+/// type Password = MaybeEncrypted<Overridable<String>>;
+///
+/// let a = Password::new(Overridable::with_priority("enc:v1:aGVsbG8=".into(), 10));
+/// let b = Password::new(Overridable::with_priority("hunter2".into(), 5));
+///
+/// let err = a.clone().merge(b.clone()).unwrap_err();
+/// assert!(!err.to_string().contains("hunter2"));
+///
+/// let merged = a.merge(b.allow_downgrade()).unwrap();
+/// assert_eq!(&*merged, "hunter2");
+/// ```
+///
+/// # serde
+///
+/// This type deserializes as one of the following:
+///
+/// * `S`
+/// * `{ value: S, allow_downgrade: bool }`
+/// * `{ allow_downgrade: bool, ..S }`, if `S` deserializes from a map
+///
+/// [encrypted]: Classification::Encrypted
+/// [plaintext]: Classification::Plaintext
+/// [`Overridable<String>`]: crate::types::Overridable
+/// [`Debug`]: core::fmt::Debug
+/// [`Display`]: core::fmt::Display
+pub struct MaybeEncrypted<S = Last<String>, P = Enc> {
+    inner: S,
+    classification: Classification,
+    allow_downgrade: bool,
+    _marker: PhantomData<fn() -> P>,
+}
+
+impl<S, P> MaybeEncrypted<S, P>
+where
+    S: Borrow<String>,
+    P: Prefix,
+{
+    /// Create a new [`MaybeEncrypted`] and classify its value.
+    pub fn new(inner: S) -> Self {
+        Self {
+            classification: Classification::of::<P>(inner.borrow()),
+            inner,
+            allow_downgrade: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<S, P> MaybeEncrypted<S, P> {
+    /// Allow this value to replace values of a different classification.
+    pub fn allow_downgrade(mut self) -> Self {
+        self.allow_downgrade = true;
+        self
+    }
+
+    /// Check whether this value may replace values of a different
+    /// classification.
+    pub fn allows_downgrade(&self) -> bool {
+        self.allow_downgrade
+    }
+
+    /// Get the classification of this value.
+    pub fn classification(&self) -> Classification {
+        self.classification
+    }
+
+    /// Check whether this value is encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.classification == Classification::Encrypted
+    }
+
+    /// Get a reference to the strategy holding the value.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Destruct this [`MaybeEncrypted`] and get the strategy holding the
+    /// value.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, P> Merge for MaybeEncrypted<S, P>
+where
+    S: Merge + Borrow<String>,
+    P: Prefix,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        let ours = (self.classification, self.allow_downgrade);
+        let theirs = (other.classification, other.allow_downgrade);
+
+        // When both values have the same classification, the only way to tell
+        // which one was kept is by its contents. This is only needed if the
+        // flags differ.
+        let their_value =
+            (ours.0 == theirs.0 && ours.1 != theirs.1).then(|| other.inner.borrow().clone());
+
+        self.inner.merge_ref(other.inner)?;
+
+        let merged = Classification::of::<P>(self.inner.borrow());
+        let theirs_kept = if ours.0 != theirs.0 {
+            merged == theirs.0
+        } else {
+            their_value.is_some_and(|x| &x == self.inner.borrow())
+        };
+
+        let (kept, discarded) = if theirs_kept {
+            (theirs, ours)
+        } else {
+            (ours, theirs)
+        };
+
+        self.classification = merged;
+        self.allow_downgrade = kept.1;
+
+        if kept.0 != discarded.0 && !kept.1 {
+            return Err(Error::custom(format!(
+                "refusing to replace {} value with {} value (set `allow_downgrade` to allow this)",
+                discarded.0, kept.0
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl<S, P> Clone for MaybeEncrypted<S, P>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            classification: self.classification,
+            allow_downgrade: self.allow_downgrade,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<S, P> fmt::Debug for MaybeEncrypted<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaybeEncrypted")
+            .field("classification", &self.classification)
+            .field("allow_downgrade", &self.allow_downgrade)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, P> fmt::Display for MaybeEncrypted<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", self.classification)
+    }
+}
+
+impl<S, P> Deref for MaybeEncrypted<S, P>
+where
+    S: Borrow<String>,
+{
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.inner.borrow()
+    }
+}
+
+impl<S, P> AsRef<str> for MaybeEncrypted<S, P>
+where
+    S: Borrow<String>,
+{
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.inner.borrow()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use serde::Deserialize;
+    use serde::de::Deserializer;
+
+    // The order of the variants matters. `S` may ignore unknown fields, so
+    // the forms with `allow_downgrade` must be tried first.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr<S> {
+        Flat {
+            allow_downgrade: bool,
+            #[serde(flatten)]
+            inner: S,
+        },
+        Nested {
+            value: S,
+            allow_downgrade: bool,
+        },
+        Raw(S),
+    }
+
+    impl<'de, S, P> Deserialize<'de> for MaybeEncrypted<S, P>
+    where
+        S: Deserialize<'de> + Borrow<String>,
+        P: Prefix,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let (inner, allow_downgrade) = match Repr::<S>::deserialize(deserializer)? {
+                Repr::Flat {
+                    allow_downgrade,
+                    inner,
+                } => (inner, allow_downgrade),
+                Repr::Nested {
+                    value,
+                    allow_downgrade,
+                } => (value, allow_downgrade),
+                Repr::Raw(inner) => (inner, false),
+            };
+
+            let mut x = Self::new(inner);
+            x.allow_downgrade = allow_downgrade;
+            Ok(x)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+    use crate::types::Overridable;
+
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    const SECRET: &str = "enc:v1:c2VjcmV0";
+    const PLAIN: &str = "hunter2";
+
+    type Prio = MaybeEncrypted<Overridable<String>>;
+    type Lst = MaybeEncrypted<Last<String>>;
+
+    fn prio(value: &str, priority: isize) -> Prio {
+        Prio::new(Overridable::with_priority(value.into(), priority))
+    }
+
+    fn last(value: &str) -> Lst {
+        Lst::new(Last(value.into()))
+    }
+
+    fn assert_redacted(e: &Error) {
+        let msg = e.to_string();
+        let debug = format!("{e:?}");
+
+        for x in [SECRET, PLAIN, "c2VjcmV0"] {
+            assert!(!msg.contains(x), "{x:?} leaked into {msg:?}");
+            assert!(!debug.contains(x), "{x:?} leaked into {debug:?}");
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        assert!(last(SECRET).is_encrypted());
+        assert!(!last(PLAIN).is_encrypted());
+        assert!(!last("ENC:abc").is_encrypted());
+
+        struct Vault;
+        impl Prefix for Vault {
+            const PREFIX: &'static str = "vault:";
+        }
+
+        let x = MaybeEncrypted::<Last<String>, Vault>::new(Last("vault:abc".into()));
+        assert!(x.is_encrypted());
+        let x = MaybeEncrypted::<Last<String>, Vault>::new(Last(SECRET.into()));
+        assert!(!x.is_encrypted());
+    }
+
+    #[test]
+    fn test_same_classification() {
+        for (a, b) in [(SECRET, "enc:other"), (PLAIN, "other")] {
+            let merged = prio(a, 10).merge(prio(b, 5)).unwrap();
+            assert_eq!(&*merged, b);
+            let merged = prio(a, 5).merge(prio(b, 10)).unwrap();
+            assert_eq!(&*merged, a);
+
+            let merged = last(a).merge(last(b)).unwrap();
+            assert_eq!(&*merged, b);
+        }
+    }
+
+    #[test]
+    fn test_different_classification() {
+        for (a, b) in [(SECRET, PLAIN), (PLAIN, SECRET)] {
+            // Regardless of which value is kept.
+            let e = prio(a, 10).merge(prio(b, 5)).unwrap_err();
+            assert!(e.kind.is_custom());
+            assert_redacted(&e);
+
+            let e = prio(a, 5).merge(prio(b, 10)).unwrap_err();
+            assert!(e.kind.is_custom());
+            assert_redacted(&e);
+
+            let e = last(a).merge(last(b)).unwrap_err();
+            assert!(e.kind.is_custom());
+            assert_redacted(&e);
+        }
+
+        let e = last(SECRET).merge(last(PLAIN)).unwrap_err();
+        assert_eq!(
+            e.kind.to_string(),
+            "refusing to replace encrypted value with plaintext value (set `allow_downgrade` to allow this)"
+        );
+
+        let e = last(PLAIN).merge(last(SECRET)).unwrap_err();
+        assert_eq!(
+            e.kind.to_string(),
+            "refusing to replace plaintext value with encrypted value (set `allow_downgrade` to allow this)"
+        );
+    }
+
+    #[test]
+    fn test_error_path() {
+        let e = Err::<(), _>(last(SECRET).merge(last(PLAIN)).unwrap_err())
+            .value("password")
+            .value("db")
+            .unwrap_err();
+
+        assert_eq!(
+            e.value
+                .components()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>(),
+            ["db", "password"]
+        );
+        assert_redacted(&e);
+    }
+
+    #[test]
+    fn test_collision() {
+        let e = prio(SECRET, 10).merge(prio(PLAIN, 10)).unwrap_err();
+        assert_eq!(e.kind, ErrorKind::Collision);
+    }
+
+    #[test]
+    fn test_allow_downgrade() {
+        // Only the flag of the value that is kept matters.
+        let merged = prio(SECRET, 10)
+            .merge(prio(PLAIN, 5).allow_downgrade())
+            .unwrap();
+        assert_eq!(&*merged, PLAIN);
+        assert!(merged.allows_downgrade());
+
+        prio(SECRET, 10)
+            .allow_downgrade()
+            .merge(prio(PLAIN, 5))
+            .unwrap_err();
+
+        let merged = last(PLAIN).merge(last(SECRET).allow_downgrade()).unwrap();
+        assert_eq!(&*merged, SECRET);
+
+        // The flag stays with the value that was kept.
+        let merged = prio(SECRET, 10)
+            .merge(prio(PLAIN, 5).allow_downgrade())
+            .unwrap()
+            .merge(prio("enc:other", 20))
+            .unwrap();
+        assert_eq!(&*merged, PLAIN);
+
+        let merged = prio(PLAIN, 10)
+            .merge(prio("other", 5).allow_downgrade())
+            .unwrap();
+        assert!(merged.allows_downgrade());
+        let merged = prio(PLAIN, 5)
+            .merge(prio("other", 10).allow_downgrade())
+            .unwrap();
+        assert!(!merged.allows_downgrade());
+    }
+
+    #[test]
+    fn test_redacted_fmt() {
+        let x = last(PLAIN);
+        assert_eq!(
+            format!("{x:?}"),
+            "MaybeEncrypted { classification: Plaintext, allow_downgrade: false, .. }"
+        );
+        assert_eq!(x.to_string(), "<plaintext>");
+        assert_eq!(last(SECRET).to_string(), "<encrypted>");
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+    use crate::types::Overridable;
+
+    #[test]
+    fn test_deserialize_raw() {
+        let x: MaybeEncrypted = serde_json::from_str("\"enc:abc\"").unwrap();
+        assert!(x.is_encrypted());
+        assert!(!x.allows_downgrade());
+
+        let x: MaybeEncrypted<Overridable<String>> =
+            serde_json::from_str("{ \"value\": \"abc\", \"priority\": 10 }").unwrap();
+        assert!(!x.is_encrypted());
+        assert_eq!(x.inner().priority(), 10.into());
+    }
+
+    #[test]
+    fn test_deserialize_allow_downgrade() {
+        let x: MaybeEncrypted =
+            serde_json::from_str("{ \"value\": \"abc\", \"allow_downgrade\": true }").unwrap();
+        assert!(!x.is_encrypted());
+        assert!(x.allows_downgrade());
+
+        let x: MaybeEncrypted<Overridable<String>> = serde_json::from_str(
+            "{ \"value\": \"abc\", \"priority\": 10, \"allow_downgrade\": true }",
+        )
+        .unwrap();
+        assert!(x.allows_downgrade());
+        assert_eq!(x.inner().priority(), 10.into());
+        assert_eq!(&*x, "abc");
+    }
+}
@@ -0,0 +1,91 @@
+//! Values that keep the smallest one when merged.
+//!
+//! See: [`Min`].
+
+use super::prelude::*;
+
+merge_thin_wrapper! {
+    /// Values that keep the smallest one when merged.
+    ///
+    /// Merging 2 values of this type keeps the lesser one, as decided by
+    /// [`Ord`]. Equal values merge cleanly and keep the value of `self`, so
+    /// there are no collisions.
+    ///
+    /// The opposite of this is [`Max`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::types::Min;
+    /// # use module::merge::Merge;
+    /// let a = Min(10);
+    /// let b = Min(30);
+    ///
+    /// let merged = a.merge(b).unwrap();
+    ///
+    /// assert_eq!(*merged, 10);
+    /// ```
+    ///
+    /// # serde
+    ///
+    /// This type deserializes like `T`.
+    ///
+    /// [`Max`]: crate::types::Max
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+    pub struct Min;
+}
+
+impl<T> Merge for Min<T>
+where
+    T: Ord,
+{
+    #[inline]
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        if other.0 < self.0 {
+            self.0 = other.0;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_ab() {
+        let a = Min(42);
+        let b = Min(43);
+
+        let merged = a.merge(b).unwrap();
+        assert_eq!(*merged, 42);
+    }
+
+    #[test]
+    fn test_merge_ba() {
+        let a = Min(42);
+        let b = Min(43);
+
+        let merged = b.merge(a).unwrap();
+        assert_eq!(*merged, 42);
+    }
+
+    #[test]
+    fn test_merge_equal() {
+        let merged = Min(42).merge(Min(42)).unwrap();
+        assert_eq!(*merged, 42);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Min<i32> = serde_json::from_str("42").unwrap();
+        assert_eq!(*x, 42);
+    }
+}
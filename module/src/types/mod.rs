@@ -1,24 +1,80 @@
 //! Types implementing various merge strategies.
 
+pub mod add_duration;
+pub mod assert_eq;
+pub mod candidates;
+pub mod coalesce;
 pub mod first;
 pub mod last;
 pub mod lines;
+pub mod locked;
+pub mod matrix;
+pub mod max;
+pub mod maybe_encrypted;
+pub mod min;
 pub mod no_merge;
+pub mod or_reset;
 pub mod ordered;
+pub mod ordered_entries;
+pub mod ordered_map;
 pub mod overridable;
+pub mod priority_list;
+pub mod replace;
+pub mod required;
+#[cfg(feature = "std")]
+pub mod spillable;
+pub mod sum;
+pub mod unique;
 
+#[doc(inline)]
+pub use self::add_duration::AddDuration;
+#[doc(inline)]
+pub use self::assert_eq::AssertEq;
+#[doc(inline)]
+pub use self::candidates::Candidates;
+#[doc(inline)]
+pub use self::coalesce::{Coalesce, OptionCoalesce};
 #[doc(inline)]
 pub use self::first::First;
 #[doc(inline)]
 pub use self::last::Last;
 #[doc(inline)]
-pub use self::lines::Lines;
+pub use self::lines::{Joined, Lines};
+#[doc(inline)]
+pub use self::locked::Locked;
+#[doc(inline)]
+pub use self::matrix::Matrix;
+#[doc(inline)]
+pub use self::max::Max;
+#[doc(inline)]
+pub use self::maybe_encrypted::MaybeEncrypted;
+#[doc(inline)]
+pub use self::min::Min;
 #[doc(inline)]
 pub use self::no_merge::NoMerge;
 #[doc(inline)]
+pub use self::or_reset::OrReset;
+#[doc(inline)]
 pub use self::ordered::Ordered;
 #[doc(inline)]
+pub use self::ordered_entries::OrderedEntries;
+#[doc(inline)]
+pub use self::ordered_map::OrderedMap;
+#[doc(inline)]
 pub use self::overridable::Overridable;
+#[doc(inline)]
+pub use self::priority_list::{Keyed, PriorityList};
+#[doc(inline)]
+pub use self::replace::Replace;
+#[doc(inline)]
+pub use self::required::Required;
+#[doc(inline)]
+#[cfg(feature = "std")]
+pub use self::spillable::Spillable;
+#[doc(inline)]
+pub use self::sum::Sum;
+#[doc(inline)]
+pub use self::unique::Unique;
 
 #[allow(unused_imports)]
 mod prelude {
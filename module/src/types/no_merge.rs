@@ -8,7 +8,8 @@ merge_thin_wrapper! {
     /// An unmergeable value.
     ///
     /// This wrapper can wrap any type and make it "unmergeable". This means
-    /// that any attempt to merge it will result in a collision error.
+    /// that any attempt to merge it will result in a collision error. To allow
+    /// merging equal values, use [`AssertEq`].
     ///
     /// # Example
     ///
@@ -26,6 +27,8 @@ merge_thin_wrapper! {
     /// # serde
     ///
     /// This type deserializes like `T`.
+    ///
+    /// [`AssertEq`]: crate::types::AssertEq
     pub struct NoMerge;
 }
 
@@ -0,0 +1,412 @@
+//! Values that can be reset to their default.
+//!
+//! See: [`OrReset`].
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use super::prelude::*;
+use crate::merge::Resolve;
+
+/// The string that resets an [`OrReset`].
+///
+/// See: [`OrReset`].
+pub trait Sentinel {
+    /// The string that means "reset", if any.
+    const SENTINEL: Option<&'static str>;
+}
+
+/// The default [`Sentinel`]. Only `{ "$reset": true }` resets the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoSentinel;
+
+impl Sentinel for NoSentinel {
+    const SENTINEL: Option<&'static str> = None;
+}
+
+/// A [`Sentinel`] that also resets the value with the string `default`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DefaultKeyword;
+
+impl Sentinel for DefaultKeyword {
+    const SENTINEL: Option<&'static str> = Some("default");
+}
+
+/// A value or a request to reset it to its default.
+///
+/// A reset discards everything merged so far. Merging works like this:
+///
+/// * A value merged with a value merges the 2 values.
+/// * Anything merged with a reset is a reset.
+/// * A reset merged with a value is that value, so a later module can set a
+///   fresh value after a reset.
+///
+/// After all modules have been merged, [`resolve()`] turns a reset into the
+/// [`Default`] of `T`, or [`resolve_with()`] into any other default.
+///
+/// [`OrReset`] composes with other types as expected. Inside an
+/// [`Overridable`], the priorities decide which value is kept first, and
+/// only then does a reset apply. A reset with a higher priority loses to a
+/// value with a lower one, just like any other value.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::types::OrReset;
+/// # use module::merge::Merge;
+/// let a = OrReset::new(vec![1, 2]);
+/// let b = OrReset::reset();
+/// let c = OrReset::new(vec![3]);
+///
+/// let merged: OrReset<Vec<i32>> = a.merge(b).unwrap();
+/// assert!(merged.is_reset());
+///
+/// let merged = merged.merge(c).unwrap();
+/// assert_eq!(merged.resolve(), [3]);
+/// ```
+///
+/// # serde
+///
+/// This type deserializes as one of the following:
+///
+/// * `{ "$reset": true }`, which is a reset
+/// * the string of the [`Sentinel`] `S`, which is a reset
+/// * `T`
+///
+/// The string sentinel is opt-in and is meant for string-typed fields,
+/// because it makes the string unavailable as a value. For example,
+/// `OrReset<String, DefaultKeyword>` resets with `log_level = "default"`.
+///
+/// [`resolve()`]: OrReset::resolve
+/// [`resolve_with()`]: OrReset::resolve_with
+/// [`Overridable`]: crate::types::Overridable
+pub struct OrReset<T, S = NoSentinel> {
+    value: Option<T>,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<T, S> OrReset<T, S> {
+    /// Create a new `value`.
+    pub fn new(value: T) -> Self {
+        Self {
+            value: Some(value),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a new reset.
+    pub fn reset() -> Self {
+        Self {
+            value: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Check whether this is a reset.
+    pub fn is_reset(&self) -> bool {
+        self.value.is_none()
+    }
+
+    /// Get the value, or [`None`] if this is a reset.
+    pub fn get(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Get the value mutably, or [`None`] if this is a reset.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.value.as_mut()
+    }
+
+    /// Destruct this [`OrReset`] and get the value, or [`None`] if this is a
+    /// reset.
+    pub fn into_option(self) -> Option<T> {
+        self.value
+    }
+
+    /// Get the value, or the [`Default`] of `T` if this is a reset.
+    pub fn resolve(self) -> T
+    where
+        T: Default,
+    {
+        self.resolve_with(T::default)
+    }
+
+    /// Get the value, or the result of `default` if this is a reset.
+    pub fn resolve_with<F>(self, default: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        self.value.unwrap_or_else(default)
+    }
+}
+
+impl<T, S> Merge for OrReset<T, S>
+where
+    T: Merge,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        match (&mut self.value, other.value) {
+            (Some(a), Some(b)) => a.merge_ref(b)?,
+            (a, b) => *a = b,
+        }
+
+        Ok(())
+    }
+}
+
+/// Turns a reset into the [`Default`] of `T`.
+impl<T, S> Resolve for OrReset<T, S>
+where
+    T: Default,
+{
+    fn resolve_in_place(&mut self) -> Result<(), Error> {
+        self.value.get_or_insert_with(T::default);
+        Ok(())
+    }
+}
+
+impl<T, S> From<T> for OrReset<T, S> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, S> Default for OrReset<T, S>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T, S> Clone for OrReset<T, S>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, S> PartialEq for OrReset<T, S>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T, S> Eq for OrReset<T, S> where T: Eq {}
+
+impl<T, S> fmt::Debug for OrReset<T, S>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Some(ref x) => f.debug_tuple("Value").field(x).finish(),
+            None => f.write_str("Reset"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use alloc::string::String;
+
+    use serde::Deserialize;
+    use serde::de::{self, Deserializer};
+
+    // The order of the variants matters. `T` may itself be a map or a string,
+    // so the reset forms must be tried first.
+    #[derive(Deserialize)]
+    #[serde(untagged, bound(deserialize = "T: Deserialize<'de>, S: Sentinel"))]
+    enum Repr<T, S> {
+        Reset(ResetMap),
+        Sentinel(SentinelStr<S>),
+        Value(T),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ResetMap {
+        #[serde(rename = "$reset", deserialize_with = "expect_true")]
+        _reset: (),
+    }
+
+    fn expect_true<'de, D>(deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match bool::deserialize(deserializer)? {
+            true => Ok(()),
+            false => Err(de::Error::custom("expected `$reset` to be `true`")),
+        }
+    }
+
+    struct SentinelStr<S>(PhantomData<S>);
+
+    impl<'de, S> Deserialize<'de> for SentinelStr<S>
+    where
+        S: Sentinel,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let Some(sentinel) = S::SENTINEL else {
+                return Err(de::Error::custom("no sentinel"));
+            };
+
+            match String::deserialize(deserializer)? {
+                x if x == sentinel => Ok(Self(PhantomData)),
+                _ => Err(de::Error::custom("not the sentinel")),
+            }
+        }
+    }
+
+    impl<'de, T, S> Deserialize<'de> for OrReset<T, S>
+    where
+        T: Deserialize<'de>,
+        S: Sentinel,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            match Repr::<T, S>::deserialize(deserializer)? {
+                Repr::Reset(_) | Repr::Sentinel(_) => Ok(Self::reset()),
+                Repr::Value(x) => Ok(Self::new(x)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Overridable;
+
+    use alloc::vec::Vec;
+
+    type X = OrReset<Vec<i32>>;
+
+    #[test]
+    fn test_merge() {
+        let merged = X::new(vec![1]).merge(X::new(vec![2])).unwrap();
+        assert_eq!(merged.get().unwrap(), &[1, 2]);
+
+        let merged = X::new(vec![1]).merge(X::reset()).unwrap();
+        assert!(merged.is_reset());
+
+        let merged = X::reset().merge(X::new(vec![2])).unwrap();
+        assert_eq!(merged.get().unwrap(), &[2]);
+
+        let merged = X::reset().merge(X::reset()).unwrap();
+        assert!(merged.is_reset());
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(X::reset().resolve(), Vec::<i32>::new());
+        assert_eq!(X::reset().resolve_with(|| vec![42]), [42]);
+        assert_eq!(X::new(vec![1]).resolve_with(|| vec![42]), [1]);
+
+        let mut x = Some(X::reset());
+        x.resolve_in_place().unwrap();
+        assert_eq!(x.unwrap().get().unwrap(), &Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_option() {
+        let merged = Some(X::new(vec![1]))
+            .merge(None)
+            .unwrap()
+            .merge(Some(X::reset()))
+            .unwrap()
+            .merge(None)
+            .unwrap();
+
+        assert!(merged.unwrap().is_reset());
+    }
+
+    #[test]
+    fn test_overridable() {
+        type O = Overridable<OrReset<i32>>;
+
+        // The priority decides first, even against a reset.
+        let merged = O::with_priority(OrReset::new(1), 10)
+            .merge(O::with_priority(OrReset::reset(), 20))
+            .unwrap();
+        assert_eq!(merged.into_value().resolve(), 1);
+
+        // The reset only applies if it wins.
+        let merged = O::with_priority(OrReset::new(1), 10)
+            .merge(O::with_priority(OrReset::reset(), 5))
+            .unwrap();
+        assert_eq!(merged.into_value().resolve(), 0);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    fn read<T>(modules: &[&str]) -> T
+    where
+        T: Merge + serde::de::DeserializeOwned,
+    {
+        modules
+            .iter()
+            .map(|x| serde_json::from_str::<T>(x).unwrap())
+            .reduce(|a, b| a.merge(b).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_set_reset_set_map() {
+        type X = OrReset<Vec<i32>>;
+
+        let x: X = read(&["[1, 2]", r#"{ "$reset": true }"#]);
+        assert!(x.is_reset());
+
+        let x: X = read(&["[1, 2]", r#"{ "$reset": true }"#, "[3]"]);
+        assert_eq!(x.resolve(), [3]);
+    }
+
+    #[test]
+    fn test_set_reset_set_sentinel() {
+        type X = OrReset<String, DefaultKeyword>;
+
+        let x: X = read(&[r#""debug""#, r#""default""#]);
+        assert_eq!(x.clone().resolve_with(|| "info".into()), "info");
+
+        let x: X = read(&[r#""debug""#, r#""default""#, r#""warn""#]);
+        assert_eq!(x.resolve(), "warn");
+
+        let x: X = read(&[r#""debug""#, r#"{ "$reset": true }"#]);
+        assert!(x.is_reset());
+    }
+
+    #[test]
+    fn test_no_sentinel() {
+        let x: OrReset<String> = serde_json::from_str(r#""default""#).unwrap();
+        assert_eq!(x.get().map(String::as_str), Some("default"));
+
+        let x: OrReset<String> = serde_json::from_str(r#"{ "$reset": true }"#).unwrap();
+        assert!(x.is_reset());
+
+        assert!(serde_json::from_str::<OrReset<String>>(r#"{ "$reset": false }"#).is_err());
+    }
+}
@@ -50,6 +50,31 @@ impl Default for Order {
 /// assert_eq!(*merged, &[3, 4, 5, 0, 1, 2]);
 /// ```
 ///
+/// # Order of the merged value
+///
+/// Only the order of the value being merged in is consulted. The merged value
+/// keeps the order of `self`, or, when an empty [`Option`] is filled, the order
+/// of the value that filled it.
+///
+/// This means that the first value of a sequence of merges decides the order
+/// of the result. That order never affects merging more values _into_ the
+/// result, but it does apply if the result is later merged into another value.
+/// Evaluators that want to avoid this can normalize the result with
+/// [`reset_order()`].
+///
+/// ```rust
+/// # use module::types::ordered::{Ordered, Order};
+/// # use module::merge::Merge;
+/// let a = None;
+/// let b = Some(Ordered::with_order(vec![0, 1], Order::After));
+///
+/// let mut merged = a.merge(b).unwrap().unwrap();
+/// assert_eq!(merged.order(), Order::After);
+///
+/// merged.reset_order();
+/// assert_eq!(merged.order(), Order::Before);
+/// ```
+///
 /// # serde
 ///
 /// This type deserializes as one of the following:
@@ -60,6 +85,7 @@ impl Default for Order {
 ///
 /// [`Before`]: Order::Before
 /// [`After`]: Order::After
+/// [`reset_order()`]: Ordered::reset_order
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Ordered<T> {
     value: T,
@@ -94,6 +120,14 @@ impl<T> Ordered<T> {
         self.order = order.into();
     }
 
+    /// Reset the order of this value to the default.
+    ///
+    /// See: [Order of the merged value](Ordered#order-of-the-merged-value).
+    #[inline]
+    pub fn reset_order(&mut self) {
+        self.order = Order::default();
+    }
+
     /// Destruct this [`Ordered`] and get the inner value.
     #[inline]
     pub fn into_value(self) -> T {
@@ -146,10 +180,20 @@ mod serde_impl {
         }
     }
 
+    /// Deserializes from `"before"` or `"after"`.
+    impl<'de> Deserialize<'de> for Order {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            OrderRepr::deserialize(deserializer).map(Into::into)
+        }
+    }
+
     #[derive(Deserialize)]
     #[serde(untagged)]
     enum Repr<T> {
-        Order { value: T, order: OrderRepr },
+        Order { value: T, order: Order },
         Value { value: T },
         Raw(T),
     }
@@ -157,7 +201,7 @@ mod serde_impl {
     impl<T> From<Repr<T>> for Ordered<T> {
         fn from(x: Repr<T>) -> Self {
             match x {
-                Repr::Order { value, order } => Self::with_order(value, Order::from(order)),
+                Repr::Order { value, order } => Self::with_order(value, order),
                 Repr::Value { value } => Ordered::new(value),
                 Repr::Raw(value) => Ordered::new(value),
             }
@@ -223,6 +267,42 @@ mod tests {
         let c = a.merge(b).unwrap();
         assert_eq!(*c, &[4, 5, 6, 0, 1, 2, 3]);
     }
+
+    #[test]
+    fn test_merged_order_is_self() {
+        let a = x(vec![0], After);
+        let b = x(vec![1], Before);
+
+        let c = a.merge(b).unwrap();
+        assert_eq!(c.order(), After);
+    }
+
+    #[test]
+    fn test_option_fill_keeps_order() {
+        let a = None;
+        let b = Some(x(vec![0, 1], After));
+
+        let c = a.merge(b).unwrap().unwrap();
+        assert_eq!(c.order(), After);
+
+        // The retained order is not consulted when merging into the value.
+        let d = c.merge(x(vec![2], After)).unwrap();
+        assert_eq!(*d, &[0, 1, 2]);
+        let e = d.merge(x(vec![3], Before)).unwrap();
+        assert_eq!(*e, &[3, 0, 1, 2]);
+        assert_eq!(e.order(), After);
+
+        // But it is when the value is merged into another one.
+        let f = x(vec![4], Before).merge(e).unwrap();
+        assert_eq!(*f, &[4, 3, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_reset_order() {
+        let mut a = x(vec![0], After);
+        a.reset_order();
+        assert_eq!(a.order(), Before);
+    }
 }
 
 #[cfg(test)]
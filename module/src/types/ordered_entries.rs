@@ -0,0 +1,265 @@
+//! Maps whose entries override each other in order.
+//!
+//! See: [`OrderedEntries`].
+
+use alloc::collections::BTreeMap;
+
+use super::ordered::Order;
+use super::prelude::*;
+
+/// A map which decides which of 2 entries with the same key is kept.
+///
+/// Merging maps recurses into the values of entries with the same key, so the
+/// order of the maps makes no difference. [`OrderedEntries`] instead keeps
+/// only one of the 2 values, without merging them. Like with [`Ordered`],
+/// the order of the value being merged in decides which one:
+///
+/// * if `order` is [`Before`], then the entries of this value win,
+/// * if `order` is [`After`], then the entries of the other value win.
+///
+/// In other words, the entry that comes last wins. Entries with keys that
+/// only one of the maps has are always kept. The values do not need to
+/// implement [`Merge`].
+///
+/// This is implemented for [`BTreeMap`] and, with the `std` feature, for
+/// [`HashMap`].
+///
+/// # Example
+///
+/// ```rust
+/// # use std::collections::BTreeMap;
+/// # use module::types::ordered::Order;
+/// # use module::types::OrderedEntries;
+/// # use module::merge::Merge;
+/// let a = OrderedEntries::new(BTreeMap::from([("a", 1), ("b", 2)]));
+/// let b = OrderedEntries::with_order(BTreeMap::from([("b", 3), ("c", 4)]), Order::After);
+///
+/// let merged = a.merge(b).unwrap();
+///
+/// assert_eq!(*merged, BTreeMap::from([("a", 1), ("b", 3), ("c", 4)]));
+/// ```
+///
+/// # Order of the merged value
+///
+/// Just like with [`Ordered`], the merged value keeps the order of `self`.
+/// See: [Order of the merged value](super::Ordered#order-of-the-merged-value).
+///
+/// # serde
+///
+/// This type deserializes as one of the following:
+///
+/// * `M`
+/// * `{ value: M }`
+/// * `{ value: M, order: "before"|"after" }`
+///
+/// [`Ordered`]: super::Ordered
+/// [`Before`]: Order::Before
+/// [`After`]: Order::After
+/// [`HashMap`]: std::collections::HashMap
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OrderedEntries<M> {
+    value: M,
+    order: Order,
+}
+
+impl<M> OrderedEntries<M> {
+    /// Create a new `value` with the default order.
+    #[inline]
+    pub fn new(value: M) -> Self {
+        Self::with_order(value, Order::default())
+    }
+
+    /// Create a new `value` with `order`.
+    #[inline]
+    pub fn with_order(value: M, order: Order) -> Self {
+        Self { value, order }
+    }
+
+    /// Get the order of this value.
+    #[inline]
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// Set the order of this value.
+    #[inline]
+    pub fn set_order<O>(&mut self, order: O)
+    where
+        O: Into<Order>,
+    {
+        self.order = order.into();
+    }
+
+    /// Reset the order of this value to the default.
+    #[inline]
+    pub fn reset_order(&mut self) {
+        self.order = Order::default();
+    }
+
+    /// Destruct this [`OrderedEntries`] and get the inner map.
+    #[inline]
+    pub fn into_value(self) -> M {
+        self.value
+    }
+}
+
+impl<K, V> Merge for OrderedEntries<BTreeMap<K, V>>
+where
+    K: Ord,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        match other.order {
+            Order::Before => other.value.into_iter().for_each(|(k, v)| {
+                self.value.entry(k).or_insert(v);
+            }),
+            Order::After => self.value.extend(other.value),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> Merge for OrderedEntries<std::collections::HashMap<K, V, S>>
+where
+    K: Eq + core::hash::Hash,
+    S: core::hash::BuildHasher,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        match other.order {
+            Order::Before => other.value.into_iter().for_each(|(k, v)| {
+                self.value.entry(k).or_insert(v);
+            }),
+            Order::After => self.value.extend(other.value),
+        }
+
+        Ok(())
+    }
+}
+
+impl<M> From<M> for OrderedEntries<M> {
+    #[inline]
+    fn from(value: M) -> Self {
+        Self::new(value)
+    }
+}
+
+impl_wrapper!(OrderedEntries<M> => M { .value });
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use serde::Deserialize;
+    use serde::de::Deserializer;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr<M> {
+        Order { value: M, order: Order },
+        Value { value: M },
+        Raw(M),
+    }
+
+    impl<M> From<Repr<M>> for OrderedEntries<M> {
+        fn from(x: Repr<M>) -> Self {
+            match x {
+                Repr::Order { value, order } => Self::with_order(value, order),
+                Repr::Value { value } => Self::new(value),
+                Repr::Raw(value) => Self::new(value),
+            }
+        }
+    }
+
+    impl<'de, M> Deserialize<'de> for OrderedEntries<M>
+    where
+        M: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            <Repr<M> as Deserialize>::deserialize(deserializer).map(Into::into)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    use Order::{After, Before};
+
+    #[inline]
+    fn x(
+        entries: &[(&'static str, i32)],
+        order: Order,
+    ) -> OrderedEntries<BTreeMap<&'static str, i32>> {
+        OrderedEntries::with_order(entries.iter().copied().collect(), order)
+    }
+
+    #[test]
+    fn test_after() {
+        let c = x(&[("a", 1), ("b", 2)], Before)
+            .merge(x(&[("b", 3), ("c", 4)], After))
+            .unwrap();
+
+        assert_eq!(*c, BTreeMap::from([("a", 1), ("b", 3), ("c", 4)]));
+    }
+
+    #[test]
+    fn test_before() {
+        let c = x(&[("a", 1), ("b", 2)], After)
+            .merge(x(&[("b", 3), ("c", 4)], Before))
+            .unwrap();
+
+        assert_eq!(*c, BTreeMap::from([("a", 1), ("b", 2), ("c", 4)]));
+        assert_eq!(c.order(), After);
+    }
+
+    #[test]
+    fn test_no_inner_merge() {
+        let a = OrderedEntries::new(BTreeMap::from([("a", Merged(false))]));
+        let b = OrderedEntries::with_order(BTreeMap::from([("a", Merged(false))]), After);
+
+        let c = a.merge(b).unwrap();
+        assert!(!c["a"].0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hash_map() {
+        use std::collections::HashMap;
+
+        let a = OrderedEntries::new(HashMap::from([("a", 1), ("b", 2)]));
+        let b = OrderedEntries::with_order(HashMap::from([("b", 3)]), After);
+        assert_eq!(a.clone().merge(b).unwrap()["b"], 3);
+
+        let b = OrderedEntries::with_order(HashMap::from([("b", 3)]), Before);
+        assert_eq!(a.merge(b).unwrap()["b"], 2);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    type X = OrderedEntries<BTreeMap<alloc::string::String, i32>>;
+
+    #[test]
+    fn test_deserialize_map() {
+        let x: X = serde_json::from_str("{ \"a\": 1 }").unwrap();
+        assert_eq!(x.order(), Order::Before);
+        assert_eq!(x["a"], 1);
+    }
+
+    #[test]
+    fn test_deserialize_value_order() {
+        let x: X =
+            serde_json::from_str("{ \"value\": { \"a\": 1 }, \"order\": \"after\" }").unwrap();
+        assert_eq!(x.order(), Order::After);
+        assert_eq!(x["a"], 1);
+    }
+}
@@ -0,0 +1,660 @@
+//! Maps with user-defined entry ordering.
+//!
+//! See: [`OrderedMap`].
+
+use core::fmt::Display;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::merge::Neutral;
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    before: Vec<K>,
+    after: Vec<K>,
+}
+
+/// A map whose entries declare their position relative to each other.
+///
+/// Each entry of an [`OrderedMap`] may require to be placed _before_ or
+/// _after_ other entries, referring to them by key. The constraints may be
+/// declared across modules, and may refer to entries that a different module
+/// defines. This makes it suitable for things like middleware or plugin
+/// chains, where the order matters and is assembled from many sources.
+///
+/// The final ordering is computed by [`sorted()`] with a topological sort.
+/// Entries that are not constrained relative to each other keep the order in
+/// which they were first inserted, so the result is always deterministic.
+///
+/// Lookups are linear in the number of entries.
+///
+/// # Merge
+///
+/// Entries of `other` are processed in `other`'s order. Entries with new keys
+/// are appended. Entries with existing keys have their values merged, with
+/// the key as value context, and their constraints combined.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::types::OrderedMap;
+/// # use module::merge::Merge;
+/// let mut a = OrderedMap::new();
+/// a.push("auth", ()).unwrap();
+/// a.push("log", ()).unwrap();
+///
+/// let mut b = OrderedMap::new();
+/// b.push_before("cors", (), "auth").unwrap();
+/// b.push_after("log", (), "compress").unwrap();
+/// b.push("compress", ()).unwrap();
+///
+/// let merged = a.merge(b).unwrap();
+///
+/// let keys: Vec<_> = merged.sorted().unwrap().into_iter().map(|(k, _)| *k).collect();
+/// assert_eq!(keys, &["cors", "auth", "compress", "log"]);
+/// ```
+///
+/// # serde
+///
+/// This type deserializes from a map. Each value is one of the following:
+///
+/// * `V`
+/// * `{ value: V }`
+/// * `{ value: V, before: K|[K], after: K|[K] }`, where both `before` and
+///   `after` are optional.
+///
+/// The order of entries in the serialized map is preserved. If a key appears
+/// more than once, the last entry replaces the earlier ones in the position of
+/// the first.
+///
+/// [`sorted()`]: OrderedMap::sorted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderedMap<K, V> {
+    entries: Vec<Entry<K, V>>,
+}
+
+impl<K, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> OrderedMap<K, V> {
+    /// Create a new empty [`OrderedMap`].
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Get the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get an iterator over all entries in insertion order.
+    ///
+    /// This order does not take into account any constraints. For the final
+    /// ordering, see: [`OrderedMap::sorted`].
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|x| (&x.key, &x.value))
+    }
+}
+
+impl<K, V> OrderedMap<K, V>
+where
+    K: Eq,
+{
+    fn position(&self, key: &K) -> Option<usize> {
+        self.entries.iter().position(|x| x.key == *key)
+    }
+
+    /// Check whether the map contains an entry for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Get the value of the entry for `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.position(key).map(|i| &self.entries[i].value)
+    }
+
+    /// Get the value of the entry for `key` mutably.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.position(key).map(|i| &mut self.entries[i].value)
+    }
+}
+
+impl<K, V> OrderedMap<K, V>
+where
+    K: Eq + Display,
+    V: Merge,
+{
+    fn push_entry(&mut self, entry: Entry<K, V>) -> Result<(), Error> {
+        let Some(i) = self.position(&entry.key) else {
+            self.entries.push(entry);
+            return Ok(());
+        };
+
+        let Entry {
+            key,
+            value,
+            before,
+            after,
+        } = entry;
+
+        let x = &mut self.entries[i];
+        x.value
+            .merge_ref(value)
+            .with_value(|| format!("\"{key}\""))?;
+
+        for k in before {
+            if !x.before.contains(&k) {
+                x.before.push(k);
+            }
+        }
+
+        for k in after {
+            if !x.after.contains(&k) {
+                x.after.push(k);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add `value` under `key`.
+    ///
+    /// If an entry for `key` already exists, `value` is merged into it.
+    pub fn push(&mut self, key: K, value: V) -> Result<(), Error> {
+        self.push_entry(Entry {
+            key,
+            value,
+            before: Vec::new(),
+            after: Vec::new(),
+        })
+    }
+
+    /// Add `value` under `key` and require it to be placed before `other`.
+    ///
+    /// If an entry for `key` already exists, `value` is merged into it.
+    pub fn push_before(&mut self, key: K, value: V, other: K) -> Result<(), Error> {
+        self.push_entry(Entry {
+            key,
+            value,
+            before: vec![other],
+            after: Vec::new(),
+        })
+    }
+
+    /// Add `value` under `key` and require it to be placed after `other`.
+    ///
+    /// If an entry for `key` already exists, `value` is merged into it.
+    pub fn push_after(&mut self, key: K, value: V, other: K) -> Result<(), Error> {
+        self.push_entry(Entry {
+            key,
+            value,
+            before: Vec::new(),
+            after: vec![other],
+        })
+    }
+}
+
+impl<K, V> OrderedMap<K, V>
+where
+    K: Eq + Display,
+{
+    /// Compute the final ordering of the entries.
+    ///
+    /// # Errors
+    ///
+    /// * If an entry refers to a key that does not exist, the error names both
+    ///   the entry and the missing key.
+    ///
+    /// * If the constraints form a cycle, the error names all keys of the
+    ///   cycle.
+    pub fn sorted(&self) -> Result<Vec<(&K, &V)>, Error> {
+        let order = self.order()?;
+        Ok(order
+            .into_iter()
+            .map(|i| (&self.entries[i].key, &self.entries[i].value))
+            .collect())
+    }
+
+    /// Destruct this [`OrderedMap`] and get its entries in their final order.
+    ///
+    /// See: [`OrderedMap::sorted`].
+    pub fn into_sorted(self) -> Result<Vec<(K, V)>, Error> {
+        let order = self.order()?;
+
+        let mut entries: Vec<Option<Entry<K, V>>> = self.entries.into_iter().map(Some).collect();
+        Ok(order
+            .into_iter()
+            .filter_map(|i| entries[i].take())
+            .map(|x| (x.key, x.value))
+            .collect())
+    }
+
+    /// Compute the final ordering as indices into `self.entries`.
+    fn order(&self) -> Result<Vec<usize>, Error> {
+        let n = self.entries.len();
+
+        // `edges[i]` holds all entries that must come after entry `i`.
+        let mut edges: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut in_degree: Vec<usize> = vec![0; n];
+
+        let resolve = |from: &K, to: &K| {
+            self.position(to).ok_or_else(|| {
+                let mut e = Error::custom(format!("'{from}' refers to unknown key '{to}'"));
+                e.value.push(format!("\"{from}\""));
+                e
+            })
+        };
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            for k in &entry.before {
+                let j = resolve(&entry.key, k)?;
+                edges[i].push(j);
+                in_degree[j] += 1;
+            }
+
+            for k in &entry.after {
+                let j = resolve(&entry.key, k)?;
+                edges[j].push(i);
+                in_degree[i] += 1;
+            }
+        }
+
+        let mut done = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+
+        // Always pick the earliest inserted entry that is ready. This keeps
+        // unconstrained entries in insertion order.
+        while let Some(i) = (0..n).find(|&i| !done[i] && in_degree[i] == 0) {
+            done[i] = true;
+            order.push(i);
+
+            for &j in &edges[i] {
+                in_degree[j] -= 1;
+            }
+        }
+
+        if order.len() == n {
+            return Ok(order);
+        }
+
+        Err(self.cycle_error(&edges, &done))
+    }
+
+    fn cycle_error(&self, edges: &[Vec<usize>], done: &[bool]) -> Error {
+        // Every remaining entry has a remaining predecessor, so walking
+        // predecessors must eventually revisit an entry.
+        let predecessor = |j: usize| {
+            (0..edges.len())
+                .find(|&i| !done[i] && edges[i].contains(&j))
+                .unwrap_or(j)
+        };
+
+        let start = (0..done.len()).find(|&i| !done[i]).unwrap_or_default();
+
+        let mut path = vec![start];
+        let mut current = predecessor(start);
+        while !path.contains(&current) {
+            path.push(current);
+            current = predecessor(current);
+        }
+
+        let cycle_start = path.iter().position(|&i| i == current).unwrap_or_default();
+        let mut cycle = path.split_off(cycle_start);
+        cycle.reverse();
+
+        // Start the cycle at its earliest inserted entry.
+        let first = cycle
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, &i)| i)
+            .map(|(pos, _)| pos)
+            .unwrap_or_default();
+        cycle.rotate_left(first);
+
+        let keys: Vec<String> = cycle
+            .iter()
+            .chain(cycle.first())
+            .map(|&i| format!("'{}'", self.entries[i].key))
+            .collect();
+
+        Error::custom(format!("ordering cycle: {}", keys.join(" -> ")))
+    }
+}
+
+impl<K, V> Merge for OrderedMap<K, V>
+where
+    K: Eq + Display,
+    V: Merge,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        other
+            .entries
+            .into_iter()
+            .try_for_each(|entry| self.push_entry(entry))
+    }
+}
+
+impl<K, V> Neutral for OrderedMap<K, V> {
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::Deserialize;
+    use serde::de::{Deserializer, MapAccess, Visitor};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Keys<K> {
+        One(K),
+        Many(Vec<K>),
+    }
+
+    impl<K> Default for Keys<K> {
+        fn default() -> Self {
+            Self::Many(Vec::new())
+        }
+    }
+
+    impl<K> From<Keys<K>> for Vec<K> {
+        fn from(x: Keys<K>) -> Self {
+            match x {
+                Keys::One(x) => vec![x],
+                Keys::Many(x) => x,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    #[serde(bound = "K: Deserialize<'de>, V: Deserialize<'de>")]
+    enum Repr<K, V> {
+        Placed {
+            value: V,
+            #[serde(default)]
+            before: Keys<K>,
+            #[serde(default)]
+            after: Keys<K>,
+        },
+        Raw(V),
+    }
+
+    struct OrderedMapVisitor<K, V>(PhantomData<(K, V)>);
+
+    impl<'de, K, V> Visitor<'de> for OrderedMapVisitor<K, V>
+    where
+        K: Deserialize<'de> + Eq,
+        V: Deserialize<'de>,
+    {
+        type Value = OrderedMap<K, V>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut x = OrderedMap::new();
+
+            while let Some((key, repr)) = map.next_entry::<K, Repr<K, V>>()? {
+                let entry = match repr {
+                    Repr::Placed {
+                        value,
+                        before,
+                        after,
+                    } => Entry {
+                        key,
+                        value,
+                        before: before.into(),
+                        after: after.into(),
+                    },
+                    Repr::Raw(value) => Entry {
+                        key,
+                        value,
+                        before: Vec::new(),
+                        after: Vec::new(),
+                    },
+                };
+
+                match x.position(&entry.key) {
+                    Some(i) => x.entries[i] = entry,
+                    None => x.entries.push(entry),
+                }
+            }
+
+            Ok(x)
+        }
+    }
+
+    impl<'de, K, V> Deserialize<'de> for OrderedMap<K, V>
+    where
+        K: Deserialize<'de> + Eq,
+        V: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_map(OrderedMapVisitor(PhantomData))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    use alloc::string::ToString;
+
+    fn keys(x: &OrderedMap<&'static str, Merged>) -> Vec<&'static str> {
+        x.sorted().unwrap().into_iter().map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let mut a = OrderedMap::new();
+        a.push("c", Merged(false)).unwrap();
+        a.push("a", Merged(false)).unwrap();
+        a.push("b", Merged(false)).unwrap();
+
+        assert_eq!(keys(&a), &["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_merge_existing() {
+        let mut a = OrderedMap::new();
+        a.push("a", Merged(false)).unwrap();
+        a.push("b", Merged(false)).unwrap();
+
+        let mut b = OrderedMap::new();
+        b.push("c", Merged(false)).unwrap();
+        b.push("a", Merged(false)).unwrap();
+
+        let c = a.merge(b).unwrap();
+
+        assert_eq!(keys(&c), &["a", "b", "c"]);
+        assert!(c.get(&"a").unwrap().0);
+        assert!(!c.get(&"b").unwrap().0);
+    }
+
+    #[test]
+    fn test_merge_error_context() {
+        let mut a = OrderedMap::new();
+        a.push("a", 1).unwrap();
+
+        let mut b = OrderedMap::new();
+        b.push("a", 2).unwrap();
+
+        let err = a.merge(b).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+
+        let path: Vec<_> = err.value.components().map(|x| x.to_string()).collect();
+        assert_eq!(path, &["\"a\""]);
+    }
+
+    #[test]
+    fn test_cross_module_constraints() {
+        let mut a = OrderedMap::new();
+        a.push("auth", Merged(false)).unwrap();
+        a.push("log", Merged(false)).unwrap();
+
+        let mut b = OrderedMap::new();
+        b.push_before("cors", Merged(false), "auth").unwrap();
+        b.push_after("log", Merged(false), "compress").unwrap();
+
+        let mut c = OrderedMap::new();
+        c.push("compress", Merged(false)).unwrap();
+        c.push_after("compress", Merged(false), "auth").unwrap();
+
+        let merged = a.merge(b).unwrap().merge(c).unwrap();
+        assert_eq!(keys(&merged), &["cors", "auth", "compress", "log"]);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut a = OrderedMap::new();
+        a.push("first", Merged(false)).unwrap();
+        a.push_before("a", Merged(false), "b").unwrap();
+        a.push_before("b", Merged(false), "c").unwrap();
+        a.push_before("c", Merged(false), "a").unwrap();
+        a.push_after("last", Merged(false), "c").unwrap();
+
+        let err = a.sorted().unwrap_err();
+        assert!(err.kind.is_custom());
+        assert_eq!(
+            err.kind.to_string(),
+            "ordering cycle: 'a' -> 'b' -> 'c' -> 'a'"
+        );
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let mut a = OrderedMap::new();
+        a.push_after("log", Merged(false), "missing").unwrap();
+
+        let err = a.sorted().unwrap_err();
+        assert_eq!(
+            err.kind.to_string(),
+            "'log' refers to unknown key 'missing'"
+        );
+
+        let path: Vec<_> = err.value.components().map(|x| x.to_string()).collect();
+        assert_eq!(path, &["\"log\""]);
+    }
+
+    #[test]
+    fn test_into_sorted() {
+        let mut a = OrderedMap::new();
+        a.push("a", 1).unwrap();
+        a.push_before("b", 2, "a").unwrap();
+
+        assert_eq!(a.into_sorted().unwrap(), &[("b", 2), ("a", 1)]);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_deserialize() {
+        let x: OrderedMap<String, Vec<i32>> = serde_json::from_str(
+            r#"{
+                "log": [1],
+                "auth": { "value": [2] },
+                "cors": { "value": [3], "before": "auth" },
+                "compress": { "value": [4], "after": ["auth"], "before": ["log", "cors"] }
+            }"#,
+        )
+        .unwrap();
+
+        let keys: Vec<&str> = x.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, &["log", "auth", "cors", "compress"]);
+
+        let err = x.sorted().unwrap_err();
+        assert_eq!(
+            err.kind.to_string(),
+            "ordering cycle: 'auth' -> 'compress' -> 'cors' -> 'auth'"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_duplicate() {
+        let x: OrderedMap<String, Vec<i32>> = serde_json::from_str(
+            r#"{
+                "log": [1],
+                "auth": [2],
+                "log": { "value": [3], "after": "auth" }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(x.len(), 2);
+        assert_eq!(x.get(&"log".to_string()), Some(&vec![3]));
+
+        let keys: Vec<&str> = x.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, &["log", "auth"]);
+
+        let sorted: Vec<&str> = x
+            .sorted()
+            .unwrap()
+            .into_iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(sorted, &["auth", "log"]);
+    }
+
+    #[test]
+    fn test_deserialize_sorted() {
+        let x: OrderedMap<String, Vec<i32>> = serde_json::from_str(
+            r#"{
+                "log": [1],
+                "auth": { "value": [2] },
+                "cors": { "value": [3], "before": "auth" }
+            }"#,
+        )
+        .unwrap();
+
+        let sorted: Vec<(&str, &[i32])> = x
+            .sorted()
+            .unwrap()
+            .into_iter()
+            .map(|(k, v)| (k.as_str(), v.as_slice()))
+            .collect();
+
+        assert_eq!(
+            sorted,
+            &[("log", &[1][..]), ("cors", &[3][..]), ("auth", &[2][..])]
+        );
+    }
+}
@@ -6,6 +6,7 @@ use core::borrow::{Borrow, BorrowMut};
 use core::cmp::Ordering;
 use core::convert::{AsMut, AsRef};
 use core::fmt;
+use core::marker::PhantomData;
 use core::ops::{Deref, DerefMut};
 
 use super::prelude::*;
@@ -32,6 +33,71 @@ impl fmt::Debug for Priority {
     }
 }
 
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The note of a collision between 2 values with the same priority.
+struct SamePriority(Priority);
+
+impl fmt::Display for SamePriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "both priority {}", self.0)
+    }
+}
+
+/// Create the error of 2 values that both have `priority`.
+pub(crate) fn collision(priority: Priority) -> Error {
+    Error::collision().with_note(SamePriority(priority))
+}
+
+/// What happens when 2 [`Overridable`] values have the same priority.
+///
+/// See: [`Tie`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TiePolicy {
+    /// Fail with a collision.
+    Collision,
+    /// Keep the value of `self`.
+    KeepSelf,
+    /// Keep the value of `other`.
+    KeepOther,
+}
+
+/// The [`TiePolicy`] of an [`Overridable`].
+///
+/// See: [`Overridable`].
+pub trait Tie {
+    /// The policy for values with the same priority.
+    const POLICY: TiePolicy;
+}
+
+/// The default [`Tie`]. Values with the same priority collide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Collision;
+
+impl Tie for Collision {
+    const POLICY: TiePolicy = TiePolicy::Collision;
+}
+
+/// A [`Tie`] that keeps the first of 2 values with the same priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeepSelf;
+
+impl Tie for KeepSelf {
+    const POLICY: TiePolicy = TiePolicy::KeepSelf;
+}
+
+/// A [`Tie`] that keeps the last of 2 values with the same priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeepOther;
+
+impl Tie for KeepOther {
+    const POLICY: TiePolicy = TiePolicy::KeepOther;
+}
+
 /// An overridable value based on priority.
 ///
 /// This is a wrapper that provides the "magic" mechanism by which values can
@@ -43,6 +109,9 @@ impl fmt::Debug for Priority {
 /// this default, it is strongly recommended you make a type alias to avoid
 /// specifying the default priority on each use.
 ///
+/// Values with the same priority collide. The [`Tie`] `S` can change that to
+/// keep either of the 2 values instead, see [`TiePolicy`].
+///
 /// # Example
 ///
 /// ```rust
@@ -56,6 +125,22 @@ impl fmt::Debug for Priority {
 /// assert_eq!(*merged, "bar");
 /// ```
 ///
+/// With a [`Tie`]:
+///
+/// ```rust
+/// # use module::types::Overridable;
+/// # use module::types::overridable::KeepOther;
+/// # use module::merge::Merge;
+/// type LastWins<T> = Overridable<T, 500, KeepOther>;
+///
+/// let a = LastWins::new("foo");
+/// let b = LastWins::new("bar");
+///
+/// let merged = a.merge(b).unwrap();
+///
+/// assert_eq!(*merged, "bar");
+/// ```
+///
 /// # serde
 ///
 /// This type deserializes as one of the following:
@@ -63,13 +148,13 @@ impl fmt::Debug for Priority {
 /// * `T`
 /// * `{ value: T }`
 /// * `{ value: T, priority: isize }`
-#[derive(Debug, Clone, Copy)]
-pub struct Overridable<T, const DEFAULT: isize = 500> {
+pub struct Overridable<T, const DEFAULT: isize = 500, S = Collision> {
     value: T,
     priority: Priority,
+    _tie: PhantomData<fn() -> S>,
 }
 
-impl<T, const DEFAULT: isize> Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> Overridable<T, DEFAULT, S> {
     /// Create a new `value` with the default priority.
     pub fn new(value: T) -> Self {
         Self::with_priority(value, Priority(DEFAULT))
@@ -80,8 +165,11 @@ impl<T, const DEFAULT: isize> Overridable<T, DEFAULT> {
     where
         P: Into<Priority>,
     {
-        let priority = priority.into();
-        Self { value, priority }
+        Self {
+            value,
+            priority: priority.into(),
+            _tie: PhantomData,
+        }
     }
 
     /// Get the priority of this value.
@@ -97,32 +185,62 @@ impl<T, const DEFAULT: isize> Overridable<T, DEFAULT> {
         self.priority = priority.into();
     }
 
+    /// Shift the priority of this value by `offset`, if it has the default
+    /// priority.
+    ///
+    /// This is how evaluators make some modules win over others when they
+    /// would otherwise collide. For example, a smaller offset for the values
+    /// of later modules makes them override those of earlier modules. A value
+    /// given the default priority explicitly is shifted too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::types::Overridable;
+    /// let mut a = Overridable::<i32>::new(42);
+    /// let mut b = Overridable::<i32>::with_priority(42, 10);
+    ///
+    /// a.shift_default(-1);
+    /// b.shift_default(-1);
+    ///
+    /// assert_eq!(a.priority(), 499.into());
+    /// assert_eq!(b.priority(), 10.into());
+    /// ```
+    pub fn shift_default(&mut self, offset: isize) {
+        if self.priority == Priority(DEFAULT) {
+            self.priority = Priority(DEFAULT.saturating_add(offset));
+        }
+    }
+
     /// Destruct this [`Overridable`] and get the inner value.
     pub fn into_value(self) -> T {
         self.value
     }
 }
 
-impl<T, const DEFAULT: isize> Merge for Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> Merge for Overridable<T, DEFAULT, S>
+where
+    S: Tie,
+{
     fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
-        match self.priority.cmp(&other.priority) {
-            Ordering::Less => Ok(()),
-            Ordering::Greater => {
+        match (self.priority.cmp(&other.priority), S::POLICY) {
+            (Ordering::Less, _) | (Ordering::Equal, TiePolicy::KeepSelf) => Ok(()),
+            (Ordering::Greater, _) | (Ordering::Equal, TiePolicy::KeepOther) => {
                 *self = other;
                 Ok(())
             }
-            Ordering::Equal => Err(Error::collision()),
+            (Ordering::Equal, TiePolicy::Collision) => Err(collision(self.priority)),
         }
     }
 }
 
-impl<T, const DEFAULT: isize> From<T> for Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> From<T> for Overridable<T, DEFAULT, S> {
     fn from(value: T) -> Self {
         Self::new(value)
     }
 }
 
-impl<T, const DEFAULT: isize> Default for Overridable<T, DEFAULT>
+impl<T, const DEFAULT: isize, S> Default for Overridable<T, DEFAULT, S>
 where
     T: Default,
 {
@@ -131,35 +249,69 @@ where
     }
 }
 
-impl<T, const DEFAULT: isize> Borrow<T> for Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> Clone for Overridable<T, DEFAULT, S>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::with_priority(self.value.clone(), self.priority)
+    }
+}
+
+impl<T, const DEFAULT: isize, S> Copy for Overridable<T, DEFAULT, S> where T: Copy {}
+
+impl<T, const DEFAULT: isize, S> PartialEq for Overridable<T, DEFAULT, S>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.priority == other.priority
+    }
+}
+
+impl<T, const DEFAULT: isize, S> Eq for Overridable<T, DEFAULT, S> where T: Eq {}
+
+impl<T, const DEFAULT: isize, S> fmt::Debug for Overridable<T, DEFAULT, S>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Overridable")
+            .field("value", &self.value)
+            .field("priority", &self.priority)
+            .finish()
+    }
+}
+
+impl<T, const DEFAULT: isize, S> Borrow<T> for Overridable<T, DEFAULT, S> {
     #[inline]
     fn borrow(&self) -> &T {
         &self.value
     }
 }
 
-impl<T, const DEFAULT: isize> BorrowMut<T> for Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> BorrowMut<T> for Overridable<T, DEFAULT, S> {
     #[inline]
     fn borrow_mut(&mut self) -> &mut T {
         &mut self.value
     }
 }
 
-impl<T, const DEFAULT: isize> AsRef<T> for Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> AsRef<T> for Overridable<T, DEFAULT, S> {
     #[inline]
     fn as_ref(&self) -> &T {
         &self.value
     }
 }
 
-impl<T, const DEFAULT: isize> AsMut<T> for Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> AsMut<T> for Overridable<T, DEFAULT, S> {
     #[inline]
     fn as_mut(&mut self) -> &mut T {
         &mut self.value
     }
 }
 
-impl<T, const DEFAULT: isize> Deref for Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> Deref for Overridable<T, DEFAULT, S> {
     type Target = T;
 
     #[inline]
@@ -168,7 +320,7 @@ impl<T, const DEFAULT: isize> Deref for Overridable<T, DEFAULT> {
     }
 }
 
-impl<T, const DEFAULT: isize> DerefMut for Overridable<T, DEFAULT> {
+impl<T, const DEFAULT: isize, S> DerefMut for Overridable<T, DEFAULT, S> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.value
@@ -190,7 +342,7 @@ mod serde_impl {
         Raw(T),
     }
 
-    impl<T, const DEFAULT: isize> From<Repr<T>> for Overridable<T, DEFAULT> {
+    impl<T, const DEFAULT: isize, S> From<Repr<T>> for Overridable<T, DEFAULT, S> {
         fn from(x: Repr<T>) -> Self {
             match x {
                 Repr::Priority { value, priority } => Overridable::with_priority(value, priority),
@@ -200,7 +352,7 @@ mod serde_impl {
         }
     }
 
-    impl<'de, T, const DEFAULT: isize> Deserialize<'de> for Overridable<T, DEFAULT>
+    impl<'de, T, const DEFAULT: isize, S> Deserialize<'de> for Overridable<T, DEFAULT, S>
     where
         T: Deserialize<'de>,
     {
@@ -218,6 +370,8 @@ mod tests {
     use super::*;
     use crate::test::*;
 
+    use alloc::string::ToString;
+
     #[inline]
     fn x<T>(value: T, priority: isize) -> Overridable<T> {
         Overridable::with_priority(value, priority)
@@ -241,14 +395,45 @@ mod tests {
         assert_eq!(*c, 32);
     }
 
+    #[test]
+    fn test_commutative() {
+        for (pa, pb) in [(10, 9), (9, 10), (-5, 5), (0, isize::MAX), (isize::MIN, 0)] {
+            let ab = x(1, pa).merge(x(2, pb)).unwrap();
+            let ba = x(2, pb).merge(x(1, pa)).unwrap();
+
+            assert_eq!(ab, ba, "priorities {pa} and {pb}");
+            assert_eq!(*ab, if pa < pb { 1 } else { 2 });
+        }
+    }
+
     #[test]
     fn test_same_priority() {
-        assert_eq!(
-            x(1, 10).merge(x(2, 10)).unwrap_err().kind,
-            ErrorKind::Collision
+        let err = x(1, 10).merge(x(2, 10)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+        assert_eq!(err.note().unwrap().to_string(), "both priority 10");
+        assert!(
+            err.to_string()
+                .starts_with("value collision (both priority 10)"),
+            "{err}"
         );
     }
 
+    #[test]
+    fn test_same_priority_policy() {
+        let a = Overridable::<_, 500, KeepSelf>::with_priority(1, 10);
+        let b = Overridable::with_priority(2, 10);
+        assert_eq!(*a.merge(b).unwrap(), 1);
+
+        let a = Overridable::<_, 500, KeepOther>::with_priority(1, 10);
+        let b = Overridable::with_priority(2, 10);
+        assert_eq!(*a.merge(b).unwrap(), 2);
+
+        // The priority still decides first.
+        let a = Overridable::<_, 500, KeepOther>::with_priority(1, 10);
+        let b = Overridable::with_priority(2, 20);
+        assert_eq!(*a.merge(b).unwrap(), 1);
+    }
+
     #[test]
     fn test_no_inner_merge() {
         let a = x(Merged(false), 10);
@@ -257,6 +442,17 @@ mod tests {
         let c = a.merge(b).unwrap();
         assert!(!c.0);
     }
+
+    #[test]
+    fn test_shift_default() {
+        let mut a = x(42, 500);
+        a.shift_default(-10);
+        assert_eq!(a.priority.0, 490);
+
+        let mut b = x(42, 7);
+        b.shift_default(-10);
+        assert_eq!(b.priority.0, 7);
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,535 @@
+//! Lists of named items ordered by priority.
+//!
+//! See: [`PriorityList`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::merge::Neutral;
+
+use super::overridable::Priority;
+use super::prelude::*;
+
+/// A value that is identified by a key.
+///
+/// See: [`PriorityList`].
+pub trait Keyed {
+    /// Get the key of this value.
+    fn key(&self) -> &str;
+}
+
+impl Keyed for String {
+    #[inline]
+    fn key(&self) -> &str {
+        self
+    }
+}
+
+impl Keyed for &str {
+    #[inline]
+    fn key(&self) -> &str {
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    key: String,
+    /// The winning definition and its priority.
+    value: Option<(T, Priority)>,
+    /// The priority set by re-rank entries, if any.
+    rank: Option<Priority>,
+}
+
+impl<T> Entry<T> {
+    fn priority(&self) -> Option<Priority> {
+        self.rank.or(self.value.as_ref().map(|(_, p)| *p))
+    }
+}
+
+/// A list of named items ordered by priority.
+///
+/// Each item is identified by its [key]. Modules may add new items, override
+/// existing items, or only change the priority of an existing item without
+/// redefining it.
+///
+/// * For items defined in more than one module, the definition with the least
+///   priority is kept, just like with [`Overridable`]. Definitions of the same
+///   item with the same priority are an error.
+///
+/// * Re-rank entries carry only a key and a priority. They change the priority
+///   of the item they refer to, regardless of which definition is kept. Two
+///   re-rank entries for the same item must agree on the priority.
+///
+/// The final list is produced by [`resolve()`]. Items are sorted by their
+/// priority, and items with the same priority keep the order in which their
+/// keys were first seen.
+///
+/// If the priority of a value is not given, the default of 500 is assumed.
+/// This can be changed by the `DEFAULT` type parameter.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::types::PriorityList;
+/// # use module::merge::Merge;
+/// let mut a = PriorityList::<&str>::new();
+/// a.push_with_priority("auth", 100).unwrap();
+/// a.push_with_priority("log", 200).unwrap();
+///
+/// let mut b = PriorityList::<&str>::new();
+/// b.push_with_priority("cors", 150).unwrap();
+/// b.rerank("log", 50).unwrap();
+///
+/// let merged = a.merge(b).unwrap();
+/// assert_eq!(merged.resolve().unwrap(), &[&"log", &"auth", &"cors"]);
+/// ```
+///
+/// # serde
+///
+/// This type deserializes from a list. Each item is one of the following:
+///
+/// * `T`
+/// * `{ value: T }`
+/// * `{ value: T, priority: isize }`
+/// * `{ key: string, priority: isize }`, a re-rank entry.
+///
+/// [key]: Keyed::key
+/// [`Overridable`]: super::Overridable
+/// [`resolve()`]: PriorityList::resolve
+#[derive(Debug, Clone)]
+pub struct PriorityList<T, const DEFAULT: isize = 500> {
+    entries: Vec<Entry<T>>,
+}
+
+impl<T, const DEFAULT: isize> Default for PriorityList<T, DEFAULT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const DEFAULT: isize> PriorityList<T, DEFAULT> {
+    /// Create a new empty [`PriorityList`].
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Get the number of keys in the list.
+    ///
+    /// This includes keys that have been re-ranked but not defined.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn entry(&self, key: &str) -> Option<&Entry<T>> {
+        self.entries.iter().find(|x| x.key == key)
+    }
+
+    fn entry_mut(&mut self, key: &str) -> &mut Entry<T> {
+        match self.entries.iter().position(|x| x.key == key) {
+            Some(i) => &mut self.entries[i],
+            None => {
+                self.entries.push(Entry {
+                    key: key.into(),
+                    value: None,
+                    rank: None,
+                });
+
+                self.entries.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Get the definition of `key`.
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.entry(key)?.value.as_ref().map(|(x, _)| x)
+    }
+
+    /// Get the effective priority of `key`.
+    ///
+    /// This is the priority of the last re-rank, if any, or else the priority
+    /// of the kept definition.
+    pub fn priority(&self, key: &str) -> Option<Priority> {
+        self.entry(key)?.priority()
+    }
+
+    /// Change the priority of `key` without defining it.
+    ///
+    /// # Errors
+    ///
+    /// If `key` has already been re-ranked with a different priority.
+    pub fn rerank<K, P>(&mut self, key: K, priority: P) -> Result<(), Error>
+    where
+        K: AsRef<str>,
+        P: Into<Priority>,
+    {
+        let key = key.as_ref();
+        let priority = priority.into();
+
+        let entry = self.entry_mut(key);
+        match entry.rank {
+            Some(x) if x != priority => Err(tie("conflicting re-ranks", key, x, priority)),
+            _ => {
+                entry.rank = Some(priority);
+                Ok(())
+            }
+        }
+    }
+
+    /// Get all defined items sorted by priority.
+    ///
+    /// # Errors
+    ///
+    /// If a key has been re-ranked but never defined.
+    pub fn resolve(&self) -> Result<Vec<&T>, Error> {
+        self.order()?
+            .into_iter()
+            .map(|i| match self.entries[i].value {
+                Some((ref x, _)) => Ok(x),
+                None => Err(undefined(&self.entries[i].key)),
+            })
+            .collect()
+    }
+
+    /// Destruct the list and get all defined items sorted by priority.
+    ///
+    /// See: [`PriorityList::resolve`].
+    pub fn into_resolved(self) -> Result<Vec<T>, Error> {
+        let order = self.order()?;
+
+        let mut entries: Vec<Option<Entry<T>>> = self.entries.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .filter_map(|i| entries[i].take())
+            .map(|x| match x.value {
+                Some((x, _)) => Ok(x),
+                None => Err(undefined(&x.key)),
+            })
+            .collect()
+    }
+
+    fn order(&self) -> Result<Vec<usize>, Error> {
+        let mut order: Vec<(Priority, usize)> = Vec::with_capacity(self.entries.len());
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let priority = entry.priority().ok_or_else(|| undefined(&entry.key))?;
+            order.push((priority, i));
+        }
+
+        // `sort` is stable, so equal priorities keep first-seen order.
+        order.sort_by_key(|(priority, _)| *priority);
+        Ok(order.into_iter().map(|(_, i)| i).collect())
+    }
+}
+
+impl<T, const DEFAULT: isize> PriorityList<T, DEFAULT>
+where
+    T: Keyed,
+{
+    /// Add `value` with the default priority.
+    ///
+    /// See: [`PriorityList::push_with_priority`].
+    pub fn push(&mut self, value: T) -> Result<(), Error> {
+        self.push_with_priority(value, DEFAULT)
+    }
+
+    /// Add `value` with `priority`.
+    ///
+    /// If an item with the same key already exists, the definition with the
+    /// least priority is kept.
+    ///
+    /// # Errors
+    ///
+    /// If an item with the same key and priority already exists.
+    pub fn push_with_priority<P>(&mut self, value: T, priority: P) -> Result<(), Error>
+    where
+        P: Into<Priority>,
+    {
+        let priority = priority.into();
+
+        let key = String::from(value.key());
+        let entry = self.entry_mut(&key);
+
+        match entry.value {
+            Some((_, x)) if x == priority => Err(tie("conflicting definitions", &key, x, priority)),
+            Some((_, x)) if x < priority => Ok(()),
+            _ => {
+                entry.value = Some((value, priority));
+                Ok(())
+            }
+        }
+    }
+}
+
+fn tie(what: &str, key: &str, a: Priority, b: Priority) -> Error {
+    let a = isize::from(a);
+    let b = isize::from(b);
+
+    let mut e = Error::custom(format!("{what} of '{key}' with priorities {a} and {b}"));
+    e.value.push(format!("\"{key}\""));
+    e
+}
+
+fn undefined(key: &str) -> Error {
+    let mut e = Error::custom(format!("'{key}' was re-ranked but never defined"));
+    e.value.push(format!("\"{key}\""));
+    e
+}
+
+impl<T, const DEFAULT: isize> Merge for PriorityList<T, DEFAULT>
+where
+    T: Keyed,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        for entry in other.entries {
+            let Entry { key, value, rank } = entry;
+
+            if let Some((value, priority)) = value {
+                self.push_with_priority(value, priority)?;
+            }
+
+            if let Some(rank) = rank {
+                self.rerank(&key, rank)?;
+            }
+
+            // Keep the first-seen position even for keys that carry nothing.
+            self.entry_mut(&key);
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, const DEFAULT: isize> Neutral for PriorityList<T, DEFAULT> {
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use serde::Deserialize;
+    use serde::de::{Deserializer, Error as _};
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Rerank {
+        key: String,
+        priority: isize,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr<T> {
+        Priority { value: T, priority: isize },
+        Value { value: T },
+        Rerank(Rerank),
+        Raw(T),
+    }
+
+    impl<'de, T, const DEFAULT: isize> Deserialize<'de> for PriorityList<T, DEFAULT>
+    where
+        T: Deserialize<'de> + Keyed,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let items = <Vec<Repr<T>> as Deserialize>::deserialize(deserializer)?;
+
+            let mut list = Self::new();
+            for item in items {
+                let r = match item {
+                    Repr::Priority { value, priority } => list.push_with_priority(value, priority),
+                    Repr::Value { value } | Repr::Raw(value) => list.push(value),
+                    Repr::Rerank(Rerank { key, priority }) => list.rerank(key, priority),
+                };
+
+                r.map_err(D::Error::custom)?;
+            }
+
+            Ok(list)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Stage {
+        name: &'static str,
+        version: u32,
+    }
+
+    impl Keyed for Stage {
+        fn key(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn stage(name: &'static str, version: u32) -> Stage {
+        Stage { name, version }
+    }
+
+    type List = PriorityList<Stage>;
+
+    fn names(x: &List) -> Vec<&'static str> {
+        x.resolve().unwrap().into_iter().map(|x| x.name).collect()
+    }
+
+    #[test]
+    fn test_add() {
+        let mut a = List::new();
+        a.push(stage("b", 0)).unwrap();
+        a.push_with_priority(stage("a", 0), 10).unwrap();
+
+        let mut b = List::new();
+        b.push(stage("c", 0)).unwrap();
+
+        let merged = a.merge(b).unwrap();
+        assert_eq!(names(&merged), &["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_override() {
+        let mut a = List::new();
+        a.push_with_priority(stage("a", 1), 10).unwrap();
+        a.push_with_priority(stage("b", 1), 20).unwrap();
+
+        let mut b = List::new();
+        b.push_with_priority(stage("a", 2), 5).unwrap();
+        b.push_with_priority(stage("b", 2), 30).unwrap();
+
+        let merged = a.merge(b).unwrap();
+        assert_eq!(merged.get("a").unwrap().version, 2);
+        assert_eq!(merged.get("b").unwrap().version, 1);
+        assert_eq!(merged.priority("a"), Some(5.into()));
+    }
+
+    #[test]
+    fn test_rerank_only() {
+        let mut a = List::new();
+        a.push_with_priority(stage("a", 1), 10).unwrap();
+        a.push_with_priority(stage("b", 1), 20).unwrap();
+
+        let mut b = List::new();
+        b.rerank("a", 30).unwrap();
+
+        let merged = a.merge(b).unwrap();
+        assert_eq!(names(&merged), &["b", "a"]);
+        assert_eq!(merged.get("a").unwrap().version, 1);
+
+        // The re-rank applies no matter which definition is kept.
+        let mut c = List::new();
+        c.push_with_priority(stage("a", 2), 1).unwrap();
+
+        let merged = merged.merge(c).unwrap();
+        assert_eq!(names(&merged), &["b", "a"]);
+        assert_eq!(merged.get("a").unwrap().version, 2);
+    }
+
+    #[test]
+    fn test_rerank_before_definition() {
+        let mut a = List::new();
+        a.rerank("a", 30).unwrap();
+
+        let err = a.resolve().unwrap_err();
+        assert_eq!(err.kind.to_string(), "'a' was re-ranked but never defined");
+
+        let mut b = List::new();
+        b.push(stage("b", 0)).unwrap();
+        b.push(stage("a", 0)).unwrap();
+
+        let merged = a.merge(b).unwrap();
+        assert_eq!(names(&merged), &["a", "b"]);
+    }
+
+    #[test]
+    fn test_tie() {
+        let mut a = List::new();
+        a.push_with_priority(stage("a", 1), 10).unwrap();
+
+        let mut b = List::new();
+        b.push_with_priority(stage("a", 2), 10).unwrap();
+
+        let err = a.merge(b).unwrap_err();
+        assert_eq!(
+            err.kind.to_string(),
+            "conflicting definitions of 'a' with priorities 10 and 10"
+        );
+        assert_eq!(
+            err.value
+                .components()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>(),
+            &["\"a\""]
+        );
+
+        let mut a = List::new();
+        a.rerank("a", 1).unwrap();
+        let err = a.rerank("a", 2).unwrap_err();
+        assert_eq!(
+            err.kind.to_string(),
+            "conflicting re-ranks of 'a' with priorities 1 and 2"
+        );
+    }
+
+    #[test]
+    fn test_order() {
+        let mut a = List::new();
+        a.push(stage("d", 0)).unwrap();
+        a.push_with_priority(stage("c", 0), 1).unwrap();
+        a.push(stage("b", 0)).unwrap();
+
+        let mut b = List::new();
+        b.push_with_priority(stage("a", 0), 1).unwrap();
+        b.rerank("b", 1).unwrap();
+
+        let merged = a.merge(b).unwrap();
+        assert_eq!(names(&merged), &["c", "b", "a", "d"]);
+
+        let stages = merged.into_resolved().unwrap();
+        assert_eq!(stages[0], stage("c", 0));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: PriorityList<String> = serde_json::from_str(
+            r#"[
+                "log",
+                { "value": "auth", "priority": 100 },
+                { "value": "cors" },
+                { "key": "log", "priority": 10 }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(x.resolve().unwrap(), &["log", "auth", "cors"]);
+    }
+
+    #[test]
+    fn test_deserialize_tie() {
+        let r = serde_json::from_str::<PriorityList<String>>(r#"["log", "log"]"#);
+        assert!(r.is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Values that are replaced wholesale instead of merged.
+//!
+//! See: [`Replace`].
+
+use super::prelude::*;
+
+merge_thin_wrapper! {
+    /// Values that are replaced wholesale instead of merged.
+    ///
+    /// Merging a [`Replace`] with another one keeps the other value and
+    /// discards this one. `T` is never merged, even if it implements
+    /// [`Merge`]. This turns off recursive merging for a nested struct, so
+    /// the last module that sets it replaces all of its fields at once,
+    /// without changing the [`Merge`] implementation of the struct itself.
+    ///
+    /// This behaves exactly like [`Last`]. Use [`Replace`] to say that a
+    /// value which could be merged should not be, and [`Last`] for values
+    /// that simply keep the most recent setting, like plain scalars.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::types::Replace;
+    /// # use module::merge::Merge;
+    /// let a = Replace(vec![0, 1, 2]);
+    /// let b = Replace(vec![3, 4]);
+    ///
+    /// let merged = a.merge(b).unwrap();
+    ///
+    /// assert_eq!(*merged, &[3, 4]);
+    /// ```
+    ///
+    /// # serde
+    ///
+    /// This type deserializes like `T`.
+    ///
+    /// [`Last`]: crate::types::Last
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+    pub struct Replace;
+}
+
+impl<T> Merge for Replace<T> {
+    #[inline]
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        self.0 = other.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    #[test]
+    fn test_merge() {
+        let merged = Replace(vec![1, 2]).merge(Replace(vec![3])).unwrap();
+        assert_eq!(*merged, [3]);
+    }
+
+    #[test]
+    fn test_no_inner_merge() {
+        let merged = Replace(Merged(false))
+            .merge(Replace(Merged(false)))
+            .unwrap();
+        assert!(!merged.0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn test_nested_struct() {
+        #[derive(Debug, Clone, PartialEq, Merge)]
+        struct Inner {
+            a: Option<i32>,
+            b: Option<i32>,
+        }
+
+        let a = Replace(Inner {
+            a: Some(1),
+            b: None,
+        });
+        let b = Replace(Inner {
+            a: None,
+            b: Some(2),
+        });
+
+        // Merging the structs would give `{ a: Some(1), b: Some(2) }`.
+        let merged = a.merge(b).unwrap();
+        assert_eq!(
+            *merged,
+            Inner {
+                a: None,
+                b: Some(2)
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Replace<i32> = serde_json::from_str("42").unwrap();
+        assert_eq!(*x, 42);
+    }
+}
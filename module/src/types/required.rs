@@ -0,0 +1,164 @@
+//! Values that must be set by some module.
+//!
+//! See: [`Required`].
+
+use crate::merge::Neutral;
+
+use super::prelude::*;
+
+/// A value that must be set by some module.
+///
+/// This is an [`Option`] that is expected to be [`Some`] once all modules
+/// have been merged. Modules may leave it unset, so that partial modules still
+/// deserialize, but [`require()`] fails if no module set it.
+///
+/// [`Required`] merges exactly like `Option<T>`.
+///
+/// # Example
+///
+/// ```rust
+/// # use module::types::Required;
+/// # use module::merge::{Context, Merge};
+/// let a = Required::<i32>::default();
+/// let b = Required::new(42);
+///
+/// let merged = a.merge(b).unwrap();
+/// assert_eq!(merged.require().unwrap(), 42);
+///
+/// let err = Required::<i32>::default().require().value("key").unwrap_err();
+/// assert_eq!(err.to_string(), "missing required value while evaluating 'key'\n");
+/// ```
+///
+/// # serde
+///
+/// This type deserializes like `Option<T>`.
+///
+/// [`require()`]: Required::require
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Required<T>(pub Option<T>);
+
+impl<T> Required<T> {
+    /// Create a new [`Required`] with `value`.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self(Some(value))
+    }
+
+    /// Destruct this [`Required`] and get the inner [`Option`].
+    #[inline]
+    pub fn into_option(self) -> Option<T> {
+        self.0
+    }
+
+    /// Get the value.
+    ///
+    /// # Errors
+    ///
+    /// If no value was set. The error is a [`ErrorKind::Custom`] with the
+    /// message `missing required value`.
+    ///
+    /// [`ErrorKind::Custom`]: crate::merge::ErrorKind::Custom
+    pub fn require(self) -> Result<T, Error> {
+        self.0
+            .ok_or_else(|| Error::custom("missing required value"))
+    }
+}
+
+impl<T> Merge for Required<T>
+where
+    T: Merge,
+{
+    #[inline]
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        self.0.merge_ref(other.0)
+    }
+}
+
+impl<T> Neutral for Required<T> {
+    #[inline]
+    fn is_neutral(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl<T> Default for Required<T> {
+    #[inline]
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> From<Option<T>> for Required<T> {
+    #[inline]
+    fn from(x: Option<T>) -> Self {
+        Self(x)
+    }
+}
+
+impl_wrapper!(Required<T> => Option<T> { .0 });
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use serde::Deserialize;
+    use serde::de::Deserializer;
+
+    impl<'de, T> Deserialize<'de> for Required<T>
+    where
+        T: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            <Option<T> as Deserialize>::deserialize(deserializer).map(Self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_merge() {
+        let merged = Required(None)
+            .merge(Required::new(vec![1]))
+            .unwrap()
+            .merge(Required::new(vec![2]))
+            .unwrap();
+        assert_eq!(merged.require().unwrap(), [1, 2]);
+
+        let err = Required::new(1).merge(Required::new(2)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Collision);
+    }
+
+    #[test]
+    fn test_require_missing() {
+        let err = Required::<Vec<i32>>::default()
+            .merge(Required(None))
+            .unwrap()
+            .require()
+            .unwrap_err();
+        assert!(err.kind.is_custom());
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Required<i32> = serde_json::from_str("42").unwrap();
+        assert_eq!(*x, Some(42));
+
+        let x: Required<i32> = serde_json::from_str("null").unwrap();
+        assert_eq!(*x, None);
+    }
+}
@@ -0,0 +1,552 @@
+//! Large lists that spill to disk.
+//!
+//! See: [`Spillable`].
+
+use core::cmp::Reverse;
+use core::fmt;
+use core::marker::PhantomData;
+use core::str::FromStr;
+
+use alloc::collections::BinaryHeap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use tempfile::NamedTempFile;
+
+use super::prelude::*;
+
+/// How the items of a [`Spillable`] are written to disk.
+pub trait Codec<T> {
+    /// Write `value` to `w`.
+    fn encode(value: &T, w: &mut dyn Write) -> io::Result<()>;
+
+    /// Read the next value from `r`.
+    ///
+    /// Returns [`None`] at the end of the input.
+    fn decode(r: &mut dyn BufRead) -> io::Result<Option<T>>;
+}
+
+/// A [`Codec`] that writes one item per line.
+///
+/// Items are written with [`Display`] and read back with [`FromStr`]. Items
+/// must not contain newlines.
+///
+/// [`Display`]: fmt::Display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Lines;
+
+impl<T> Codec<T> for Lines
+where
+    T: fmt::Display + FromStr,
+    T::Err: fmt::Display,
+{
+    fn encode(value: &T, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "{value}")
+    }
+
+    fn decode(r: &mut dyn BufRead) -> io::Result<Option<T>> {
+        let mut line = String::new();
+        if r.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        line.parse()
+            .map(Some)
+            .map_err(|e: T::Err| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+}
+
+/// A list that moves its items to temporary files once it grows too large.
+///
+/// [`Spillable`] merges like a [`Vec`], by appending. While it holds fewer
+/// than `THRESHOLD` items in memory, that is all it does. Once it reaches
+/// `THRESHOLD` items, they are written to a temporary file with the [`Codec`]
+/// `C` and removed from memory. So at most `THRESHOLD` items are kept in
+/// memory, no matter how many modules add to the list.
+///
+/// The items are read back with [`finalize()`], in the order they were
+/// added, or with [`finalize_sorted_dedup()`], sorted and without
+/// duplicates. Temporary files are removed when the [`Spillable`] is
+/// dropped or finalized.
+///
+/// Temporary files are created in the [spill directory] with random names
+/// and, on Unix, are only readable by the current user.
+///
+/// IO errors while spilling are returned from [`Merge`] as
+/// [`ErrorKind::Custom`].
+///
+/// # Example
+///
+/// ```rust
+/// # use module::merge::Merge;
+/// # use module::types::spillable::{Lines, Spillable};
+/// let mut a = Spillable::<u32, Lines, 2>::new();
+/// a.extend([3, 1, 3]).unwrap();
+///
+/// let mut b = Spillable::new();
+/// b.extend([2, 1]).unwrap();
+///
+/// let merged = a.merge(b).unwrap();
+///
+/// let mut items = Vec::new();
+/// merged.finalize_sorted_dedup(|x| items.push(x)).unwrap();
+/// assert_eq!(items, [1, 2, 3]);
+/// ```
+///
+/// # serde
+///
+/// This type deserializes from a sequence of `T`. Items are added one at a
+/// time, as they are deserialized, so a large sequence is never held in
+/// memory all at once.
+///
+/// [`finalize()`]: Spillable::finalize
+/// [`finalize_sorted_dedup()`]: Spillable::finalize_sorted_dedup
+/// [`ErrorKind::Custom`]: crate::merge::ErrorKind::Custom
+/// [spill directory]: Spillable::spill_dir
+pub struct Spillable<T, C = Lines, const THRESHOLD: usize = 65536> {
+    dir: Option<PathBuf>,
+    spills: Vec<SpillFile>,
+    tail: Vec<T>,
+    len: usize,
+    _codec: PhantomData<C>,
+}
+
+impl<T, C, const THRESHOLD: usize> Spillable<T, C, THRESHOLD> {
+    /// Create a new empty [`Spillable`].
+    pub fn new() -> Self {
+        Self {
+            dir: None,
+            spills: Vec::new(),
+            tail: Vec::new(),
+            len: 0,
+            _codec: PhantomData,
+        }
+    }
+
+    /// Get the number of items.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of temporary files the items have been spilled to.
+    pub fn spill_count(&self) -> usize {
+        self.spills.len()
+    }
+
+    /// Get the directory temporary files are created in.
+    ///
+    /// [`None`] means the directory of [`std::env::temp_dir`]. Defaults to
+    /// [`None`].
+    pub fn spill_dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// Set the directory temporary files are created in.
+    ///
+    /// Only files created from now on are affected. When merging, the
+    /// directory of `self` is used and that of the other value is ignored.
+    ///
+    /// See: [`Spillable::spill_dir`].
+    pub fn set_spill_dir<P>(&mut self, dir: Option<P>)
+    where
+        P: Into<PathBuf>,
+    {
+        self.dir = dir.map(Into::into);
+    }
+
+    /// Set the directory temporary files are created in.
+    ///
+    /// See: [`Spillable::spill_dir`].
+    pub fn with_spill_dir<P>(mut self, dir: Option<P>) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.set_spill_dir(dir);
+        self
+    }
+}
+
+impl<T, C, const THRESHOLD: usize> Spillable<T, C, THRESHOLD>
+where
+    C: Codec<T>,
+{
+    /// Add `value` to the end of the list.
+    ///
+    /// # Errors
+    ///
+    /// If the items have to be spilled and that fails.
+    pub fn push(&mut self, value: T) -> io::Result<()> {
+        self.tail.push(value);
+        self.len += 1;
+
+        if self.tail.len() >= THRESHOLD.max(1) {
+            self.spill()?;
+        }
+
+        Ok(())
+    }
+
+    /// Add all items of `iter` to the end of the list.
+    ///
+    /// # Errors
+    ///
+    /// If the items have to be spilled and that fails.
+    pub fn extend<I>(&mut self, iter: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        iter.into_iter().try_for_each(|x| self.push(x))
+    }
+
+    /// Write the items in memory to a new temporary file.
+    fn spill(&mut self) -> io::Result<()> {
+        if self.tail.is_empty() {
+            return Ok(());
+        }
+
+        let file = SpillFile::write::<T, C>(self.dir.as_deref(), &self.tail)?;
+        self.spills.push(file);
+        self.tail.clear();
+        Ok(())
+    }
+
+    /// Pass every item to `sink`, in the order they were added.
+    ///
+    /// # Errors
+    ///
+    /// If reading the spilled items fails.
+    pub fn finalize(mut self, mut sink: impl FnMut(T)) -> Result<(), Error> {
+        for file in &self.spills {
+            let mut r = file.open()?;
+            while let Some(x) = C::decode(&mut r)? {
+                sink(x);
+            }
+        }
+
+        self.tail.drain(..).for_each(sink);
+        Ok(())
+    }
+
+    /// Pass every item to `sink` in ascending order, skipping duplicates.
+    ///
+    /// The items of each temporary file are sorted on their own and then all
+    /// files are merged together, so no more than `THRESHOLD` items are
+    /// kept in memory at once.
+    ///
+    /// # Errors
+    ///
+    /// If reading or writing the spilled items fails.
+    pub fn finalize_sorted_dedup(mut self, mut sink: impl FnMut(T)) -> Result<(), Error>
+    where
+        T: Ord,
+    {
+        // Sorted runs, one for each spill file and one for the tail.
+        let mut runs = Vec::with_capacity(self.spills.len() + 1);
+        for file in &self.spills {
+            let mut items = Vec::new();
+            let mut r = file.open()?;
+            while let Some(x) = C::decode(&mut r)? {
+                items.push(x);
+            }
+
+            items.sort_unstable();
+            items.dedup();
+            runs.push(SpillFile::write::<T, C>(self.dir.as_deref(), &items)?);
+        }
+
+        self.tail.sort_unstable();
+        self.tail.dedup();
+        runs.push(SpillFile::write::<T, C>(self.dir.as_deref(), &self.tail)?);
+        self.tail = Vec::new();
+        self.spills.clear();
+
+        let mut readers = runs
+            .iter()
+            .map(SpillFile::open)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut heap = BinaryHeap::with_capacity(readers.len());
+        for (i, r) in readers.iter_mut().enumerate() {
+            if let Some(x) = C::decode(r)? {
+                heap.push(Reverse((x, i)));
+            }
+        }
+
+        let mut last: Option<T> = None;
+        while let Some(Reverse((x, i))) = heap.pop() {
+            if let Some(next) = C::decode(&mut readers[i])? {
+                heap.push(Reverse((next, i)));
+            }
+
+            if last.as_ref() == Some(&x) {
+                continue;
+            }
+
+            if let Some(prev) = last.replace(x) {
+                sink(prev);
+            }
+        }
+
+        last.into_iter().for_each(sink);
+        Ok(())
+    }
+}
+
+impl<T, C, const THRESHOLD: usize> Merge for Spillable<T, C, THRESHOLD>
+where
+    C: Codec<T>,
+{
+    fn merge_ref(&mut self, mut other: Self) -> Result<(), Error> {
+        // Keep the order of the items: if `other` has spilled, the items of
+        // `self` still in memory must go before the files of `other`.
+        if !other.spills.is_empty() {
+            self.spill()?;
+            self.spills.append(&mut other.spills);
+            self.len += other.len - other.tail.len();
+        }
+
+        self.extend(other.tail.drain(..))?;
+        Ok(())
+    }
+}
+
+impl<T, C, const THRESHOLD: usize> Default for Spillable<T, C, THRESHOLD> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C, const THRESHOLD: usize> fmt::Debug for Spillable<T, C, THRESHOLD>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spillable")
+            .field("dir", &self.dir)
+            .field("len", &self.len)
+            .field("spills", &self.spills.len())
+            .field("tail", &self.tail)
+            .finish()
+    }
+}
+
+/// A temporary file that is removed when dropped.
+struct SpillFile {
+    file: NamedTempFile,
+}
+
+impl SpillFile {
+    /// Write `items` to a new temporary file in `dir`.
+    ///
+    /// The file is created with a random name, so it cannot be guessed in
+    /// advance, and with permissions that only allow the owner to read it.
+    fn write<T, C>(dir: Option<&Path>, items: &[T]) -> io::Result<Self>
+    where
+        C: Codec<T>,
+    {
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("module-spill-");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(fs::Permissions::from_mode(0o600));
+        }
+
+        let file = match dir {
+            Some(dir) => builder.tempfile_in(dir)?,
+            None => builder.tempfile()?,
+        };
+
+        // The file is removed if writing fails.
+        let mut w = BufWriter::new(file);
+        items.iter().try_for_each(|x| C::encode(x, &mut w))?;
+
+        let file = w.into_inner().map_err(io::IntoInnerError::into_error)?;
+        Ok(Self { file })
+    }
+
+    fn open(&self) -> io::Result<BufReader<fs::File>> {
+        self.file.reopen().map(BufReader::new)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use serde::de::{Deserialize, Deserializer, Error as _, SeqAccess, Visitor};
+
+    struct SpillableVisitor<T, C, const THRESHOLD: usize>(PhantomData<(T, C)>);
+
+    impl<'de, T, C, const THRESHOLD: usize> Visitor<'de> for SpillableVisitor<T, C, THRESHOLD>
+    where
+        T: Deserialize<'de>,
+        C: Codec<T>,
+    {
+        type Value = Spillable<T, C, THRESHOLD>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut this = Spillable::new();
+            while let Some(x) = seq.next_element()? {
+                this.push(x).map_err(A::Error::custom)?;
+            }
+
+            Ok(this)
+        }
+    }
+
+    impl<'de, T, C, const THRESHOLD: usize> Deserialize<'de> for Spillable<T, C, THRESHOLD>
+    where
+        T: Deserialize<'de>,
+        C: Codec<T>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_seq(SpillableVisitor(PhantomData))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Small = Spillable<u64, Lines, 4>;
+
+    fn collect(x: Small) -> Vec<u64> {
+        let mut out = Vec::new();
+        x.finalize(|x| out.push(x)).unwrap();
+        out
+    }
+
+    /// A deterministic sequence of numbers with many duplicates.
+    fn numbers(seed: u64, n: usize) -> impl Iterator<Item = u64> {
+        let mut x = seed;
+        (0..n).map(move |_| {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (x >> 33) % 100
+        })
+    }
+
+    #[test]
+    fn test_spill() {
+        let mut a = Small::new();
+        a.extend(0..10).unwrap();
+
+        assert_eq!(a.len(), 10);
+        assert_eq!(a.spill_count(), 2);
+        assert_eq!(a.tail.len(), 2);
+
+        assert_eq!(collect(a), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_merge_order() {
+        let mut a = Small::new();
+        a.extend(0..6).unwrap();
+        let mut b = Small::new();
+        b.extend(6..15).unwrap();
+        let mut c = Small::new();
+        c.extend(15..17).unwrap();
+
+        let merged = a.merge(b).unwrap().merge(c).unwrap();
+        assert_eq!(merged.len(), 17);
+        assert_eq!(collect(merged), (0..17).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_sorted_dedup() {
+        let mut merged = Small::new();
+        let mut reference = Vec::new();
+
+        for seed in 0..5 {
+            let mut x = Small::new();
+            x.extend(numbers(seed, 37)).unwrap();
+            reference.extend(numbers(seed, 37));
+
+            merged.merge_ref(x).unwrap();
+        }
+
+        assert!(merged.spill_count() > 2, "should spill to many files");
+
+        reference.sort_unstable();
+        reference.dedup();
+
+        let mut out = Vec::new();
+        merged.finalize_sorted_dedup(|x| out.push(x)).unwrap();
+        assert_eq!(out, reference);
+    }
+
+    #[test]
+    fn test_cleanup() {
+        let mut a = Small::new();
+        a.extend(0..9).unwrap();
+
+        let paths: Vec<PathBuf> = a
+            .spills
+            .iter()
+            .map(|x| x.file.path().to_path_buf())
+            .collect();
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|x| x.exists()), "spill files should exist");
+
+        drop(a);
+        assert!(
+            paths.iter().all(|x| !x.exists()),
+            "spill files should be removed on drop"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_spill_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut a = Small::new().with_spill_dir(Some(dir.path()));
+        a.extend(0..4).unwrap();
+        assert_eq!(a.spill_dir(), Some(dir.path()));
+
+        let path = a.spills[0].file.path();
+        assert_eq!(path.parent(), Some(dir.path()));
+
+        let mode = fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        assert_eq!(collect(a), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialize() {
+        let a: Small = serde_json::from_str("[5, 4, 3, 2, 1, 0]").unwrap();
+        assert_eq!(a.spill_count(), 1);
+        assert_eq!(collect(a), [5, 4, 3, 2, 1, 0]);
+
+        let err = serde_json::from_str::<Small>("{}").unwrap_err();
+        assert!(err.to_string().contains("expected a sequence"), "{err}");
+    }
+}
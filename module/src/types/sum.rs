@@ -0,0 +1,119 @@
+//! Numbers that are added together when merged.
+//!
+//! See: [`Sum`].
+
+use super::prelude::*;
+
+merge_thin_wrapper! {
+    /// Numbers that are added together when merged.
+    ///
+    /// Merging 2 values of this type results in their sum. This is useful for
+    /// counters that every module adds to.
+    ///
+    /// Addition is done with the [`Add`] implementation of `T`, so this type
+    /// does not guard against overflow. With primitive integers, an overflow
+    /// panics in debug builds and wraps in release builds. Use
+    /// [`Saturating`] as `T` to clamp the sum instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::types::Sum;
+    /// # use module::merge::Merge;
+    /// let a = Sum(2);
+    /// let b = Sum(3);
+    ///
+    /// let merged = a.merge(b).unwrap();
+    ///
+    /// assert_eq!(*merged, 5);
+    /// ```
+    ///
+    /// With [`Saturating`]:
+    ///
+    /// ```rust
+    /// # use core::num::Saturating;
+    /// # use module::types::Sum;
+    /// # use module::merge::Merge;
+    /// let a = Sum(Saturating(u8::MAX));
+    /// let b = Sum(Saturating(1));
+    ///
+    /// let merged = a.merge(b).unwrap();
+    ///
+    /// assert_eq!(merged.into_inner(), Saturating(u8::MAX));
+    /// ```
+    ///
+    /// # serde
+    ///
+    /// This type deserializes like `T`.
+    ///
+    /// [`Add`]: core::ops::Add
+    /// [`Saturating`]: core::num::Saturating
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+    pub struct Sum;
+}
+
+impl<T> Sum<T> {
+    /// Create a new [`Sum`].
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Destruct this [`Sum`] and get the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Merge for Sum<T>
+where
+    T: core::ops::Add<Output = T> + Copy,
+{
+    #[inline]
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        self.0 = self.0 + other.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::num::Saturating;
+
+    #[test]
+    fn test_merge() {
+        assert_eq!(Sum(2).merge(Sum(3)).unwrap(), Sum(5));
+        assert_eq!(Sum(3).merge(Sum(2)).unwrap(), Sum(5));
+    }
+
+    #[test]
+    fn test_merge_many() {
+        let merged = (1..=4)
+            .map(Sum::new)
+            .try_fold(Sum(0), Merge::merge)
+            .unwrap();
+
+        assert_eq!(merged.into_inner(), 10);
+    }
+
+    #[test]
+    fn test_saturating() {
+        let merged = Sum(Saturating(250u8)).merge(Sum(Saturating(10))).unwrap();
+        assert_eq!(merged.into_inner(), Saturating(u8::MAX));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Sum<i32> = serde_json::from_str("42").unwrap();
+        assert_eq!(*x, 42);
+    }
+}
@@ -0,0 +1,85 @@
+//! Lists that skip duplicates when merged.
+//!
+//! See: [`Unique`].
+
+use alloc::vec::Vec;
+
+use super::prelude::*;
+
+merge_thin_wrapper! {
+    /// Lists that skip duplicates when merged.
+    ///
+    /// [`Vec`] merges by appending, so the same item may end up in the merged
+    /// list more than once. [`Unique`] appends only the items of the other
+    /// list that are not already present. Items keep the order in which they
+    /// were first seen.
+    ///
+    /// Items are compared with [`PartialEq`], so every item of the other list
+    /// is compared against every item of this list. This costs `O(n * m)`
+    /// and may be slow for large lists.
+    ///
+    /// Duplicates that are already in `self` are kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use module::types::Unique;
+    /// # use module::merge::Merge;
+    /// let a = Unique(vec![1, 2, 3]);
+    /// let b = Unique(vec![3, 4, 1]);
+    ///
+    /// let merged = a.merge(b).unwrap();
+    ///
+    /// assert_eq!(*merged, &[1, 2, 3, 4]);
+    /// ```
+    ///
+    /// # serde
+    ///
+    /// This type deserializes like `T`.
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+    pub struct Unique;
+}
+
+impl<E> Merge for Unique<Vec<E>>
+where
+    E: PartialEq,
+{
+    fn merge_ref(&mut self, other: Self) -> Result<(), Error> {
+        for x in other.0 {
+            if !self.0.contains(&x) {
+                self.0.push(x);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let merged = Unique(vec![1, 2, 3]).merge(Unique(vec![3, 4, 1])).unwrap();
+        assert_eq!(*merged, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_merge_duplicates_in_other() {
+        let merged = Unique(vec![1]).merge(Unique(vec![2, 2, 1])).unwrap();
+        assert_eq!(*merged, [1, 2]);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let x: Unique<Vec<i32>> = serde_json::from_str("[1, 2]").unwrap();
+        assert_eq!(*x, [1, 2]);
+    }
+}
@@ -0,0 +1,70 @@
+#![allow(missing_docs)]
+
+use std::collections::BTreeMap;
+use std::panic;
+
+use module::redact::{self, Mode, Policy, Redacted};
+use module::test_util::assert_merged_eq;
+
+// The global policy is shared by all tests of this binary, so everything is
+// checked from a single test.
+#[test]
+fn test_global_policy() {
+    assert_eq!(
+        redact::render_value("db.password", &"hunter2"),
+        Redacted::Value("hunter2".into())
+    );
+
+    redact::set_policy(Policy::new(Mode::Mask).with_pattern("*.password"));
+
+    assert_eq!(
+        redact::render_value("db.password", &"hunter2"),
+        Redacted::Masked
+    );
+    assert_eq!(
+        redact::render_value("db.user", &"admin"),
+        Redacted::Value("admin".into())
+    );
+
+    // Visible from other threads too.
+    std::thread::spawn(|| {
+        assert_eq!(
+            redact::render_value("db.password", &"hunter2"),
+            Redacted::Masked
+        );
+    })
+    .join()
+    .unwrap();
+
+    // Scoped policies take precedence.
+    redact::with_policy(Policy::new(Mode::Hash).with_pattern("*.password"), || {
+        assert!(matches!(
+            redact::render_value("db.password", &"hunter2"),
+            Redacted::Hashed(_)
+        ));
+    });
+
+    let a = BTreeMap::from([("db", BTreeMap::from([("password", vec!["a"])]))]);
+    let b = BTreeMap::from([("db", BTreeMap::from([("password", vec!["b"])]))]);
+    let expected = BTreeMap::from([("db", BTreeMap::from([("password", vec!["a", "c"])]))]);
+
+    redact::set_policy(Policy::new(Mode::Mask).with_pattern("db.password[*]"));
+
+    let err = panic::catch_unwind(|| assert_merged_eq!(a, b => expected)).unwrap_err();
+    let msg = err.downcast::<String>().unwrap();
+    assert_eq!(
+        *msg,
+        "assertion `merged == expected` failed
+differing values:
+  db.password[1]:
+      merged: <redacted>
+    expected: <redacted>
+"
+    );
+
+    redact::clear_policy();
+    assert_eq!(
+        redact::render_value("db.password", &"hunter2"),
+        Redacted::Value("hunter2".into())
+    );
+}
@@ -0,0 +1,14 @@
+#![allow(missing_docs)]
+
+#[test]
+fn test_ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
+
+#[test]
+#[cfg(feature = "test-macros")]
+fn test_ui_matrix() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui-matrix/*.rs");
+}
@@ -0,0 +1,19 @@
+use module::test_util::merge_test_matrix;
+
+#[allow(dead_code)]
+struct Config {
+    port: Option<u16>,
+}
+
+#[merge_test_matrix]
+mod tests {
+    use super::*;
+
+    matrix! {
+        Config {
+            port: overwrite,
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use module::Merge;
+
+#[derive(Merge)]
+struct Config {
+    #[merge(trim_neutral)]
+    value: Option<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use module::Merge;
+
+struct Opaque;
+
+#[derive(Merge)]
+struct Config {
+    #[merge(skip(deny_other))]
+    internal: Opaque,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use module::Merge;
+
+#[derive(Merge)]
+struct Config {
+    #[merge(skip(sometimes))]
+    internal: i32,
+}
+
+fn main() {}
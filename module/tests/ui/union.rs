@@ -0,0 +1,9 @@
+use module::Merge;
+
+#[derive(Merge)]
+union Config {
+    a: u32,
+    b: f32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use module::Merge;
+
+#[derive(Merge)]
+struct Config {
+    #[merge(renam = "x")]
+    value: i32,
+}
+
+fn main() {}